@@ -0,0 +1,2285 @@
+//! Argument parsing for the `axin` procedural macro.
+//!
+//! This module defines the structures and parsing logic for handling
+//! the various parameters accepted by the `#[axin(...)]` attribute macro.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use std::path::PathBuf;
+use syn::{
+    bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    Block, Expr, ExprLit, Ident, Lit, LitBool, LitInt, LitStr, Meta, Path, Stmt, Token,
+};
+
+use crate::defaults::{crate_defaults, Defaults};
+
+/// Parameter name constants.
+pub mod param_names {
+    /// The "prologue" parameter name.
+    pub const PROLOGUE: &str = "prologue";
+    /// The "epilogue" parameter name.
+    pub const EPILOGUE: &str = "epilogue";
+    /// The "on_enter" parameter name.
+    pub const ON_ENTER: &str = "on_enter";
+    /// The "on_exit" parameter name.
+    pub const ON_EXIT: &str = "on_exit";
+    /// The "decorator" parameter name.
+    pub const DECORATOR: &str = "decorator";
+    /// The "decorator_select" parameter name.
+    pub const DECORATOR_SELECT: &str = "decorator_select";
+    /// The "adapt" flag name.
+    pub const ADAPT: &str = "adapt";
+    /// The "on_error" parameter name.
+    pub const ON_ERROR: &str = "on_error";
+    /// The "assert_returns" parameter name.
+    pub const ASSERT_RETURNS: &str = "assert_returns";
+    /// The "slow_log" parameter name.
+    pub const SLOW_LOG: &str = "slow_log";
+    /// The "warn_call_site" parameter name.
+    pub const WARN_CALL_SITE: &str = "warn_call_site";
+    /// The "count_calls" parameter name.
+    pub const COUNT_CALLS: &str = "count_calls";
+    /// The "minimal" flag name.
+    pub const MINIMAL: &str = "minimal";
+    /// The "raw_body" flag name.
+    pub const RAW_BODY: &str = "raw_body";
+    /// The "explain" flag name.
+    pub const EXPLAIN: &str = "explain";
+    /// The "warn_if_trivial" flag name.
+    pub const WARN_IF_TRIVIAL: &str = "warn_if_trivial";
+    /// The "deny_trivial_decorator" flag name.
+    pub const DENY_TRIVIAL_DECORATOR: &str = "deny_trivial_decorator";
+    /// The "allow_trivial" flag name.
+    pub const ALLOW_TRIVIAL: &str = "allow_trivial";
+    /// The "document" flag name.
+    pub const DOCUMENT: &str = "document";
+    /// The "instrument_compat" parameter name.
+    pub const INSTRUMENT_COMPAT: &str = "instrument_compat";
+    /// The "debug_only" flag name.
+    pub const DEBUG_ONLY: &str = "debug_only";
+    /// The "skip" parameter name.
+    pub const SKIP: &str = "skip";
+    /// The "only" parameter name.
+    pub const ONLY: &str = "only";
+    /// The "hook_errors" parameter name.
+    pub const HOOK_ERRORS: &str = "hook_errors";
+    /// The "hook_error_sink" parameter name.
+    pub const HOOK_ERROR_SINK: &str = "hook_error_sink";
+    /// The "init" parameter name.
+    pub const INIT: &str = "init";
+    /// The "mock_in_tests" parameter name.
+    pub const MOCK_IN_TESTS: &str = "mock_in_tests";
+    /// The "event_id" parameter name.
+    pub const EVENT_ID: &str = "event_id";
+    /// The "no_defaults" flag name.
+    pub const NO_DEFAULTS: &str = "no_defaults";
+    /// The "opt" parameter name.
+    pub const OPT: &str = "opt";
+    /// The "twin" parameter name.
+    pub const TWIN: &str = "twin";
+    /// The "packed" parameter name.
+    pub const PACKED: &str = "packed";
+    /// The "phase_timing" parameter name.
+    pub const PHASE_TIMING: &str = "phase_timing";
+    /// The "assert_order" parameter name.
+    pub const ASSERT_ORDER: &str = "assert_order";
+    /// The "profile" parameter name.
+    pub const PROFILE: &str = "profile";
+    /// The "strict_prologue" parameter name.
+    pub const STRICT_PROLOGUE: &str = "strict_prologue";
+    /// The "stub_missing" parameter name.
+    pub const STUB_MISSING: &str = "stub_missing";
+    /// The "preset" parameter name.
+    pub const PRESET: &str = "preset";
+    /// The "on_panic" parameter name.
+    pub const ON_PANIC: &str = "on_panic";
+    /// The "on_exit_always" parameter name.
+    pub const ON_EXIT_ALWAYS: &str = "on_exit_always";
+    /// The "on_ok" parameter name.
+    pub const ON_OK: &str = "on_ok";
+    /// The "on_err" parameter name.
+    pub const ON_ERR: &str = "on_err";
+    /// The "order_const" parameter name.
+    pub const ORDER_CONST: &str = "order_const";
+    /// The "cfg" parameter name.
+    pub const CFG: &str = "cfg";
+    /// The "nested_body" flag name.
+    pub const NESTED_BODY: &str = "nested_body";
+    /// The "generated_prefix" parameter name.
+    pub const GENERATED_PREFIX: &str = "generated_prefix";
+
+    /// All supported parameter names for error messages.
+    pub const ALL_PARAMS: &[&str] = &[
+        PROLOGUE,
+        EPILOGUE,
+        ON_ENTER,
+        ON_EXIT,
+        ON_ERROR,
+        DECORATOR,
+        DECORATOR_SELECT,
+        ADAPT,
+        ASSERT_RETURNS,
+        SLOW_LOG,
+        WARN_CALL_SITE,
+        COUNT_CALLS,
+        MINIMAL,
+        RAW_BODY,
+        EXPLAIN,
+        WARN_IF_TRIVIAL,
+        DENY_TRIVIAL_DECORATOR,
+        ALLOW_TRIVIAL,
+        DOCUMENT,
+        INSTRUMENT_COMPAT,
+        DEBUG_ONLY,
+        SKIP,
+        ONLY,
+        HOOK_ERRORS,
+        HOOK_ERROR_SINK,
+        INIT,
+        MOCK_IN_TESTS,
+        EVENT_ID,
+        NO_DEFAULTS,
+        OPT,
+        TWIN,
+        PACKED,
+        PHASE_TIMING,
+        ASSERT_ORDER,
+        PROFILE,
+        STRICT_PROLOGUE,
+        STUB_MISSING,
+        PRESET,
+        ON_PANIC,
+        ON_EXIT_ALWAYS,
+        ON_OK,
+        ON_ERR,
+        ORDER_CONST,
+        CFG,
+        NESTED_BODY,
+        GENERATED_PREFIX,
+    ];
+}
+
+/// Names of the phases of a call that `assert_order` traces, in the order they run.
+pub const ORDER_PHASES: &[&str] = &[
+    "init",
+    "count",
+    "enter",
+    "decorator",
+    "prologue",
+    "body",
+    "epilogue",
+    "assert",
+    "slow_log",
+    "error",
+    "ok",
+    "err",
+    "exit",
+    "exit_always",
+];
+
+/// Function call specification supporting both simple paths and parameterized calls.
+///
+/// Represents function references in macro arguments, supporting:
+/// - Simple function names: `my_function`
+/// - Parameterized calls: `my_function("arg1", 42)`
+///
+/// Either may be followed by `as alias`, a name under which the function is reported in documentation and manifests,
+/// e.g. to tell `db::flush` and `cache::flush` apart. The alias never changes how the function is called.
+#[derive(Clone)]
+pub enum FunctionSpec {
+    /// Simple function path without arguments, and its alias
+    Simple(Path, Option<Ident>),
+    /// Function call with arguments, and its alias
+    WithArgs(Path, Punctuated<Expr, Token![,]>, Option<Ident>),
+}
+
+impl FunctionSpec {
+    /// The path of the function.
+    pub fn path(&self) -> &Path {
+        match self {
+            FunctionSpec::Simple(path, _) | FunctionSpec::WithArgs(path, _, _) => path,
+        }
+    }
+
+    /// The name the function is reported under: its alias if it has one, and its path otherwise.
+    pub fn reported_name(&self) -> String {
+        match self {
+            FunctionSpec::Simple(_, Some(alias)) | FunctionSpec::WithArgs(_, _, Some(alias)) => {
+                alias.to_string()
+            }
+            _ => self.path().to_token_stream().to_string().replace(' ', ""),
+        }
+    }
+}
+
+impl Parse for FunctionSpec {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: Path = input.parse()?;
+        let args = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(Punctuated::<Expr, Token![,]>::parse_terminated(&content)?)
+        } else {
+            None
+        };
+        let alias = match input.parse::<Option<Token![as]>>()? {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+
+        match args {
+            Some(args) => Ok(FunctionSpec::WithArgs(path, args, alias)),
+            None => Ok(FunctionSpec::Simple(path, alias)),
+        }
+    }
+}
+
+/// Parse the comma-separated phases of `assert_order`, each of which must be one of [`ORDER_PHASES`].
+fn parse_order_phases(phases: &LitStr) -> syn::Result<Vec<String>> {
+    let value = phases.value();
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    value
+        .split(',')
+        .map(|phase| {
+            let phase = phase.trim();
+            if ORDER_PHASES.contains(&phase) {
+                Ok(phase.to_string())
+            } else {
+                Err(syn::Error::new_spanned(
+                    phases,
+                    format!(
+                        "unknown phase `{}`, expected one of {}",
+                        phase,
+                        ORDER_PHASES.join(", ")
+                    ),
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Check the prologue and epilogue statements of all `axin` attributes of a function, given by their arguments, if any
+/// of them gives `strict_prologue`: statements may not contain `unsafe` or `static mut`, nor mention a path any of these
+/// attributes denies.
+///
+/// The statements are scanned as tokens, so that the arguments of macro invocations are checked as well. Paths are
+/// matched as written, so a denied function called through a `use` of it is not caught.
+pub fn check_strict_prologue<'a>(
+    layers: impl IntoIterator<Item = &'a AxinArgs> + Clone,
+) -> syn::Result<()> {
+    let mut strict = false;
+    let mut denied: Vec<&Path> = Vec::new();
+    for arg in layers.clone().into_iter().flat_map(|args| &args.args) {
+        if let AxinArg::StrictPrologue { deny, .. } = arg {
+            strict = true;
+            denied.extend(deny);
+        }
+    }
+    if !strict {
+        return Ok(());
+    }
+
+    let stmts = layers
+        .into_iter()
+        .flat_map(|args| &args.args)
+        .filter_map(|arg| match arg {
+            AxinArg::Prologue { stmts } | AxinArg::Epilogue { stmts } => {
+                Some(stmts.iter().map(move |stmt| (arg.name(), stmt)))
+            }
+            _ => None,
+        })
+        .flatten();
+    for (name, stmt) in stmts {
+        if let Some(construct) = forbidden_construct(stmt.to_token_stream(), &denied) {
+            return Err(syn::Error::new_spanned(
+                stmt,
+                format!(
+                    "`strict_prologue` does not allow {} in {} statements; move the statement into the function \
+                     body, where it is reviewed like the rest of it",
+                    construct, name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that no decorator is given twice to a function, across all of its `axin` attributes given by their arguments:
+/// the attributes stacked on it, and the one of its `impl` block or module if any. The same decorator, with the same
+/// setup call if any, is most likely a mistake, such as a line copied and not edited, and would wrap the function
+/// twice. The error points at both.
+pub fn check_duplicate_decorators<'a>(
+    layers: impl IntoIterator<Item = &'a AxinArgs>,
+) -> syn::Result<()> {
+    let mut given: Vec<&FunctionSpec> = Vec::new();
+    for arg in layers.into_iter().flat_map(|args| &args.args) {
+        let AxinArg::Decorator { func, .. } = arg else {
+            continue;
+        };
+        let written = func.to_token_stream().to_string();
+        if let Some(first) = given
+            .iter()
+            .find(|first| first.to_token_stream().to_string() == written)
+        {
+            let mut error = syn::Error::new_spanned(
+                func.path(),
+                format!(
+                    "duplicate decorator `{}`, which would wrap the function twice",
+                    func.reported_name()
+                ),
+            );
+            error.combine(syn::Error::new_spanned(
+                first.path(),
+                "decorator already specified here",
+            ));
+            return Err(error);
+        }
+        given.push(func);
+    }
+    Ok(())
+}
+
+/// Describe the first construct in `tokens` that `strict_prologue` rejects, looking into groups.
+fn forbidden_construct(tokens: TokenStream, denied: &[&Path]) -> Option<String> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Ident(ident) if ident == "unsafe" => return Some("`unsafe`".to_string()),
+            TokenTree::Ident(ident)
+                if ident == "static"
+                    && matches!(tokens.get(index + 1), Some(TokenTree::Ident(next)) if next == "mut") =>
+            {
+                return Some("`static mut`".to_string());
+            }
+            TokenTree::Ident(_) => {
+                if let Some(path) = denied
+                    .iter()
+                    .find(|path| starts_with_path(&tokens[index..], path))
+                {
+                    return Some(format!(
+                        "the denied path `{}`",
+                        path.to_token_stream().to_string().replace(' ', "")
+                    ));
+                }
+            }
+            TokenTree::Group(group) => {
+                if let Some(construct) = forbidden_construct(group.stream(), denied) {
+                    return Some(construct);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `tokens` start with the segments of `path`, separated by `::`, ignoring a leading `::` of the path and any
+/// generic arguments.
+fn starts_with_path(tokens: &[TokenTree], path: &Path) -> bool {
+    let mut rest = tokens;
+    for (index, segment) in path.segments.iter().enumerate() {
+        if index > 0 {
+            match rest {
+                [TokenTree::Punct(first), TokenTree::Punct(second), tail @ ..]
+                    if first.as_char() == ':' && second.as_char() == ':' =>
+                {
+                    rest = tail;
+                }
+                _ => return false,
+            }
+        }
+        match rest {
+            [TokenTree::Ident(ident), tail @ ..] if *ident == segment.ident => rest = tail,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Parse the options of `profile(...)`, of which there is only `slow = threshold`, returning the threshold if given.
+fn parse_profile_options(input: ParseStream) -> syn::Result<Option<Expr>> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let option: Ident = input.parse()?;
+    if option != "slow" {
+        return Err(syn::Error::new_spanned(
+            &option,
+            format!("unknown option `{}` of `profile`, expected `slow`", option),
+        ));
+    }
+    input.parse::<Token![=]>()?;
+    parse_duration(input.parse()?).map(Some)
+}
+
+/// Turn an integer literal with a unit, like `250ms`, into the `Duration` it stands for, and leave other expressions to
+/// be durations themselves.
+fn parse_duration(duration: Expr) -> syn::Result<Expr> {
+    let lit = match &duration {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit,
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(lit),
+            ..
+        }) => {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "expected a whole number of a unit, like `1500ms`",
+            ));
+        }
+        _ => return Ok(duration),
+    };
+    let constructor = match lit.suffix() {
+        "ns" => quote! { from_nanos },
+        "us" => quote! { from_micros },
+        "ms" => quote! { from_millis },
+        "s" => quote! { from_secs },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "expected a duration like `250ms`, in `ns`, `us`, `ms` or `s`, or an expression of type `Duration`",
+            ));
+        }
+    };
+    let value = LitInt::new(lit.base10_digits(), lit.span());
+    Ok(parse_quote! { ::core::time::Duration::#constructor(#value) })
+}
+
+/// Modifiers given after a decorator, as in `decorator(label_by("GET"), borrow_args, no_args)`.
+#[derive(Default)]
+struct DecoratorModifiers {
+    /// `borrow_args`, passing references to the arguments to the decorator's setup call.
+    borrow_args: bool,
+    /// `no_args`, passing the decorator a callable capturing the arguments, like `adapt`.
+    no_args: bool,
+}
+
+impl DecoratorModifiers {
+    /// Parse the modifiers following a decorator, each preceded by a comma.
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut modifiers = DecoratorModifiers::default();
+        loop {
+            // Leave a trailing comma to the caller
+            let ahead = input.fork();
+            if ahead.parse::<Option<Token![,]>>()?.is_none() || ahead.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+            let modifier: Ident = input.parse()?;
+            match modifier.to_string().as_str() {
+                "borrow_args" => modifiers.borrow_args = true,
+                "no_args" => modifiers.no_args = true,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        modifier,
+                        "expected `borrow_args` or `no_args` after the decorator",
+                    ));
+                }
+            }
+        }
+        Ok(modifiers)
+    }
+}
+
+/// Parse the optional `instrumented = true | false` following a mock, preceded by a comma. Mocks are instrumented
+/// unless told otherwise.
+fn parse_instrumented(input: ParseStream) -> syn::Result<bool> {
+    // Leave a trailing comma to the caller
+    let ahead = input.fork();
+    if ahead.parse::<Option<Token![,]>>()?.is_none() || ahead.is_empty() {
+        return Ok(true);
+    }
+    input.parse::<Token![,]>()?;
+    let modifier: Ident = input.parse()?;
+    if modifier != "instrumented" {
+        return Err(syn::Error::new_spanned(
+            modifier,
+            "expected `instrumented = true` or `instrumented = false` after the mock",
+        ));
+    }
+    input.parse::<Token![=]>()?;
+    Ok(input.parse::<LitBool>()?.value)
+}
+
+/// What to do with the error of a fallible hook, as given to `hook_errors = "..."` or a hook's `errors = "..."`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookErrorPolicy {
+    /// `"log"`: pass the error to the `hook_error_sink`.
+    Log,
+    /// `"ignore"`: drop the error.
+    Ignore,
+    /// `"propagate"`: return the error from the instrumented function with `?`.
+    Propagate,
+}
+
+impl Parse for HookErrorPolicy {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let policy: LitStr = input.parse()?;
+        match policy.value().as_str() {
+            "log" => Ok(HookErrorPolicy::Log),
+            "ignore" => Ok(HookErrorPolicy::Ignore),
+            "propagate" => Ok(HookErrorPolicy::Propagate),
+            _ => Err(syn::Error::new_spanned(
+                policy,
+                "unknown hook error policy, expected one of \"log\", \"ignore\" or \"propagate\"",
+            )),
+        }
+    }
+}
+
+/// The id of a function's events, as given to `event_id = ...`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventId {
+    /// `auto`: derived from the function's name by `axin::event_id`.
+    Auto,
+    /// An integer literal fitting in a `u16`.
+    Explicit(u16),
+}
+
+impl Parse for EventId {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) {
+            let keyword: Ident = input.parse()?;
+            if keyword != "auto" {
+                return Err(syn::Error::new_spanned(
+                    keyword,
+                    "expected `auto` or an integer literal as the event id",
+                ));
+            }
+            return Ok(EventId::Auto);
+        }
+        let id: LitInt = input.parse()?;
+        id.base10_parse().map(EventId::Explicit).map_err(|_| {
+            syn::Error::new_spanned(&id, "event ids are `u16`, so they range from 0 to 0xffff")
+        })
+    }
+}
+
+/// The value of an option, as given to `opt(name = value)` or as the default of an `__axin_opt("name", default)`
+/// placeholder in the crate's defaults.
+#[derive(Clone)]
+pub enum OptionValue {
+    /// A literal, such as `"billing"` or `3`.
+    Lit(Lit),
+    /// An identifier, such as the name of a function or a constant.
+    Ident(Ident),
+}
+
+impl OptionValue {
+    /// What kind of value this is, which an option's value and its default must agree on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OptionValue::Lit(Lit::Str(_)) => "a string",
+            OptionValue::Lit(Lit::Int(_)) => "an integer",
+            OptionValue::Lit(Lit::Float(_)) => "a float",
+            OptionValue::Lit(Lit::Bool(_)) => "a boolean",
+            OptionValue::Lit(Lit::Char(_)) => "a character",
+            OptionValue::Lit(_) => "a byte string",
+            OptionValue::Ident(_) => "an identifier",
+        }
+    }
+}
+
+impl Parse for OptionValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Lit) {
+            Ok(OptionValue::Lit(input.parse()?))
+        } else if input.peek(Ident) {
+            Ok(OptionValue::Ident(input.parse()?))
+        } else {
+            Err(input.error("expected a literal or an identifier as the value of the option"))
+        }
+    }
+}
+
+impl ToTokens for OptionValue {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            OptionValue::Lit(lit) => lit.to_tokens(tokens),
+            OptionValue::Ident(ident) => ident.to_tokens(tokens),
+        }
+    }
+}
+
+/// An option given in `opt(...)`, e.g. `component = "billing"`.
+#[derive(Clone)]
+pub struct OptionArg {
+    /// Name of the option.
+    pub name: Ident,
+    /// Value substituted for the option's placeholders.
+    pub value: OptionValue,
+}
+
+impl Parse for OptionArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        Ok(OptionArg {
+            name,
+            value: input.parse()?,
+        })
+    }
+}
+
+/// `with_args` or `with_args(skip(name, ...))` after an entry hook, passing the function's name and a borrowed tuple
+/// of its arguments to the hook.
+#[derive(Clone)]
+pub struct HookArgs {
+    /// Parameters left out of the tuple, such as ones without the traits the hook requires.
+    pub skip: Vec<Ident>,
+}
+
+/// Modifiers given after the function of a hook, as in `on_exit(hook, when = predicate, errors = "log")`.
+#[derive(Default)]
+struct HookModifiers {
+    /// `when = predicate`, only accepted by entry and exit hooks.
+    when: Option<Expr>,
+    /// `errors = "..."`, overriding `hook_errors` for this hook.
+    errors: Option<HookErrorPolicy>,
+    /// `with_args`, only accepted by entry hooks.
+    with_args: Option<HookArgs>,
+    /// `auto_into`, converting the literal arguments of the hook to its parameter types.
+    auto_into: bool,
+    /// `after_drop`, only accepted by exit hooks.
+    after_drop: Option<Ident>,
+}
+
+impl HookModifiers {
+    /// Parse the modifiers following the function `func` of the hook `hook`, each preceded by a comma. `when` is only
+    /// accepted by entry and exit hooks, `with_args` by entry hooks, and `after_drop` by exit hooks. `auto_into` is
+    /// applied to `func` right away.
+    fn parse(input: ParseStream, hook: &str, func: &mut FunctionSpec) -> syn::Result<Self> {
+        let allow_when = hook == param_names::ON_ENTER || hook == param_names::ON_EXIT;
+        let allow_with_args = hook == param_names::ON_ENTER;
+        let allow_after_drop = hook == param_names::ON_EXIT;
+        let mut modifiers = HookModifiers::default();
+        loop {
+            // Leave a trailing comma to the caller
+            let ahead = input.fork();
+            if ahead.parse::<Option<Token![,]>>()?.is_none() || ahead.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+            let modifier: Ident = input.parse()?;
+            match modifier.to_string().as_str() {
+                "when" if allow_when => {
+                    input.parse::<Token![=]>()?;
+                    modifiers.when = Some(input.parse()?);
+                }
+                "errors" => {
+                    input.parse::<Token![=]>()?;
+                    modifiers.errors = Some(input.parse()?);
+                }
+                "with_args" if allow_with_args => {
+                    modifiers.with_args = Some(HookArgs {
+                        skip: parse_skipped_args(input)?,
+                    });
+                }
+                "auto_into" => {
+                    modifiers.auto_into = true;
+                }
+                "after_drop" if allow_after_drop => {
+                    modifiers.after_drop = Some(modifier);
+                }
+                "with_args" => {
+                    return Err(syn::Error::new_spanned(
+                        modifier,
+                        format!(
+                            "`with_args` is only supported by `on_enter`, since the arguments are moved into the \
+                             function before `{}` runs",
+                            hook
+                        ),
+                    ));
+                }
+                _ => {
+                    let expected = if allow_with_args {
+                        "`when = predicate`, `errors = \"...\"`, `with_args` or `auto_into`"
+                    } else if allow_after_drop {
+                        "`when = predicate`, `errors = \"...\"`, `auto_into` or `after_drop`"
+                    } else {
+                        "`errors = \"...\"` or `auto_into`"
+                    };
+                    return Err(syn::Error::new_spanned(
+                        modifier,
+                        format!("expected {} after the hook", expected),
+                    ));
+                }
+            }
+        }
+        if modifiers.auto_into {
+            convert_literal_args(func);
+        }
+        Ok(modifiers)
+    }
+}
+
+/// Wrap every literal argument of `func` in `Into::into`, so that the hook's parameter types drive their conversion,
+/// e.g. of a string literal to a `String`. Other arguments are left alone, so that nothing else is converted behind the
+/// attribute author's back, and so are numbers without a suffix, whose type the parameter types already decide: as the
+/// argument of `Into::into`, they would fall back to `i32` or `f64` instead.
+fn convert_literal_args(func: &mut FunctionSpec) {
+    if let FunctionSpec::WithArgs(_, args, _) = func {
+        for arg in args.iter_mut() {
+            let Expr::Lit(lit) = arg else {
+                continue;
+            };
+            let unsuffixed = match &lit.lit {
+                Lit::Int(int) => int.suffix().is_empty(),
+                Lit::Float(float) => float.suffix().is_empty(),
+                _ => false,
+            };
+            if !unsuffixed {
+                *arg = parse_quote! { ::core::convert::Into::into(#lit) };
+            }
+        }
+    }
+}
+
+/// Parse the optional `(skip(name, ...))` following `with_args`.
+fn parse_skipped_args(input: ParseStream) -> syn::Result<Vec<Ident>> {
+    if !input.peek(syn::token::Paren) {
+        return Ok(Vec::new());
+    }
+    let content;
+    parenthesized!(content in input);
+    let keyword: Ident = content.parse()?;
+    if keyword != "skip" {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "expected `skip(name, ...)` after `with_args`",
+        ));
+    }
+    let names;
+    parenthesized!(names in content);
+    let skip = Punctuated::<Ident, Token![,]>::parse_terminated(&names)?;
+    if !content.is_empty() {
+        return Err(content.error("unexpected tokens after `skip(...)`"));
+    }
+    Ok(skip.into_iter().collect())
+}
+
+/// Level of a span, as given to `instrument_compat(level = ...)`.
+#[derive(Clone)]
+pub enum InstrumentLevel {
+    /// One of the level names accepted by `tracing`, such as `"debug"`, mapped to the `tracing::Level` constant.
+    Named(Ident),
+    /// Any other expression evaluating to a `tracing::Level`, such as `Level::DEBUG`.
+    Expr(Expr),
+}
+
+/// A field given in `instrument_compat(fields(...))`, e.g. `user = %user_id` or `http.status`.
+#[derive(Clone)]
+pub struct InstrumentField {
+    /// Dotted name of the field.
+    pub name: Punctuated<Ident, Token![.]>,
+    /// `%` or `?` before the value, choosing `Display` or `Debug` recording.
+    pub sigil: Option<TokenStream>,
+    /// Value of the field, empty if the field is only declared.
+    pub value: Option<Expr>,
+}
+
+impl Parse for InstrumentField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = Punctuated::parse_separated_nonempty_with(input, Ident::parse_any)?;
+        if !input.peek(Token![=]) {
+            return Ok(InstrumentField {
+                name,
+                sigil: None,
+                value: None,
+            });
+        }
+
+        input.parse::<Token![=]>()?;
+        let sigil = if input.peek(Token![%]) {
+            Some(input.parse::<Token![%]>()?.into_token_stream())
+        } else if input.peek(Token![?]) {
+            Some(input.parse::<Token![?]>()?.into_token_stream())
+        } else {
+            None
+        };
+        Ok(InstrumentField {
+            name,
+            sigil,
+            value: Some(input.parse()?),
+        })
+    }
+}
+
+/// Options of `instrument_compat(...)`, the subset of `#[tracing::instrument]` arguments supported by axin.
+#[derive(Clone, Default)]
+pub struct InstrumentOptions {
+    /// `name = "..."`, defaulting to the function name.
+    pub name: Option<LitStr>,
+    /// `target = "..."`, defaulting to the module path.
+    pub target: Option<LitStr>,
+    /// `level = ...`, defaulting to `INFO`.
+    pub level: Option<InstrumentLevel>,
+    /// `skip(a, b)`: parameters not recorded as fields.
+    pub skip: Vec<Ident>,
+    /// `skip_all`: record no parameters as fields.
+    pub skip_all: bool,
+    /// `fields(...)`: additional fields recorded after the parameters.
+    pub fields: Punctuated<InstrumentField, Token![,]>,
+}
+
+impl InstrumentOptions {
+    /// The replacement suggested for a `#[tracing::instrument]` option axin does not support.
+    fn unsupported_hint(option: &str) -> Option<&'static str> {
+        match option {
+            "err" => Some("use `on_error` instead"),
+            "ret" => Some("use a decorator instead"),
+            "parent" | "follows_from" => Some("create the span in a decorator instead"),
+            _ => None,
+        }
+    }
+
+    fn parse_level(input: ParseStream) -> syn::Result<InstrumentLevel> {
+        if input.peek(LitStr) {
+            let level: LitStr = input.parse()?;
+            let name = match level.value().to_ascii_lowercase().as_str() {
+                "trace" => "TRACE",
+                "debug" => "DEBUG",
+                "info" => "INFO",
+                "warn" => "WARN",
+                "error" => "ERROR",
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        level,
+                        "unknown level, expected one of \"trace\", \"debug\", \"info\", \"warn\" or \"error\"",
+                    ))
+                }
+            };
+            Ok(InstrumentLevel::Named(Ident::new(name, level.span())))
+        } else {
+            Ok(InstrumentLevel::Expr(input.parse()?))
+        }
+    }
+}
+
+impl Parse for InstrumentOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = InstrumentOptions::default();
+
+        while !input.is_empty() {
+            let option: Ident = input.parse()?;
+            match option.to_string().as_str() {
+                "name" => {
+                    input.parse::<Token![=]>()?;
+                    options.name = Some(input.parse()?);
+                }
+                "target" => {
+                    input.parse::<Token![=]>()?;
+                    options.target = Some(input.parse()?);
+                }
+                "level" => {
+                    input.parse::<Token![=]>()?;
+                    options.level = Some(Self::parse_level(input)?);
+                }
+                "skip" => {
+                    let content;
+                    parenthesized!(content in input);
+                    options
+                        .skip
+                        .extend(Punctuated::<Ident, Token![,]>::parse_terminated(&content)?);
+                }
+                "skip_all" => options.skip_all = true,
+                "fields" => {
+                    let content;
+                    parenthesized!(content in input);
+                    options.fields.extend(
+                        Punctuated::<InstrumentField, Token![,]>::parse_terminated(&content)?,
+                    );
+                }
+                name => {
+                    let message = match Self::unsupported_hint(name) {
+                        Some(hint) => format!("`{}` is not supported by axin, {}", name, hint),
+                        None => format!("unknown `instrument_compat` option `{}`", name),
+                    };
+                    return Err(syn::Error::new_spanned(option, message));
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(options)
+    }
+}
+
+impl ToTokens for FunctionSpec {
+    /// Render the specification back in the form it was written in the attribute, without its alias.
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            FunctionSpec::Simple(path, _) => path.to_tokens(tokens),
+            FunctionSpec::WithArgs(path, args, _) => tokens.extend(quote! { #path(#args) }),
+        }
+    }
+}
+
+/// Collection of arguments for the `axin` macro.
+///
+/// Contains a comma-separated list of macro parameters such as
+/// `prologue(...)`, `on_enter(...)`, `decorator(...)`, and `on_exit(...)`.
+#[derive(Clone, Default)]
+pub struct AxinArgs {
+    pub args: Punctuated<AxinArg, Token![,]>,
+    /// The crate's `axin.toml`, if defaults from it were merged into the arguments.
+    pub defaults_file: Option<PathBuf>,
+}
+
+/// The `skip` and `only` filters of an attribute, each with the keyword it was given with.
+#[derive(Default)]
+pub struct ModuleFilters {
+    pub skip: Vec<(Ident, Punctuated<Ident, Token![,]>)>,
+    pub only: Vec<(Ident, Punctuated<Ident, Token![,]>)>,
+}
+
+impl ModuleFilters {
+    /// The keyword of the first filter, if any filter is present.
+    pub fn first_keyword(&self) -> Option<&Ident> {
+        self.skip
+            .iter()
+            .chain(&self.only)
+            .map(|(keyword, _)| keyword)
+            .next()
+    }
+
+    /// All function names mentioned by the filters.
+    pub fn names(&self) -> impl Iterator<Item = &Ident> {
+        self.skip.iter().chain(&self.only).flat_map(|(_, fns)| fns)
+    }
+
+    /// Whether the attribute applies to the function named `name`.
+    pub fn includes(&self, name: &Ident) -> bool {
+        let skipped = self
+            .skip
+            .iter()
+            .any(|(_, fns)| fns.iter().any(|f| f == name));
+        let selected = self.only.is_empty()
+            || self
+                .only
+                .iter()
+                .any(|(_, fns)| fns.iter().any(|f| f == name));
+        selected && !skipped
+    }
+}
+
+/// Individual argument types supported by the `axin` macro.
+///
+/// Each variant represents a specific instrumentation feature:
+/// - Prologue: Statements inserted at function start
+/// - Epilogue: Statements inserted after the function body, before its value is returned
+/// - OnEnter: Function called before main function
+/// - OnExit: Function called after main function
+/// - OnError: Function called with the error when the main function returns `Err`
+/// - OnOk, OnErr: Functions called when the main function returns `Ok` or `Err`, seeing the value or the error
+/// - OrderConst: Flag emitting a constant listing the phases of a call, in order
+/// - Decorator: Function wrapper for the main function
+/// - DecoratorSelect: Function wrappers for the main function, one of which is chosen on every call
+/// - Adapt: Flag presenting the main function to decorators as a callable without arguments
+/// - AssertReturns: Expected return value checked in test builds
+/// - SlowLog: Function called with the duration of calls slower than a threshold
+/// - WarnCallSite: Deprecation note shown where the function is called
+/// - CountCalls: Call counter incremented on every call
+/// - Minimal: Flag selecting the closure-free generation mode
+/// - RawBody: Flag emitting the body unchanged, with hooks spliced around it
+/// - Explain: Flag requesting a compile-time description of the generated code
+/// - WarnIfTrivial, DenyTrivialDecorator, AllowTrivial: Flags checking for decorators on trivial functions
+/// - Document: Flag appending a description of the instrumentation to the function's documentation
+/// - InstrumentCompat: `tracing` span entered for the call, configured like `#[tracing::instrument]`
+/// - DebugOnly: Flag restricting the instrumentation to builds with `debug_assertions`
+/// - Cfg: Configuration predicate restricting the instrumentation to the builds it holds for
+/// - Skip, Only: Filters selecting the functions of a module the attribute applies to
+/// - HookErrors, HookErrorSink: Handling of errors returned by hooks
+/// - Init: Expression evaluated once, before the first call runs any instrumentation
+/// - MockInTests: Function replacing the main function's body in test builds
+/// - EventId: Compact id of the function, available to hooks
+/// - NoDefaults: Flag opting out of the crate's default arguments
+/// - Opt: Values of the options the crate's default arguments take
+/// - Twin: Name of an uninstrumented copy of the function
+/// - Packed: Flag passing the arguments to decorators packed into a struct
+/// - PhaseTiming: Function called with the time each phase of a call took
+/// - AssertOrder: Phases every call is expected to run, in order, checked in test builds
+/// - Profile: Timing and slow call logging of every call, reported to the sinks registered with the runtime
+/// - StrictPrologue: Flag rejecting prologue statements with `unsafe`, `static mut` or calls to denied paths
+/// - StubMissing: Hooks replaced by generated functions doing nothing
+/// - Preset: Named set of arguments from the crate's `axin.toml`
+/// - OnPanic, OnExitAlways: Functions run by guards when the call unwinds, or in any case
+/// - NestedBody: Flag running the body in a nested function rather than a closure
+/// - GeneratedPrefix: Prefix of the placeholders and generated names, replacing `__axin_`
+#[derive(Clone)]
+pub enum AxinArg {
+    /// `prologue(statement1; statement2; ...)`
+    ///
+    /// Statements to insert at the beginning of the function body.
+    Prologue { stmts: Vec<Stmt> },
+    /// `epilogue(statement1; statement2; ...)`
+    ///
+    /// Statements to insert after the function body, run when the body completes without returning early.
+    Epilogue { stmts: Vec<Stmt> },
+    /// `on_enter(function)` or `on_enter(function(args))`, optionally followed by `, when = predicate`,
+    /// `, errors = "..."` and `, with_args`
+    ///
+    /// Function to execute before the main function, if the predicate (if any) holds. With `with_args`, the function's
+    /// name and a borrowed tuple of its arguments are passed to it as well.
+    OnEnter {
+        func: FunctionSpec,
+        when: Option<Expr>,
+        errors: Option<HookErrorPolicy>,
+        with_args: Option<HookArgs>,
+    },
+    /// `on_exit(function)` or `on_exit(function(args))`, optionally followed by `, when = predicate`,
+    /// `, errors = "..."` and `, after_drop`
+    ///
+    /// Function to execute after the main function, if the predicate (if any) holds. With `after_drop`, the hook is
+    /// required to run after the locals and arguments of the function are dropped, which every exit hook of a wrapped
+    /// body does.
+    OnExit {
+        func: FunctionSpec,
+        when: Option<Expr>,
+        errors: Option<HookErrorPolicy>,
+        after_drop: Option<Ident>,
+    },
+    /// `on_error(function)` or `on_error(function(args))`, optionally followed by `, errors = "..."`
+    ///
+    /// Function to execute with a reference to the error when the main function returns `Err`.
+    OnError {
+        func: FunctionSpec,
+        errors: Option<HookErrorPolicy>,
+    },
+    /// `on_ok(function)` or `on_ok(function(args))`, optionally followed by `, errors = "..."`
+    ///
+    /// Function to execute when the main function returns `Ok`, whose arguments see a reference to the value as
+    /// `__axin_ok`.
+    OnOk {
+        func: FunctionSpec,
+        errors: Option<HookErrorPolicy>,
+    },
+    /// `on_err(function)` or `on_err(function(args))`, optionally followed by `, errors = "..."`
+    ///
+    /// Function to execute when the main function returns `Err`, whose arguments see a reference to the error as
+    /// `__axin_err`.
+    OnErr {
+        func: FunctionSpec,
+        errors: Option<HookErrorPolicy>,
+    },
+    /// `order_const`
+    ///
+    /// Emit a constant `<NAME>_AXIN_ORDER` next to the function, listing the phases its calls run, in order.
+    OrderConst { keyword: Ident },
+    /// `decorator(function)` or `decorator(function(args))`, optionally followed by `borrow_args` and `no_args`
+    ///
+    /// Decorator function to wrap the main function. Several decorators are nested, the first given outermost. With
+    /// `borrow_args`, references to the function's arguments are passed to the decorator's setup call as well. With
+    /// `no_args`, the decorator is passed a callable without arguments, as with `adapt`.
+    Decorator {
+        func: FunctionSpec,
+        borrow_args: bool,
+        no_args: bool,
+    },
+    /// `decorator_select(selector, [decorator1, decorator2(args), ...])`
+    ///
+    /// Decorators to choose from on every call, by the position `selector` evaluates to.
+    DecoratorSelect {
+        keyword: Ident,
+        selector: Expr,
+        decorators: Punctuated<FunctionSpec, Token![,]>,
+    },
+    /// `adapt`
+    ///
+    /// Capture the parameters in the callable passed to the decorator, instead of forwarding them, so that a decorator
+    /// written for functions without parameters wraps functions of any arity, including methods taking `self`.
+    Adapt,
+    /// `assert_returns(expr)`
+    ///
+    /// Expected return value, asserted after every call in `cfg(test)` builds.
+    AssertReturns { expected: Expr },
+    /// `slow_log(threshold, function)` or `slow_log(threshold, function(args))`
+    ///
+    /// Function to execute with the elapsed time when the decorator and function body took longer than `threshold`.
+    SlowLog { threshold: Expr, func: FunctionSpec },
+    /// `warn_call_site("message")`
+    ///
+    /// Mark the function `#[deprecated]` with the given note, so that callers get a warning.
+    WarnCallSite { message: LitStr },
+    /// `count_calls(COUNTER)`
+    ///
+    /// A static `axin::CallCounter` incremented on every call.
+    CountCalls { counter: Path },
+    /// `minimal`
+    ///
+    /// Emit the original body unchanged, preceded only by counters and argument-less entry hooks.
+    Minimal { keyword: Ident },
+    /// `raw_body`
+    ///
+    /// Emit the original body unchanged, with entry hooks before it and exit hooks run when it returns.
+    RawBody { keyword: Ident },
+    /// `explain`
+    ///
+    /// Report the generated code as a compile-time warning at the attribute.
+    Explain { keyword: Ident },
+    /// `warn_if_trivial`
+    ///
+    /// Warn if a decorator wraps a function that is empty or only forwards to another function.
+    WarnIfTrivial,
+    /// `deny_trivial_decorator`
+    ///
+    /// Like `warn_if_trivial`, but emit an error instead.
+    DenyTrivialDecorator,
+    /// `allow_trivial`
+    ///
+    /// Allow decorators on trivial functions, overriding `warn_if_trivial` and `deny_trivial_decorator`.
+    AllowTrivial,
+    /// `document`
+    ///
+    /// Append a section listing the active instrumentation to the function's doc comments.
+    Document,
+    /// `instrument_compat(skip(a), fields(k = v), level = "debug", name = "...")`
+    ///
+    /// Enter a `tracing` span for the call, configured like `#[tracing::instrument]`.
+    InstrumentCompat { options: InstrumentOptions },
+    /// `debug_only`
+    ///
+    /// Instrument the function only when `debug_assertions` are enabled, and emit it unchanged otherwise.
+    DebugOnly,
+    /// `cfg(predicate)`
+    ///
+    /// Instrument the function only in the builds `predicate` holds for, as `#[cfg(predicate)]`, and emit it unchanged
+    /// otherwise.
+    Cfg { predicate: Meta },
+    /// `skip(function1, function2, ...)`
+    ///
+    /// Functions of a module the attribute is not applied to.
+    Skip {
+        keyword: Ident,
+        fns: Punctuated<Ident, Token![,]>,
+    },
+    /// `only(function1, function2, ...)`
+    ///
+    /// The only functions of a module the attribute is applied to.
+    Only {
+        keyword: Ident,
+        fns: Punctuated<Ident, Token![,]>,
+    },
+    /// `hook_errors = "log" | "ignore" | "propagate"`
+    ///
+    /// What to do with errors returned by entry, exit and error hooks, unless a hook overrides it.
+    HookErrors {
+        keyword: Ident,
+        policy: HookErrorPolicy,
+    },
+    /// `hook_error_sink = function` or `hook_error_sink = function(args)`
+    ///
+    /// Function to execute with a reference to the error of a hook under the `"log"` policy.
+    HookErrorSink { func: FunctionSpec },
+    /// `init(expr)`
+    ///
+    /// Expression evaluated once per function, before the instrumentation of its first call runs.
+    Init { expr: Expr },
+    /// `mock_in_tests(function)` or `mock_in_tests(function(args))`, optionally followed by `, instrumented = false`
+    ///
+    /// Function called with the arguments instead of the body under `cfg(test)`, still instrumented unless
+    /// `instrumented = false` is given.
+    MockInTests {
+        func: FunctionSpec,
+        instrumented: bool,
+    },
+    /// `event_id = 0x1042` or `event_id = auto`
+    ///
+    /// Id of the function, bound to `__axin_event_id` for hooks to log compact events with.
+    EventId { keyword: Ident, id: EventId },
+    /// `no_defaults`
+    ///
+    /// Leave out the default arguments from the crate's `axin.toml`.
+    NoDefaults,
+    /// `opt(name1 = value1, name2 = value2, ...)`
+    ///
+    /// Values of the options of the crate's default arguments, substituted for their `__axin_opt("name", default)`
+    /// placeholders.
+    Opt {
+        options: Punctuated<OptionArg, Token![,]>,
+    },
+    /// `twin = "name"`
+    ///
+    /// Name of a copy of the function emitted next to it, with the same visibility, attributes and generics, but without
+    /// any instrumentation.
+    Twin { keyword: Ident, name: Ident },
+    /// `packed = struct`
+    ///
+    /// Pass the arguments to the decorator as one value, of a struct generated for the function with a field for each
+    /// parameter, instead of one by one.
+    Packed { keyword: Ident },
+    /// `phase_timing(function)` or `phase_timing(function(args))`
+    ///
+    /// Function to execute after every call with an `axin::PhaseTimings`, the time the entry hook, the decorator
+    /// outside of the body, the body and the hooks after it took.
+    PhaseTiming { func: FunctionSpec },
+    /// `assert_order("enter,body,exit")`
+    ///
+    /// Phases every call is expected to run, in order. In test builds, each phase that runs is traced, and the call
+    /// asserts that its trace matches before returning.
+    AssertOrder { phases: Vec<String> },
+    /// `profile` or `profile(slow = 250ms)`
+    ///
+    /// Report the timings of every call, and calls slower than the threshold (100ms unless given), to the sinks
+    /// registered with `axin::set_profile_sinks`.
+    Profile { keyword: Ident, slow: Option<Expr> },
+    /// `strict_prologue` or `strict_prologue(deny = [path1, path2, ...])`
+    ///
+    /// Reject prologue statements of all attributes of the function that contain `unsafe` or `static mut`, or mention
+    /// one of the denied paths, so that prologues cannot hide side effects that need reviewing.
+    StrictPrologue { deny: Vec<Path> },
+    /// `stub_missing(hook1, hook2, ...)`
+    ///
+    /// Hooks that do not exist yet, whose calls go to generated functions doing nothing instead, with a warning.
+    StubMissing {
+        keyword: Ident,
+        hooks: Punctuated<Ident, Token![,]>,
+    },
+    /// `preset(name)`
+    ///
+    /// The arguments of the preset `name` from the crate's `axin.toml`, which replace it when the attribute is parsed.
+    Preset { name: Ident },
+    /// `on_panic(function)` or `on_panic(function(args))`
+    ///
+    /// Function to execute when the call unwinds, by a guard dropped during the unwinding.
+    OnPanic { func: FunctionSpec },
+    /// `on_exit_always(function)` or `on_exit_always(function(args))`
+    ///
+    /// Function to execute after the exit hook when the call returns, and by a guard dropped during the unwinding when
+    /// it panics.
+    OnExitAlways { func: FunctionSpec },
+    /// `nested_body`
+    ///
+    /// Run the body in a nested function with the function's signature rather than in a closure, where the nested
+    /// function can see everything the body refers to.
+    NestedBody,
+    /// `generated_prefix = "__obs_"`
+    ///
+    /// Prefix of the placeholders and of the names of generated items, replacing `__axin_`, such as `__obs_result` for
+    /// `__axin_result` and `__obs` for the call context `__axin`, so that names the crate already has with the
+    /// `__axin_` prefix are left alone.
+    GeneratedPrefix { prefix: LitStr },
+}
+
+impl AxinArg {
+    /// The parameter name this argument was given with.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AxinArg::Prologue { .. } => param_names::PROLOGUE,
+            AxinArg::Epilogue { .. } => param_names::EPILOGUE,
+            AxinArg::OnEnter { .. } => param_names::ON_ENTER,
+            AxinArg::OnExit { .. } => param_names::ON_EXIT,
+            AxinArg::OnError { .. } => param_names::ON_ERROR,
+            AxinArg::OnOk { .. } => param_names::ON_OK,
+            AxinArg::OnErr { .. } => param_names::ON_ERR,
+            AxinArg::OrderConst { .. } => param_names::ORDER_CONST,
+            AxinArg::Decorator { .. } => param_names::DECORATOR,
+            AxinArg::DecoratorSelect { .. } => param_names::DECORATOR_SELECT,
+            AxinArg::Adapt => param_names::ADAPT,
+            AxinArg::AssertReturns { .. } => param_names::ASSERT_RETURNS,
+            AxinArg::SlowLog { .. } => param_names::SLOW_LOG,
+            AxinArg::WarnCallSite { .. } => param_names::WARN_CALL_SITE,
+            AxinArg::CountCalls { .. } => param_names::COUNT_CALLS,
+            AxinArg::Minimal { .. } => param_names::MINIMAL,
+            AxinArg::RawBody { .. } => param_names::RAW_BODY,
+            AxinArg::Explain { .. } => param_names::EXPLAIN,
+            AxinArg::WarnIfTrivial => param_names::WARN_IF_TRIVIAL,
+            AxinArg::DenyTrivialDecorator => param_names::DENY_TRIVIAL_DECORATOR,
+            AxinArg::AllowTrivial => param_names::ALLOW_TRIVIAL,
+            AxinArg::Document => param_names::DOCUMENT,
+            AxinArg::InstrumentCompat { .. } => param_names::INSTRUMENT_COMPAT,
+            AxinArg::DebugOnly => param_names::DEBUG_ONLY,
+            AxinArg::Cfg { .. } => param_names::CFG,
+            AxinArg::Skip { .. } => param_names::SKIP,
+            AxinArg::Only { .. } => param_names::ONLY,
+            AxinArg::HookErrors { .. } => param_names::HOOK_ERRORS,
+            AxinArg::HookErrorSink { .. } => param_names::HOOK_ERROR_SINK,
+            AxinArg::Init { .. } => param_names::INIT,
+            AxinArg::MockInTests { .. } => param_names::MOCK_IN_TESTS,
+            AxinArg::EventId { .. } => param_names::EVENT_ID,
+            AxinArg::NoDefaults => param_names::NO_DEFAULTS,
+            AxinArg::Opt { .. } => param_names::OPT,
+            AxinArg::Twin { .. } => param_names::TWIN,
+            AxinArg::Packed { .. } => param_names::PACKED,
+            AxinArg::PhaseTiming { .. } => param_names::PHASE_TIMING,
+            AxinArg::AssertOrder { .. } => param_names::ASSERT_ORDER,
+            AxinArg::Profile { .. } => param_names::PROFILE,
+            AxinArg::StrictPrologue { .. } => param_names::STRICT_PROLOGUE,
+            AxinArg::StubMissing { .. } => param_names::STUB_MISSING,
+            AxinArg::Preset { .. } => param_names::PRESET,
+            AxinArg::OnPanic { .. } => param_names::ON_PANIC,
+            AxinArg::OnExitAlways { .. } => param_names::ON_EXIT_ALWAYS,
+            AxinArg::NestedBody => param_names::NESTED_BODY,
+            AxinArg::GeneratedPrefix { .. } => param_names::GENERATED_PREFIX,
+        }
+    }
+}
+
+impl Parse for AxinArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = AxinArgs {
+            args: Punctuated::parse_terminated(input)?,
+            defaults_file: None,
+        };
+        if args
+            .args
+            .iter()
+            .any(|arg| matches!(arg, AxinArg::Preset { .. }))
+        {
+            let defaults =
+                crate_defaults().map_err(|error| syn::Error::new(Span::call_site(), error))?;
+            args.expand_presets(defaults.as_ref())?;
+        }
+        args.validate()?;
+        Ok(args)
+    }
+}
+
+impl AxinArgs {
+    /// Parse the arguments of `#[axin_use(...)]`, the names of presets, as the argument `preset(name)` for each.
+    pub fn parse_presets(input: ParseStream) -> syn::Result<Self> {
+        let names = Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
+        if names.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                "`axin_use` takes the names of one or more presets of `axin.toml`",
+            ));
+        }
+        let presets = names.iter().map(|name| quote! { preset(#name) });
+        syn::parse2(quote! { #(#presets),* })
+    }
+
+    /// Replace every `preset(name)` by the arguments of the preset from the crate's configuration file `defaults`, in
+    /// place, so that they are checked and merged with the crate's defaults like arguments written out.
+    pub fn expand_presets(&mut self, defaults: Option<&Defaults>) -> syn::Result<()> {
+        let mut expanded = Punctuated::new();
+        for arg in std::mem::take(&mut self.args) {
+            let AxinArg::Preset { name } = arg else {
+                expanded.push(arg);
+                continue;
+            };
+            let Some(defaults) = defaults else {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    "`preset` names a preset of `axin.toml`, which this crate does not have",
+                ));
+            };
+            expanded.extend(defaults.parse_preset(&name)?);
+            self.defaults_file = Some(defaults.path.clone());
+        }
+        self.args = expanded;
+        Ok(())
+    }
+
+    /// Split off the `skip` and `only` filters, which select the functions of a module the attribute applies to.
+    pub fn take_module_filters(&mut self) -> ModuleFilters {
+        let mut filters = ModuleFilters::default();
+        let args = std::mem::take(&mut self.args);
+        for arg in args {
+            match arg {
+                AxinArg::Skip { keyword, fns } => filters.skip.push((keyword, fns)),
+                AxinArg::Only { keyword, fns } => filters.only.push((keyword, fns)),
+                arg => self.args.push(arg),
+            }
+        }
+        filters
+    }
+
+    /// The keyword of an explicit `event_id`, which cannot be shared by the functions of a module or an `impl` block.
+    pub fn explicit_event_id(&self) -> Option<&Ident> {
+        self.args.iter().find_map(|arg| match arg {
+            AxinArg::EventId {
+                keyword,
+                id: EventId::Explicit(_),
+            } => Some(keyword),
+            _ => None,
+        })
+    }
+
+    /// The keyword of a `twin`, which cannot be shared by the functions of a module or an `impl` block.
+    pub fn twin_keyword(&self) -> Option<&Ident> {
+        self.args.iter().find_map(|arg| match arg {
+            AxinArg::Twin { keyword, .. } => Some(keyword),
+            _ => None,
+        })
+    }
+
+    /// The keyword of `order_const`, if given.
+    pub fn order_const_keyword(&self) -> Option<&Ident> {
+        self.args.iter().find_map(|arg| match arg {
+            AxinArg::OrderConst { keyword } => Some(keyword),
+            _ => None,
+        })
+    }
+
+    /// The prefix given with `generated_prefix`, if any.
+    pub fn generated_prefix(&self) -> Option<&LitStr> {
+        self.args.iter().find_map(|arg| match arg {
+            AxinArg::GeneratedPrefix { prefix } => Some(prefix),
+            _ => None,
+        })
+    }
+
+    /// Use `prefix` for the placeholders and generated names, unless the attribute gives a prefix of its own.
+    pub fn inherit_prefix(&mut self, prefix: Option<&LitStr>) {
+        if let (None, Some(prefix)) = (self.generated_prefix(), prefix) {
+            self.args.push(AxinArg::GeneratedPrefix {
+                prefix: prefix.clone(),
+            });
+        }
+    }
+
+    /// The names of the twins given with `twin = "name"`.
+    pub fn twins(&self) -> impl Iterator<Item = &Ident> {
+        self.args.iter().filter_map(|arg| match arg {
+            AxinArg::Twin { name, .. } => Some(name),
+            _ => None,
+        })
+    }
+
+    /// Split off the twins, which are emitted from the function before it is instrumented.
+    pub fn remove_twins(&mut self) {
+        self.remove_matching(|arg| matches!(arg, AxinArg::Twin { .. }));
+    }
+
+    /// Split off `debug_only` and the `cfg` predicates, returning the predicate the builds instrumenting the function
+    /// satisfy, if any, see [`AxinArgs::gate`].
+    pub fn take_gate(&mut self) -> Option<TokenStream> {
+        let gate = self.gate();
+        self.remove_matching(|arg| matches!(arg, AxinArg::DebugOnly | AxinArg::Cfg { .. }));
+        gate
+    }
+
+    /// Split off the `no_defaults` flag, returning whether it was present.
+    pub fn take_no_defaults(&mut self) -> bool {
+        self.remove_matching(|arg| matches!(arg, AxinArg::NoDefaults))
+    }
+
+    /// Split off the options, which are substituted into the crate's defaults before.
+    pub fn remove_options(&mut self) {
+        self.remove_matching(|arg| matches!(arg, AxinArg::Opt { .. }));
+    }
+
+    /// The options given in all `opt(...)` arguments.
+    pub fn options(&self) -> impl Iterator<Item = &OptionArg> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                AxinArg::Opt { options } => Some(options),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Whether the `no_defaults` flag is present.
+    pub fn has_no_defaults(&self) -> bool {
+        self.args
+            .iter()
+            .any(|arg| matches!(arg, AxinArg::NoDefaults))
+    }
+
+    /// The configuration predicate the builds the attribute instruments the function in satisfy, if it does not in all
+    /// of them: `debug_assertions` with `debug_only`, the predicate of `cfg(...)`, or all of these if several are given.
+    pub fn gate(&self) -> Option<TokenStream> {
+        let predicates: Vec<TokenStream> = self
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                AxinArg::DebugOnly => Some(quote! { debug_assertions }),
+                AxinArg::Cfg { predicate, .. } => Some(predicate.to_token_stream()),
+                _ => None,
+            })
+            .collect();
+        match predicates.as_slice() {
+            [] => None,
+            [predicate] => Some(predicate.clone()),
+            predicates => Some(quote! { all(#(#predicates),*) }),
+        }
+    }
+
+    /// Remove all arguments `matches` holds for, returning whether there were any.
+    fn remove_matching(&mut self, matches: impl Fn(&AxinArg) -> bool) -> bool {
+        let len = self.args.len();
+        let args = std::mem::take(&mut self.args);
+        self.args = args.into_iter().filter(|arg| !matches(arg)).collect();
+        self.args.len() != len
+    }
+
+    /// Check that the arguments can be used together. Run on parsing, and again after merging in the crate's defaults.
+    pub fn validate(&self) -> syn::Result<()> {
+        self.validate_minimal()?;
+        self.validate_raw_body()?;
+        self.validate_decorators()?;
+        self.validate_packed()?;
+        self.validate_profile()?;
+        self.validate_hook_errors()
+    }
+
+    /// Check that `decorator` and `decorator_select` are not used together, since a function has only one place to put
+    /// a decorator in, and that only the first of several decorators borrows the arguments: the others are nested in
+    /// it and set up after the arguments are moved into its call.
+    fn validate_decorators(&self) -> syn::Result<()> {
+        let mut decorators = self.args.iter().filter_map(|arg| match arg {
+            AxinArg::Decorator {
+                func, borrow_args, ..
+            } => Some((func, *borrow_args)),
+            _ => None,
+        });
+        let has_decorator = decorators.next().is_some();
+        if let Some((func, _)) = decorators.find(|(_, borrow_args)| *borrow_args) {
+            return Err(syn::Error::new_spanned(
+                func,
+                "`borrow_args` can only be given to the first decorator, which is set up before the arguments are \
+                 moved into the call",
+            ));
+        }
+        let select_keyword = self.args.iter().find_map(|arg| match arg {
+            AxinArg::DecoratorSelect { keyword, .. } => Some(keyword),
+            _ => None,
+        });
+        match select_keyword {
+            Some(keyword) if has_decorator => Err(syn::Error::new_spanned(
+                keyword,
+                "`decorator_select` cannot be combined with `decorator`; add the decorator to the selection instead",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that packed arguments are passed to a decorator, which is not adapted to take none.
+    fn validate_packed(&self) -> syn::Result<()> {
+        let Some(keyword) = self.args.iter().find_map(|arg| match arg {
+            AxinArg::Packed { keyword } => Some(keyword),
+            _ => None,
+        }) else {
+            return Ok(());
+        };
+
+        let decorated = self.args.iter().any(|arg| {
+            matches!(
+                arg,
+                AxinArg::Decorator { .. } | AxinArg::DecoratorSelect { .. }
+            )
+        });
+        if !decorated {
+            return Err(syn::Error::new_spanned(
+                keyword,
+                "`packed` only changes how the arguments are passed to a decorator, and there is none",
+            ));
+        }
+        let adapted = self.args.iter().find_map(|arg| match arg {
+            AxinArg::Adapt => Some("adapt"),
+            AxinArg::Decorator { no_args: true, .. } => Some("no_args"),
+            _ => None,
+        });
+        if let Some(adapted) = adapted {
+            return Err(syn::Error::new_spanned(
+                keyword,
+                format!(
+                    "`packed` cannot be combined with `{}`, which passes no arguments to the decorator",
+                    adapted
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that `profile` is not combined with the parameters it is made of, which a function can only have one of.
+    fn validate_profile(&self) -> syn::Result<()> {
+        let Some(keyword) = self.args.iter().find_map(|arg| match arg {
+            AxinArg::Profile { keyword, .. } => Some(keyword),
+            _ => None,
+        }) else {
+            return Ok(());
+        };
+        match self
+            .args
+            .iter()
+            .find(|arg| matches!(arg, AxinArg::PhaseTiming { .. } | AxinArg::SlowLog { .. }))
+        {
+            Some(arg) => Err(syn::Error::new_spanned(
+                keyword,
+                format!(
+                    "`profile` cannot be combined with `{}`, which it already includes; report to the profile sinks \
+                     instead, or replace `profile` with `phase_timing` and `slow_log`",
+                    arg.name()
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that a `hook_error_sink` is given if any hook errors are to be logged.
+    fn validate_hook_errors(&self) -> syn::Result<()> {
+        if self
+            .args
+            .iter()
+            .any(|arg| matches!(arg, AxinArg::HookErrorSink { .. }))
+        {
+            return Ok(());
+        }
+
+        for arg in &self.args {
+            let keyword = match arg {
+                AxinArg::HookErrors {
+                    keyword,
+                    policy: HookErrorPolicy::Log,
+                } => keyword.to_token_stream(),
+                AxinArg::OnEnter {
+                    func,
+                    errors: Some(HookErrorPolicy::Log),
+                    ..
+                }
+                | AxinArg::OnExit {
+                    func,
+                    errors: Some(HookErrorPolicy::Log),
+                    ..
+                }
+                | AxinArg::OnError {
+                    func,
+                    errors: Some(HookErrorPolicy::Log),
+                }
+                | AxinArg::OnOk {
+                    func,
+                    errors: Some(HookErrorPolicy::Log),
+                }
+                | AxinArg::OnErr {
+                    func,
+                    errors: Some(HookErrorPolicy::Log),
+                } => func.to_token_stream(),
+                _ => continue,
+            };
+            return Err(syn::Error::new_spanned(
+                keyword,
+                "logging hook errors needs a `hook_error_sink = function` to log them with",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that only parameters compatible with `minimal` mode are used together with it.
+    ///
+    /// Minimal mode emits the original body unchanged, so nothing may run after the body or wrap it, and entry hooks
+    /// must be argument-less.
+    fn validate_minimal(&self) -> syn::Result<()> {
+        let Some(keyword) = self.args.iter().find_map(|arg| match arg {
+            AxinArg::Minimal { keyword } => Some(keyword),
+            _ => None,
+        }) else {
+            return Ok(());
+        };
+
+        for arg in &self.args {
+            match arg {
+                AxinArg::Minimal { .. }
+                | AxinArg::CountCalls { .. }
+                | AxinArg::Init { .. }
+                | AxinArg::WarnCallSite { .. }
+                | AxinArg::Explain { .. }
+                | AxinArg::WarnIfTrivial
+                | AxinArg::DenyTrivialDecorator
+                | AxinArg::AllowTrivial
+                | AxinArg::Document
+                | AxinArg::OrderConst { .. }
+                | AxinArg::DebugOnly
+                | AxinArg::Cfg { .. }
+                | AxinArg::NoDefaults
+                | AxinArg::Opt { .. }
+                | AxinArg::Twin { .. }
+                | AxinArg::Skip { .. }
+                | AxinArg::Only { .. }
+                | AxinArg::StrictPrologue { .. }
+                | AxinArg::StubMissing { .. }
+                | AxinArg::GeneratedPrefix { .. } => {}
+                AxinArg::OnEnter {
+                    func: FunctionSpec::Simple(..),
+                    errors: None,
+                    with_args: None,
+                    ..
+                } => {}
+                AxinArg::OnEnter {
+                    errors: Some(_), ..
+                } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        "`minimal` mode does not support handling the errors of hooks",
+                    ));
+                }
+                AxinArg::OnEnter { .. } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        "`minimal` mode only supports entry hooks without arguments",
+                    ));
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        format!(
+                            "`minimal` mode does not support '{}', since it emits the function body unchanged",
+                            arg.name(),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that only parameters compatible with `raw_body` are used together with it.
+    ///
+    /// The body is emitted unchanged rather than wrapped in a callable, so nothing may wrap it or inspect its result,
+    /// and the exit hook is run by a guard dropped when the body returns, where errors of hooks cannot be handled.
+    fn validate_raw_body(&self) -> syn::Result<()> {
+        let Some(keyword) = self.args.iter().find_map(|arg| match arg {
+            AxinArg::RawBody { keyword } => Some(keyword),
+            _ => None,
+        }) else {
+            return Ok(());
+        };
+
+        for arg in &self.args {
+            match arg {
+                AxinArg::RawBody { .. }
+                | AxinArg::CountCalls { .. }
+                | AxinArg::Init { .. }
+                | AxinArg::WarnCallSite { .. }
+                | AxinArg::Explain { .. }
+                | AxinArg::WarnIfTrivial
+                | AxinArg::DenyTrivialDecorator
+                | AxinArg::AllowTrivial
+                | AxinArg::Document
+                | AxinArg::OrderConst { .. }
+                | AxinArg::DebugOnly
+                | AxinArg::Cfg { .. }
+                | AxinArg::NoDefaults
+                | AxinArg::Opt { .. }
+                | AxinArg::Twin { .. }
+                | AxinArg::Skip { .. }
+                | AxinArg::Only { .. }
+                | AxinArg::StrictPrologue { .. }
+                | AxinArg::StubMissing { .. }
+                | AxinArg::GeneratedPrefix { .. }
+                | AxinArg::EventId { .. }
+                | AxinArg::OnEnter { errors: None, .. }
+                | AxinArg::OnExit {
+                    when: None,
+                    errors: None,
+                    after_drop: None,
+                    ..
+                } => {}
+                AxinArg::OnExit {
+                    after_drop: Some(after_drop),
+                    ..
+                } => {
+                    return Err(syn::Error::new_spanned(
+                        after_drop,
+                        "`raw_body` does not support `after_drop`, since the exit hook's guard is dropped before the \
+                         function's arguments",
+                    ));
+                }
+                AxinArg::OnEnter { .. } | AxinArg::OnExit { when: None, .. } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        "`raw_body` does not support handling the errors of hooks",
+                    ));
+                }
+                AxinArg::OnExit { .. } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        "`raw_body` does not support `when` on exit hooks, since the result is returned directly",
+                    ));
+                }
+                AxinArg::Decorator { .. } | AxinArg::DecoratorSelect { .. } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        format!(
+                            "`raw_body` does not support '{}', since the body is not wrapped in a callable to \
+                             decorate",
+                            arg.name(),
+                        ),
+                    ));
+                }
+                AxinArg::Prologue { .. } | AxinArg::Epilogue { .. } => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        format!(
+                            "`raw_body` does not support '{}', since it emits the function body unchanged; write the \
+                             statements into the body instead",
+                            arg.name(),
+                        ),
+                    ));
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        keyword,
+                        format!(
+                            "`raw_body` does not support '{}', since it emits the function body unchanged",
+                            arg.name(),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Parse for AxinArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let name_str = name.to_string();
+        if !param_names::ALL_PARAMS.contains(&name_str.as_str()) {
+            let supported_params = param_names::ALL_PARAMS.join(", ");
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "Unsupported parameter: '{}'. Supported parameters are: {}",
+                    name_str, supported_params
+                ),
+            ));
+        }
+
+        match name_str.as_str() {
+            param_names::MINIMAL => return Ok(AxinArg::Minimal { keyword: name }),
+            param_names::RAW_BODY => return Ok(AxinArg::RawBody { keyword: name }),
+            param_names::EXPLAIN => return Ok(AxinArg::Explain { keyword: name }),
+            param_names::WARN_IF_TRIVIAL => return Ok(AxinArg::WarnIfTrivial),
+            param_names::DENY_TRIVIAL_DECORATOR => return Ok(AxinArg::DenyTrivialDecorator),
+            param_names::ALLOW_TRIVIAL => return Ok(AxinArg::AllowTrivial),
+            param_names::DOCUMENT => return Ok(AxinArg::Document),
+            param_names::ORDER_CONST => return Ok(AxinArg::OrderConst { keyword: name }),
+            param_names::ADAPT => return Ok(AxinArg::Adapt),
+            param_names::NESTED_BODY => return Ok(AxinArg::NestedBody),
+            param_names::DEBUG_ONLY => return Ok(AxinArg::DebugOnly),
+            param_names::NO_DEFAULTS => return Ok(AxinArg::NoDefaults),
+            param_names::SKIP if !input.peek(syn::token::Paren) => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`#[axin(skip)]` marks a function of a module or a method of an `impl` block that `axin` is \
+                     applied to, to leave it alone, and cannot be used anywhere else",
+                ));
+            }
+            param_names::STRICT_PROLOGUE if !input.peek(syn::token::Paren) => {
+                return Ok(AxinArg::StrictPrologue { deny: Vec::new() });
+            }
+            param_names::PROFILE if !input.peek(syn::token::Paren) => {
+                return Ok(AxinArg::Profile {
+                    keyword: name,
+                    slow: None,
+                });
+            }
+            param_names::HOOK_ERRORS => {
+                input.parse::<Token![=]>()?;
+                return Ok(AxinArg::HookErrors {
+                    keyword: name,
+                    policy: input.parse()?,
+                });
+            }
+            param_names::PACKED => {
+                input.parse::<Token![=]>()?;
+                if input.parse::<Option<Token![struct]>>()?.is_none() {
+                    return Err(input
+                        .error("expected `struct`, the only representation of packed arguments"));
+                }
+                return Ok(AxinArg::Packed { keyword: name });
+            }
+            param_names::TWIN => {
+                input.parse::<Token![=]>()?;
+                let twin: LitStr = input.parse()?;
+                return Ok(AxinArg::Twin {
+                    keyword: name,
+                    name: twin.parse().map_err(|_| {
+                        syn::Error::new_spanned(&twin, "expected the name of a function")
+                    })?,
+                });
+            }
+            param_names::EVENT_ID => {
+                input.parse::<Token![=]>()?;
+                return Ok(AxinArg::EventId {
+                    keyword: name,
+                    id: input.parse()?,
+                });
+            }
+            param_names::GENERATED_PREFIX => {
+                input.parse::<Token![=]>()?;
+                let prefix: LitStr = input.parse()?;
+                let value = prefix.value();
+                let stem = value.strip_suffix('_').unwrap_or_default();
+                if syn::parse_str::<Ident>(stem).is_err()
+                    || syn::parse_str::<Ident>(&value).is_err()
+                {
+                    return Err(syn::Error::new_spanned(
+                        &prefix,
+                        "expected a prefix ending in `_` that makes identifiers with and without the `_`, like \
+                         \"__obs_\"",
+                    ));
+                }
+                return Ok(AxinArg::GeneratedPrefix { prefix });
+            }
+            param_names::HOOK_ERROR_SINK => {
+                input.parse::<Token![=]>()?;
+                return Ok(AxinArg::HookErrorSink {
+                    func: input.parse()?,
+                });
+            }
+            _ => {}
+        }
+
+        let content;
+        parenthesized!(content in input);
+
+        let arg = match name_str.as_str() {
+            param_names::PROLOGUE => AxinArg::Prologue {
+                stmts: content.call(Block::parse_within)?,
+            },
+            param_names::EPILOGUE => AxinArg::Epilogue {
+                stmts: content.call(Block::parse_within)?,
+            },
+            param_names::ON_ENTER => {
+                let mut func = content.parse()?;
+                let HookModifiers {
+                    when,
+                    errors,
+                    with_args,
+                    ..
+                } = HookModifiers::parse(&content, param_names::ON_ENTER, &mut func)?;
+                AxinArg::OnEnter {
+                    func,
+                    when,
+                    errors,
+                    with_args,
+                }
+            }
+            param_names::ON_EXIT => {
+                let mut func = content.parse()?;
+                let HookModifiers {
+                    when,
+                    errors,
+                    after_drop,
+                    ..
+                } = HookModifiers::parse(&content, param_names::ON_EXIT, &mut func)?;
+                AxinArg::OnExit {
+                    func,
+                    when,
+                    errors,
+                    after_drop,
+                }
+            }
+            param_names::ON_ERROR => {
+                let mut func = content.parse()?;
+                let HookModifiers { errors, .. } =
+                    HookModifiers::parse(&content, param_names::ON_ERROR, &mut func)?;
+                AxinArg::OnError { func, errors }
+            }
+            param_names::ON_OK => {
+                let mut func = content.parse()?;
+                let HookModifiers { errors, .. } =
+                    HookModifiers::parse(&content, param_names::ON_OK, &mut func)?;
+                AxinArg::OnOk { func, errors }
+            }
+            param_names::ON_ERR => {
+                let mut func = content.parse()?;
+                let HookModifiers { errors, .. } =
+                    HookModifiers::parse(&content, param_names::ON_ERR, &mut func)?;
+                AxinArg::OnErr { func, errors }
+            }
+            param_names::DECORATOR => {
+                let func = content.parse()?;
+                let DecoratorModifiers {
+                    borrow_args,
+                    no_args,
+                } = DecoratorModifiers::parse(&content)?;
+                AxinArg::Decorator {
+                    func,
+                    borrow_args,
+                    no_args,
+                }
+            }
+            param_names::ASSERT_RETURNS => AxinArg::AssertReturns {
+                expected: content.parse()?,
+            },
+            param_names::INIT => AxinArg::Init {
+                expr: content.parse()?,
+            },
+            param_names::CFG => AxinArg::Cfg {
+                predicate: content.parse()?,
+            },
+            param_names::MOCK_IN_TESTS => {
+                let func = content.parse()?;
+                let instrumented = parse_instrumented(&content)?;
+                AxinArg::MockInTests { func, instrumented }
+            }
+            param_names::DECORATOR_SELECT => {
+                let selector = content.parse()?;
+                content.parse::<Token![,]>()?;
+                let list;
+                let brackets = bracketed!(list in content);
+                let decorators = Punctuated::parse_terminated(&list)?;
+                if decorators.is_empty() {
+                    return Err(syn::Error::new(
+                        brackets.span.join(),
+                        "`decorator_select` needs at least one decorator to select from",
+                    ));
+                }
+                AxinArg::DecoratorSelect {
+                    keyword: name,
+                    selector,
+                    decorators,
+                }
+            }
+            param_names::SLOW_LOG => {
+                let threshold = content.parse()?;
+                content.parse::<Token![,]>()?;
+                let func = content.parse()?;
+                AxinArg::SlowLog { threshold, func }
+            }
+            param_names::WARN_CALL_SITE => AxinArg::WarnCallSite {
+                message: content.parse()?,
+            },
+            param_names::COUNT_CALLS => AxinArg::CountCalls {
+                counter: content.parse()?,
+            },
+            param_names::PHASE_TIMING => AxinArg::PhaseTiming {
+                func: content.parse()?,
+            },
+            param_names::ON_PANIC => AxinArg::OnPanic {
+                func: content.parse()?,
+            },
+            param_names::ON_EXIT_ALWAYS => AxinArg::OnExitAlways {
+                func: content.parse()?,
+            },
+            param_names::ASSERT_ORDER => AxinArg::AssertOrder {
+                phases: parse_order_phases(&content.parse()?)?,
+            },
+            param_names::PROFILE => AxinArg::Profile {
+                keyword: name,
+                slow: parse_profile_options(&content)?,
+            },
+            param_names::STRICT_PROLOGUE => {
+                let option: Ident = content.parse()?;
+                if option != "deny" {
+                    return Err(syn::Error::new_spanned(
+                        &option,
+                        format!(
+                            "unknown option `{}` of `strict_prologue`, expected `deny`",
+                            option
+                        ),
+                    ));
+                }
+                content.parse::<Token![=]>()?;
+                let list;
+                bracketed!(list in content);
+                AxinArg::StrictPrologue {
+                    deny: Punctuated::<Path, Token![,]>::parse_terminated(&list)?
+                        .into_iter()
+                        .collect(),
+                }
+            }
+            param_names::PRESET => AxinArg::Preset {
+                name: content.parse()?,
+            },
+            param_names::STUB_MISSING => AxinArg::StubMissing {
+                keyword: name,
+                hooks: Punctuated::parse_terminated(&content)?,
+            },
+            param_names::SKIP => AxinArg::Skip {
+                keyword: name,
+                fns: Punctuated::parse_terminated(&content)?,
+            },
+            param_names::ONLY => AxinArg::Only {
+                keyword: name,
+                fns: Punctuated::parse_terminated(&content)?,
+            },
+            param_names::OPT => AxinArg::Opt {
+                options: Punctuated::parse_terminated(&content)?,
+            },
+            param_names::INSTRUMENT_COMPAT => {
+                if cfg!(not(feature = "tracing")) {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "`instrument_compat` requires the `tracing` feature of axin",
+                    ));
+                }
+                AxinArg::InstrumentCompat {
+                    options: content.parse()?,
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        // A trailing comma after the last value is fine, as in any other list
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+        Ok(arg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse attribute arguments, panicking with the error message if they are rejected.
+    fn parse(args: &str) -> AxinArgs {
+        syn::parse_str(args).unwrap_or_else(|error| panic!("`{}` was rejected: {}", args, error))
+    }
+
+    /// The arguments of a parameterized function specification.
+    fn spec_args(spec: &FunctionSpec) -> Vec<String> {
+        match spec {
+            FunctionSpec::Simple(..) => Vec::new(),
+            FunctionSpec::WithArgs(_, args, _) => args
+                .iter()
+                .map(|arg| arg.to_token_stream().to_string())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn trailing_comma_after_arguments() {
+        let args = parse("on_enter(a), decorator(d),");
+        assert_eq!(args.args.len(), 2);
+    }
+
+    #[test]
+    fn trailing_comma_in_function_arguments() {
+        let args = parse(r#"on_enter(trace("a", 1,))"#);
+        let AxinArg::OnEnter { func, .. } = &args.args[0] else {
+            panic!("expected an entry hook");
+        };
+        assert_eq!(spec_args(func), ["\"a\"", "1"]);
+    }
+
+    #[test]
+    fn trailing_comma_after_single_values() {
+        for args in [
+            "on_enter(a,)",
+            "on_error(report(1),)",
+            "decorator(d,)",
+            "decorator(d, borrow_args,)",
+            "decorator(d, borrow_args, no_args,)",
+            "mock_in_tests(fake,)",
+            "mock_in_tests(fake, instrumented = false,)",
+            "assert_returns(42,)",
+            "warn_call_site(\"old\",)",
+            "count_calls(CALLS,)",
+            "slow_log(LIMIT, report,)",
+            "on_exit(cleanup,)",
+            "on_exit(cleanup, when = result.is_err(),)",
+        ] {
+            assert_eq!(parse(args).args.len(), 1, "`{}`", args);
+        }
+    }
+
+    #[test]
+    fn trailing_comma_in_lists() {
+        let args = parse("decorator_select(mode(), [fast, slow,],), skip(a, b,), only(c,)");
+        let AxinArg::DecoratorSelect { decorators, .. } = &args.args[0] else {
+            panic!("expected `decorator_select`");
+        };
+        assert_eq!(decorators.len(), 2);
+        let AxinArg::Skip { fns, .. } = &args.args[1] else {
+            panic!("expected `skip`");
+        };
+        assert_eq!(fns.len(), 2);
+    }
+
+    #[test]
+    fn comment_only_groups_are_empty() {
+        let args = parse(
+            "on_enter(trace(/* no arguments yet */)), prologue(// nothing to prepare\n), skip(\n// none\n)",
+        );
+        let AxinArg::OnEnter { func, .. } = &args.args[0] else {
+            panic!("expected an entry hook");
+        };
+        assert!(spec_args(func).is_empty());
+        assert!(matches!(&args.args[1], AxinArg::Prologue { stmts } if stmts.is_empty()));
+        assert!(matches!(&args.args[2], AxinArg::Skip { fns, .. } if fns.is_empty()));
+    }
+
+    #[test]
+    fn comments_between_arguments() {
+        let args = parse(
+            "on_enter(trace /* hook */), // timing\n decorator(timing(1, /* retries */ 3)), /* done */",
+        );
+        assert_eq!(args.args.len(), 2);
+        let AxinArg::Decorator { func, .. } = &args.args[1] else {
+            panic!("expected a decorator");
+        };
+        assert_eq!(spec_args(func), ["1", "3"]);
+    }
+
+    #[test]
+    fn rustfmt_formatted_attribute() {
+        // As rustfmt formats an attribute too long for one line
+        let args = parse(
+            r#"
+    prologue(let started = std::time::Instant::now();),
+    on_enter(trace(
+        "loading", // what is happening
+        &__axin,
+    )),
+    decorator_select(
+        mode(),
+        [
+            fast,
+            retry(3), // flaky backends
+        ],
+    ),
+    on_exit(
+        alert_oncall,
+        when = result.is_err(),
+    ),
+    slow_log(
+        std::time::Duration::from_millis(100),
+        report_slow(&__axin),
+    ),
+"#,
+        );
+        let names: Vec<_> = args.args.iter().map(AxinArg::name).collect();
+        assert_eq!(
+            names,
+            [
+                "prologue",
+                "on_enter",
+                "decorator_select",
+                "on_exit",
+                "slow_log"
+            ]
+        );
+        let AxinArg::OnEnter { func, .. } = &args.args[1] else {
+            panic!("expected an entry hook");
+        };
+        assert_eq!(spec_args(func), ["\"loading\"", "& __axin"]);
+        let AxinArg::OnExit { when, .. } = &args.args[3] else {
+            panic!("expected an exit hook");
+        };
+        assert!(when.is_some());
+    }
+
+    #[test]
+    fn double_commas_are_rejected() {
+        for args in [
+            "on_enter(a),, decorator(d)",
+            "on_enter(a,,)",
+            "decorator(d,, borrow_args)",
+            "skip(a,, b)",
+        ] {
+            assert!(syn::parse_str::<AxinArgs>(args).is_err(), "`{}`", args);
+        }
+    }
+}
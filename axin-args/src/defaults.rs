@@ -0,0 +1,918 @@
+//! Crate-wide default arguments and presets, read from an `axin.toml` at the root of the crate or its workspace.
+//!
+//! The file is read with a small subset of TOML, which is all it needs:
+//!
+//! ```toml
+//! [defaults]
+//! # Arguments as written in `#[axin(...)]`, either as one string or as an array of strings
+//! args = ["on_enter(crate::trace(&__axin))", "count_calls(crate::CALLS)"]
+//!
+//! # Arguments that `preset(service)` stands for
+//! [presets.service]
+//! args = "decorator(crate::retry), on_error(crate::alert)"
+//! ```
+//!
+//! The defaults may leave values to each function, with `__axin_opt("name", default)` placeholders replaced by the
+//! value of the function's `opt(name = value)`, or by the default if the function does not give one. Presets are the
+//! same for every function using them, and take no options.
+
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+    time::SystemTime,
+};
+use syn::{
+    parse::{Parse, ParseStream, Parser as _},
+    parse_quote,
+    punctuated::Punctuated,
+    Ident, LitStr, Stmt, Token,
+};
+
+use crate::args::{AxinArg, AxinArgs, EventId, OptionArg, OptionValue};
+
+/// Name of the configuration file.
+const FILE_NAME: &str = "axin.toml";
+/// Name of the placeholders standing for the value of an option in the defaults.
+const OPTION_PLACEHOLDER: &str = "__axin_opt";
+
+/// The defaults and presets read from a configuration file.
+#[derive(Clone)]
+pub struct Defaults {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// The default arguments, joined into one comma-separated list.
+    pub args: String,
+    /// The presets by name, with their arguments joined into one comma-separated list, in the order of the file.
+    pub presets: Vec<(String, String)>,
+}
+
+impl Defaults {
+    /// Parse the default arguments with the given options substituted for their placeholders, rejecting arguments
+    /// that cannot apply to every function alike.
+    pub fn parse_args(&self, options: &[&OptionArg]) -> syn::Result<AxinArgs> {
+        let error = |message: String| {
+            syn::Error::new(
+                Span::call_site(),
+                format!("invalid defaults in `{}`: {}", self.path.display(), message),
+            )
+        };
+        for (index, option) in options.iter().enumerate() {
+            if options[..index]
+                .iter()
+                .any(|other| other.name == option.name)
+            {
+                return Err(syn::Error::new_spanned(
+                    &option.name,
+                    format!("the option `{}` is given more than once", option.name),
+                ));
+            }
+        }
+
+        let tokens: TokenStream = self
+            .args
+            .parse()
+            .map_err(|lex_error: proc_macro2::LexError| error(lex_error.to_string()))?;
+        let mut substitution = Substitution {
+            defaults: self,
+            options,
+            names: Vec::new(),
+        };
+        let tokens = substitution.substitute(tokens)?;
+        if let Some(unknown) = options
+            .iter()
+            .find(|option| !substitution.names.contains(&option.name.to_string()))
+        {
+            let known = match substitution.names.as_slice() {
+                [] => "no options".to_string(),
+                names => {
+                    let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+                    format!("the options {}", names.join(", "))
+                }
+            };
+            return Err(syn::Error::new_spanned(
+                &unknown.name,
+                format!(
+                    "unknown option `{}`, the defaults in `{}` take {}",
+                    unknown.name,
+                    self.path.display(),
+                    known
+                ),
+            ));
+        }
+
+        let args = parse_shared_args(tokens, "a default", &error)?;
+        let args = AxinArgs {
+            args,
+            defaults_file: None,
+        };
+        args.validate()
+            .map_err(|parse_error| error(parse_error.to_string()))?;
+        Ok(args)
+    }
+
+    /// Parse the arguments of the preset `name`, which are checked together with the other arguments of the attribute
+    /// using the preset. Errors point at the name.
+    pub fn parse_preset(&self, name: &Ident) -> syn::Result<Punctuated<AxinArg, Token![,]>> {
+        let Some((_, args)) = self.presets.iter().find(|(preset, _)| name == preset) else {
+            let known = match self.presets.as_slice() {
+                [] => "no presets".to_string(),
+                presets => {
+                    let names: Vec<_> = presets
+                        .iter()
+                        .map(|(name, _)| format!("`{}`", name))
+                        .collect();
+                    format!("the presets {}", names.join(", "))
+                }
+            };
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "unknown preset `{}`, `{}` defines {}",
+                    name,
+                    self.path.display(),
+                    known
+                ),
+            ));
+        };
+        let error = |message: String| {
+            syn::Error::new_spanned(
+                name,
+                format!(
+                    "invalid preset `{}` in `{}`: {}",
+                    name,
+                    self.path.display(),
+                    message
+                ),
+            )
+        };
+        let tokens: TokenStream = args
+            .parse()
+            .map_err(|lex_error: proc_macro2::LexError| error(lex_error.to_string()))?;
+        if mentions_placeholder(tokens.clone()) {
+            return Err(error(format!(
+                "presets take no options, so `{}` can only be used in the defaults",
+                OPTION_PLACEHOLDER
+            )));
+        }
+        parse_shared_args(tokens, "part of a preset", &error)
+    }
+}
+
+/// Parse arguments shared by many functions, rejecting those that cannot apply to every function alike, and presets,
+/// which only attributes may use. `what` tells what the arguments are, as in "'skip' cannot be a default".
+fn parse_shared_args(
+    tokens: TokenStream,
+    what: &str,
+    error: &dyn Fn(String) -> syn::Error,
+) -> syn::Result<Punctuated<AxinArg, Token![,]>> {
+    let args = Punctuated::<AxinArg, Token![,]>::parse_terminated
+        .parse2(tokens)
+        .map_err(|parse_error| error(parse_error.to_string()))?;
+    for arg in &args {
+        match arg {
+            AxinArg::Skip { .. }
+            | AxinArg::Only { .. }
+            | AxinArg::NoDefaults
+            | AxinArg::Opt { .. }
+            | AxinArg::Twin { .. }
+            | AxinArg::Preset { .. } => {
+                return Err(error(format!("'{}' cannot be {}", arg.name(), what)));
+            }
+            AxinArg::EventId {
+                id: EventId::Explicit(_),
+                ..
+            } => {
+                return Err(error(
+                    "an explicit `event_id` would be shared by all functions of the crate; use `event_id = auto`"
+                        .to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(args)
+}
+
+/// Whether `tokens` contain an option placeholder, including inside nested groups.
+fn mentions_placeholder(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == OPTION_PLACEHOLDER,
+        TokenTree::Group(group) => mentions_placeholder(group.stream()),
+        _ => false,
+    })
+}
+
+/// The placeholder of an option, `__axin_opt("name", default)`, without its name.
+struct Placeholder {
+    name: LitStr,
+    default: OptionValue,
+}
+
+impl Parse for Placeholder {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let default = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Placeholder { name, default })
+    }
+}
+
+/// Replacement of the option placeholders in the defaults by the values a function gives.
+struct Substitution<'a> {
+    defaults: &'a Defaults,
+    options: &'a [&'a OptionArg],
+    /// Names of the options the placeholders found so far stand for.
+    names: Vec<String>,
+}
+
+impl Substitution<'_> {
+    /// Replace the placeholders in `tokens`, nested ones included.
+    fn substitute(&mut self, tokens: TokenStream) -> syn::Result<TokenStream> {
+        let mut output = TokenStream::new();
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Ident(ident) if ident == OPTION_PLACEHOLDER => match tokens.next() {
+                    Some(TokenTree::Group(group))
+                        if group.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        output.extend(self.value(group.stream())?);
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            format!(
+                                "invalid defaults in `{}`: expected `{}(\"name\", default)`",
+                                self.defaults.path.display(),
+                                OPTION_PLACEHOLDER
+                            ),
+                        ));
+                    }
+                },
+                TokenTree::Group(group) => {
+                    let mut substituted =
+                        Group::new(group.delimiter(), self.substitute(group.stream())?);
+                    substituted.set_span(group.span());
+                    output.extend([TokenTree::Group(substituted)]);
+                }
+                token => output.extend([token]),
+            }
+        }
+        Ok(output)
+    }
+
+    /// The value of the option whose placeholder has the arguments `args`, checked to be of the kind of its default.
+    fn value(&mut self, args: TokenStream) -> syn::Result<TokenStream> {
+        let path = self.defaults.path.display();
+        let Placeholder { name, default } = syn::parse2(args).map_err(|error| {
+            syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "invalid defaults in `{}`: expected `{}(\"name\", default)`, {}",
+                    path, OPTION_PLACEHOLDER, error
+                ),
+            )
+        })?;
+        let name = name.value();
+        let option = self.options.iter().find(|option| option.name == name);
+        self.names.push(name);
+        let Some(option) = option else {
+            return Ok(default.into_token_stream());
+        };
+        if option.value.kind() != default.kind() {
+            return Err(syn::Error::new_spanned(
+                &option.value,
+                format!(
+                    "the option `{}` takes {}, like its default in `{}`, but is given {}",
+                    option.name,
+                    default.kind(),
+                    path,
+                    option.value.kind()
+                ),
+            ));
+        }
+        Ok(option.value.to_token_stream())
+    }
+}
+
+/// The defaults of the crate being compiled, if it has a configuration file.
+///
+/// The file is looked for in the directory of the crate's manifest, then in its ancestors up to the root of the
+/// workspace, i.e. the first one whose manifest has a `[workspace]` table. A crate outside of any workspace only has
+/// the file next to its manifest, so a stray `axin.toml` above it, e.g. in the home directory, does not apply.
+///
+/// The directories to look in are found once per manifest directory. The file itself is parsed once per path and
+/// modification time, so a long-running process expanding macros, like an IDE's, picks up edits to it.
+pub fn crate_defaults() -> Result<Option<Defaults>, String> {
+    type Cache<K, V> = Mutex<HashMap<K, V>>;
+    static DIRS: OnceLock<Cache<PathBuf, Vec<PathBuf>>> = OnceLock::new();
+    static FILES: OnceLock<Cache<(PathBuf, SystemTime), Result<Defaults, String>>> =
+        OnceLock::new();
+
+    let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") else {
+        return Ok(None);
+    };
+    let path = DIRS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(PathBuf::from(manifest_dir))
+        .or_insert_with_key(|manifest_dir| search_dirs(manifest_dir))
+        .iter()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file());
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|error| format!("failed to read `{}`: {}", path.display(), error))?;
+    FILES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry((path, modified))
+        .or_insert_with_key(|(path, _)| load(path))
+        .clone()
+        .map(Some)
+}
+
+/// The directories to look for the configuration file in, nearest first: `manifest_dir` and its ancestors up to the
+/// root of its workspace, or only `manifest_dir` if no ancestor is the root of a workspace.
+fn search_dirs(manifest_dir: &Path) -> Vec<PathBuf> {
+    let is_workspace_root = |dir: &Path| {
+        fs::read_to_string(dir.join("Cargo.toml"))
+            .is_ok_and(|manifest| manifest.lines().any(|line| line.trim() == "[workspace]"))
+    };
+    match manifest_dir.ancestors().position(is_workspace_root) {
+        Some(root) => manifest_dir
+            .ancestors()
+            .take(root + 1)
+            .map(Path::to_path_buf)
+            .collect(),
+        None => vec![manifest_dir.to_path_buf()],
+    }
+}
+
+/// Read and parse the configuration file at `path`.
+fn load(path: &Path) -> Result<Defaults, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("failed to read `{}`: {}", path.display(), error))?;
+    let (args, presets) =
+        parse(&text).map_err(|error| format!("invalid `{}`: {}", path.display(), error))?;
+    Ok(Defaults {
+        path: path.to_path_buf(),
+        args,
+        presets,
+    })
+}
+
+/// A statement including the configuration file at `path` in the function, so that Cargo rebuilds the crate when the
+/// file changes. Cargo only knows about the files the compiler reads, and not about the ones a macro reads.
+pub fn tracking_stmt(path: &Path) -> Option<Stmt> {
+    let path = path.to_str()?;
+    Some(parse_quote! {
+        const _: &[u8] = ::core::include_bytes!(#path);
+    })
+}
+
+/// Parse the text of a configuration file, returning its default arguments joined into one list, and its presets by
+/// name, each with its arguments joined into one list.
+fn parse(text: &str) -> Result<(String, Vec<(String, String)>), String> {
+    let mut parser = Parser {
+        rest: text,
+        line: 1,
+    };
+    // The tables in the order of the file, `None` standing for `[defaults]` and the others for presets
+    let mut tables: Vec<(Option<&str>, Option<Vec<String>>)> = Vec::new();
+    loop {
+        parser.skip_trivia();
+        if parser.rest.is_empty() {
+            break;
+        }
+        if parser.eat('[') {
+            let table = match parser.key()? {
+                "defaults" => None,
+                "presets" if parser.eat('.') => Some(parser.key()?),
+                table => {
+                    return Err(parser.error(&format!(
+                        "unknown table `[{}]`, expected `[defaults]` or `[presets.name]`",
+                        table
+                    )))
+                }
+            };
+            parser.expect(']')?;
+            if tables.iter().any(|(other, _)| *other == table) {
+                return Err(parser.error(&match table {
+                    Some(name) => format!("duplicate table `[presets.{}]`", name),
+                    None => "duplicate table `[defaults]`".to_string(),
+                }));
+            }
+            tables.push((table, None));
+        } else {
+            let key = parser.key()?;
+            let Some((_, args)) = tables.last_mut().filter(|_| key == "args") else {
+                return Err(parser.error(&format!(
+                    "unknown key `{}`, expected `args` in the `[defaults]` or a `[presets.name]` table",
+                    key
+                )));
+            };
+            if args.is_some() {
+                return Err(parser.error("duplicate key `args`"));
+            }
+            parser.skip_spaces();
+            parser.expect('=')?;
+            parser.skip_spaces();
+            *args = Some(parser.value()?);
+        }
+        parser.end_of_line()?;
+    }
+
+    let mut defaults = String::new();
+    let mut presets = Vec::new();
+    for (table, args) in tables {
+        let args = args.unwrap_or_default().join(", ");
+        match table {
+            Some(name) => presets.push((name.to_string(), args)),
+            None => defaults = args,
+        }
+    }
+    Ok((defaults, presets))
+}
+
+/// A cursor into the text of a configuration file.
+struct Parser<'a> {
+    /// The text not parsed yet.
+    rest: &'a str,
+    /// The line the cursor is on, starting at 1.
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", expected)))
+        }
+    }
+
+    /// Skip spaces and tabs.
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    /// Skip whitespace, line breaks included, and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => {
+                    self.bump();
+                }
+                Some('#') => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Skip the rest of the line, which may only hold a comment.
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            self.skip_trivia();
+            return Ok(());
+        }
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some('\r') if self.rest.starts_with("\r\n") => Ok(()),
+            Some(_) => Err(self.error("expected the end of the line")),
+        }
+    }
+
+    /// Parse a bare key.
+    fn key(&mut self) -> Result<&'a str, String> {
+        let len = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest.len());
+        if len == 0 {
+            return Err(self.error("expected a key"));
+        }
+        let (key, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(key)
+    }
+
+    /// Parse a string or an array of strings.
+    fn value(&mut self) -> Result<Vec<String>, String> {
+        if !self.eat('[') {
+            return Ok(vec![self.string()?]);
+        }
+        let mut values = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.eat(']') {
+                return Ok(values);
+            }
+            values.push(self.string()?);
+            self.skip_trivia();
+            if !self.eat(',') {
+                self.skip_trivia();
+                self.expect(']')?;
+                return Ok(values);
+            }
+        }
+    }
+
+    /// Parse a basic `"..."` or a literal `'...'` string on a single line.
+    fn string(&mut self) -> Result<String, String> {
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("expected a string")),
+        };
+        if self.rest.starts_with(&quote.to_string().repeat(3)) {
+            return Err(self.error("multi-line strings are not supported"));
+        }
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => return Ok(value),
+                Some('\\') if quote == '"' => value.push(self.escape()?),
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    /// Parse the escape sequence following a backslash in a basic string.
+    fn escape(&mut self) -> Result<char, String> {
+        let escaped = match self.bump() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some(unicode @ ('u' | 'U')) => {
+                let len = if unicode == 'u' { 4 } else { 8 };
+                let code = self
+                    .rest
+                    .get(..len)
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?;
+                self.rest = &self.rest[len..];
+                code
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        Ok(escaped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_as_string_or_array() {
+        assert_eq!(
+            parse("[defaults]\nargs = \"on_enter(trace), count_calls(CALLS)\"\n")
+                .unwrap()
+                .0,
+            "on_enter(trace), count_calls(CALLS)"
+        );
+        let text = r#"
+            # Org-wide defaults
+            [defaults]
+            args = [
+                "on_enter(log(\"enter\"))", # logged
+                'event_id = auto',
+            ] # trailing comment
+        "#;
+        assert_eq!(
+            parse(text).unwrap().0,
+            r#"on_enter(log("enter")), event_id = auto"#
+        );
+        assert_eq!(parse("# nothing yet\n[defaults]\n").unwrap().0, "");
+        assert_eq!(
+            parse("[defaults]\r\nargs = [\"adapt\"]\r\n").unwrap().0,
+            "adapt"
+        );
+    }
+
+    #[test]
+    fn presets_by_name() {
+        let text = r#"
+            [presets.service]
+            args = ["on_enter(trace)", "count_calls(CALLS)"]
+
+            [defaults]
+            args = "adapt"
+
+            [presets.quiet-io]
+        "#;
+        let (args, presets) = parse(text).unwrap();
+        assert_eq!(args, "adapt");
+        assert_eq!(
+            presets,
+            [
+                (
+                    "service".to_string(),
+                    "on_enter(trace), count_calls(CALLS)".to_string()
+                ),
+                ("quiet-io".to_string(), String::new()),
+            ]
+        );
+        assert!(parse("[presets.service]\nargs = \"adapt\"\n")
+            .unwrap()
+            .0
+            .is_empty());
+    }
+
+    #[test]
+    fn errors_point_at_their_line() {
+        assert_eq!(
+            parse("args = \"adapt\"").unwrap_err(),
+            "line 1: unknown key `args`, expected `args` in the `[defaults]` or a `[presets.name]` table"
+        );
+        assert_eq!(
+            parse("[defaults]\n\n[tables]").unwrap_err(),
+            "line 3: unknown table `[tables]`, expected `[defaults]` or `[presets.name]`"
+        );
+        assert_eq!(
+            parse("[presets]\nargs = \"adapt\"").unwrap_err(),
+            "line 1: unknown table `[presets]`, expected `[defaults]` or `[presets.name]`"
+        );
+        assert_eq!(
+            parse("[presets.a]\n[defaults]\n[presets.a]").unwrap_err(),
+            "line 3: duplicate table `[presets.a]`"
+        );
+        assert_eq!(
+            parse("[defaults]\nargs = \"adapt").unwrap_err(),
+            "line 2: unterminated string"
+        );
+        assert_eq!(
+            parse("[defaults]\nargs = [\"adapt\" \"minimal\"]").unwrap_err(),
+            "line 2: expected `]`"
+        );
+        assert_eq!(
+            parse("[defaults]\nargs = \"\"\nargs = \"adapt\"").unwrap_err(),
+            "line 3: duplicate key `args`"
+        );
+        assert_eq!(
+            parse("[defaults]\nargs = \"adapt\" minimal").unwrap_err(),
+            "line 2: expected the end of the line"
+        );
+    }
+
+    /// Defaults with the given arguments, read from a file named `axin.toml`.
+    fn defaults(args: &str) -> Defaults {
+        Defaults {
+            path: PathBuf::from("axin.toml"),
+            args: args.to_string(),
+            presets: Vec::new(),
+        }
+    }
+
+    /// Parse the default arguments `args` with the options given in `opt(...)`, rendering the hooks among the
+    /// arguments, or the error.
+    fn substituted(args: &str, opt: &str) -> Result<String, String> {
+        let given: AxinArgs = syn::parse_str(&format!("opt({})", opt)).unwrap();
+        let options: Vec<&OptionArg> = given.options().collect();
+        let parsed = defaults(args)
+            .parse_args(&options)
+            .map_err(|error| error.to_string())?;
+        let rendered: Vec<_> = parsed
+            .args
+            .iter()
+            .map(|arg| match arg {
+                AxinArg::OnEnter { func, .. } | AxinArg::OnExit { func, .. } => {
+                    format!("{}({})", arg.name(), func.to_token_stream())
+                }
+                arg => arg.name().to_string(),
+            })
+            .collect();
+        Ok(rendered.join(", "))
+    }
+
+    #[test]
+    fn defaults_that_cannot_apply_to_every_function() {
+        let error = |args: &str| substituted(args, "").unwrap_err();
+        assert!(substituted("on_enter(trace), event_id = auto", "").is_ok());
+        assert!(substituted(
+            r#"on_enter(trace(&__obs)), generated_prefix = "__obs_""#,
+            ""
+        )
+        .is_ok());
+        assert_eq!(
+            error("skip(main)"),
+            "invalid defaults in `axin.toml`: 'skip' cannot be a default"
+        );
+        assert_eq!(
+            error("opt(level = 1)"),
+            "invalid defaults in `axin.toml`: 'opt' cannot be a default"
+        );
+        assert_eq!(
+            error(r#"twin = "fast""#),
+            "invalid defaults in `axin.toml`: 'twin' cannot be a default"
+        );
+        assert_eq!(
+            error("event_id = 7"),
+            "invalid defaults in `axin.toml`: an explicit `event_id` would be shared by all functions of the crate; \
+             use `event_id = auto`"
+        );
+        assert_eq!(
+            error("on_entr(trace)"),
+            "invalid defaults in `axin.toml`: Unsupported parameter: 'on_entr'. Supported parameters are: prologue, \
+             epilogue, on_enter, on_exit, on_error, decorator, decorator_select, adapt, assert_returns, slow_log, \
+             warn_call_site, count_calls, minimal, raw_body, explain, warn_if_trivial, deny_trivial_decorator, \
+             allow_trivial, document, instrument_compat, debug_only, skip, only, hook_errors, hook_error_sink, init, \
+             mock_in_tests, event_id, no_defaults, opt, twin, packed, phase_timing, assert_order, profile, \
+             strict_prologue, stub_missing, preset, on_panic, on_exit_always, on_ok, on_err, order_const, cfg, \
+             nested_body, generated_prefix"
+        );
+    }
+
+    #[test]
+    fn options_are_substituted_or_defaulted() {
+        let args = r#"on_enter(trace(__axin_opt("component", "unknown"), __axin_opt("level", 1))),
+            on_exit(__axin_opt("exit", done))"#;
+        assert_eq!(
+            substituted(args, "").unwrap(),
+            r#"on_enter(trace ("unknown" , 1)), on_exit(done)"#
+        );
+        assert_eq!(
+            substituted(args, r#"level = 3, component = "billing", exit = audit"#).unwrap(),
+            r#"on_enter(trace ("billing" , 3)), on_exit(audit)"#
+        );
+    }
+
+    #[test]
+    fn options_must_match_their_placeholders() {
+        let args = r#"on_enter(trace(__axin_opt("component", "unknown")))"#;
+        assert_eq!(
+            substituted(args, "component = 42").unwrap_err(),
+            "the option `component` takes a string, like its default in `axin.toml`, but is given an integer"
+        );
+        assert_eq!(
+            substituted(args, "component = billing").unwrap_err(),
+            "the option `component` takes a string, like its default in `axin.toml`, but is given an identifier"
+        );
+        assert_eq!(
+            substituted(args, r#"componet = "billing""#).unwrap_err(),
+            "unknown option `componet`, the defaults in `axin.toml` take the options `component`"
+        );
+        assert_eq!(
+            substituted("on_enter(trace)", r#"component = "billing""#).unwrap_err(),
+            "unknown option `component`, the defaults in `axin.toml` take no options"
+        );
+        assert_eq!(
+            substituted(args, r#"component = "a", component = "b""#).unwrap_err(),
+            "the option `component` is given more than once"
+        );
+        assert_eq!(
+            substituted("on_enter(trace(__axin_opt))", "").unwrap_err(),
+            r#"invalid defaults in `axin.toml`: expected `__axin_opt("name", default)`"#
+        );
+        assert_eq!(
+            substituted(r#"on_enter(trace(__axin_opt("component")))"#, "").unwrap_err(),
+            r#"invalid defaults in `axin.toml`: expected `__axin_opt("name", default)`, expected `,`"#
+        );
+    }
+    /// Expand the presets in the arguments `args`, with the presets given by name and arguments, rendering the names
+    /// of the arguments they expand to, or the error.
+    fn expanded(presets: &[(&str, &str)], args: &str) -> Result<String, String> {
+        let mut defaults = defaults("");
+        defaults.presets = presets
+            .iter()
+            .map(|(name, args)| (name.to_string(), args.to_string()))
+            .collect();
+        let mut parsed = AxinArgs {
+            args: Punctuated::parse_terminated.parse_str(args).unwrap(),
+            defaults_file: None,
+        };
+        parsed
+            .expand_presets(Some(&defaults))
+            .map_err(|error| error.to_string())?;
+        assert_eq!(parsed.defaults_file, Some(defaults.path));
+        let names: Vec<_> = parsed.args.iter().map(AxinArg::name).collect();
+        Ok(names.join(", "))
+    }
+
+    #[test]
+    fn presets_expand_in_place() {
+        let presets = [
+            ("service", "on_enter(trace), count_calls(CALLS)"),
+            ("alerts", "on_error(alert)"),
+            ("empty", ""),
+        ];
+        assert_eq!(
+            expanded(
+                &presets,
+                "adapt, preset(service), preset(empty), on_exit(done), preset(alerts)"
+            )
+            .unwrap(),
+            "adapt, on_enter, count_calls, on_exit, on_error"
+        );
+        assert_eq!(
+            expanded(&presets, "preset(servce)").unwrap_err(),
+            "unknown preset `servce`, `axin.toml` defines the presets `service`, `alerts`, `empty`"
+        );
+        assert_eq!(
+            expanded(&[], "preset(service)").unwrap_err(),
+            "unknown preset `service`, `axin.toml` defines no presets"
+        );
+    }
+
+    #[test]
+    fn presets_that_cannot_apply_to_every_function() {
+        let error = |args: &str| expanded(&[("service", args)], "preset(service)").unwrap_err();
+        assert_eq!(
+            error("preset(other)"),
+            "invalid preset `service` in `axin.toml`: 'preset' cannot be part of a preset"
+        );
+        assert_eq!(
+            error("no_defaults"),
+            "invalid preset `service` in `axin.toml`: 'no_defaults' cannot be part of a preset"
+        );
+        assert_eq!(
+            error(r#"on_enter(trace(__axin_opt("level", 1)))"#),
+            "invalid preset `service` in `axin.toml`: presets take no options, so `__axin_opt` can only be used in \
+             the defaults"
+        );
+        assert_eq!(
+            error("on_enter(trace"),
+            "invalid preset `service` in `axin.toml`: cannot parse string into token stream"
+        );
+    }
+
+    #[test]
+    fn search_dirs_end_at_the_workspace_root() {
+        let root = std::env::temp_dir().join(format!("axin-search-dirs-{}", std::process::id()));
+        let member = root.join("workspace").join("crates").join("member");
+        let standalone = root.join("standalone");
+        fs::create_dir_all(&member).unwrap();
+        fs::create_dir_all(&standalone).unwrap();
+        fs::write(
+            root.join("workspace/Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+        fs::write(
+            standalone.join("Cargo.toml"),
+            "[package]\nname = \"standalone\"\n",
+        )
+        .unwrap();
+
+        let member_dirs = search_dirs(&member);
+        let standalone_dirs = search_dirs(&standalone);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            member_dirs,
+            [
+                member.clone(),
+                root.join("workspace/crates"),
+                root.join("workspace")
+            ]
+        );
+        assert_eq!(standalone_dirs, [standalone]);
+    }
+}
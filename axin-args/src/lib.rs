@@ -0,0 +1,8 @@
+//! The arguments of the `axin` attribute, parsed and validated, and the crate defaults and presets read from
+//! `axin.toml`.
+//!
+//! Shared by the procedural macros of `axin-macros` and by `cargo axin-suggest`, so that the tool only suggests
+//! attributes the macros accept. This crate is an implementation detail of both, with no stable API of its own.
+
+pub mod args;
+pub mod defaults;
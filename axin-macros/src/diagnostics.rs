@@ -0,0 +1,374 @@
+//! Diagnostics of the `axin` procedural macro that go beyond plain errors.
+//!
+//! Stable proc macros cannot emit notes or warnings, so they are reported as deprecation warnings on generated unit
+//! structs, pointing at the relevant tokens of the input. Generated code shown in the `explain` note is
+//! pretty-printed with `prettyplease` when the `diagnostics-pretty` feature is enabled, and rendered as plain tokens
+//! otherwise.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote_spanned, ToTokens};
+use syn::{parse_quote, Block, Expr, FnArg, ItemFn, ReturnType, Stmt};
+
+/// How to react when a decorator wraps a trivial function, see [`check_trivial_decorator`].
+#[derive(Default)]
+pub enum TrivialPolicy {
+    /// Say nothing.
+    #[default]
+    Allow,
+    /// Emit a warning.
+    Warn,
+    /// Emit an error.
+    Deny,
+}
+
+/// Check whether a decorator is applied to a trivial function, i.e. one with an empty body or one that only forwards
+/// to another function, which usually means the attribute belongs on the function being forwarded to.
+///
+/// Returns the warning statement to attach to the generated function, if any, or the error under
+/// [`TrivialPolicy::Deny`].
+pub fn check_trivial_decorator(
+    input_fn: &ItemFn,
+    has_decorator: bool,
+    policy: &TrivialPolicy,
+) -> syn::Result<Option<Stmt>> {
+    if !has_decorator || matches!(policy, TrivialPolicy::Allow) || !is_trivial_body(&input_fn.block)
+    {
+        return Ok(None);
+    }
+
+    let ident = &input_fn.sig.ident;
+    let message = format!(
+        "`{}` is trivial, so its decorator most likely belongs on the function it forwards to; \
+         add `allow_trivial` to the attribute if this is intended",
+        ident
+    );
+    match policy {
+        TrivialPolicy::Deny => Err(syn::Error::new_spanned(ident, message)),
+        _ => Ok(Some(warning(
+            ident.span(),
+            "axin_trivial_decorator",
+            &message,
+        ))),
+    }
+}
+
+/// Build the warning that the hooks listed in `stub_missing` are stubbed out, at its keyword.
+pub fn stubbed_hooks_warning(keyword: &Ident, hooks: &[Ident]) -> Stmt {
+    let names: Vec<_> = hooks.iter().map(|hook| format!("`{}`", hook)).collect();
+    let message = match names.as_slice() {
+        [name] => format!(
+            "the hook {} is stubbed out, so calls of this function skip it; remove it from `stub_missing` once it \
+             exists",
+            name
+        ),
+        _ => format!(
+            "the hooks {} are stubbed out, so calls of this function skip them; remove them from `stub_missing` \
+             once they exist",
+            names.join(", ")
+        ),
+    };
+    warning(keyword.span(), "axin_stubbed_hooks", &message)
+}
+
+/// Whether a function body is empty or a single forwarding call.
+fn is_trivial_body(block: &Block) -> bool {
+    match block.stmts.as_slice() {
+        [] => true,
+        [Stmt::Expr(expr, _)] => is_forwarding_call(expr),
+        _ => false,
+    }
+}
+
+/// Whether an expression is a call whose arguments are all plain values, possibly awaited or followed by `?`.
+fn is_forwarding_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(call) => call.args.iter().all(is_plain_value),
+        Expr::MethodCall(call) => {
+            is_plain_value(&call.receiver) && call.args.iter().all(is_plain_value)
+        }
+        Expr::Await(expr) => is_forwarding_call(&expr.base),
+        Expr::Try(expr) => is_forwarding_call(&expr.expr),
+        _ => false,
+    }
+}
+
+/// Whether an expression is a path, literal, field access or reference to one of them.
+fn is_plain_value(expr: &Expr) -> bool {
+    match expr {
+        Expr::Path(_) | Expr::Lit(_) => true,
+        Expr::Field(field) => is_plain_value(&field.base),
+        Expr::Reference(reference) => is_plain_value(&reference.expr),
+        _ => false,
+    }
+}
+
+/// Build a statement that makes the compiler emit `message` as a warning at `span`.
+///
+/// `name` becomes part of the warning text, so it should tell what the warning is about.
+fn warning(span: Span, name: &str, message: &str) -> Stmt {
+    // The use of the struct carries the span, so that the warning points there
+    let use_stmt = {
+        let name = Ident::new(name, span);
+        quote_spanned! {span=> let _ = #name; }
+    };
+    let name = format_ident!("{}", name);
+    parse_quote! {
+        {
+            #[deprecated(note = #message)]
+            #[allow(non_camel_case_types)]
+            struct #name;
+            #use_stmt
+        }
+    }
+}
+
+/// Attach the given warnings, and the explanation requested by the `explain` keyword if any, to the generated
+/// function.
+pub fn attach_diagnostics(
+    generated: TokenStream,
+    mut warnings: Vec<Stmt>,
+    explain: Option<&Ident>,
+) -> TokenStream {
+    if warnings.is_empty() && explain.is_none() {
+        return generated;
+    }
+
+    let mut item: ItemFn = match syn::parse2(generated.clone()) {
+        Ok(item) => item,
+        // Nothing sensible to attach to, let the compiler report the generated code as it is.
+        Err(_) => return generated,
+    };
+
+    if let Some(keyword) = explain {
+        warnings.push(warning(keyword.span(), "axin_explain", &explanation(&item)));
+    }
+    item.block.stmts.splice(0..0, warnings);
+
+    item.into_token_stream()
+}
+
+/// Prefix of the statements whose lines are quoted separately in the explanation.
+const INNER_CALLABLE_PREFIX: &str = "let original_fn";
+/// Prefix of the statement calling the decorator or the inner callable.
+const CALL_PREFIX: &str = "let __result";
+/// What follows the result's type annotation in the statement calling one of the decorators of `decorator_select`.
+const SELECT_MARKER: &str = "= match (";
+
+/// Build the explanation text for a generated function.
+fn explanation(item: &ItemFn) -> String {
+    let rendered = render(item);
+    let mut note = format!("axin expansion of `{}`:\n", item.sig.ident);
+
+    // The inner callable is quoted up to its body, and the `match` of `decorator_select` up to its arms only. A
+    // plain call is quoted in full, as its arguments tell how the function's parameters are forwarded.
+    let selects = rendered
+        .lines()
+        .any(|line| line.trim().starts_with(CALL_PREFIX) && line.contains(SELECT_MARKER));
+    if let Some(head) = quote_stmt(&rendered, INNER_CALLABLE_PREFIX, true) {
+        note.push_str(&format!("  inner callable: {}\n", head));
+    }
+    // With `mock_in_tests`, a second inner callable calls the mock in test builds, which is quoted in full
+    let mock_start = rendered
+        .match_indices(INNER_CALLABLE_PREFIX)
+        .nth(1)
+        .map(|(index, _)| {
+            rendered[..index]
+                .rfind('\n')
+                .map_or(0, |newline| newline + 1)
+        });
+    if let Some(mock) =
+        mock_start.and_then(|start| quote_stmt(&rendered[start..], INNER_CALLABLE_PREFIX, false))
+    {
+        note.push_str(&format!("  in tests: {}\n", mock));
+    }
+    // With nothing but a decorator, the body is a single call of it with the inner callable inline, which is quoted up
+    // to the callable's body, or in full if it is rendered on a single line. The parameters of the callable would be
+    // taken for arguments of the call, so it is not wrapped.
+    let inline_call = match &item.block.stmts[..] {
+        [Stmt::Expr(_, None)] => rendered
+            .lines()
+            .skip_while(|line| !line.ends_with('{'))
+            .nth(1)
+            .and_then(|line| quote_stmt(&rendered, line.trim(), bracket_depth(line) > 0)),
+        _ => None,
+    };
+    if let Some(call) = &inline_call {
+        note.push_str(&format!("  call with the inner callable: {}\n", call));
+    }
+    if let Some(mut call) = quote_stmt(&rendered, CALL_PREFIX, selects) {
+        if let (true, Some(open)) = (selects, call.find('{')) {
+            call.truncate(open + 1);
+        } else if !selects && !call.contains('\n') && call.len() > MAX_QUOTED_WIDTH {
+            // As a call forwarding many parameters is rendered without `diagnostics-pretty`
+            call = wrap_call(&call).join("\n  ");
+        }
+        note.push_str(&format!("  call: {}\n", call));
+    }
+    if selects {
+        note.push_str(&format!("  shape: {}\n", select_shape(item)));
+    }
+
+    note.push_str("full expansion:\n");
+    for line in rendered.lines() {
+        note.push_str("    ");
+        note.push_str(line);
+        note.push('\n');
+    }
+    note.truncate(note.trim_end().len());
+    note
+}
+
+/// Width above which a call rendered on a single line is wrapped when quoted.
+const MAX_QUOTED_WIDTH: usize = 80;
+
+/// Quote the statement whose first line starts with `prefix`, indenting its continuation lines under the quote.
+///
+/// With `head_only`, the quote ends at the first line opening a block or ending the statement. Otherwise the statement
+/// is quoted in full.
+fn quote_stmt(rendered: &str, prefix: &str, head_only: bool) -> Option<String> {
+    let mut lines = rendered
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with(prefix));
+    let first = lines.next()?;
+    let base_indent = first.len() - first.trim_start().len();
+
+    let mut stmt = vec![first.trim().to_string()];
+    let mut depth = bracket_depth(first);
+    loop {
+        let last = &stmt[stmt.len() - 1];
+        let done = if head_only {
+            last.ends_with('{') || last.ends_with(';')
+        } else {
+            depth <= 0
+        };
+        if done {
+            break;
+        }
+        let Some(line) = lines.next() else { break };
+        depth += bracket_depth(line);
+        let indent = (line.len() - line.trim_start().len()).min(base_indent);
+        stmt.push(line[indent..].trim_end().to_string());
+    }
+
+    Some(stmt.join("\n  "))
+}
+
+/// Net number of brackets a line of rendered code opens.
+fn bracket_depth(line: &str) -> isize {
+    line.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Split a single-line statement into lines, one per argument of its outermost call.
+fn wrap_call(line: &str) -> Vec<String> {
+    let Some(open) = line.find('(') else {
+        return vec![line.to_string()];
+    };
+
+    let mut lines = vec![line[..=open].to_string()];
+    let mut depth = 0;
+    let mut start = open + 1;
+    for (index, c) in line.char_indices().skip(open) {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let arg = line[start..index].trim();
+                    if !arg.is_empty() {
+                        lines.push(format!("    {},", arg));
+                    }
+                    lines.push(line[index..].trim().to_string());
+                    return lines;
+                }
+            }
+            ',' if depth == 1 => {
+                lines.push(format!("    {},", line[start..index].trim()));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    vec![line.to_string()]
+}
+
+/// Describe the common shape all decorators of `decorator_select` must have.
+fn select_shape(item: &ItemFn) -> String {
+    let (names, types): (Vec<_>, Vec<_>) = item
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some((
+                pat_type.pat.to_token_stream().to_string(),
+                pat_type.ty.to_token_stream().to_string(),
+            )),
+            FnArg::Receiver(_) => None,
+        })
+        .unzip();
+    let output = match &item.sig.output {
+        ReturnType::Default => "()".to_string(),
+        ReturnType::Type(_, ty) => ty.to_token_stream().to_string(),
+    };
+    let call_args: Vec<_> = std::iter::once("original_fn".to_string())
+        .chain(names)
+        .collect();
+
+    format!(
+        "every selected decorator must be callable as `decorator({})` with `original_fn: impl FnOnce({}) -> {}`, \
+         and return `{}`",
+        call_args.join(", "),
+        types.join(", "),
+        output,
+        output,
+    )
+}
+
+/// Render a function as Rust source text.
+#[cfg(feature = "diagnostics-pretty")]
+fn render(item: &ItemFn) -> String {
+    prettyplease::unparse(&syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![syn::Item::Fn(item.clone())],
+    })
+}
+
+/// Render a function as Rust source text.
+///
+/// Without `diagnostics-pretty` this is the token stream's own formatting, with one attribute or statement per line.
+/// The compiler breaks the formatting of long token streams into several lines, which is undone, so that a statement
+/// can always be found by its first line.
+#[cfg(not(feature = "diagnostics-pretty"))]
+fn render(item: &ItemFn) -> String {
+    let one_line = |tokens: &dyn ToTokens| {
+        let text = tokens.to_token_stream().to_string();
+        text.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+    };
+    let mut rendered = String::new();
+    for attr in &item.attrs {
+        rendered.push_str(&format!("{}\n", one_line(attr)));
+    }
+    let header = format!("{} {}", one_line(&item.vis), one_line(&item.sig));
+    rendered.push_str(&format!("{} {{\n", header.trim_start()));
+    for stmt in &item.block.stmts {
+        // Attributes of statements go on lines of their own, as with `diagnostics-pretty`
+        let mut stmt = stmt.clone();
+        let attrs = match &mut stmt {
+            Stmt::Local(local) => std::mem::take(&mut local.attrs),
+            Stmt::Macro(stmt_macro) => std::mem::take(&mut stmt_macro.attrs),
+            _ => Vec::new(),
+        };
+        for attr in &attrs {
+            rendered.push_str(&format!("    {}\n", one_line(attr)));
+        }
+        rendered.push_str(&format!("    {}\n", one_line(&stmt)));
+    }
+    rendered.push_str("}\n");
+    rendered
+}
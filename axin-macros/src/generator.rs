@@ -0,0 +1,4517 @@
+//! Code generation for the `axin` procedural macro.
+//!
+//! This module contains the logic for transforming annotated functions
+//! according to the specified instrumentation parameters.
+//!
+//! The generated code only depends on the macro input: every identifier introduced, such as `original_fn`,
+//! `__result` or the `__AXIN_CALLS` static, is a fixed name, and the only input-derived names are taken from the
+//! function name. Nothing is derived from counters or randomness, so that builds stay reproducible. The inner callable
+//! and the result are bound with mixed-site spans, which are just as reproducible, so that no name in the input can
+//! collide with them.
+//!
+//! The generated code allocates nothing and dispatches nothing dynamically: the inner callable, decorators and guards
+//! are generic values on the stack. Anything that has to allocate, such as the trace recorded for `assert_order`,
+//! belongs to an opt-in parameter. `tests/allocations.rs` counts the allocations of instrumented functions to keep it
+//! that way.
+
+use crate::{
+    args::{
+        check_duplicate_decorators, AxinArg, EventId, FunctionSpec, HookArgs, HookErrorPolicy,
+        InstrumentLevel, InstrumentOptions, ORDER_PHASES,
+    },
+    diagnostics::{stubbed_hooks_warning, TrivialPolicy},
+};
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use proc_macro_crate::{crate_name, FoundCrate};
+use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
+use std::cell::RefCell;
+use syn::{
+    ext::IdentExt,
+    parse_quote, parse_quote_spanned,
+    spanned::Spanned,
+    token::Brace,
+    visit_mut::{self, VisitMut},
+    AttrStyle, Attribute, Expr, FnArg, GenericParam, Generics, Ident, Item, ItemConst, ItemFn,
+    Lifetime, LifetimeParam, MacroDelimiter, ParenthesizedGenericArguments, Pat, Path, Receiver,
+    ReturnType, Signature, Stmt, Token, Type, TypeBareFn, TypeReference, Variadic, Visibility,
+};
+
+/// Prefix of the placeholders and of the generated names code from the macro input can see, unless `generated_prefix`
+/// gives another one, see [`AxinConfig::mangle`]. The call context placeholder is its stem, without the last `_`.
+pub const DEFAULT_PREFIX: &str = "__axin_";
+/// Name of the call context placeholder available to hook and decorator arguments.
+pub const CONTEXT_PLACEHOLDER: &str = "__axin";
+/// Name of the event id placeholder available to hook and decorator arguments.
+const EVENT_ID_PLACEHOLDER: &str = "__axin_event_id";
+/// Name under which the `when` predicate of `on_exit` sees a reference to the result.
+const EXIT_RESULT: &str = "result";
+/// Name under which the `when` predicate of `on_exit` sees the elapsed time of the call.
+const EXIT_ELAPSED: &str = "elapsed";
+/// Name under which `on_exit` sees whether the body was called.
+const BODY_RAN: &str = "__axin_body_ran";
+/// Name under which the arguments of `on_exit` see a reference to the result.
+const RESULT_PLACEHOLDER: &str = "__axin_result";
+/// Name under which the arguments of `on_ok` see a reference to the success value.
+const OK_PLACEHOLDER: &str = "__axin_ok";
+/// Name under which the arguments of `on_err` see a reference to the error.
+const ERR_PLACEHOLDER: &str = "__axin_err";
+/// Threshold above which `profile` reports calls as slow, unless given another one.
+const PROFILE_SLOW_MILLIS: u64 = 100;
+/// Name of the binding of the inner callable, which runs the body.
+const INNER_CALLABLE: &str = "original_fn";
+/// Name of the nested function running the body, see [`nested_original`].
+const NESTED_ORIGINAL: &str = "__axin_original";
+/// Name of the function constraining the parameter types of a closure, see [`generate_typed_callable_helper`].
+const TYPED_CALLABLE: &str = "__axin_typed";
+/// Name of the packed arguments in the callables wrapping the inner callable, and prefix of their struct, see
+/// [`PackedArgs`].
+const PACKED_ARGS: &str = "__axin_args";
+/// Prefix of the names of the functions standing in for stubbed hooks, see [`stub_missing_hooks`].
+const STUB_PREFIX: &str = "__axin_stub_";
+/// Name of the binding of the result of the call.
+const CALL_RESULT: &str = "__result";
+
+/// An identifier for a binding of the generated code that code from the macro input must never see. With mixed-site
+/// hygiene, like the locals of `macro_rules!` macros, it neither refers to nor is shadowed by a binding of the same name
+/// in the body, the prologue or the arguments of the attribute.
+fn hygienic(name: &str) -> Ident {
+    Ident::new(name, Span::mixed_site())
+}
+
+/// The name `name` given with the [`DEFAULT_PREFIX`], or its stem, with `prefix` instead. Other names, like
+/// [`EXIT_RESULT`], are kept as they are.
+fn mangle(prefix: &str, name: &str) -> String {
+    let stem = DEFAULT_PREFIX.trim_end_matches('_');
+    match name.strip_prefix(DEFAULT_PREFIX) {
+        Some(rest) => format!("{}{}", prefix, rest),
+        None if name == stem => prefix[..prefix.len() - 1].to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// The names bound for attribute arguments to refer to, and what they stand for. A parameter or a binding of the
+/// prologue cannot share a name the attribute refers to, see [`check_reserved_names`]. The crate documentation lists
+/// these names with their descriptions as given here.
+pub const RESERVED_NAMES: &[(&str, &str)] = &[
+    (
+        CONTEXT_PLACEHOLDER,
+        "the call context, an `AxinCtx`, in the arguments of hooks and decorators",
+    ),
+    (
+        EVENT_ID_PLACEHOLDER,
+        "the id given with `event_id`, in the arguments of hooks and decorators",
+    ),
+    (
+        EXIT_RESULT,
+        "a reference to the return value, in the `when` predicate of `on_exit`",
+    ),
+    (
+        EXIT_ELAPSED,
+        "the time the decorator and the body took, in the `when` predicate of `on_exit`",
+    ),
+    (
+        BODY_RAN,
+        "whether the function body was called, in the arguments and the `when` predicate of `on_exit`",
+    ),
+    (
+        RESULT_PLACEHOLDER,
+        "a reference to the return value, in the arguments and the `when` predicate of `on_exit`",
+    ),
+    (
+        OK_PLACEHOLDER,
+        "a reference to the success value, in the arguments of `on_ok`",
+    ),
+    (
+        ERR_PLACEHOLDER,
+        "a reference to the error, in the arguments of `on_err`",
+    ),
+];
+
+/// Generate the enhanced function with the specified instrumentation features.
+///
+/// Transforms the original function by adding prologue statements, entry/exit hooks,
+/// and decorator wrapping according to the provided configuration.
+///
+/// ## Parameters
+///
+/// - `input_fn`: The original function to be enhanced
+/// - `config`: The instrumentation to add, see [`AxinConfig`]
+///
+/// ## Returns
+///
+/// Token stream representing the transformed function code.
+pub fn generate_enhanced_function(input_fn: ItemFn, config: &AxinConfig) -> TokenStream {
+    let AxinConfig {
+        prologue_stmts,
+        epilogue_stmts,
+        decorator_fn,
+        inner_decorators,
+        borrow_args,
+        decorator_select,
+        on_enter_fn,
+        on_exit_fn,
+        on_error_fn,
+        on_ok_fn,
+        on_err_fn,
+        hook_error_sink,
+        assert_returns,
+        slow_log,
+        outer_attrs,
+        count_calls,
+        init,
+        minimal,
+        raw_body,
+        adapt,
+        nested_body,
+        instrument,
+        mock_in_tests,
+        event_id,
+        packed,
+        phase_timing,
+        assert_order,
+        on_panic_fn,
+        on_exit_always_fn,
+        ..
+    } = config;
+    let original_fn = input_fn.clone();
+    // Inner attributes of the body, such as `#![allow(...)]`, are parsed as attributes of the function, and stay in the
+    // body, which is spliced into the generated code as a whole, braces included
+    let (inner_attrs, fn_attrs): (Vec<&Attribute>, Vec<&Attribute>) = original_fn
+        .attrs
+        .iter()
+        .partition(|attr| matches!(attr.style, AttrStyle::Inner(_)));
+    let fn_vis = &original_fn.vis;
+    let fn_sig = &original_fn.sig;
+    let fn_output = &fn_sig.output;
+    let original_block = original_fn.block;
+    let original_body = braced(original_block.brace_token, |tokens| {
+        tokens.append_all(&inner_attrs);
+        tokens.append_all(&original_block.stmts);
+    });
+    let fn_name = fn_sig.ident.to_string();
+
+    // In minimal mode, the original statements are kept as they are, and only the counter and entry hooks, which are
+    // known to be argument-less (see `AxinArgs::validate_minimal`), are put in front of them. `raw_body` keeps them as
+    // well, and also allows hook arguments and exit hooks, which are run by a guard dropped when the body returns,
+    // however it does (see `AxinArgs::validate_raw_body`).
+    if *minimal || *raw_body {
+        let mentioned = |placeholder: &str| {
+            [on_enter_fn, on_exit_fn]
+                .into_iter()
+                .flatten()
+                .any(|hook| mentions_ident(hook.func.to_token_stream(), placeholder))
+                || on_enter_fn
+                    .as_ref()
+                    .and_then(|hook| hook.when.as_ref())
+                    .is_some_and(|when| mentions_ident(when.to_token_stream(), placeholder))
+                || mentions_ident(original_block.to_token_stream(), placeholder)
+        };
+        let mut stmts = Vec::new();
+        let uses_context = *raw_body && mentioned(&config.mangle(CONTEXT_PLACEHOLDER));
+        if init.is_some() || uses_context {
+            stmts.push(generate_state(&fn_name));
+        }
+        if let Some(init) = &init {
+            stmts.push(generate_init(init));
+        }
+        if let Some(event_id) = event_id.filter(|_| mentioned(&config.mangle(EVENT_ID_PLACEHOLDER)))
+        {
+            stmts.push(generate_event_id(event_id, &fn_name, config));
+        }
+        if uses_context {
+            stmts.push(generate_context_binding(&fn_name, config));
+        }
+        if let Some(counter) = &count_calls {
+            stmts.push(generate_counter_increment(counter));
+        }
+        // Parameters bound by patterns are taken under generated names if they are passed to the entry hook, and
+        // destructured after it
+        let mut sig = fn_sig.clone();
+        let mut hook_args = Vec::new();
+        let mut destructure_stmts: Vec<Stmt> = Vec::new();
+        if on_enter_fn
+            .as_ref()
+            .is_some_and(|hook| hook.with_args.is_some())
+        {
+            for (index, arg) in sig.inputs.iter_mut().enumerate() {
+                if let FnArg::Typed(pat_type) = arg {
+                    if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                        hook_args.push(pat_ident.ident.clone());
+                    } else {
+                        let name = Ident::new(&format!("__arg{}", index), Span::call_site());
+                        let pat = &pat_type.pat;
+                        destructure_stmts.push(parse_quote! { let #pat = #name; });
+                        *pat_type.pat = parse_quote! { #name };
+                        hook_args.push(name);
+                    }
+                }
+            }
+        }
+        if let Some(on_enter) = &on_enter_fn {
+            let call_expr = generate_entry_hook_call(on_enter, &fn_name, &hook_args);
+            stmts.push(generate_entry_hook_stmt(
+                parse_quote! { #call_expr; },
+                on_enter,
+            ));
+        }
+        stmts.extend(destructure_stmts);
+        if let Some(on_exit) = &on_exit_fn {
+            let axin = axin_crate_path();
+            let call_expr = generate_function_call(&on_exit.func);
+            // Nothing can keep the body from running without a decorator
+            if mentions_ident(on_exit.func.to_token_stream(), &config.mangle(BODY_RAN)) {
+                let body_ran = config.placeholder(BODY_RAN);
+                stmts.push(parse_quote! {
+                    let #body_ran = true;
+                });
+            }
+            stmts.push(parse_quote! {
+                let __exit_guard = #axin::__private::ExitGuard::new(|| {
+                    #call_expr;
+                });
+            });
+        }
+        let block = if stmts.is_empty() {
+            original_body
+        } else if inner_attrs.is_empty() {
+            // Like the closure's, see below
+            braced(original_block.brace_token, |tokens| {
+                tokens.append_all(&stmts);
+                tokens.append_all(&original_block.stmts);
+            })
+        } else {
+            braced(original_block.brace_token, |tokens| {
+                tokens.append_all(&stmts);
+                tokens.extend(original_body);
+            })
+        };
+        return quote! {
+            #(#fn_attrs)*
+            #(#outer_attrs)*
+            #fn_vis #sig #block
+        };
+    }
+
+    // An async function awaits its inner future. Without a decorator, nothing needs the parameters to be passed
+    // through, so the inner future captures them like when adapting: a closure taking borrowed parameters could not
+    // return a future borrowing them.
+    let signature = SignatureProfile::new(fn_sig);
+    let is_async = signature.is_async();
+    let decorated = decorator_fn.is_some() || decorator_select.is_some();
+    let captures = *adapt || (is_async && !decorated);
+    // A `&mut self` receiver the exit hook uses is reborrowed by a capturing callable rather than moved into it, so the
+    // callable does not move anything by itself: the parameters are moved into it by rebinding them instead, so that
+    // they are still dropped when it returns
+    let reborrows_self = captures && signature.reborrows_self(config);
+    let move_token = (!reborrows_self).then(|| quote! { move });
+    // A mock that is not instrumented replaces the whole function instead, see `generate_mock_function`
+    let mock = mock_in_tests
+        .as_ref()
+        .filter(|(_, instrumented)| *instrumented)
+        .map(|(func, _)| func);
+
+    // Build the argument list for the inner original function. Parameters bound by patterns, such as `Json(body)`,
+    // are taken under generated names by the outer function, and destructured by the inner one, which captures the
+    // values when capturing. Packed arguments are destructured the same way after being unpacked from their fields.
+    let mut outer_sig = fn_sig.clone();
+    let mut args = Vec::new();
+    let mut call_args = Vec::new();
+    let mut hook_args = Vec::new();
+    let mut destructure_stmts: Vec<Stmt> = Vec::new();
+    for (index, arg) in outer_sig.inputs.iter_mut().enumerate() {
+        if let FnArg::Typed(pat_type) = arg {
+            if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                args.push(pat_ident.ident.clone());
+                call_args.push(pat_ident.ident.clone());
+                hook_args.push(pat_ident.ident.clone());
+            } else {
+                let name = Ident::new(&format!("__arg{}", index), Span::call_site());
+                if captures || *packed {
+                    let pat = &pat_type.pat;
+                    destructure_stmts.push(parse_quote! { let #pat = #name; });
+                }
+                *pat_type.pat = parse_quote! { #name };
+                call_args.push(name.clone());
+                hook_args.push(name);
+            }
+        }
+    }
+    let borrowed_args = if *borrow_args {
+        call_args.clone()
+    } else {
+        Vec::new()
+    };
+    if captures {
+        call_args.clear();
+    }
+    let args: Vec<&Ident> = args.iter().collect();
+    let call_args: Vec<&Ident> = call_args.iter().collect();
+
+    // Packed arguments are passed as a value of a struct declared in the body, with a field for each parameter. The
+    // outer function only moves its parameters into the struct, so they are never mutated there.
+    let packed_args = packed.then(|| {
+        let packed_args = PackedArgs::new(&outer_sig, &config.mangle(PACKED_ARGS));
+        strip_mutability(&mut outer_sig);
+        packed_args
+    });
+    // A mutable parameter is mutated by the inner callable, which takes it as mutable as well, and warns if it is not.
+    // Unless capturing by moving, the outer function only mutates it if a hook or decorator argument does.
+    if !captures || reborrows_self {
+        for arg in outer_sig.inputs.iter_mut() {
+            if let FnArg::Typed(pat_type) = arg {
+                if matches!(&*pat_type.pat, Pat::Ident(pat_ident) if pat_ident.mutability.is_some())
+                {
+                    pat_type.attrs.push(parse_quote! { #[allow(unused_mut)] });
+                }
+            }
+        }
+    }
+
+    // Build the inner function body. Prologue statements are put in front of the original body's statements, which
+    // stay a block of their own where the body has inner attributes, so that those are spliced in as written. A
+    // `return` or `?` in the prologue leaves the inner function only: decorators and exit hooks still see it as a
+    // normal return. For the same reason, bindings made by the prologue or the body, including shadows of parameters,
+    // never leak into the outer body, where all hook and decorator arguments are evaluated: those always see the
+    // original parameters. Epilogue statements follow the body, whose value is bound until they have run, so they see
+    // the parameters and the prologue's bindings, but not the body's. A `return` or `?` in the body leaves the inner
+    // function directly, and skips them. Items declared in the body are visible in all of it, so they are moved in
+    // front of the prologue, which can then refer to them as well, as can the epilogue.
+    // A capturing callable takes all parameters, even ones its body does not use, so that they are dropped with it
+    // when it returns, before the exit hooks run, as they are when passed to a callable that does not capture.
+    let mut inner_stmts: Vec<Stmt> = Vec::new();
+    if captures {
+        if signature.takes_self_by_value() {
+            inner_stmts.push(parse_quote! { let _ = &self; });
+        }
+        for arg in &outer_sig.inputs {
+            if let FnArg::Typed(pat_type) = arg {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    let (mutability, arg) = (&pat_ident.mutability, &pat_ident.ident);
+                    inner_stmts.push(if reborrows_self {
+                        parse_quote! {
+                            #[allow(unused_mut)]
+                            let #mutability #arg = #arg;
+                        }
+                    } else {
+                        parse_quote! { let _ = &#arg; }
+                    });
+                }
+            }
+        }
+    }
+    inner_stmts.extend(destructure_stmts);
+    let (original_body, body_stmts) = if prologue_stmts.is_empty() && epilogue_stmts.is_empty() {
+        (original_body, original_block.stmts.iter().collect())
+    } else {
+        let (items, body_stmts) = split_items(&original_block.stmts, &inner_attrs);
+        inner_stmts.extend(items.into_iter().cloned());
+        let original_body = braced(original_block.brace_token, |tokens| {
+            tokens.append_all(&inner_attrs);
+            tokens.append_all(&body_stmts);
+        });
+        (original_body, body_stmts)
+    };
+    // With `assert_order`, every phase that runs is traced as it starts, see `generate_order_mark`
+    let plan = ExecutionPlan::new(config);
+    let mark = |phase: &str| plan.emit(phase, assert_order.is_some());
+    if !prologue_stmts.is_empty() {
+        inner_stmts.extend(mark("prologue"));
+    }
+    inner_stmts.extend(prologue_stmts.iter().cloned());
+    inner_stmts.extend(mark("body"));
+    // The type of the body's value is the inner callable's output, which an `impl Trait` output leaves to be inferred
+    let inner_output = match fn_output {
+        ReturnType::Type(_, ty) => infer_impl_trait(ty),
+        ReturnType::Default => parse_quote! { () },
+    };
+    let inner_block = if !epilogue_stmts.is_empty() {
+        let mark = mark("epilogue");
+        // Inner attributes are not allowed in a block initializing a binding, but in a block nested in it
+        let original_body = if inner_attrs.is_empty() {
+            original_body
+        } else {
+            quote! { { #original_body } }
+        };
+        braced(original_block.brace_token, |tokens| {
+            tokens.append_all(&inner_stmts);
+            tokens.extend(quote! {
+                let __body_value: #inner_output = #original_body;
+                #mark
+                #(#epilogue_stmts)*
+                __body_value
+            });
+        })
+    } else if inner_stmts.is_empty() && inner_attrs.is_empty() {
+        original_body
+    } else if inner_attrs.is_empty() {
+        // The body's statements follow the generated ones directly, which a block of their own ending the closure's
+        // would only add `unused_braces` warnings to
+        braced(original_block.brace_token, |tokens| {
+            tokens.append_all(&inner_stmts);
+            tokens.append_all(body_stmts);
+        })
+    } else {
+        // Inner attributes are only allowed in a block nested in the closure's, not in the closure's own
+        braced(original_block.brace_token, |tokens| {
+            tokens.append_all(&inner_stmts);
+            tokens.extend(original_body);
+        })
+    };
+
+    // Build the final function body
+    let mut final_stmts = Vec::new();
+
+    // Note where the trace of this call starts, so that it can be checked at the end
+    let axin = axin_crate_path();
+    if assert_order.is_some() {
+        final_stmts.push(parse_quote! {
+            #[cfg(test)]
+            let __order_start = #axin::__private::order_start();
+        });
+    }
+
+    // Bind the event id and the call context only if something refers to them
+    let slow_log_fn = slow_log.as_ref().map(|(_, func)| func);
+    let enter_condition = on_enter_fn.as_ref().and_then(|hook| hook.when.as_ref());
+    let exit_condition = on_exit_fn.as_ref().and_then(|hook| hook.when.as_ref());
+    let uses_placeholder = |placeholder: &str| {
+        [
+            on_enter_fn.as_ref().map(|hook| &hook.func),
+            decorator_fn.as_ref(),
+            on_exit_fn.as_ref().map(|hook| &hook.func),
+            on_error_fn.as_ref().map(|hook| &hook.func),
+            on_ok_fn.as_ref().map(|hook| &hook.func),
+            on_err_fn.as_ref().map(|hook| &hook.func),
+            slow_log_fn,
+            hook_error_sink.as_ref(),
+            phase_timing.as_ref(),
+            on_panic_fn.as_ref(),
+            on_exit_always_fn.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(inner_decorators)
+        .chain(
+            decorator_select
+                .iter()
+                .flat_map(|(_, decorators)| decorators),
+        )
+        .any(|spec| mentions_ident(spec.to_token_stream(), placeholder))
+            || decorator_select
+                .iter()
+                .any(|(selector, _)| mentions_ident(selector.to_token_stream(), placeholder))
+            || [enter_condition, exit_condition]
+                .into_iter()
+                .flatten()
+                .any(|when| mentions_ident(when.to_token_stream(), placeholder))
+            || mentions_ident(inner_block.clone(), placeholder)
+    };
+    let uses_context = uses_placeholder(&config.mangle(CONTEXT_PLACEHOLDER));
+    if init.is_some() || uses_context {
+        final_stmts.push(generate_state(&fn_name));
+    }
+
+    // Run the initialization, so that it precedes everything else the instrumentation does
+    if let Some(init) = &init {
+        let init = match mark("init") {
+            Some(mark) => parse_quote! { { #mark #init } },
+            None => init.clone(),
+        };
+        final_stmts.push(generate_init(&init));
+    }
+
+    if let Some(event_id) =
+        event_id.filter(|_| uses_placeholder(&config.mangle(EVENT_ID_PLACEHOLDER)))
+    {
+        final_stmts.push(generate_event_id(event_id, &fn_name, config));
+    }
+    if uses_context {
+        final_stmts.push(generate_context_binding(&fn_name, config));
+    }
+
+    // Count the call
+    if let Some(counter) = &count_calls {
+        final_stmts.extend(mark("count"));
+        final_stmts.push(generate_counter_increment(counter));
+    }
+
+    // Enter the span, so that it covers the hooks and the decorator as well. A span guard must not be held across an
+    // `.await`, so the rest of an async function is run as a future instrumented with the span instead.
+    let mut instrumented_from = None;
+    if let Some(options) = &instrument {
+        final_stmts.push(generate_span(options, &fn_name, &args));
+        if is_async {
+            instrumented_from = Some(final_stmts.len());
+        } else {
+            final_stmts.push(parse_quote! {
+                let __span_guard = __span.enter();
+            });
+        }
+    }
+
+    // Add on_enter call, guarded by its predicate if there is one, and time it for `phase_timing`
+    if phase_timing.is_some() {
+        final_stmts.push(parse_quote! {
+            let __phase_start = #axin::__private::Instant::now();
+        });
+    }
+    if let Some(on_enter) = &on_enter_fn {
+        let call_expr = generate_entry_hook_call(on_enter, &fn_name, &hook_args);
+        let hook_stmt = generate_hook_stmt(call_expr, on_enter, hook_error_sink.as_ref());
+        let hook_stmt = with_mark(mark("enter"), hook_stmt);
+        final_stmts.push(generate_entry_hook_stmt(hook_stmt, on_enter));
+    }
+    if phase_timing.is_some() {
+        final_stmts.push(parse_quote! {
+            let __phase_enter = __phase_start.elapsed();
+        });
+    }
+
+    // The parameters are moved into the call, so the exit hook gets clones of the ones it refers to, made before the
+    // call and rebound under their names around the hook, see `exit_hook_params`
+    let exit_params = on_exit_fn
+        .as_ref()
+        .map(|hook| exit_hook_params(fn_sig, hook))
+        .unwrap_or_default();
+    let (exit_clones, exit_rebindings) = clone_params(&exit_params, "__exit_arg");
+    final_stmts.extend(exit_clones);
+
+    // The guards running `on_exit_always` and `on_panic` are set up before the call as well, with clones of their own.
+    // The first is dropped after the exit hook, and the second disarmed once the call returns, so that either runs as
+    // the call unwinds otherwise. The panic hook's guard is declared last, so that it runs first then.
+    let guards = [
+        (
+            "__always_guard",
+            "AlwaysGuard",
+            on_exit_always_fn,
+            "__always_arg",
+            on_exit_always_fn.as_ref().and_then(|_| mark("exit_always")),
+        ),
+        (
+            "__panic_guard",
+            "PanicGuard",
+            on_panic_fn,
+            "__panic_arg",
+            None,
+        ),
+    ];
+    for (guard, kind, spec, prefix, mark) in guards {
+        let Some(spec) = spec else {
+            continue;
+        };
+        let params = mentioned_params(fn_sig, spec.to_token_stream());
+        let (clones, rebindings) = clone_params(&params, prefix);
+        final_stmts.extend(clones);
+        let guard = Ident::new(guard, Span::call_site());
+        let kind = Ident::new(kind, Span::call_site());
+        let call_expr = generate_function_call(spec);
+        final_stmts.push(parse_quote! {
+            let #guard = #axin::__private::#kind::new(|| {
+                #(#rebindings)*
+                #mark
+                #call_expr;
+            });
+        });
+    }
+
+    // A parameterized decorator is set up before the arguments are moved into the call, so its arguments may borrow
+    // the function's parameters, as long as the decorator it returns does not hold on to the borrow. With
+    // `borrow_args`, references to all arguments are appended to the setup call, and the decorator it returns still
+    // gets the arguments themselves. When adapting, the parameters are moved into the inner callable already, so it is
+    // set up before that.
+    let decorator_setup: Option<Stmt> = match &decorator_fn {
+        Some(decorator) if *borrow_args => {
+            let setup_call =
+                generate_function_call_with(decorator, quote! { #(&#borrowed_args),* });
+            Some(parse_quote! {
+                let __decorator = #setup_call;
+            })
+        }
+        Some(decorator @ FunctionSpec::WithArgs(..)) => {
+            let setup_call = generate_function_call(decorator);
+            Some(parse_quote! {
+                let __decorator = #setup_call;
+            })
+        }
+        _ => None,
+    };
+    if *adapt {
+        final_stmts.extend(decorator_setup.clone());
+    }
+
+    // Define the inner original function. Parameters that only custom span fields refer to are unused inside it. When
+    // capturing, it takes the parameters, including any `self` receiver, from the outer function and no arguments.
+    // Otherwise, with `nested_body`, it is a nested function where one can see everything the body refers to, see
+    // `nested_original`.
+    // Neither a closure nor a turbofish can name an `impl Trait` type, so those are left to be inferred. The output of
+    // an async body is pinned down by `typed_future`, so that `?` in it still knows what to convert to. Unlike a
+    // closure's, an async block's output type is not known while its body is checked, so the body first returns a value
+    // of that type, which it never does, to coerce the tail and the other `return`s to it, such as to a `Box<dyn Trait>`.
+    let inner_body = |block: TokenStream| {
+        if is_async {
+            let block = block_contents(block);
+            quote! {
+                -> _ {
+                    #axin::__private::typed_future::<#inner_output, _>(async move {
+                        if false {
+                            return #axin::__private::async_output::<#inner_output>();
+                        }
+                        #block
+                    })
+                }
+            }
+        } else {
+            quote! { -> #inner_output #block }
+        }
+    };
+    let real_body = inner_body(inner_block.clone());
+
+    // The types of the parameters of a closure must be known when its body is checked, but it cannot name the type of
+    // an `impl Trait` parameter. A closure taking one is passed through `__axin_typed`, which infers the parameter
+    // types from references to the arguments the closure is called with.
+    let impl_trait_params = !captures && !*packed && has_impl_trait_params(fn_sig);
+    if impl_trait_params {
+        final_stmts.push(generate_typed_callable_helper(call_args.len(), config));
+    }
+    let typed_callable = |closure: TokenStream| {
+        if impl_trait_params {
+            let typed = config.placeholder(TYPED_CALLABLE);
+            quote! { #typed(#(&#call_args,)* #closure) }
+        } else {
+            closure
+        }
+    };
+    let real_cfg = mock.map(|_| quote! { #[cfg(not(test))] });
+    let (inner_callable, call_result) = (hygienic(INNER_CALLABLE), hygienic(CALL_RESULT));
+    // The index of the statement binding the inner callable and the callable itself, for calling it inline instead
+    let mut bound_callable = None;
+    if captures {
+        let closure = quote! { #move_token || #real_body };
+        if mock.is_none() {
+            bound_callable = Some((final_stmts.len(), closure.clone()));
+        }
+        final_stmts.push(parse_quote! {
+            #real_cfg
+            let #inner_callable = #closure;
+        });
+    } else {
+        let allow_unused = instrument
+            .as_ref()
+            .filter(|options| !options.fields.is_empty())
+            .map(|_| quote! { #[allow(unused_variables)] });
+        let nested = nested_body.then(|| nested_original(fn_sig, config, &inner_block));
+        let closure = match nested.flatten() {
+            Some(turbofish) if packed_args.is_none() => {
+                let nested_fn = config.placeholder(NESTED_ORIGINAL);
+                let (generics, inputs) = (&fn_sig.generics, &fn_sig.inputs);
+                let where_clause = &generics.where_clause;
+                typed_callable(quote! {
+                    {
+                        fn #nested_fn #generics(#inputs) #fn_output #where_clause #inner_block
+                        #nested_fn #turbofish
+                    }
+                })
+            }
+            _ => {
+                let inputs = match &packed_args {
+                    Some(packed_args) => packed_args.pattern(true),
+                    None => closure_inputs(fn_sig),
+                };
+                typed_callable(quote! { |#inputs| #real_body })
+            }
+        };
+        if mock.is_none() && allow_unused.is_none() {
+            bound_callable = Some((final_stmts.len(), closure.clone()));
+        }
+        final_stmts.push(parse_quote! {
+            #real_cfg
+            #allow_unused
+            let #inner_callable = #closure;
+        });
+    }
+
+    // In test builds, the inner callable calls the mock with all arguments instead. It is defined with the same
+    // parameters as the real one, so that the instrumentation around it is the same.
+    if let Some(mock) = mock {
+        let mock_call = generate_mock_call(mock, &outer_sig);
+        let mark = mark("body");
+        let mock_body = inner_body(quote! { { #mark #mock_call } });
+        if captures {
+            final_stmts.push(parse_quote! {
+                #[cfg(test)]
+                let #inner_callable = #move_token || #mock_body;
+            });
+        } else {
+            let mut mock_sig = outer_sig.clone();
+            strip_mutability(&mut mock_sig);
+            let mock_inputs = match &packed_args {
+                Some(packed_args) => packed_args.pattern(false),
+                None => closure_inputs(&mock_sig),
+            };
+            let closure = typed_callable(quote! { |#mock_inputs| #mock_body });
+            final_stmts.push(parse_quote! {
+                #[cfg(test)]
+                let #inner_callable = #closure;
+            });
+        }
+    }
+    let await_result = is_async.then(|| quote! { .await });
+
+    // The inner callable may be wrapped in callables doing something around the body. A wrapper takes the same
+    // parameters, so that it fits the same decorators. The wrapped callable is moved out of its binding to be called,
+    // so that a callable capturing a `&mut self` receiver is called once rather than mutably borrowed.
+    let (wrapper_inputs, wrapper_args) = match &packed_args {
+        Some(_) => {
+            let args = config.placeholder(PACKED_ARGS);
+            (quote! { #args }, quote! { #args })
+        }
+        None if captures => (TokenStream::new(), TokenStream::new()),
+        None => {
+            let mut wrapper_sig = outer_sig.clone();
+            strip_mutability(&mut wrapper_sig);
+            (closure_inputs(&wrapper_sig), quote! { #(#call_args),* })
+        }
+    };
+
+    // For `phase_timing`, the inner callable is wrapped in one recording the time the body took, so that the rest of
+    // the time the decorator took is known to be its own.
+    if phase_timing.is_some() {
+        let (inputs, args) = (&wrapper_inputs, &wrapper_args);
+        final_stmts.push(parse_quote! {
+            let __phase_body = #axin::__private::Cell::new(#axin::__private::Duration::ZERO);
+        });
+        let wrapper = typed_callable(quote! {
+            |#inputs| -> #inner_output {
+                let __phase_body_start = #axin::__private::Instant::now();
+                let #call_result = { #inner_callable }(#args);
+                __phase_body.set(__phase_body_start.elapsed());
+                #call_result
+            }
+        });
+        final_stmts.push(parse_quote! {
+            let #inner_callable = #wrapper;
+        });
+    }
+
+    // For exit hooks asking whether the body ran, the inner callable is wrapped in one flagging that it was called,
+    // before the body starts, so the flag is set as well when the body panics and the decorator catches the panic. An
+    // async body is flagged when its future is created, whether or not the decorator then polls it.
+    let body_ran_used = on_exit_fn.as_ref().is_some_and(|hook| {
+        mentions_ident(hook.func.to_token_stream(), &config.mangle(BODY_RAN))
+            || mentions_ident(hook.when.to_token_stream(), &config.mangle(BODY_RAN))
+    });
+    if body_ran_used {
+        final_stmts.push(parse_quote! {
+            let __body_ran = #axin::__private::Cell::new(false);
+        });
+        let wrapper = typed_callable(quote! {
+            |#wrapper_inputs| {
+                __body_ran.set(true);
+                let #call_result = { #inner_callable }(#wrapper_args);
+                #call_result
+            }
+        });
+        final_stmts.push(parse_quote! {
+            let #inner_callable = #wrapper;
+        });
+    }
+
+    // Decorators after the first are nested in it by wrapping the inner callable in callables calling them, the last
+    // one innermost. A parameterized one is set up when the decorator around it calls into it, like a selected one.
+    for decorator in inner_decorators.iter().rev() {
+        let decorator_expr = match decorator {
+            FunctionSpec::Simple(path, _) => quote! { #path },
+            FunctionSpec::WithArgs(..) => {
+                let setup_call = generate_function_call(decorator);
+                quote! { { #setup_call } }
+            }
+        };
+        let wrapper_args: Vec<TokenStream> = match &packed_args {
+            Some(_) => vec![config.placeholder(PACKED_ARGS).into_token_stream()],
+            None if captures => Vec::new(),
+            None => call_args.iter().map(ToTokens::to_token_stream).collect(),
+        };
+        let decorator_call =
+            generate_decorator_call(&decorator_expr, &quote! { #inner_callable }, &wrapper_args);
+        let wrapper = typed_callable(quote! {
+            |#wrapper_inputs| #decorator_call
+        });
+        final_stmts.push(parse_quote! {
+            let #inner_callable = #wrapper;
+        });
+    }
+
+    // Start timing the decorator and the function body
+    let exit_condition_uses_elapsed =
+        exit_condition.is_some_and(|when| mentions_ident(when.to_token_stream(), EXIT_ELAPSED));
+    if slow_log.is_some() || exit_condition_uses_elapsed {
+        final_stmts.push(parse_quote! {
+            let __start = #axin::__private::Instant::now();
+        });
+    }
+    if phase_timing.is_some() {
+        final_stmts.push(parse_quote! {
+            let __phase_call = #axin::__private::Instant::now();
+        });
+    }
+
+    // Call decorator or directly call the original function. The result is annotated with the return type, so that a
+    // decorator's output type is known when the exit hook's predicate uses the result, before it is returned. An
+    // `impl Trait` return type names no type to annotate with. Callables kept in bindings are moved into their calls,
+    // so that whatever they capture is dropped when they return rather than at the end of the function: the exit
+    // hooks run after the body's locals and arguments are gone.
+    let result_ty = (!matches!(inner_output, Type::Infer(_))).then(|| quote! { : #inner_output });
+    let decorator_args: Vec<TokenStream> = match &packed_args {
+        Some(packed_args) => {
+            final_stmts.extend(packed_args.items());
+            vec![packed_args.construction()]
+        }
+        None => call_args.iter().map(ToTokens::to_token_stream).collect(),
+    };
+    if decorator_fn.is_some() || decorator_select.is_some() {
+        final_stmts.extend(mark("decorator"));
+    }
+    // The index of the statement calling a decorator without setup, and the decorator, for calling it inline instead
+    let mut plain_decorator_call = None;
+    if let Some(decorator) = &decorator_fn {
+        let decorator_expr = match (decorator_setup, decorator) {
+            (Some(setup), _) => {
+                if !*adapt {
+                    final_stmts.push(setup);
+                }
+                quote! { { __decorator } }
+            }
+            (None, decorator) => {
+                let path = decorator.path();
+                plain_decorator_call = Some((final_stmts.len(), quote! { #path }));
+                quote! { #path }
+            }
+        };
+        let decorator_call = generate_decorator_call(
+            &decorator_expr,
+            &quote! { #inner_callable },
+            &decorator_args,
+        );
+        final_stmts.push(parse_quote! {
+            let #call_result #result_ty = #decorator_call #await_result;
+        });
+    } else if let Some((selector, decorators)) = &decorator_select {
+        // Every arm calls its decorator the same way, so all of them must accept the same arguments and return the
+        // function's return type. Only the selected decorator is set up.
+        let arms = decorators.iter().enumerate().map(|(index, decorator)| {
+            let call = match decorator {
+                FunctionSpec::Simple(path, _) => generate_decorator_call(
+                    &quote! { #path },
+                    &quote! { #inner_callable },
+                    &decorator_args,
+                ),
+                FunctionSpec::WithArgs(..) => {
+                    let setup_call = generate_function_call(decorator);
+                    let decorator_call = generate_decorator_call(
+                        &quote! { __decorator },
+                        &quote! { #inner_callable },
+                        &decorator_args,
+                    );
+                    quote! {
+                        {
+                            let __decorator = #setup_call;
+                            #decorator_call
+                        }
+                    }
+                }
+            };
+            quote! { #index => #call #await_result, }
+        });
+        let message = format!(
+            "`{}` selected decorator {{}}, but only {} decorators are given",
+            fn_name,
+            decorators.len()
+        );
+        final_stmts.push(parse_quote! {
+            let #call_result #result_ty = match (#selector) as usize {
+                #(#arms)*
+                __index => ::core::panic!(#message, __index),
+            };
+        });
+    } else {
+        final_stmts.push(parse_quote! {
+            let #call_result #result_ty = { #inner_callable }(#(#call_args),*) #await_result;
+        });
+    }
+    if on_panic_fn.is_some() {
+        final_stmts.push(parse_quote! {
+            __panic_guard.disarm();
+        });
+    }
+
+    // Stop timing, so that the hooks below are not included
+    if slow_log.is_some() || exit_condition_uses_elapsed {
+        final_stmts.push(parse_quote! {
+            let __elapsed = __start.elapsed();
+        });
+    }
+    if body_ran_used {
+        let body_ran = config.placeholder(BODY_RAN);
+        final_stmts.push(parse_quote! {
+            let #body_ran = __body_ran.get();
+        });
+    }
+    if phase_timing.is_some() {
+        final_stmts.push(parse_quote! {
+            let __phase_decorator = __phase_call.elapsed().saturating_sub(__phase_body.get());
+        });
+        final_stmts.push(parse_quote! {
+            let __phase_exit_start = #axin::__private::Instant::now();
+        });
+    }
+
+    // Check the result against the expected value, in test builds only
+    if let Some(expected) = &assert_returns {
+        let message = format!("`{}` returned an unexpected value", fn_name);
+        final_stmts.extend(mark("assert"));
+        final_stmts.push(parse_quote! {
+            #[cfg(test)]
+            ::core::assert_eq!(#call_result, #expected, #message);
+        });
+    }
+
+    // Report the call if it was slow
+    if let Some((threshold, slow_log_fn)) = &slow_log {
+        let call_expr = generate_function_call_with(slow_log_fn, quote! { __elapsed });
+        let mark = mark("slow_log");
+        final_stmts.push(parse_quote! {
+            if __elapsed > #threshold {
+                #mark
+                #call_expr;
+            }
+        });
+    }
+
+    // Add on_error call. The error is only borrowed, and its type is left to the hook to bound, so any `E` (including
+    // `anyhow::Error` and `Box<dyn Error>`) works as long as the hook accepts it.
+    if let Some(on_error) = &on_error_fn {
+        let call_expr = generate_function_call_with(&on_error.func, quote! { __error });
+        let hook_stmt = generate_hook_stmt(call_expr, on_error, hook_error_sink.as_ref());
+        let mark = mark("error");
+        final_stmts.push(parse_quote! {
+            if let ::core::result::Result::Err(__error) = &#call_result {
+                #mark
+                #hook_stmt
+            }
+        });
+    }
+
+    // Add on_ok and on_err calls, matching the result as a `Result`, so that other return types fail to compile, at the
+    // hook. The success value and the error are only borrowed, under their placeholders if the hook refers to them.
+    if let Some(first) = on_ok_fn.as_ref().or(on_err_fn.as_ref()) {
+        let span = first.func.path().span();
+        let arm = |hook: &Option<Hook>, variant: &str, placeholder: &str, phase: &str| {
+            let variant = Ident::new(variant, Span::call_site());
+            let Some(hook) = hook else {
+                return quote_spanned! {span=> ::core::result::Result::#variant(_) => {} };
+            };
+            let binding = mentions_ident(hook.func.to_token_stream(), placeholder)
+                .then(|| Ident::new(placeholder, Span::call_site()))
+                .map_or_else(|| quote! { _ }, ToTokens::into_token_stream);
+            let pattern = quote_spanned! {hook.func.path().span()=>
+                ::core::result::Result::#variant(#binding)
+            };
+            let call_expr = generate_function_call(&hook.func);
+            let hook_stmt = generate_hook_stmt(call_expr, hook, hook_error_sink.as_ref());
+            let mark = mark(phase);
+            quote! { #pattern => { #mark #hook_stmt } }
+        };
+        let ok_arm = arm(on_ok_fn, "Ok", &config.mangle(OK_PLACEHOLDER), "ok");
+        let err_arm = arm(on_err_fn, "Err", &config.mangle(ERR_PLACEHOLDER), "err");
+        final_stmts.push(parse_quote! {
+            match &#call_result {
+                #ok_arm
+                #err_arm
+            }
+        });
+    }
+
+    // Add on_exit call, guarded by its predicate if there is one. The predicate sees the result and the elapsed time
+    // only as a borrowed `result` and a copied `elapsed`, the time the decorator and the body took, both scoped to the
+    // condition. The hook's arguments and the predicate alike see the result borrowed as `__axin_result`.
+    if let Some(on_exit) = &on_exit_fn {
+        let placeholder = config.placeholder(RESULT_PLACEHOLDER);
+        if mentions_ident(on_exit.func.to_token_stream(), &placeholder.to_string())
+            || mentions_ident(on_exit.when.to_token_stream(), &placeholder.to_string())
+        {
+            final_stmts.push(parse_quote! {
+                let #placeholder = &#call_result;
+            });
+        }
+        let call_expr = generate_function_call(&on_exit.func);
+        let hook_stmt = generate_hook_stmt(call_expr, on_exit, hook_error_sink.as_ref());
+        let hook_stmt = with_mark(mark("exit"), hook_stmt);
+        let exit_stmt = match &on_exit.when {
+            Some(when) => {
+                let result_binding = mentions_ident(when.to_token_stream(), EXIT_RESULT)
+                    .then(|| quote! { let result = &#call_result; });
+                let elapsed_binding =
+                    exit_condition_uses_elapsed.then(|| quote! { let elapsed = __elapsed; });
+                parse_quote! {
+                    if {
+                        #result_binding
+                        #elapsed_binding
+                        let __when: bool = #when;
+                        __when
+                    } {
+                        #hook_stmt
+                    }
+                }
+            }
+            None => hook_stmt,
+        };
+        if exit_rebindings.is_empty() {
+            final_stmts.push(exit_stmt);
+        } else {
+            final_stmts.push(parse_quote! {
+                {
+                    #(#exit_rebindings)*
+                    #exit_stmt
+                }
+            });
+        }
+    }
+    if on_exit_always_fn.is_some() {
+        final_stmts.push(parse_quote! {
+            ::core::mem::drop(__always_guard);
+        });
+    }
+
+    // Report the timings of the phases, after everything else
+    if let Some(phase_timing) = &phase_timing {
+        let call_expr = generate_function_call_with(
+            phase_timing,
+            quote! {
+                #axin::PhaseTimings::new(
+                    __phase_enter,
+                    __phase_decorator,
+                    __phase_body.get(),
+                    __phase_exit_start.elapsed(),
+                )
+            },
+        );
+        final_stmts.push(parse_quote! {
+            #call_expr;
+        });
+    }
+
+    // Check the trace of the call, in test builds only
+    if let Some(phases) = &assert_order {
+        final_stmts.push(parse_quote! {
+            #[cfg(test)]
+            #axin::__private::assert_order(__order_start, &[#(#phases),*], #fn_name);
+        });
+    }
+
+    // Always return the result, even if it's `()`. Between being produced and returned, the result is only ever
+    // borrowed, so `#[must_use]` return types keep warning at the call sites of the instrumented function. With nothing
+    // but a decorator without setup, the function is a single call of the decorator instead, with the inner callable
+    // written inline, whose result is the function's tail.
+    match (bound_callable, plain_decorator_call) {
+        (Some((0, callable)), Some((1, decorator))) if final_stmts.len() == 2 => {
+            let call = generate_decorator_call(&decorator, &callable, &decorator_args);
+            final_stmts = vec![Stmt::Expr(parse_quote! { #call #await_result }, None)];
+        }
+        _ => final_stmts.push(parse_quote! {
+            return #call_result;
+        }),
+    }
+
+    if let Some(start) = instrumented_from {
+        let instrumented_stmts = final_stmts.split_off(start);
+        final_stmts.push(parse_quote! {
+            return #axin::__private::tracing::Instrument::instrument(
+                async move { #(#instrumented_stmts)* },
+                __span,
+            )
+            .await;
+        });
+    }
+
+    plan.check_emitted();
+
+    // Build the final function
+    let final_block = syn::Block {
+        brace_token: original_block.brace_token,
+        stmts: final_stmts,
+    };
+
+    // Mutable parameters captured by the real body are left unmutated by the mock
+    let allow_unused_mut =
+        (captures && mock.is_some()).then(|| quote! { #[cfg_attr(test, allow(unused_mut))] });
+
+    quote! {
+        #(#fn_attrs)*
+        #(#outer_attrs)*
+        #allow_unused_mut
+        #fn_vis #outer_sig #final_block
+    }
+}
+
+/// Split the statements of a body into the items it declares and the other statements, so that the items can be put in
+/// front of statements inserted before the body.
+///
+/// Items are visible in their whole block, so moving them up changes nothing for the body, except for `macro_rules!`
+/// macros, which are only visible after their definition, and for inner attributes of the body, which would no longer
+/// apply to the items. With either of them, nothing is split off.
+fn split_items<'a>(
+    stmts: &'a [Stmt],
+    inner_attrs: &[&Attribute],
+) -> (Vec<&'a Stmt>, Vec<&'a Stmt>) {
+    let defines_macro = stmts.iter().any(|stmt| {
+        let mac = match stmt {
+            Stmt::Item(Item::Macro(item)) => &item.mac,
+            Stmt::Macro(stmt) => &stmt.mac,
+            _ => return false,
+        };
+        mac.path.is_ident("macro_rules")
+    });
+    if defines_macro || !inner_attrs.is_empty() {
+        return (Vec::new(), stmts.iter().collect());
+    }
+    stmts
+        .iter()
+        .partition(|stmt| matches!(stmt, Stmt::Item(item) if !matches!(item, Item::Macro(_))))
+}
+
+/// Generate the function replacing an instrumented one in test builds with `mock_in_tests(..., instrumented = false)`.
+///
+/// It has the same signature as the original function, with patterns replaced by generated names, and only calls the
+/// mock with all arguments.
+pub fn generate_mock_function(input_fn: &ItemFn, mock: &FunctionSpec) -> TokenStream {
+    let mut sig = input_fn.sig.clone();
+    for (index, arg) in sig.inputs.iter_mut().enumerate() {
+        if let FnArg::Typed(pat_type) = arg {
+            if !matches!(&*pat_type.pat, Pat::Ident(_)) {
+                let name = Ident::new(&format!("__arg{}", index), Span::call_site());
+                *pat_type.pat = parse_quote! { #name };
+            }
+        }
+    }
+    strip_mutability(&mut sig);
+    let call = generate_mock_call(mock, &sig);
+
+    let (inner_attrs, attrs): (Vec<&Attribute>, Vec<&Attribute>) = input_fn
+        .attrs
+        .iter()
+        .partition(|attr| matches!(attr.style, AttrStyle::Inner(_)));
+    let vis = &input_fn.vis;
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            #(#inner_attrs)*
+            #call
+        }
+    }
+}
+
+/// Generate the call of a mock with all parameters of `sig`, which are bound by identifiers only, passing the receiver
+/// first if there is one. The call is awaited for async functions.
+fn generate_mock_call(mock: &FunctionSpec, sig: &Signature) -> TokenStream {
+    let args = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Receiver(_) => quote! { self },
+        FnArg::Typed(pat_type) => match &*pat_type.pat {
+            Pat::Ident(pat_ident) => pat_ident.ident.to_token_stream(),
+            pat => pat.to_token_stream(),
+        },
+    });
+    let call = generate_function_call_with(mock, quote! { #(#args),* });
+    let await_call = sig.asyncness.map(|_| quote! { .await });
+    quote! { #call #await_call }
+}
+
+/// Build a block with the given braces, whose spans point at the original body, and the contents `fill` appends.
+fn braced(brace: Brace, fill: impl FnOnce(&mut TokenStream)) -> TokenStream {
+    let mut block = TokenStream::new();
+    brace.surround(&mut block, fill);
+    block
+}
+
+/// The contents of the braced `block`, to be spliced into another block, unless they start with inner attributes, which
+/// are only allowed at the start of a block. A block of its own ending another one would get `unused_braces` warnings
+/// pointing at the body.
+fn block_contents(block: TokenStream) -> TokenStream {
+    let mut tokens = block.clone().into_iter();
+    let Some(TokenTree::Group(group)) = tokens.next() else {
+        return block;
+    };
+    let mut contents = group.stream().into_iter();
+    let inner_attrs = matches!(
+        (contents.next(), contents.next()),
+        (Some(TokenTree::Punct(pound)), Some(TokenTree::Punct(bang)))
+            if pound.as_char() == '#' && bang.as_char() == '!'
+    );
+    if group.delimiter() != Delimiter::Brace || tokens.next().is_some() || inner_attrs {
+        return block;
+    }
+    group.stream()
+}
+
+/// Remove `mut` from the parameters of `sig` bound by value, which a mock only passes on.
+fn strip_mutability(sig: &mut Signature) {
+    for arg in sig.inputs.iter_mut() {
+        match arg {
+            FnArg::Receiver(receiver) if receiver.reference.is_none() => {
+                receiver.mutability = None;
+            }
+            FnArg::Typed(pat_type) => {
+                if let Pat::Ident(pat_ident) = &mut *pat_type.pat {
+                    pat_ident.mutability = None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The struct the arguments are packed into with `packed = struct`, declared in the body of the instrumented function.
+///
+/// It is named after the function, such as `__AxinArgsFetchReport` for `fetch_report`, and has a field for each
+/// parameter, named like it, or like the generated name of a parameter bound by a pattern. Its `Debug` implementation
+/// shows the field names. The function's generic parameters are copied, since an item declared in a body cannot use
+/// those of the function, and elided lifetimes of references are named, since a field cannot elide them. A marker field
+/// uses all of the function's type and lifetime parameters, as they need not appear in the parameters' types.
+struct PackedArgs {
+    /// Name of the struct
+    name: Ident,
+    /// Generic parameters of the function, followed by the named elided lifetimes
+    generics: Generics,
+    /// Names and types of the fields, in the order of the parameters
+    fields: Vec<(Ident, Type)>,
+    /// Whether the fields bound by `mut` parameters are unpacked into mutable bindings
+    mutable: Vec<bool>,
+    /// Types the marker field uses, if the function has any type or lifetime parameters
+    marker: Vec<TokenStream>,
+}
+
+impl PackedArgs {
+    /// Describe the struct for a function whose parameters are all bound to names, like those of the outer function.
+    /// The struct is named like `prefix`, the name of the packed arguments, in `UpperCamelCase` after its leading
+    /// underscores.
+    fn new(sig: &Signature, prefix: &str) -> Self {
+        let stem = prefix.trim_start_matches('_');
+        let name = format!(
+            "{}{}{}",
+            &prefix[..prefix.len() - stem.len()],
+            upper_camel_case(stem),
+            upper_camel_case(&sig.ident.unraw().to_string())
+        );
+        let mut lifetimes = NameElidedLifetimes::default();
+        let mut fields = Vec::new();
+        let mut mutable = Vec::new();
+        for arg in &sig.inputs {
+            if let FnArg::Typed(pat_type) = arg {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    let mut ty = (*pat_type.ty).clone();
+                    lifetimes.visit_type_mut(&mut ty);
+                    fields.push((pat_ident.ident.clone(), ty));
+                    mutable.push(pat_ident.mutability.is_some());
+                }
+            }
+        }
+
+        let mut generics = sig.generics.clone();
+        let first_non_lifetime = generics.lifetimes().count();
+        for (index, lifetime) in lifetimes.names.into_iter().enumerate() {
+            generics.params.insert(
+                first_non_lifetime + index,
+                GenericParam::Lifetime(LifetimeParam::new(lifetime)),
+            );
+        }
+        let marker = sig
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Lifetime(param) => {
+                    let lifetime = &param.lifetime;
+                    Some(quote! { &#lifetime () })
+                }
+                GenericParam::Type(param) => {
+                    let ident = &param.ident;
+                    Some(quote! { *const #ident })
+                }
+                GenericParam::Const(_) => None,
+            })
+            .collect();
+
+        PackedArgs {
+            name: Ident::new(&name, Span::call_site()),
+            generics,
+            fields,
+            mutable,
+            marker,
+        }
+    }
+
+    /// Generate the declaration of the struct and its `Debug` implementation.
+    ///
+    /// A field is only required to implement `Debug` where the implementation applies if its type mentions a type
+    /// parameter, the same way `#[derive(Debug)]` bounds the type parameters.
+    fn items(&self) -> Vec<Stmt> {
+        let name = &self.name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let params = &self.generics.params;
+        let (field_names, field_types): (Vec<_>, Vec<_>) =
+            self.fields.iter().map(|(name, ty)| (name, ty)).unzip();
+        let marker = (!self.marker.is_empty()).then(|| {
+            let marker = &self.marker;
+            quote! { __marker: ::core::marker::PhantomData<fn() -> (#(#marker,)*)>, }
+        });
+
+        let type_params: Vec<String> = self
+            .generics
+            .type_params()
+            .map(|param| param.ident.to_string())
+            .collect();
+        let mut debug_where = self.generics.clone().make_where_clause().clone();
+        for ty in &field_types {
+            let tokens = ty.to_token_stream();
+            if type_params
+                .iter()
+                .any(|param| mentions_ident(tokens.clone(), param))
+            {
+                debug_where
+                    .predicates
+                    .push(parse_quote! { #ty: ::core::fmt::Debug });
+            }
+        }
+        let struct_name = name.to_string();
+        let field_strs = field_names.iter().map(|name| name.unraw().to_string());
+
+        vec![
+            parse_quote! {
+                struct #name<#params> #where_clause {
+                    #(#field_names: #field_types,)*
+                    #marker
+                }
+            },
+            parse_quote! {
+                impl #impl_generics ::core::fmt::Debug for #name #ty_generics #debug_where {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.debug_struct(#struct_name)
+                            #(.field(#field_strs, &self.#field_names))*
+                            .finish()
+                    }
+                }
+            },
+        ]
+    }
+
+    /// Generate the expression packing the parameters into a value of the struct.
+    ///
+    /// The function's generic parameters are passed on explicitly, since those only the marker uses are not inferred.
+    fn construction(&self) -> TokenStream {
+        let name = &self.name;
+        let args = self.generics.params.iter().map(|param| match param {
+            GenericParam::Lifetime(param)
+                if param.lifetime.ident.to_string().starts_with("__axin") =>
+            {
+                quote! { '_ }
+            }
+            GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            GenericParam::Type(param) => param.ident.to_token_stream(),
+            GenericParam::Const(param) => param.ident.to_token_stream(),
+        });
+        let name = quote! { #name::<#(#args),*> };
+        let field_names = self.fields.iter().map(|(name, _)| name);
+        let marker =
+            (!self.marker.is_empty()).then(|| quote! { __marker: ::core::marker::PhantomData, });
+        quote! { #name { #(#field_names,)* #marker } }
+    }
+
+    /// Generate the pattern unpacking a value of the struct into the parameters, made mutable again if `mutable`.
+    fn pattern(&self, mutable: bool) -> TokenStream {
+        let name = &self.name;
+        let field_names = self
+            .fields
+            .iter()
+            .zip(&self.mutable)
+            .map(|((name, _), is_mut)| {
+                let mutability = (mutable && *is_mut).then(|| quote! { mut });
+                quote! { #mutability #name }
+            });
+        let rest = (!self.marker.is_empty()).then(|| quote! { .. });
+        quote! { #name { #(#field_names,)* #rest } }
+    }
+}
+
+/// Name the elided lifetimes of references and `'_` in a type, as `'__axin0`, `'__axin1` and so on.
+///
+/// Lifetimes elided in function pointers and `Fn` bounds belong to those, and are left alone.
+#[derive(Default)]
+struct NameElidedLifetimes {
+    names: Vec<Lifetime>,
+}
+
+impl NameElidedLifetimes {
+    fn fresh(&mut self) -> Lifetime {
+        let lifetime = Lifetime::new(&format!("'__axin{}", self.names.len()), Span::call_site());
+        self.names.push(lifetime.clone());
+        lifetime
+    }
+}
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, ty: &mut TypeReference) {
+        if ty.lifetime.is_none() {
+            ty.lifetime = Some(self.fresh());
+        }
+        visit_mut::visit_type_reference_mut(self, ty);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.fresh();
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {
+    }
+}
+
+/// Convert a `snake_case` name to `UpperCamelCase`.
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars))
+                .into_iter()
+                .flatten()
+        })
+        .collect()
+}
+
+/// The parts of the instrumented function's signature that decide whether and how it can be instrumented.
+///
+/// Any combination of `async`, `unsafe`, an `extern` ABI, generic parameters and a receiver is instrumented the same
+/// way: the outer function keeps its signature as written, such as `pub async unsafe extern "C" fn`, and the inner
+/// callable is a closure, returning an async block for async functions, with no modifiers of its own. It runs in the
+/// unsafe context of an `unsafe fn` like the original body did, and is called from the ABI's side of the outer
+/// function. Twins and mocks replacing the function in test builds copy the signature as written. [`check`] rejects the
+/// rest with targeted errors, so that none surfaces as an error inside the generated code.
+///
+/// [`check`]: SignatureProfile::check
+pub(crate) struct SignatureProfile {
+    /// `const`, if the function is a `const fn`
+    constness: Option<Token![const]>,
+    /// `async`, if the function is async
+    asyncness: Option<Token![async]>,
+    /// The `...` of a C-variadic function
+    variadic: Option<Variadic>,
+    /// The `self` receiver of a method
+    receiver: Option<Receiver>,
+    /// The parameters bound to a name that are mutable references
+    mutable_refs: Vec<Ident>,
+}
+
+impl SignatureProfile {
+    /// Classify the signature `sig`.
+    pub fn new(sig: &Signature) -> Self {
+        Self {
+            constness: sig.constness,
+            asyncness: sig.asyncness,
+            variadic: sig.variadic.clone(),
+            receiver: sig.receiver().cloned(),
+            mutable_refs: sig
+                .inputs
+                .iter()
+                .filter_map(|arg| match arg {
+                    FnArg::Typed(pat_type) => match (&*pat_type.pat, &*pat_type.ty) {
+                        (Pat::Ident(pat_ident), Type::Reference(reference))
+                            if reference.mutability.is_some() =>
+                        {
+                            Some(pat_ident.ident.clone())
+                        }
+                        _ => None,
+                    },
+                    FnArg::Receiver(_) => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the function is async, so that its inner callable returns a future the outer function awaits.
+    pub fn is_async(&self) -> bool {
+        self.asyncness.is_some()
+    }
+
+    /// Whether the function is a method taking `self` by value, which a capturing callable must move.
+    pub fn takes_self_by_value(&self) -> bool {
+        self.receiver
+            .as_ref()
+            .is_some_and(|receiver| receiver.reference.is_none())
+    }
+
+    /// How the function takes its receiver, if it is a method.
+    fn receiver_kind(&self) -> Option<ReceiverKind> {
+        let receiver = self.receiver.as_ref()?;
+        Some(match &*receiver.ty {
+            Type::Reference(reference) if reference.mutability.is_some() => ReceiverKind::Mutable,
+            Type::Reference(_) => ReceiverKind::Shared,
+            _ => ReceiverKind::Value,
+        })
+    }
+
+    /// Whether the inner callable only reborrows a `&mut self` receiver rather than moving it, so that the exit hook
+    /// can use `self` once the callable has returned. [`check`](Self::check) rejects the receivers exit hooks cannot
+    /// use.
+    pub fn reborrows_self(&self, config: &AxinConfig) -> bool {
+        self.receiver_kind() == Some(ReceiverKind::Mutable) && exit_hook_self(config).is_some()
+    }
+
+    /// Check that the function `name` can be instrumented as configured.
+    pub fn check(&self, name: &Ident, config: &AxinConfig) -> syn::Result<()> {
+        // Hooks, decorators and the inner callable cannot run in const contexts
+        if let Some(constness) = &self.constness {
+            return Err(syn::Error::new_spanned(
+                constness,
+                format!(
+                    "`{}` cannot be instrumented, since it is a `const fn` and the instrumentation cannot run in const \
+                     contexts; remove `const`, or skip the function when instrumenting a module or an `impl` block",
+                    name
+                ),
+            ));
+        }
+
+        // Nothing but the function itself can take C variadic arguments, so they cannot be passed on
+        if let Some(variadic) = &self.variadic {
+            return Err(syn::Error::new_spanned(
+                variadic,
+                format!(
+                    "`{}` cannot be instrumented, since its variadic arguments cannot be passed on to the inner callable",
+                    name
+                ),
+            ));
+        }
+
+        // The inner callable can only take a `self` receiver by capturing it, and a decorator is only passed such a
+        // callable with `adapt`
+        if let Some(receiver) = &self.receiver {
+            if !config.minimal && !config.raw_body && !config.adapt {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "methods taking `self` can only be decorated with `adapt`, which passes the decorator a callable \
+                     capturing the receiver",
+                ));
+            }
+        }
+
+        // Exit hooks run after the body, which owns a receiver taken by value by then. A `&mut self` receiver is only
+        // reborrowed by a synchronous inner callable: the future of an async body keeps it, and so does the guard
+        // running the exit hooks of `raw_body` while the body runs.
+        if let (Some(kind), Some(mention)) = (self.receiver_kind(), exit_hook_self(config)) {
+            let reason = match kind {
+                ReceiverKind::Value => Some(
+                    "it takes `self` by value, which the body consumes; take `&self` or `&mut self`, or use `self` in \
+                     an entry hook instead",
+                ),
+                ReceiverKind::Mutable if self.is_async() => Some(
+                    "it is an async method taking `&mut self`, which the future of its body keeps; take `&self`, or \
+                     use `self` in an entry hook instead",
+                ),
+                ReceiverKind::Mutable if config.raw_body => Some(
+                    "it takes `&mut self`, which the guard running the exit hooks of `raw_body` would borrow while the \
+                     body runs; use `adapt` instead of `raw_body`",
+                ),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                return Err(syn::Error::new(
+                    mention.span(),
+                    format!(
+                        "the exit hook of `{}` cannot use `self`, since {}",
+                        name, reason
+                    ),
+                ));
+            }
+        }
+
+        // The guards of `on_panic` and `on_exit_always` hold on to what their hooks borrow while the body runs, which
+        // has the receiver and mutable references to itself, unless the receiver is a shared reference
+        let guard_hooks = [
+            ("panic hook", &config.on_panic_fn),
+            ("always-run exit hook", &config.on_exit_always_fn),
+        ];
+        for (kind, spec) in guard_hooks {
+            let Some(spec) = spec else {
+                continue;
+            };
+            let receiver = match self.receiver_kind() {
+                Some(ReceiverKind::Shared) | None => None,
+                Some(_) => Some("self".to_string()),
+            };
+            let mention = receiver
+                .into_iter()
+                .chain(self.mutable_refs.iter().map(ToString::to_string))
+                .find_map(|param| find_ident(spec.to_token_stream(), &param));
+            if let Some(mention) = mention {
+                return Err(syn::Error::new(
+                    mention.span(),
+                    format!(
+                        "the {} of `{}` cannot use `{}`, since the guard running it would borrow it while the body \
+                         has it by value or mutably; use it in `on_exit` instead",
+                        kind, name, mention
+                    ),
+                ));
+            }
+        }
+
+        // A panic unwinds through the poll of an async function's future, not through its drop, which an executor
+        // catching the panic leaves for later, if it drops the future at all
+        if let (Some(asyncness), Some(_)) = (&self.asyncness, &config.on_panic_fn) {
+            return Err(syn::Error::new_spanned(
+                asyncness,
+                "`on_panic` does not support async functions, whose futures are dropped once their panics are caught; \
+                 use `on_exit_always` instead",
+            ));
+        }
+
+        // The body of an async function only runs when its future is awaited, interleaved with whatever else runs
+        // then
+        if let (Some(asyncness), Some(_)) = (&self.asyncness, &config.phase_timing) {
+            let parameter = if config.profile {
+                "profile"
+            } else {
+                "phase_timing"
+            };
+            return Err(syn::Error::new_spanned(
+                asyncness,
+                format!(
+                    "`{}` does not support async functions, whose phases are interleaved with other tasks",
+                    parameter
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How a method takes its receiver: by shared or mutable reference, or by value, as `self: Box<Self>` does too.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReceiverKind {
+    Shared,
+    Mutable,
+    Value,
+}
+
+/// The first mention of `self` in the exit hook's arguments or predicate, if any.
+fn exit_hook_self(config: &AxinConfig) -> Option<Ident> {
+    let hook = config.on_exit_fn.as_ref()?;
+    let mut tokens = hook.func.to_token_stream();
+    hook.when.to_tokens(&mut tokens);
+    find_ident(tokens, "self")
+}
+
+/// Check that the arguments of `input_fn` can be packed into a struct declared in its body.
+///
+/// A field cannot have an `impl Trait` type, and an item declared in a body cannot use `Self`.
+pub fn check_packed_args(input_fn: &ItemFn) -> syn::Result<()> {
+    for arg in &input_fn.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            let ty = pat_type.ty.to_token_stream();
+            if mentions_ident(ty.clone(), "impl") {
+                return Err(syn::Error::new_spanned(
+                    &pat_type.ty,
+                    "`packed` cannot pack an argument of an `impl Trait` type, use a generic parameter instead",
+                ));
+            }
+            if mentions_ident(ty, "Self") {
+                return Err(syn::Error::new_spanned(
+                    &pat_type.ty,
+                    "`packed` cannot pack an argument whose type mentions `Self`, name the type instead",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Generate the statement creating the `tracing` span of `instrument_compat`.
+///
+/// Like `#[tracing::instrument]`, the span records every parameter that is not skipped with its `Debug`
+/// representation, unless a custom field of the same name replaces it.
+fn generate_span(options: &InstrumentOptions, fn_name: &str, args: &[&Ident]) -> Stmt {
+    let axin = axin_crate_path();
+    let tracing = quote! { #axin::__private::tracing };
+
+    let target = match &options.target {
+        Some(target) => quote! { #target },
+        None => quote! { ::core::module_path!() },
+    };
+    let level = match &options.level {
+        Some(InstrumentLevel::Named(level)) => quote! { #tracing::Level::#level },
+        Some(InstrumentLevel::Expr(level)) => quote! { #level },
+        None => quote! { #tracing::Level::INFO },
+    };
+    let name = match &options.name {
+        Some(name) => quote! { #name },
+        None => quote! { #fn_name },
+    };
+
+    let recorded = args.iter().copied().filter(|arg| {
+        !options.skip_all
+            && !options.skip.contains(arg)
+            && !options
+                .fields
+                .iter()
+                .any(|field| field.name.len() == 1 && field.name[0] == **arg)
+    });
+    // Fields declared without a value are recorded later, so they start out empty
+    let fields = options.fields.iter().map(|field| {
+        let name = &field.name;
+        match &field.value {
+            Some(value) => {
+                let sigil = &field.sigil;
+                quote! { #name = #sigil #value }
+            }
+            None => quote! { #name = #tracing::field::Empty },
+        }
+    });
+
+    parse_quote! {
+        let __span = #tracing::span!(
+            target: #target,
+            #level,
+            #name,
+            #(#recorded = #tracing::field::debug(&#recorded),)*
+            #(#fields),*
+        );
+    }
+}
+
+/// Check the options of `instrument_compat` against the function they are applied to.
+pub fn check_instrument_compat(input_fn: &ItemFn, options: &InstrumentOptions) -> syn::Result<()> {
+    check_skipped_params(input_fn, &options.skip)
+}
+
+/// Check the parameters an entry hook's `with_args` skips against the function it is applied to.
+pub fn check_hook_args(input_fn: &ItemFn, hook: &Hook) -> syn::Result<()> {
+    match &hook.with_args {
+        Some(with_args) => check_skipped_params(input_fn, &with_args.skip),
+        None => Ok(()),
+    }
+}
+
+/// Check that only `on_exit` refers to the result placeholder, since everything else runs before the function returns,
+/// or without seeing what it returns, like the guard running the exit hook of a `raw_body` function.
+pub fn check_result_placeholder(config: &AxinConfig) -> syn::Result<()> {
+    let mut before_return = TokenStream::new();
+    if let Some(on_enter) = &config.on_enter_fn {
+        on_enter.func.to_tokens(&mut before_return);
+        on_enter.when.to_tokens(&mut before_return);
+    }
+    config.decorator_fn.to_tokens(&mut before_return);
+    before_return.append_all(&config.inner_decorators);
+    if let Some((selector, decorators)) = &config.decorator_select {
+        selector.to_tokens(&mut before_return);
+        before_return.append_all(decorators);
+    }
+    before_return.append_all(&config.prologue_stmts);
+    before_return.append_all(&config.epilogue_stmts);
+    config.init.to_tokens(&mut before_return);
+    let placeholder = config.mangle(RESULT_PLACEHOLDER);
+    if let Some(ident) = find_ident(before_return, &placeholder) {
+        return Err(syn::Error::new_spanned(
+            &ident,
+            format!(
+                "`{}` refers to the return value, which only the arguments and the predicate of `on_exit` can use; \
+                 this runs before the function returns",
+                placeholder
+            ),
+        ));
+    }
+
+    let mut other_hooks = TokenStream::new();
+    for hook in [&config.on_error_fn, &config.on_ok_fn, &config.on_err_fn]
+        .into_iter()
+        .flatten()
+    {
+        hook.func.to_tokens(&mut other_hooks);
+    }
+    if let Some((_, slow_log_fn)) = &config.slow_log {
+        slow_log_fn.to_tokens(&mut other_hooks);
+    }
+    config.hook_error_sink.to_tokens(&mut other_hooks);
+    config.phase_timing.to_tokens(&mut other_hooks);
+    config.on_panic_fn.to_tokens(&mut other_hooks);
+    config.on_exit_always_fn.to_tokens(&mut other_hooks);
+    if let Some(ident) = find_ident(other_hooks, &placeholder) {
+        return Err(syn::Error::new_spanned(
+            &ident,
+            format!(
+                "`{}` refers to the return value, which only the arguments and the predicate of `on_exit` can use",
+                placeholder
+            ),
+        ));
+    }
+
+    if config.raw_body {
+        let on_exit = config
+            .on_exit_fn
+            .as_ref()
+            .map(|hook| hook.func.to_token_stream());
+        if let Some(ident) = on_exit.and_then(|tokens| find_ident(tokens, &placeholder)) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!(
+                    "`{}` cannot be used with `raw_body`, whose exit hook is run by a guard that does not see the \
+                     return value",
+                    placeholder
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that only `on_ok` refers to the success value placeholder, and only `on_err` to the error placeholder, since
+/// nothing else runs where the result is matched.
+pub fn check_outcome_placeholders(config: &AxinConfig) -> syn::Result<()> {
+    let hooks = [
+        (config.mangle(OK_PLACEHOLDER), "on_ok", &config.on_ok_fn),
+        (config.mangle(ERR_PLACEHOLDER), "on_err", &config.on_err_fn),
+    ];
+    for (placeholder, parameter, own) in hooks {
+        let own = own.as_ref().map(|hook| &hook.func);
+        let mut elsewhere: TokenStream = config
+            .function_specs()
+            .filter(|spec| !own.is_some_and(|own| std::ptr::eq(own, *spec)))
+            .map(ToTokens::to_token_stream)
+            .collect();
+        for hook in [&config.on_enter_fn, &config.on_exit_fn]
+            .into_iter()
+            .flatten()
+        {
+            hook.when.to_tokens(&mut elsewhere);
+        }
+        if let Some((selector, _)) = &config.decorator_select {
+            selector.to_tokens(&mut elsewhere);
+        }
+        elsewhere.append_all(&config.prologue_stmts);
+        elsewhere.append_all(&config.epilogue_stmts);
+        config.init.to_tokens(&mut elsewhere);
+        if let Some(ident) = find_ident(elsewhere, &placeholder) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!(
+                    "`{}` is only bound in the arguments of `{}`",
+                    placeholder, parameter
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Replace the hooks listed in `stub_missing` by generated functions doing nothing, and return the statements declaring
+/// them, along with the warning naming the stubbed hooks.
+///
+/// A stub takes as many arguments as its hook is called with, of any types, so that the arguments are still evaluated
+/// and the parameters they use are not left unused. It is named after the hook with the `__axin_stub_` prefix, so that
+/// the body keeps seeing whatever the hook's name refers to, and the hook keeps its name in documentation and manifests
+/// as its alias. Only hooks named by a single identifier can be stubbed, and decorators cannot be, since the function
+/// would not run without them.
+pub fn stub_missing_hooks(config: &mut AxinConfig) -> syn::Result<Vec<Stmt>> {
+    let Some((keyword, names)) = config.stub_missing.take() else {
+        return Ok(Vec::new());
+    };
+    let prefix = config.mangle(STUB_PREFIX);
+    let decorators = config
+        .decorator_fn
+        .iter()
+        .chain(&config.inner_decorators)
+        .chain(
+            config
+                .decorator_select
+                .iter()
+                .flat_map(|(_, decorators)| decorators),
+        );
+    for decorator in decorators {
+        if let Some(name) = names.iter().find(|name| decorator.path().is_ident(*name)) {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "`{}` is a decorator, which `stub_missing` cannot stub, since the function would not run without \
+                     it; remove the decorator until it exists",
+                    name
+                ),
+            ));
+        }
+    }
+
+    // The hooks with the number of arguments they are called with, besides those of the attribute
+    let with_args = config
+        .on_enter_fn
+        .as_ref()
+        .is_some_and(|hook| hook.with_args.is_some());
+    let hooks = [
+        (
+            config.on_enter_fn.as_mut().map(|hook| &mut hook.func),
+            if with_args { 2 } else { 0 },
+        ),
+        (config.on_exit_fn.as_mut().map(|hook| &mut hook.func), 0),
+        (config.on_error_fn.as_mut().map(|hook| &mut hook.func), 1),
+        (config.on_ok_fn.as_mut().map(|hook| &mut hook.func), 0),
+        (config.on_err_fn.as_mut().map(|hook| &mut hook.func), 0),
+        (config.slow_log.as_mut().map(|(_, func)| func), 1),
+        (config.phase_timing.as_mut(), 1),
+        (config.on_panic_fn.as_mut(), 0),
+        (config.on_exit_always_fn.as_mut(), 0),
+    ];
+    let mut stubs: Vec<(&Ident, usize)> = Vec::new();
+    for (spec, added) in hooks {
+        let Some(spec) = spec else {
+            continue;
+        };
+        let Some(name) = names.iter().find(|name| spec.path().is_ident(*name)) else {
+            continue;
+        };
+        let arity = match &*spec {
+            FunctionSpec::Simple(..) => added,
+            FunctionSpec::WithArgs(_, args, _) => args.len() + added,
+        };
+        match stubs.iter().find(|(stubbed, _)| *stubbed == name) {
+            Some((_, stub_arity)) if *stub_arity != arity => {
+                return Err(syn::Error::new_spanned(
+                    spec.path(),
+                    format!(
+                        "`{}` is called with {} arguments here and {} elsewhere, but `stub_missing` can only stub a \
+                         hook called with the same number of arguments everywhere",
+                        name, arity, stub_arity
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => stubs.push((name, arity)),
+        }
+        let stub = Ident::new(&format!("{}{}", prefix, name), spec.path().span());
+        let path = parse_quote! { #stub };
+        match spec {
+            FunctionSpec::Simple(hook_path, alias)
+            | FunctionSpec::WithArgs(hook_path, _, alias) => {
+                *hook_path = path;
+                alias.get_or_insert_with(|| name.clone());
+            }
+        }
+    }
+    if let Some(name) = names
+        .iter()
+        .find(|name| !stubs.iter().any(|(stubbed, _)| stubbed == name))
+    {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "`{}` is not the name of an entry, exit, error, success, failure, panic, slow call or phase timing \
+                 hook of this attribute, which are the hooks `stub_missing` can stub",
+                name
+            ),
+        ));
+    }
+
+    let mut stmts: Vec<Stmt> = stubs
+        .iter()
+        .map(|(name, arity)| {
+            let stub = Ident::new(&format!("{}{}", prefix, name), name.span());
+            let params: Vec<Ident> = (0..*arity)
+                .map(|index| Ident::new(&format!("__A{}", index), Span::call_site()))
+                .collect();
+            parse_quote! {
+                fn #stub<#(#params),*>(#(_: #params),*) {}
+            }
+        })
+        .collect();
+    stmts.push(stubbed_hooks_warning(&keyword, &names));
+    Ok(stmts)
+}
+
+/// Check that no parameter of `input_fn` and no binding of the prologue is named like one of the [`RESERVED_NAMES`] the
+/// attribute refers to, with the attribute's prefix, see [`AxinConfig::mangle`]. The placeholder would shadow the parameter in the arguments of hooks and decorators, and the
+/// prologue's binding would shadow the placeholder in the body, so either refers to something else than written.
+pub fn check_reserved_names(input_fn: &ItemFn, config: &AxinConfig) -> syn::Result<()> {
+    let mut arg_tokens = config
+        .prologue_stmts
+        .iter()
+        .map(ToTokens::to_token_stream)
+        .collect::<TokenStream>();
+    for hook in [
+        &config.on_enter_fn,
+        &config.on_exit_fn,
+        &config.on_error_fn,
+        &config.on_ok_fn,
+        &config.on_err_fn,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        hook.func.to_tokens(&mut arg_tokens);
+        hook.when.to_tokens(&mut arg_tokens);
+    }
+    config.decorator_fn.to_tokens(&mut arg_tokens);
+    arg_tokens.append_all(&config.inner_decorators);
+    if let Some((selector, decorators)) = &config.decorator_select {
+        selector.to_tokens(&mut arg_tokens);
+        arg_tokens.append_all(decorators);
+    }
+    if let Some((_, slow_log_fn)) = &config.slow_log {
+        slow_log_fn.to_tokens(&mut arg_tokens);
+    }
+    config.hook_error_sink.to_tokens(&mut arg_tokens);
+    config.phase_timing.to_tokens(&mut arg_tokens);
+    config.on_panic_fn.to_tokens(&mut arg_tokens);
+    config.on_exit_always_fn.to_tokens(&mut arg_tokens);
+    let exit_condition = config
+        .on_exit_fn
+        .as_ref()
+        .and_then(|hook| hook.when.as_ref())
+        .map(ToTokens::to_token_stream)
+        .unwrap_or_default();
+    let referenced = |name: &str| match name {
+        EXIT_RESULT | EXIT_ELAPSED => mentions_ident(exit_condition.clone(), name),
+        EVENT_ID_PLACEHOLDER => {
+            config.event_id.is_some() && mentions_ident(arg_tokens.clone(), &config.mangle(name))
+        }
+        _ => mentions_ident(arg_tokens.clone(), &config.mangle(name)),
+    };
+
+    /// Collects the names bound by patterns.
+    struct Bindings(Vec<Ident>);
+
+    impl VisitMut for Bindings {
+        fn visit_pat_ident_mut(&mut self, pat_ident: &mut syn::PatIdent) {
+            self.0.push(pat_ident.ident.clone());
+            visit_mut::visit_pat_ident_mut(self, pat_ident);
+        }
+    }
+
+    let mut params = Bindings(Vec::new());
+    for arg in &input_fn.sig.inputs {
+        if let FnArg::Typed(pat_type) = arg {
+            params.visit_pat_mut(&mut pat_type.pat.as_ref().clone());
+        }
+    }
+    let mut prologue_bindings = Bindings(Vec::new());
+    for stmt in &config.prologue_stmts {
+        if let Stmt::Local(local) = stmt {
+            prologue_bindings.visit_pat_mut(&mut local.pat.clone());
+        }
+    }
+    let bindings = params
+        .0
+        .into_iter()
+        .map(|ident| (ident, "parameter"))
+        .chain(
+            prologue_bindings
+                .0
+                .into_iter()
+                .map(|ident| (ident, "binding")),
+        );
+    for (ident, kind) in bindings {
+        let reserved = RESERVED_NAMES
+            .iter()
+            .find(|(name, _)| ident == config.mangle(name) && referenced(name));
+        if let Some((name, meaning)) = reserved {
+            let name = config.mangle(name);
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!(
+                    "`{}` is reserved for {}, which the attribute refers to; rename the {}",
+                    name, meaning, kind
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that no hook or decorator path starts with a name imported by a `use` inside the body of `input_fn`. Hooks and
+/// decorators are called outside of the body, where its imports do not apply, so such a path would refer to whatever
+/// the name means where the function is defined, if anything. Glob imports are not checked, since the names they import
+/// are not known here.
+pub fn check_body_imports(input_fn: &ItemFn, config: &AxinConfig) -> syn::Result<()> {
+    /// Collects the names imported by `use` items.
+    struct Imports(Vec<Ident>);
+
+    impl VisitMut for Imports {
+        fn visit_use_name_mut(&mut self, name: &mut syn::UseName) {
+            self.0.push(name.ident.clone());
+        }
+
+        fn visit_use_rename_mut(&mut self, rename: &mut syn::UseRename) {
+            self.0.push(rename.rename.clone());
+        }
+    }
+
+    let mut imports = Imports(Vec::new());
+    imports.visit_block_mut(&mut input_fn.block.as_ref().clone());
+    if imports.0.is_empty() {
+        return Ok(());
+    }
+
+    for spec in config.function_specs() {
+        let path = spec.path();
+        let Some(first) = path
+            .segments
+            .first()
+            .filter(|_| path.leading_colon.is_none())
+        else {
+            continue;
+        };
+        if imports.0.contains(&first.ident) {
+            return Err(syn::Error::new_spanned(
+                path,
+                format!(
+                    "`{}` is imported inside the body of `{}`, but hook and decorator paths are resolved where the \
+                     function is defined, outside of its body; move the `use` out of the function, or write the \
+                     full path",
+                    first.ident, input_fn.sig.ident
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that no hook, decorator, mock or sink refers to `input_fn` itself, e.g. after copying an attribute from another
+/// function, which would call the function from its own instrumentation and recurse until the stack overflows.
+///
+/// A single-segment path or one starting with `self::` refers to a free function of the same name, and `Self::name` to
+/// an associated one. An associated function, one of an `impl` block as told by `associated`, or one taking `self` or
+/// mentioning `Self` in its signature, is never called through a bare name, which resolves to a free function even
+/// inside the block, so only `Self::name` refers to it. Paths through other modules are never flagged, since they name
+/// other functions.
+pub fn check_recursion(
+    input_fn: &ItemFn,
+    config: &AxinConfig,
+    associated: bool,
+) -> syn::Result<()> {
+    let name = &input_fn.sig.ident;
+    let associated = associated
+        || input_fn.sig.receiver().is_some()
+        || find_ident(input_fn.sig.to_token_stream(), "Self").is_some();
+    for spec in config.function_specs() {
+        let path = spec.path();
+        let segments: Vec<_> = path.segments.iter().map(|segment| &segment.ident).collect();
+        let recurses = path.leading_colon.is_none()
+            && match segments.as_slice() {
+                [last] => !associated && *last == name,
+                [prefix, last] => {
+                    *last == name && (*prefix == "Self" || (*prefix == "self" && !associated))
+                }
+                _ => false,
+            };
+        if recurses {
+            return Err(syn::Error::new_spanned(
+                path,
+                format!(
+                    "`{}` refers to the function being instrumented, which would recurse; to call `{}` without its \
+                     instrumentation, give it a `twin = \"...\"` and refer to the twin instead",
+                    path.to_token_stream().to_string().replace(' ', ""),
+                    name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that every name in `skip` is a parameter of `input_fn`.
+fn check_skipped_params(input_fn: &ItemFn, skip: &[Ident]) -> syn::Result<()> {
+    for skip in skip {
+        let exists = input_fn.sig.inputs.iter().any(|arg| match arg {
+            FnArg::Typed(pat_type) => {
+                matches!(&*pat_type.pat, Pat::Ident(pat_ident) if pat_ident.ident == *skip)
+            }
+            FnArg::Receiver(_) => false,
+        });
+        if !exists {
+            return Err(syn::Error::new_spanned(
+                skip,
+                format!(
+                    "cannot skip `{}`, which is not a parameter of `{}`",
+                    skip, input_fn.sig.ident
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate the statement binding the state the function keeps across calls, for `init` and the call context.
+///
+/// The state is local to the function, so every function has its own, even if it has the same name as another one.
+/// The static is declared in a block of its own, so that it does not shadow an item of the same name that `init` or
+/// the hooks refer to. With the `test-support` feature, the state is registered on the first call, under the path that
+/// the accessor made by [`reset_accessor`] resets.
+fn generate_state(fn_name: &str) -> Stmt {
+    let axin = axin_crate_path();
+    let register = cfg!(feature = "test-support").then(|| {
+        quote! {
+            __AXIN_STATE.register(
+                ::core::concat!(::core::module_path!(), "::", #fn_name),
+                || __AXIN_STATE.reset(),
+            );
+        }
+    });
+    parse_quote! {
+        let __fn_state = {
+            static __AXIN_STATE: #axin::__private::FnState = #axin::__private::FnState::new();
+            #register
+            &__AXIN_STATE
+        };
+    }
+}
+
+/// Check whether the expansion of a function keeps state across calls, see [`generate_state`].
+pub fn keeps_state(expanded: TokenStream) -> bool {
+    mentions_ident(expanded, "__AXIN_STATE")
+}
+
+/// Whether a function an attribute is applied to directly may be a method of a trait `impl` block, which cannot have
+/// items the trait does not declare: it takes `self` or refers to `Self` in its signature, so that it is in an `impl`
+/// block, and has no visibility, which the methods of trait `impl` blocks cannot have.
+pub fn may_implement_trait(input_fn: &ItemFn) -> bool {
+    matches!(input_fn.vis, Visibility::Inherited)
+        && (input_fn.sig.receiver().is_some()
+            || mentions_ident(input_fn.sig.to_token_stream(), "Self"))
+}
+
+/// Generate the accessor `reset_<name>_axin_state()` resetting the state of the function, to be emitted next to it
+/// with the same visibility, if the `test-support` feature is enabled. Methods taking `self` get none, since an
+/// accessor next to one would be an associated function of its type, and the state is reset by `reset_all()` instead.
+/// The accessor is meant for tests only, and is hidden from rustdoc.
+pub fn reset_accessor(input_fn: &ItemFn) -> Option<ItemFn> {
+    if !cfg!(feature = "test-support") || input_fn.sig.receiver().is_some() {
+        return None;
+    }
+    let axin = axin_crate_path();
+    let vis = &input_fn.vis;
+    let name = &input_fn.sig.ident;
+    let fn_name = name.to_string();
+    let accessor = Ident::new(
+        &format!("reset_{}_axin_state", fn_name.trim_start_matches("r#")),
+        name.span(),
+    );
+    let doc = format!(
+        " Reset the state the instrumentation of `{}` keeps across calls, as if it was not called yet.",
+        fn_name
+    );
+    Some(parse_quote! {
+        #[doc = #doc]
+        #[doc(hidden)]
+        #[allow(dead_code, non_snake_case)]
+        #vis fn #accessor() {
+            #axin::test_support::reset(::core::concat!(::core::module_path!(), "::", #fn_name));
+        }
+    })
+}
+
+/// Generate the statement binding the call context placeholder.
+///
+/// Each instrumented function gets its own call counter, so `call_id` counts the calls of that function only.
+fn generate_context_binding(fn_name: &str, config: &AxinConfig) -> Stmt {
+    let axin = axin_crate_path();
+    let placeholder = config.placeholder(CONTEXT_PLACEHOLDER);
+    parse_quote! {
+        let #placeholder = #axin::AxinCtx::new(
+            #fn_name,
+            ::core::module_path!(),
+            __fn_state.next_call(),
+        );
+    }
+}
+
+/// Generate the constant holding the event id.
+///
+/// An automatic id is computed by `axin::event_id` at compile time, so that the macro and the function users decode
+/// events with cannot disagree.
+fn generate_event_id(event_id: EventId, fn_name: &str, config: &AxinConfig) -> Stmt {
+    let axin = axin_crate_path();
+    let placeholder = config.placeholder(EVENT_ID_PLACEHOLDER);
+    let value = match event_id {
+        EventId::Auto => quote! { #axin::event_id(#fn_name) },
+        EventId::Explicit(id) => quote! { #id },
+    };
+    parse_quote! {
+        #[allow(non_upper_case_globals)]
+        const #placeholder: u16 = #value;
+    }
+}
+
+/// Generate the statement evaluating `init` on the first call only.
+///
+/// The state recording that it ran is the function's own, see [`generate_state`], so every function evaluates its own
+/// `init`, even if it is the same expression as another function's. Concurrent first calls wait until the expression
+/// has been evaluated.
+fn generate_init(init: &Expr) -> Stmt {
+    parse_quote! {
+        __fn_state.init(|| {
+            #init;
+        });
+    }
+}
+
+/// A phase of a call, one of [`ORDER_PHASES`], with the hooks or decorators run in it, if any.
+pub struct PlanStep {
+    pub phase: &'static str,
+    pub detail: Option<String>,
+}
+
+impl PlanStep {
+    /// The step as listed by `order_const`, as in `"enter:trace"` or `"body"`.
+    pub fn render(&self) -> String {
+        match &self.detail {
+            Some(detail) => format!("{}:{}", self.phase, detail),
+            None => self.phase.to_string(),
+        }
+    }
+}
+
+/// The phases the instrumentation of one attribute runs in a call, in order, whether or not they are traced.
+///
+/// The generator traces the phases through the plan as it emits the code running them, and checks in debug builds,
+/// such as its tests, that it emitted the code of exactly the phases planned. `order_const` lists the steps of the
+/// plans of all attributes of a function, nested as the attributes are, see [`ExecutionPlan::nest`].
+pub struct ExecutionPlan {
+    steps: Vec<PlanStep>,
+    /// The phases whose code was emitted so far
+    emitted: RefCell<Vec<String>>,
+}
+
+impl ExecutionPlan {
+    pub fn new(config: &AxinConfig) -> Self {
+        let hook = |hook: &Option<Hook>| hook.as_ref().map(|hook| Some(hook.func.reported_name()));
+        let names = |decorators: &mut dyn Iterator<Item = &FunctionSpec>, separator: &str| {
+            decorators
+                .map(FunctionSpec::reported_name)
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        let steps = ORDER_PHASES
+            .iter()
+            .filter_map(|&phase| {
+                let detail = match phase {
+                    "init" => config.init.as_ref().map(|_| None),
+                    "count" => config
+                        .count_calls
+                        .as_ref()
+                        .map(|counter| Some(path_to_string(counter))),
+                    "enter" => hook(&config.on_enter_fn),
+                    // Nested decorators are called in turn, and the selected one of several in their place
+                    "decorator" => match (&config.decorator_fn, &config.decorator_select) {
+                        (Some(decorator), _) => Some(Some(names(
+                            &mut std::iter::once(decorator).chain(&config.inner_decorators),
+                            " > ",
+                        ))),
+                        (None, Some((_, decorators))) => {
+                            Some(Some(names(&mut decorators.iter(), " | ")))
+                        }
+                        (None, None) => None,
+                    },
+                    "prologue" => (!config.prologue_stmts.is_empty()).then_some(None),
+                    "body" => Some(None),
+                    "epilogue" => (!config.epilogue_stmts.is_empty()).then_some(None),
+                    "assert" => config.assert_returns.as_ref().map(|_| None),
+                    "slow_log" => config.slow_log.as_ref().map(|(_, func)| {
+                        Some(if config.profile {
+                            "profile".to_string()
+                        } else {
+                            func.reported_name()
+                        })
+                    }),
+                    "error" => hook(&config.on_error_fn),
+                    "ok" => hook(&config.on_ok_fn),
+                    "err" => hook(&config.on_err_fn),
+                    "exit" => hook(&config.on_exit_fn),
+                    "exit_always" => config
+                        .on_exit_always_fn
+                        .as_ref()
+                        .map(|func| Some(func.reported_name())),
+                    _ => unreachable!("no plan for the phase `{}`", phase),
+                };
+                detail.map(|detail| PlanStep { phase, detail })
+            })
+            .collect();
+        ExecutionPlan {
+            steps,
+            emitted: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Nest the plan of an attribute stacked below into this one, after the body, whose code runs the instrumentation of
+    /// the attribute below.
+    pub fn nest(mut self, inner: ExecutionPlan) -> Self {
+        if let Some(body) = self.steps.iter().position(|step| step.phase == "body") {
+            self.steps.splice(body + 1..body + 1, inner.steps);
+        }
+        self
+    }
+
+    pub fn steps(&self) -> &[PlanStep] {
+        &self.steps
+    }
+
+    /// Note that the code running `phase` is emitted, returning the statement tracing it if it is to be traced.
+    fn emit(&self, phase: &str, traced: bool) -> Option<Stmt> {
+        debug_assert!(
+            self.steps.iter().any(|step| step.phase == phase),
+            "the phase `{}` is not planned",
+            phase
+        );
+        self.emitted.borrow_mut().push(phase.to_string());
+        traced.then(|| generate_order_mark(phase))
+    }
+
+    /// Check that the code of every phase planned is emitted.
+    fn check_emitted(&self) {
+        let emitted = self.emitted.borrow();
+        for step in &self.steps {
+            debug_assert!(
+                emitted.iter().any(|phase| phase == step.phase),
+                "the phase `{}` is planned, but not emitted",
+                step.phase
+            );
+        }
+    }
+}
+
+/// Generate the constant `<NAME>_AXIN_ORDER` listing the steps of `plan`, for `order_const`, to be emitted next to the
+/// function with the same visibility, and hidden from rustdoc like the accessor resetting its state.
+pub fn generate_order_const(input_fn: &ItemFn, plan: &ExecutionPlan) -> ItemConst {
+    let vis = &input_fn.vis;
+    let fn_name = input_fn.sig.ident.unraw().to_string();
+    let name = Ident::new(
+        &format!("{}_AXIN_ORDER", fn_name.to_uppercase()),
+        input_fn.sig.ident.span(),
+    );
+    let doc = format!(
+        " The phases a call of `{}` runs, in order, each followed by the hooks or decorators run in it.",
+        fn_name
+    );
+    let steps = plan.steps().iter().map(PlanStep::render);
+    parse_quote! {
+        #[doc = #doc]
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #vis const #name: &[&str] = &[#(#steps),*];
+    }
+}
+
+/// Generate the statement tracing that `phase` of a call runs, for `assert_order`.
+///
+/// The trace is only kept in test builds, so the statement is compiled out of all others.
+fn generate_order_mark(phase: &str) -> Stmt {
+    let axin = axin_crate_path();
+    parse_quote! {
+        #[cfg(test)]
+        #axin::__private::order_mark(#phase);
+    }
+}
+
+/// Put `mark`, if there is one, in front of `stmt` in a block of their own.
+fn with_mark(mark: Option<Stmt>, stmt: Stmt) -> Stmt {
+    match mark {
+        Some(mark) => parse_quote! {
+            {
+                #mark
+                #stmt
+            }
+        },
+        None => stmt,
+    }
+}
+
+/// Generate the statement incrementing a user-provided call counter.
+fn generate_counter_increment(counter: &Path) -> Stmt {
+    let axin = axin_crate_path();
+    parse_quote! {
+        #axin::CallCounter::increment(&#counter);
+    }
+}
+
+/// Path to the `axin` crate as seen from the crate being expanded.
+///
+/// Falls back to `::axin` if the crate cannot be located, e.g. when it is renamed in a way that `proc-macro-crate`
+/// does not understand, or when expanding inside `axin` itself (which declares `extern crate self as axin`).
+fn axin_crate_path() -> TokenStream {
+    match crate_name("axin") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote! { ::#name }
+        }
+        _ => quote! { ::axin },
+    }
+}
+
+/// The parameters of `sig` as the parameters of a closure, whose types cannot be `impl Trait`, see
+/// [`infer_impl_trait`].
+fn closure_inputs(sig: &Signature) -> TokenStream {
+    let inputs = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Typed(pat_type) => {
+            let attrs = &pat_type.attrs;
+            let pat = &pat_type.pat;
+            let ty = infer_impl_trait(&pat_type.ty);
+            quote! { #(#attrs)* #pat: #ty }
+        }
+        FnArg::Receiver(receiver) => receiver.to_token_stream(),
+    });
+    quote! { #(#inputs),* }
+}
+
+/// The turbofish naming the nested function running `body` for `nested_body` if it can be one, rather than a closure. A
+/// nested function shows up under a name of its own in backtraces, but cannot see the locals of the outer body, such as
+/// the call context, nor the generic parameters and `Self` type of an `impl` block. Whether a function without a
+/// receiver is a method using the generic parameters of its block cannot be told from the function alone, which is why
+/// the nested function must be asked for; it is still left out for methods of an `impl` block the attribute is applied
+/// to, for functions that take a receiver, are async or unsafe, or mention `Self`. It declares the same generic
+/// parameters as the function, and the turbofish passes the types and constants on, leaving the lifetimes to be
+/// inferred.
+fn nested_original(
+    sig: &Signature,
+    config: &AxinConfig,
+    body: &TokenStream,
+) -> Option<TokenStream> {
+    let free = !config.associated
+        && sig.receiver().is_none()
+        && sig.asyncness.is_none()
+        && sig.unsafety.is_none()
+        && !mentions_ident(sig.to_token_stream(), "Self")
+        && !mentions_ident(body.clone(), "Self");
+    let outer_locals = [CONTEXT_PLACEHOLDER, EVENT_ID_PLACEHOLDER];
+    if !free
+        || outer_locals
+            .iter()
+            .any(|name| mentions_ident(body.clone(), &config.mangle(name)))
+    {
+        return None;
+    }
+    let params: Vec<&Ident> = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    Some(if params.is_empty() {
+        TokenStream::new()
+    } else {
+        quote! { ::<#(#params),*> }
+    })
+}
+
+/// Whether the type of a parameter of `sig` mentions `impl Trait`.
+fn has_impl_trait_params(sig: &Signature) -> bool {
+    sig.inputs.iter().any(|arg| match arg {
+        FnArg::Typed(pat_type) => mentions_ident(pat_type.ty.to_token_stream(), "impl"),
+        FnArg::Receiver(_) => false,
+    })
+}
+
+/// Generate the function `__axin_typed`, named with the attribute's prefix, which takes references to `arity` arguments
+/// and returns the callable given after them, constrained to take those arguments. A closure passed to it has its
+/// parameter types inferred from the arguments, since closures passed to a function are checked after its other
+/// arguments.
+fn generate_typed_callable_helper(arity: usize, config: &AxinConfig) -> Stmt {
+    let typed = config.placeholder(TYPED_CALLABLE);
+    let params: Vec<Ident> = (0..arity)
+        .map(|index| Ident::new(&format!("__A{}", index), Span::call_site()))
+        .collect();
+    parse_quote! {
+        fn #typed<#(#params,)* __R, __F: FnOnce(#(#params),*) -> __R>(#(_: &#params,)* f: __F) -> __F {
+            f
+        }
+    }
+}
+
+/// Replace every `impl Trait` in a type by `_`, to be inferred where the type itself cannot be named.
+fn infer_impl_trait(ty: &Type) -> Type {
+    struct InferImplTrait;
+
+    impl VisitMut for InferImplTrait {
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            if let Type::ImplTrait(_) = ty {
+                *ty = parse_quote! { _ };
+            } else {
+                visit_mut::visit_type_mut(self, ty);
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    InferImplTrait.visit_type_mut(&mut ty);
+    ty
+}
+
+/// Check whether a token stream mentions the given identifier anywhere, including inside nested groups.
+fn mentions_ident(tokens: TokenStream, name: &str) -> bool {
+    find_ident(tokens, name).is_some()
+}
+
+/// The parameters of a function bound to a name that its exit hook refers to in its arguments or its predicate, each with
+/// its first mention there, to be cloned before they are moved into the call.
+fn exit_hook_params(sig: &Signature, hook: &Hook) -> Vec<(Ident, Ident)> {
+    let mut tokens = hook.func.to_token_stream();
+    hook.when.to_tokens(&mut tokens);
+    mentioned_params(sig, tokens)
+}
+
+/// The parameters of a function bound to a name that `tokens` refer to, each with its first mention there.
+///
+/// Parameters of reference types are left out, since the call leaves them to the hooks: shared references are copied,
+/// and mutable ones reborrowed unless the inner callable captures them.
+fn mentioned_params(sig: &Signature, tokens: TokenStream) -> Vec<(Ident, Ident)> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => match (&*pat_type.pat, &*pat_type.ty) {
+                (_, Type::Reference(_)) => None,
+                (Pat::Ident(pat_ident), _) => {
+                    find_ident(tokens.clone(), &pat_ident.ident.to_string())
+                        .map(|mention| (pat_ident.ident.clone(), mention))
+                }
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+/// Statements cloning the parameters `params`, made before the call into bindings named `prefix` and an index, and
+/// statements rebinding the clones under the names of the parameters, around the hook they are kept for.
+fn clone_params(params: &[(Ident, Ident)], prefix: &str) -> (Vec<Stmt>, Vec<Stmt>) {
+    params
+        .iter()
+        .enumerate()
+        .map(|(index, (param, mention))| -> (Stmt, Stmt) {
+            let kept = Ident::new(&format!("{}{}", prefix, index), Span::call_site());
+            (
+                parse_quote_spanned! {mention.span()=>
+                    let #kept = ::core::clone::Clone::clone(&#mention);
+                },
+                parse_quote! {
+                    #[allow(unused_variables)]
+                    let #param = #kept;
+                },
+            )
+        })
+        .unzip()
+}
+
+/// Find the first mention of the given identifier in a token stream, including inside nested groups.
+fn find_ident(tokens: TokenStream, name: &str) -> Option<Ident> {
+    tokens.into_iter().find_map(|token| match token {
+        TokenTree::Ident(ident) if ident == name => Some(ident),
+        TokenTree::Group(group) => find_ident(group.stream(), name),
+        _ => None,
+    })
+}
+
+/// Generate function call expression from a function specification.
+///
+/// Converts a `FunctionSpec` into the appropriate function call token stream,
+/// handling both simple function calls and calls with arguments.
+fn generate_function_call(func_spec: &FunctionSpec) -> TokenStream {
+    match func_spec {
+        FunctionSpec::Simple(path, _) => {
+            quote! { #path() }
+        }
+        FunctionSpec::WithArgs(path, args, _) => {
+            quote! { #path(#args) }
+        }
+    }
+}
+
+/// Generate function call expression from a function specification, appending an extra argument.
+///
+/// The extra argument is passed after any arguments given in the specification.
+fn generate_function_call_with(func_spec: &FunctionSpec, extra: TokenStream) -> TokenStream {
+    match func_spec {
+        FunctionSpec::Simple(path, _) => {
+            quote! { #path(#extra) }
+        }
+        FunctionSpec::WithArgs(path, args, _) if args.is_empty() => {
+            quote! { #path(#extra) }
+        }
+        FunctionSpec::WithArgs(path, args, _) => {
+            let args = args.iter();
+            quote! { #path(#(#args,)* #extra) }
+        }
+    }
+}
+
+/// Generate the statement calling a hook, handling its error according to the hook's policy.
+///
+/// Without a policy, the hook's return value is discarded as it is. Otherwise, it goes through
+/// `axin::HookResult`, so that hooks returning `()` are accepted too, as infallible ones.
+fn generate_hook_stmt(call_expr: TokenStream, hook: &Hook, sink: Option<&FunctionSpec>) -> Stmt {
+    let axin = axin_crate_path();
+    let hook_result = quote! { #axin::HookResult::into_hook_result(#call_expr) };
+    match (hook.errors, sink) {
+        (None, _) => parse_quote! { #call_expr; },
+        (Some(HookErrorPolicy::Ignore), _) => parse_quote! { let _ = #hook_result; },
+        (Some(HookErrorPolicy::Propagate), _) => parse_quote! { #hook_result?; },
+        (Some(HookErrorPolicy::Log), Some(sink)) => {
+            let sink_call = generate_function_call_with(sink, quote! { &__hook_error });
+            parse_quote! {
+                if let ::core::result::Result::Err(__hook_error) = #hook_result {
+                    #sink_call;
+                }
+            }
+        }
+        // Rejected by `AxinArgs::validate_hook_errors`
+        (Some(HookErrorPolicy::Log), None) => unreachable!("logging hook errors without a sink"),
+    }
+}
+
+/// Generate the call of an entry hook.
+///
+/// With `with_args`, the function's name and a borrowed tuple of references to its arguments, except the skipped ones,
+/// are appended to the hook's arguments. `args` are the names the arguments are bound to, in order; a `self` receiver
+/// is never passed.
+fn generate_entry_hook_call(hook: &Hook, fn_name: &str, args: &[Ident]) -> TokenStream {
+    let Some(with_args) = &hook.with_args else {
+        return generate_function_call(&hook.func);
+    };
+    let args = args.iter().filter(|arg| !with_args.skip.contains(arg));
+    generate_function_call_with(&hook.func, quote! { #fn_name, &(#(&#args,)*) })
+}
+
+/// Guard the statement calling an entry hook with the hook's predicate, if there is one.
+///
+/// The predicate is evaluated before the hook's arguments, so arguments that are expensive to compute are only
+/// computed if the hook is called. It sees nothing but the function's parameters and generic parameters, such as a
+/// `const VERBOSE: bool` the caller picks. It is bound before it is tested, so that a predicate starting with a block,
+/// such as `unsafe { LEVEL } > 2`, is not taken for a statement of its own.
+fn generate_entry_hook_stmt(hook_stmt: Stmt, hook: &Hook) -> Stmt {
+    match &hook.when {
+        Some(when) => parse_quote! {
+            if { let __when: bool = #when; __when } {
+                #hook_stmt
+            }
+        },
+        None => hook_stmt,
+    }
+}
+
+/// Generate decorator call expression for wrapping the original function.
+///
+/// `decorator` is the expression evaluating to the decorator: its path for simple decorators, or the binding holding
+/// the result of a parameterized decorator's setup call. `callable` is the inner callable, usually its binding. The
+/// original function arguments are passed through after the inner function to maintain the function signature.
+fn generate_decorator_call(
+    decorator: &TokenStream,
+    callable: &TokenStream,
+    orig_args: &[TokenStream],
+) -> TokenStream {
+    if orig_args.is_empty() {
+        quote! { #decorator(#callable) }
+    } else {
+        quote! { #decorator(#callable, #(#orig_args),*) }
+    }
+}
+
+/// Generate doc attributes appending a list of the active instrumentation to the function's doc comments.
+///
+/// The section starts with an empty line, so it becomes a paragraph of its own after any existing documentation.
+fn generate_instrumentation_doc(items: &[String]) -> Vec<Attribute> {
+    let mut lines = vec![String::new(), " Instrumented by axin:".to_string()];
+    if items.is_empty() {
+        lines.push(" - nothing".to_string());
+    }
+    lines.extend(items.iter().map(|item| format!(" - {}", item)));
+    lines
+        .iter()
+        .map(|line| parse_quote! { #[doc = #line] })
+        .collect()
+}
+
+/// Render a path the way it is usually written, without the spaces of its token representation.
+fn path_to_string(path: &Path) -> String {
+    path.to_token_stream().to_string().replace(' ', "")
+}
+
+/// Terminate a prologue statement so that further statements can follow it.
+///
+/// Only a trailing expression without a semicolon needs one. Block-like expressions such as `if`, `match`, loops and
+/// (labeled) blocks are complete statements already and are kept exactly as written; `let` statements, including
+/// `let ... else`, items and macro statements are passed through untouched.
+fn terminate_stmt(stmt: Stmt) -> Stmt {
+    match stmt {
+        Stmt::Expr(expr, None) if !is_block_like(&expr) => {
+            // The semicolon borrows the expression's span rather than the call site, which may be meaningless for
+            // code coming from `include!` or other macros.
+            let semi = Token![;](expr.span());
+            Stmt::Expr(expr, Some(semi))
+        }
+        stmt => stmt,
+    }
+}
+
+/// Whether an expression can end a statement without a semicolon.
+fn is_block_like(expr: &Expr) -> bool {
+    match expr {
+        Expr::Block(_)
+        | Expr::Const(_)
+        | Expr::ForLoop(_)
+        | Expr::If(_)
+        | Expr::Loop(_)
+        | Expr::Match(_)
+        | Expr::TryBlock(_)
+        | Expr::Unsafe(_)
+        | Expr::While(_) => true,
+        Expr::Macro(mac) => matches!(mac.mac.delimiter, MacroDelimiter::Brace(_)),
+        _ => false,
+    }
+}
+
+/// An entry, exit or error hook.
+pub(crate) struct Hook {
+    /// Function specification
+    pub func: FunctionSpec,
+    /// Predicate deciding whether the hook is called, for entry and exit hooks
+    pub when: Option<Expr>,
+    /// What to do with the error the hook returns, from its own `errors` or the attribute's `hook_errors`
+    pub errors: Option<HookErrorPolicy>,
+    /// Whether the function's name and arguments are passed to the hook, for entry hooks
+    pub with_args: Option<HookArgs>,
+}
+
+/// The instrumentation requested by the arguments of one `axin` attribute, see [`process_attribute_args`].
+pub(crate) struct AxinConfig {
+    /// Prologue statements to insert
+    pub prologue_stmts: Vec<Stmt>,
+    /// Epilogue statements to insert after the body
+    pub epilogue_stmts: Vec<Stmt>,
+    /// Decorator function specification
+    pub decorator_fn: Option<FunctionSpec>,
+    /// Decorators given after the first, each wrapped by the one before
+    pub inner_decorators: Vec<FunctionSpec>,
+    /// Whether the decorator's setup call borrows the function's arguments
+    pub borrow_args: bool,
+    /// Decorator selector and the decorators to select from
+    pub decorator_select: Option<(Expr, Vec<FunctionSpec>)>,
+    /// Entry hook
+    pub on_enter_fn: Option<Hook>,
+    /// Exit hook
+    pub on_exit_fn: Option<Hook>,
+    /// Error hook
+    pub on_error_fn: Option<Hook>,
+    /// Hook run when the function returns `Ok`
+    pub on_ok_fn: Option<Hook>,
+    /// Hook run when the function returns `Err`
+    pub on_err_fn: Option<Hook>,
+    /// Function called with the errors of hooks under the `"log"` policy
+    pub hook_error_sink: Option<FunctionSpec>,
+    /// Expected return value
+    pub assert_returns: Option<Expr>,
+    /// Slow call threshold and hook
+    pub slow_log: Option<(Expr, FunctionSpec)>,
+    /// Attributes generated for the instrumented function, emitted after the function's own attributes
+    pub outer_attrs: Vec<Attribute>,
+    /// Call counter
+    pub count_calls: Option<Path>,
+    /// Expression evaluated once, before the first call is instrumented
+    pub init: Option<Expr>,
+    /// Whether minimal mode is enabled
+    pub minimal: bool,
+    /// Whether the body is emitted unchanged, with the hooks around it
+    pub raw_body: bool,
+    /// Whether the inner callable is adapted to take no arguments
+    pub adapt: bool,
+    /// Whether the body runs in a nested function where it can, see [`nested_original`]
+    pub nested_body: bool,
+    /// The `explain` keyword, if present
+    pub explain: Option<Ident>,
+    /// How to react to decorators on trivial functions
+    pub trivial_policy: TrivialPolicy,
+    /// Options of the `tracing` span to enter
+    pub instrument: Option<InstrumentOptions>,
+    /// Function replacing the body in test builds, and whether the instrumentation still applies to it
+    pub mock_in_tests: Option<(FunctionSpec, bool)>,
+    /// Id bound to the event id placeholder
+    pub event_id: Option<EventId>,
+    /// Whether the arguments are passed to the decorator packed into a struct
+    pub packed: bool,
+    /// Function called with the timings of the phases of every call
+    pub phase_timing: Option<FunctionSpec>,
+    /// Phases every call is expected to run, in order
+    pub assert_order: Option<Vec<String>>,
+    /// Whether the phase timing and slow call hooks report to the profile sinks
+    pub profile: bool,
+    /// The `stub_missing` keyword, if present, and the hooks to stub
+    pub stub_missing: Option<(Ident, Vec<Ident>)>,
+    /// Hook run by a guard when the call unwinds
+    pub on_panic_fn: Option<FunctionSpec>,
+    /// Hook run after the exit hook, or by a guard when the call unwinds
+    pub on_exit_always_fn: Option<FunctionSpec>,
+    /// Whether the function is a method of the `impl` block the attribute was applied to
+    pub associated: bool,
+    /// Prefix of the placeholders and generated names, from `generated_prefix`, see [`AxinConfig::mangle`]
+    pub prefix: String,
+}
+
+impl AxinConfig {
+    /// The name `name`, one of the placeholders or generated names given with the [`DEFAULT_PREFIX`], with the
+    /// attribute's prefix instead.
+    pub(crate) fn mangle(&self, name: &str) -> String {
+        mangle(&self.prefix, name)
+    }
+
+    /// The identifier of the placeholder or generated name `name` with the attribute's prefix, which code from the
+    /// macro input can refer to.
+    fn placeholder(&self, name: &str) -> Ident {
+        Ident::new(&self.mangle(name), Span::call_site())
+    }
+
+    /// The functions the instrumentation calls: hooks, decorators, the slow call hook, sinks and the mock.
+    pub(crate) fn function_specs(&self) -> impl Iterator<Item = &FunctionSpec> {
+        let hooks = [
+            &self.on_enter_fn,
+            &self.on_exit_fn,
+            &self.on_error_fn,
+            &self.on_ok_fn,
+            &self.on_err_fn,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|hook| &hook.func);
+        let decorators = self
+            .decorator_fn
+            .iter()
+            .chain(&self.inner_decorators)
+            .chain(
+                self.decorator_select
+                    .iter()
+                    .flat_map(|(_, decorators)| decorators),
+            );
+        let others = [
+            self.slow_log.as_ref().map(|(_, func)| func),
+            self.hook_error_sink.as_ref(),
+            self.phase_timing.as_ref(),
+            self.on_panic_fn.as_ref(),
+            self.on_exit_always_fn.as_ref(),
+            self.mock_in_tests.as_ref().map(|(func, _)| func),
+        ];
+        hooks.chain(decorators).chain(others.into_iter().flatten())
+    }
+}
+
+/// Process and extract components from attribute arguments.
+///
+/// Parses the macro arguments and separates them into their respective components, collected in an [`AxinConfig`].
+/// Module filters, `debug_only`, `cfg`, `no_defaults`, options and twins must have been split off before. Fails on a
+/// decorator given twice, pointing at both, see [`check_duplicate_decorators`].
+pub(crate) fn process_attribute_args(
+    attribute_args: crate::args::AxinArgs,
+) -> syn::Result<AxinConfig> {
+    check_duplicate_decorators([&attribute_args])?;
+    let mut prologue_stmts: Vec<Stmt> = Vec::new();
+    let mut epilogue_stmts: Vec<Stmt> = Vec::new();
+    let mut decorator_fn: Option<FunctionSpec> = None;
+    let mut inner_decorators: Vec<FunctionSpec> = Vec::new();
+    let mut borrow_args = false;
+    let mut decorator_select: Option<(Expr, Vec<FunctionSpec>)> = None;
+    let mut on_enter_fn: Option<Hook> = None;
+    let mut on_exit_fn: Option<Hook> = None;
+    let mut on_error_fn: Option<Hook> = None;
+    let mut on_ok_fn: Option<Hook> = None;
+    let mut on_err_fn: Option<Hook> = None;
+    let mut hook_errors: Option<HookErrorPolicy> = None;
+    let mut hook_error_sink: Option<FunctionSpec> = None;
+    let mut assert_returns: Option<Expr> = None;
+    let mut slow_log: Option<(Expr, FunctionSpec)> = None;
+    let mut outer_attrs: Vec<Attribute> = Vec::new();
+    let mut count_calls: Option<Path> = None;
+    let mut init: Option<Expr> = None;
+    let mut minimal = false;
+    let mut raw_body = false;
+    let mut adapt = false;
+    let mut nested_body = false;
+    let mut explain: Option<Ident> = None;
+    let mut trivial_policy = TrivialPolicy::Allow;
+    let mut allow_trivial = false;
+    let mut document = false;
+    let mut instrument: Option<InstrumentOptions> = None;
+    let mut mock_in_tests: Option<(FunctionSpec, bool)> = None;
+    let mut event_id: Option<EventId> = None;
+    let mut packed = false;
+    let mut phase_timing: Option<FunctionSpec> = None;
+    let mut assert_order: Option<Vec<String>> = None;
+    let mut profile: Option<Expr> = None;
+    let mut stub_missing: Option<(Ident, Vec<Ident>)> = None;
+    let mut on_panic_fn: Option<FunctionSpec> = None;
+    let mut on_exit_always_fn: Option<FunctionSpec> = None;
+    let mut prefix = DEFAULT_PREFIX.to_string();
+
+    for arg in attribute_args.args.into_iter() {
+        match arg {
+            AxinArg::Prologue { stmts } => {
+                prologue_stmts.extend(stmts.into_iter().map(terminate_stmt));
+            }
+            AxinArg::Epilogue { stmts } => {
+                epilogue_stmts.extend(stmts.into_iter().map(terminate_stmt));
+            }
+            AxinArg::OnEnter {
+                func,
+                when,
+                errors,
+                with_args,
+            } => {
+                on_enter_fn = Some(Hook {
+                    func,
+                    when,
+                    errors,
+                    with_args,
+                });
+            }
+            // Every exit hook runs after the locals and arguments are dropped, so `after_drop` changes nothing here
+            AxinArg::OnExit {
+                func, when, errors, ..
+            } => {
+                on_exit_fn = Some(Hook {
+                    func,
+                    when,
+                    errors,
+                    with_args: None,
+                });
+            }
+            AxinArg::OnError { func, errors } => {
+                on_error_fn = Some(Hook {
+                    func,
+                    when: None,
+                    errors,
+                    with_args: None,
+                });
+            }
+            AxinArg::OnOk { func, errors } => {
+                on_ok_fn = Some(Hook {
+                    func,
+                    when: None,
+                    errors,
+                    with_args: None,
+                });
+            }
+            AxinArg::OnErr { func, errors } => {
+                on_err_fn = Some(Hook {
+                    func,
+                    when: None,
+                    errors,
+                    with_args: None,
+                });
+            }
+            AxinArg::Decorator {
+                func,
+                borrow_args: borrow,
+                no_args,
+            } => {
+                // Only the first decorator can borrow the arguments, see `AxinArgs::validate_decorators`
+                if decorator_fn.is_none() {
+                    decorator_fn = Some(func);
+                    borrow_args = borrow;
+                } else {
+                    inner_decorators.push(func);
+                }
+                adapt |= no_args;
+            }
+            AxinArg::DecoratorSelect {
+                selector,
+                decorators,
+                ..
+            } => {
+                decorator_select = Some((selector, decorators.into_iter().collect()));
+            }
+            AxinArg::AssertReturns { expected } => {
+                assert_returns = Some(expected);
+            }
+            AxinArg::SlowLog { threshold, func } => {
+                slow_log = Some((threshold, func));
+            }
+            AxinArg::WarnCallSite { message } => {
+                outer_attrs.push(parse_quote! { #[deprecated(note = #message)] });
+            }
+            AxinArg::CountCalls { counter } => {
+                count_calls = Some(counter);
+            }
+            AxinArg::Minimal { .. } => {
+                minimal = true;
+            }
+            AxinArg::RawBody { .. } => {
+                raw_body = true;
+            }
+            AxinArg::Adapt => {
+                adapt = true;
+            }
+            AxinArg::NestedBody => {
+                nested_body = true;
+            }
+            AxinArg::GeneratedPrefix { prefix: value } => {
+                prefix = value.value();
+            }
+            AxinArg::Explain { keyword } => {
+                explain = Some(keyword);
+            }
+            AxinArg::WarnIfTrivial => {
+                if !matches!(trivial_policy, TrivialPolicy::Deny) {
+                    trivial_policy = TrivialPolicy::Warn;
+                }
+            }
+            AxinArg::DenyTrivialDecorator => {
+                trivial_policy = TrivialPolicy::Deny;
+            }
+            AxinArg::AllowTrivial => {
+                allow_trivial = true;
+            }
+            AxinArg::Document => {
+                document = true;
+            }
+            AxinArg::InstrumentCompat { options } => {
+                instrument = Some(options);
+            }
+            // Module filters, flags, options and twins are split off before, see `AxinArgs::take_module_filters`,
+            // `AxinArgs::take_gate`, `AxinArgs::take_no_defaults`, `AxinArgs::remove_options` and
+            // `AxinArgs::remove_twins`. The order constant is emitted for all attributes of the function together.
+            AxinArg::Skip { .. }
+            | AxinArg::Only { .. }
+            | AxinArg::DebugOnly
+            | AxinArg::Cfg { .. }
+            | AxinArg::NoDefaults
+            | AxinArg::Opt { .. }
+            | AxinArg::Twin { .. }
+            | AxinArg::OrderConst { .. } => {}
+            AxinArg::HookErrors { policy, .. } => {
+                hook_errors = Some(policy);
+            }
+            AxinArg::HookErrorSink { func } => {
+                hook_error_sink = Some(func);
+            }
+            AxinArg::Init { expr } => {
+                init = Some(expr);
+            }
+            AxinArg::MockInTests { func, instrumented } => {
+                mock_in_tests = Some((func, instrumented));
+            }
+            AxinArg::EventId { id, .. } => {
+                event_id = Some(id);
+            }
+            AxinArg::Packed { .. } => {
+                packed = true;
+            }
+            AxinArg::PhaseTiming { func } => {
+                phase_timing = Some(func);
+            }
+            AxinArg::OnPanic { func } => {
+                on_panic_fn = Some(func);
+            }
+            AxinArg::OnExitAlways { func } => {
+                on_exit_always_fn = Some(func);
+            }
+            AxinArg::AssertOrder { phases } => {
+                assert_order = Some(phases);
+            }
+            // Prologues are checked across all attributes of the function before, see `check_strict_prologue`
+            AxinArg::StrictPrologue { .. } => {}
+            AxinArg::StubMissing { keyword, hooks } => {
+                stub_missing
+                    .get_or_insert_with(|| (keyword, Vec::new()))
+                    .1
+                    .extend(hooks);
+            }
+            // Presets are replaced by their arguments when the attribute is parsed
+            AxinArg::Preset { .. } => {}
+            AxinArg::Profile { slow, .. } => {
+                profile = Some(slow.unwrap_or_else(|| {
+                    parse_quote! { ::core::time::Duration::from_millis(#PROFILE_SLOW_MILLIS) }
+                }));
+            }
+        }
+    }
+
+    // Hooks without a policy of their own follow the attribute's one
+    for hook in [
+        &mut on_enter_fn,
+        &mut on_exit_fn,
+        &mut on_error_fn,
+        &mut on_ok_fn,
+        &mut on_err_fn,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        hook.errors = hook.errors.or(hook_errors);
+    }
+
+    if document {
+        let mut items = Vec::new();
+        if init.is_some() {
+            items.push("one-time initialization".to_string());
+        }
+        match event_id {
+            Some(EventId::Auto) => items.push("event id derived from the name".to_string()),
+            Some(EventId::Explicit(id)) => items.push(format!("event id `{:#06x}`", id)),
+            None => {}
+        }
+        if let Some((mock, _)) = &mock_in_tests {
+            items.push(format!("mock `{}` in test builds", mock.reported_name()));
+        }
+        if !prologue_stmts.is_empty() {
+            items.push("prologue statements".to_string());
+        }
+        if !epilogue_stmts.is_empty() {
+            items.push("epilogue statements".to_string());
+        }
+        if let Some(counter) = &count_calls {
+            items.push(format!("call counter `{}`", path_to_string(counter)));
+        }
+        if let Some(options) = &instrument {
+            items.push(match &options.name {
+                Some(name) => format!("`tracing` span `{}`", name.value()),
+                None => "`tracing` span".to_string(),
+            });
+        }
+        let hooks = [
+            ("entry hook", on_enter_fn.as_ref().map(|hook| &hook.func)),
+            ("decorator", decorator_fn.as_ref()),
+            ("slow call hook", slow_log.as_ref().map(|(_, func)| func)),
+            ("error hook", on_error_fn.as_ref().map(|hook| &hook.func)),
+            ("success hook", on_ok_fn.as_ref().map(|hook| &hook.func)),
+            ("failure hook", on_err_fn.as_ref().map(|hook| &hook.func)),
+            ("exit hook", on_exit_fn.as_ref().map(|hook| &hook.func)),
+            ("always-run exit hook", on_exit_always_fn.as_ref()),
+            ("panic hook", on_panic_fn.as_ref()),
+            ("phase timing hook", phase_timing.as_ref()),
+        ];
+        for (kind, spec) in hooks {
+            if let Some(spec) = spec {
+                items.push(format!("{} `{}`", kind, spec.reported_name()));
+            }
+            if kind == "decorator" {
+                for decorator in &inner_decorators {
+                    items.push(format!("nested decorator `{}`", decorator.reported_name()));
+                }
+            }
+            if let (Some((_, decorators)), "decorator") = (&decorator_select, kind) {
+                let names: Vec<_> = decorators
+                    .iter()
+                    .map(|decorator| format!("`{}`", decorator.reported_name()))
+                    .collect();
+                items.push(format!("decorator selected from {}", names.join(", ")));
+            }
+        }
+        if let Some((_, hooks)) = &stub_missing {
+            let names: Vec<_> = hooks.iter().map(|hook| format!("`{}`", hook)).collect();
+            items.push(format!("hooks stubbed out: {}", names.join(", ")));
+        }
+        if packed {
+            items.push("arguments packed into a struct".to_string());
+        }
+        if assert_order.is_some() {
+            items.push("order of the phases asserted in test builds".to_string());
+        }
+        if profile.is_some() {
+            items.push(
+                "profiled, reporting to the sinks registered with `axin::set_profile_sinks`"
+                    .to_string(),
+            );
+        }
+        outer_attrs.extend(generate_instrumentation_doc(&items));
+    }
+
+    // The profile is made of the phase timing and slow call hooks, reporting to the runtime's sinks
+    let profiled = profile.is_some();
+    if let Some(threshold) = profile {
+        let axin = axin_crate_path();
+        let context = Ident::new(&mangle(&prefix, CONTEXT_PLACEHOLDER), Span::call_site());
+        phase_timing = Some(parse_quote! { #axin::__private::profile_record(&#context) });
+        slow_log = Some((
+            threshold,
+            parse_quote! { #axin::__private::profile_slow(&#context) },
+        ));
+    }
+
+    Ok(AxinConfig {
+        prologue_stmts,
+        epilogue_stmts,
+        decorator_fn,
+        inner_decorators,
+        borrow_args,
+        decorator_select,
+        on_enter_fn,
+        on_exit_fn,
+        on_error_fn,
+        on_ok_fn,
+        on_err_fn,
+        hook_error_sink,
+        assert_returns,
+        slow_log,
+        outer_attrs,
+        count_calls,
+        init,
+        minimal,
+        raw_body,
+        adapt,
+        nested_body,
+        explain,
+        trivial_policy: if allow_trivial {
+            TrivialPolicy::Allow
+        } else {
+            trivial_policy
+        },
+        instrument,
+        mock_in_tests,
+        event_id,
+        packed,
+        phase_timing,
+        assert_order,
+        profile: profiled,
+        stub_missing,
+        on_panic_fn,
+        on_exit_always_fn,
+        associated: false,
+        prefix,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::AxinArgs;
+
+    /// Parse the attribute arguments `args` into a configuration.
+    fn config(args: &str) -> AxinConfig {
+        process_attribute_args(syn::parse_str(args).unwrap()).unwrap()
+    }
+
+    /// Parse the attribute arguments `args`, which are expected to be rejected, and return the error message.
+    fn parse_error(args: &str) -> String {
+        match syn::parse_str::<AxinArgs>(args) {
+            Ok(_) => panic!("`{}` was accepted", args),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    /// Render anything made of tokens, for comparisons with `quote!`.
+    fn tokens(tokens: impl ToTokens) -> String {
+        tokens.into_token_stream().to_string()
+    }
+
+    /// Render a function specification as `path` or `path(args)`.
+    fn spec(spec: &FunctionSpec) -> String {
+        match spec {
+            FunctionSpec::Simple(path, _) => tokens(path),
+            FunctionSpec::WithArgs(path, args, _) => tokens(quote! { #path(#args) }),
+        }
+    }
+
+    #[test]
+    fn empty_arguments_configure_nothing() {
+        let config = config("");
+        assert!(config.prologue_stmts.is_empty());
+        assert!(config.epilogue_stmts.is_empty());
+        assert!(config.decorator_fn.is_none());
+        assert!(config.decorator_select.is_none());
+        assert!(config.on_enter_fn.is_none());
+        assert!(config.on_exit_fn.is_none());
+        assert!(config.on_error_fn.is_none());
+        assert!(config.outer_attrs.is_empty());
+        assert!(!config.minimal && !config.adapt);
+        assert!(config.explain.is_none());
+        assert!(matches!(config.trivial_policy, TrivialPolicy::Allow));
+    }
+
+    #[test]
+    fn epilogue_statements_follow_the_body() {
+        let input_fn: ItemFn = parse_quote! {
+            fn work(x: u32) -> u32 {
+                const STEP: u32 = 1;
+                x + STEP
+            }
+        };
+        let generated = generate_enhanced_function(
+            input_fn,
+            &config("prologue(let y = x;), epilogue(check(x, y))"),
+        );
+        assert_eq!(
+            tokens(generated),
+            tokens(quote! {
+                fn work(x: u32) -> u32 {
+                    let original_fn = |x: u32| -> u32 {
+                        const STEP: u32 = 1;
+                        let y = x;
+                        let __body_value: u32 = {
+                            x + STEP
+                        };
+                        check(x, y);
+                        __body_value
+                    };
+                    let __result: u32 = { original_fn }(x);
+                    return __result;
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn nested_body_runs_the_body_in_a_nested_function() {
+        let input_fn: ItemFn = parse_quote! {
+            fn pick<'a, T: Clone, const N: usize>(items: &'a [T; N]) -> T where T: Default {
+                items.first().cloned().unwrap_or_default()
+            }
+        };
+        let generated = tokens(generate_enhanced_function(
+            input_fn.clone(),
+            &config("nested_body, decorator(timing)"),
+        ));
+        assert!(generated.contains(&tokens(quote! {
+            fn __axin_original<'a, T: Clone, const N: usize>(items: &'a [T; N]) -> T where T: Default {
+                items.first().cloned().unwrap_or_default()
+            }
+            __axin_original::<T, N>
+        })));
+
+        // Without it, the function may be a method using the generic parameters of its `impl` block, which a nested
+        // function could not see
+        let generated = tokens(generate_enhanced_function(
+            input_fn,
+            &config("decorator(timing)"),
+        ));
+        assert!(!generated.contains("__axin_original"), "{}", generated);
+
+        // The inner callable stays a closure where a nested function could not see what the body refers to
+        let fallbacks: [ItemFn; 3] = [
+            parse_quote! { fn make() -> Self { Self::default() } },
+            parse_quote! { async fn load(id: u32) -> u32 { id } },
+            parse_quote! { fn describe(id: u32) -> u32 { __axin.name().len() as u32 + id } },
+        ];
+        for input_fn in fallbacks {
+            let generated = tokens(generate_enhanced_function(
+                input_fn,
+                &config("nested_body, decorator(timing)"),
+            ));
+            assert!(!generated.contains("__axin_original"), "{}", generated);
+        }
+        let input_fn: ItemFn = parse_quote! { fn make() -> u32 { 1 } };
+        let mut associated = config("nested_body, decorator(timing)");
+        associated.associated = true;
+        let generated = tokens(generate_enhanced_function(input_fn, &associated));
+        assert!(!generated.contains("__axin_original"), "{}", generated);
+    }
+
+    #[test]
+    fn prologue_statements_are_terminated() {
+        let config = config("prologue(let x = 1; check(x))");
+        assert_eq!(config.prologue_stmts.len(), 2);
+        assert_eq!(
+            tokens(&config.prologue_stmts[1]),
+            tokens(quote! { check(x); })
+        );
+    }
+
+    #[test]
+    fn block_like_prologue_statements_are_kept() {
+        let config = config("prologue(if x { return; } let y = x;)");
+        assert_eq!(
+            tokens(&config.prologue_stmts[0]),
+            tokens(quote! { if x { return; } })
+        );
+    }
+
+    #[test]
+    fn hooks_keep_their_arguments() {
+        let config =
+            config(r#"on_enter(setup), on_exit(cleanup("done", 1)), on_error(report(&__axin))"#);
+        assert_eq!(spec(&config.on_enter_fn.as_ref().unwrap().func), "setup");
+        let on_exit = config.on_exit_fn.as_ref().unwrap();
+        assert_eq!(spec(&on_exit.func), tokens(quote! { cleanup("done", 1) }));
+        assert!(on_exit.when.is_none());
+        assert_eq!(
+            spec(&config.on_error_fn.as_ref().unwrap().func),
+            tokens(quote! { report(&__axin) })
+        );
+    }
+
+    #[test]
+    fn entry_hook_predicate() {
+        let config =
+            config(r#"on_enter(log_details(&input), when = VERBOSE && !QUIET, errors = "ignore")"#);
+        let on_enter = config.on_enter_fn.as_ref().unwrap();
+        assert_eq!(spec(&on_enter.func), tokens(quote! { log_details(&input) }));
+        assert_eq!(
+            tokens(on_enter.when.as_ref().unwrap()),
+            tokens(quote! { VERBOSE && !QUIET })
+        );
+        assert_eq!(on_enter.errors, Some(HookErrorPolicy::Ignore));
+    }
+
+    #[test]
+    fn entry_hook_arguments() {
+        let all = config("on_enter(audit, with_args)");
+        let with_args = all.on_enter_fn.as_ref().unwrap().with_args.as_ref();
+        assert!(with_args.unwrap().skip.is_empty());
+
+        let config =
+            config(r#"on_enter(audit("billing"), with_args(skip(conn, pool)), when = VERBOSE)"#);
+        let on_enter = config.on_enter_fn.as_ref().unwrap();
+        let skip = &on_enter.with_args.as_ref().unwrap().skip;
+        assert_eq!(skip, &["conn", "pool"]);
+        assert!(on_enter.when.is_some());
+
+        assert_eq!(
+            parse_error("on_exit(audit, with_args)"),
+            "`with_args` is only supported by `on_enter`, since the arguments are moved into the function before \
+             `on_exit` runs"
+        );
+        assert_eq!(
+            parse_error("on_enter(audit, with_args(conn))"),
+            "expected `skip(name, ...)` after `with_args`"
+        );
+        assert_eq!(
+            parse_error("minimal, on_enter(audit, with_args)"),
+            "`minimal` mode only supports entry hooks without arguments"
+        );
+    }
+
+    #[test]
+    fn exit_hook_predicate() {
+        let config = config("on_exit(alert, when = result.is_err() && elapsed > LIMIT)");
+        let on_exit = config.on_exit_fn.as_ref().unwrap();
+        assert_eq!(spec(&on_exit.func), "alert");
+        assert_eq!(
+            tokens(on_exit.when.as_ref().unwrap()),
+            tokens(quote! { result.is_err() && elapsed > LIMIT })
+        );
+    }
+
+    #[test]
+    fn decorator_select_keeps_the_order() {
+        let config = config("decorator_select(mode(), [fast, debug(1), slow])");
+        let (selector, decorators) = config.decorator_select.as_ref().unwrap();
+        assert_eq!(tokens(selector), tokens(quote! { mode() }));
+        let decorators: Vec<_> = decorators.iter().map(spec).collect();
+        assert_eq!(decorators, ["fast", &tokens(quote! { debug(1) }), "slow"]);
+    }
+
+    #[test]
+    fn slow_log_threshold_and_hook() {
+        let config = config("slow_log(Duration::from_millis(5), report(&__axin))");
+        let (threshold, hook) = config.slow_log.as_ref().unwrap();
+        assert_eq!(
+            tokens(threshold),
+            tokens(quote! { Duration::from_millis(5) })
+        );
+        assert_eq!(spec(hook), tokens(quote! { report(&__axin) }));
+    }
+
+    #[test]
+    fn profile_reports_timings_and_slow_calls_to_the_sinks() {
+        let profiled = config("profile");
+        assert!(profiled.profile);
+        let (threshold, hook) = profiled.slow_log.as_ref().unwrap();
+        assert_eq!(
+            tokens(threshold),
+            tokens(quote! { ::core::time::Duration::from_millis(100u64) })
+        );
+        assert_eq!(
+            spec(hook),
+            tokens(quote! { ::axin::__private::profile_slow(&__axin) })
+        );
+        assert_eq!(
+            spec(profiled.phase_timing.as_ref().unwrap()),
+            tokens(quote! { ::axin::__private::profile_record(&__axin) })
+        );
+
+        // Literals with a unit become durations, and other expressions are left alone
+        for (slow, expected) in [
+            ("250ms", quote! { ::core::time::Duration::from_millis(250) }),
+            ("2s", quote! { ::core::time::Duration::from_secs(2) }),
+            ("0x10us", quote! { ::core::time::Duration::from_micros(16) }),
+            ("LIMIT", quote! { LIMIT }),
+        ] {
+            let profiled = config(&format!("profile(slow = {})", slow));
+            assert_eq!(tokens(&profiled.slow_log.unwrap().0), tokens(expected));
+        }
+        assert_eq!(
+            parse_error("profile(slow = 1.5s)"),
+            "expected a whole number of a unit, like `1500ms`"
+        );
+    }
+
+    #[test]
+    fn call_site_warning_becomes_deprecated_attribute() {
+        let config = config(r#"warn_call_site("use `new` instead")"#);
+        assert_eq!(
+            tokens(&config.outer_attrs[0]),
+            tokens(quote! { #[deprecated(note = "use `new` instead")] })
+        );
+    }
+
+    #[test]
+    fn document_lists_the_instrumentation() {
+        let config = config(
+            "document, on_enter(trace), decorator(timing(3) as timed), count_calls(crate::CALLS), \
+             init(setup()), mock_in_tests(fake)",
+        );
+        let lines: Vec<_> = config
+            .outer_attrs
+            .iter()
+            .map(|attr| match &attr.meta {
+                syn::Meta::NameValue(doc) => tokens(&doc.value),
+                _ => panic!("expected a doc attribute"),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                r#""""#,
+                r#"" Instrumented by axin:""#,
+                r#"" - one-time initialization""#,
+                r#"" - mock `fake` in test builds""#,
+                r#"" - call counter `crate::CALLS`""#,
+                r#"" - entry hook `trace`""#,
+                r#"" - decorator `timed`""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn trivial_policy_prefers_deny_and_allow() {
+        let config_ = config("deny_trivial_decorator, warn_if_trivial");
+        assert!(matches!(config_.trivial_policy, TrivialPolicy::Deny));
+        let config_ = config("warn_if_trivial");
+        assert!(matches!(config_.trivial_policy, TrivialPolicy::Warn));
+        let config_ = config("allow_trivial, deny_trivial_decorator");
+        assert!(matches!(config_.trivial_policy, TrivialPolicy::Allow));
+    }
+
+    #[test]
+    fn flags_and_counter() {
+        let config_ =
+            config("minimal, init(setup()), count_calls(CALLS), on_enter(trace), explain");
+        assert!(config_.minimal);
+        assert_eq!(
+            tokens(config_.init.as_ref().unwrap()),
+            tokens(quote! { setup() })
+        );
+        assert_eq!(tokens(config_.count_calls.as_ref().unwrap()), "CALLS");
+        assert_eq!(tokens(config_.explain.as_ref().unwrap()), "explain");
+
+        let config_ = config("decorator(logged), adapt, assert_returns(Ok(1))");
+        assert!(config_.adapt && !config_.minimal && !config_.borrow_args);
+        assert_eq!(
+            tokens(config_.assert_returns.as_ref().unwrap()),
+            tokens(quote! { Ok(1) })
+        );
+    }
+
+    #[test]
+    fn hook_error_policy_applies_unless_overridden() {
+        let config_ = config(
+            r#"on_enter(connect), on_exit(cleanup, errors = "ignore"), on_error(report),
+               hook_errors = "propagate""#,
+        );
+        let policy = |hook: &Option<Hook>| hook.as_ref().unwrap().errors;
+        assert_eq!(
+            policy(&config_.on_enter_fn),
+            Some(HookErrorPolicy::Propagate)
+        );
+        assert_eq!(policy(&config_.on_exit_fn), Some(HookErrorPolicy::Ignore));
+        assert_eq!(
+            policy(&config_.on_error_fn),
+            Some(HookErrorPolicy::Propagate)
+        );
+
+        let config_ =
+            config(r#"on_enter(connect, errors = "log"), hook_error_sink = sink(&__axin)"#);
+        assert_eq!(policy(&config_.on_enter_fn), Some(HookErrorPolicy::Log));
+        assert_eq!(
+            spec(config_.hook_error_sink.as_ref().unwrap()),
+            tokens(quote! { sink(&__axin) })
+        );
+    }
+
+    #[test]
+    fn hook_errors_are_checked() {
+        assert_eq!(
+            parse_error(r#"on_enter(connect), hook_errors = "log""#),
+            "logging hook errors needs a `hook_error_sink = function` to log them with"
+        );
+        assert_eq!(
+            parse_error(r#"on_exit(cleanup, errors = "log")"#),
+            "logging hook errors needs a `hook_error_sink = function` to log them with"
+        );
+        assert_eq!(
+            parse_error(r#"hook_errors = "panic""#),
+            r#"unknown hook error policy, expected one of "log", "ignore" or "propagate""#
+        );
+        assert_eq!(
+            parse_error(r#"on_error(report, when = true)"#),
+            r#"expected `errors = "..."` or `auto_into` after the hook"#
+        );
+        assert_eq!(
+            parse_error(r#"minimal, on_enter(connect, errors = "ignore")"#),
+            "`minimal` mode does not support handling the errors of hooks"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn instrument_compat_options() {
+        let config = config(
+            r#"instrument_compat(name = "load", level = "debug", skip(password), fields(user.id = id))"#,
+        );
+        let options = config.instrument.as_ref().unwrap();
+        assert_eq!(options.name.as_ref().unwrap().value(), "load");
+        assert!(matches!(&options.level, Some(InstrumentLevel::Named(level)) if level == "DEBUG"));
+        assert_eq!(options.skip.len(), 1);
+        assert_eq!(tokens(&options.fields[0].name), tokens(quote! { user.id }));
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    #[test]
+    fn instrument_compat_requires_tracing() {
+        assert_eq!(
+            parse_error("instrument_compat()"),
+            "`instrument_compat` requires the `tracing` feature of axin"
+        );
+    }
+
+    #[test]
+    fn impl_trait_types_are_inferred() {
+        let ty: Type =
+            parse_quote! { Result<(impl Display + Send, Vec<impl Fn() -> u8>), Box<dyn Error>> };
+        let inferred: Type = parse_quote! { Result<(_, Vec<_>), Box<dyn Error>> };
+        assert_eq!(tokens(infer_impl_trait(&ty)), tokens(inferred));
+    }
+
+    #[test]
+    fn unknown_parameter_is_rejected() {
+        let error = parse_error("on_entry(setup)");
+        assert!(
+            error.starts_with("Unsupported parameter: 'on_entry'."),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn decorator_and_decorator_select_conflict() {
+        let error = parse_error("decorator(a), decorator_select(0, [b])");
+        assert!(error.starts_with("`decorator_select` cannot be combined with `decorator`"));
+    }
+
+    #[test]
+    fn minimal_rejects_wrapping() {
+        let error = parse_error("minimal, decorator(timing)");
+        assert_eq!(
+            error,
+            "`minimal` mode does not support 'decorator', since it emits the function body unchanged"
+        );
+        let error = parse_error(r#"minimal, on_enter(trace("x"))"#);
+        assert_eq!(
+            error,
+            "`minimal` mode only supports entry hooks without arguments"
+        );
+    }
+
+    #[test]
+    fn exit_hook_predicate_needs_when() {
+        let error = parse_error("on_exit(alert, if = result.is_err())");
+        assert_eq!(error, "expected identifier, found keyword `if`");
+        let error = parse_error("on_exit(alert, unless = failed)");
+        assert_eq!(
+            error,
+            "expected `when = predicate`, `errors = \"...\"`, `auto_into` or `after_drop` after the hook"
+        );
+    }
+
+    #[test]
+    fn raw_body_rejects_wrapping() {
+        let config_ =
+            config("raw_body, on_enter(trace(&__axin)), on_exit(done), count_calls(CALLS)");
+        assert!(config_.raw_body && !config_.minimal);
+        assert_eq!(
+            parse_error("raw_body, decorator(timing)"),
+            "`raw_body` does not support 'decorator', since the body is not wrapped in a callable to decorate"
+        );
+        assert_eq!(
+            parse_error("raw_body, prologue(let x = 1;)"),
+            "`raw_body` does not support 'prologue', since it emits the function body unchanged; write the \
+             statements into the body instead"
+        );
+        assert_eq!(
+            parse_error("raw_body, on_exit(done, when = result.is_ok())"),
+            "`raw_body` does not support `when` on exit hooks, since the result is returned directly"
+        );
+        assert_eq!(
+            parse_error(r#"raw_body, on_exit(done, errors = "ignore")"#),
+            "`raw_body` does not support handling the errors of hooks"
+        );
+        assert_eq!(
+            parse_error("raw_body, on_error(report)"),
+            "`raw_body` does not support 'on_error', since it emits the function body unchanged"
+        );
+    }
+
+    #[test]
+    fn decorator_may_borrow_the_arguments() {
+        let config_ = config("decorator(label_by(\"GET\"), borrow_args)");
+        assert!(config_.borrow_args);
+        assert_eq!(
+            spec(config_.decorator_fn.as_ref().unwrap()),
+            tokens(quote! { label_by("GET") })
+        );
+        let error = parse_error("decorator(label_by, borrow)");
+        assert_eq!(
+            error,
+            "expected `borrow_args` or `no_args` after the decorator"
+        );
+    }
+
+    #[test]
+    fn decorator_without_args_adapts() {
+        let config_ = config("decorator(timing_decorator, no_args)");
+        assert!(config_.adapt && !config_.borrow_args);
+        let config_ = config("decorator(label_by(\"GET\"), no_args, borrow_args)");
+        assert!(config_.adapt && config_.borrow_args);
+        let error = parse_error("decorator(d, no_args), packed = struct");
+        assert_eq!(
+            error,
+            "`packed` cannot be combined with `no_args`, which passes no arguments to the decorator"
+        );
+    }
+
+    #[test]
+    fn later_decorators_are_nested() {
+        let config_ = config("decorator(a, borrow_args), decorator(b), decorator(c(1), no_args)");
+        assert_eq!(
+            path_to_string(config_.decorator_fn.as_ref().unwrap().path()),
+            "a"
+        );
+        let inner: Vec<String> = config_
+            .inner_decorators
+            .iter()
+            .map(|decorator| path_to_string(decorator.path()))
+            .collect();
+        assert_eq!(inner, ["b", "c"]);
+        assert!(config_.borrow_args && config_.adapt);
+        let error = parse_error("decorator(a), decorator(b, borrow_args)");
+        assert_eq!(
+            error,
+            "`borrow_args` can only be given to the first decorator, which is set up before the arguments are moved \
+             into the call"
+        );
+    }
+
+    #[test]
+    fn duplicate_decorators_are_rejected() {
+        let args =
+            syn::parse_str("decorator(a(1)), decorator(a(2)), decorator(b), decorator(a(1))")
+                .unwrap();
+        let messages: Vec<String> = match process_attribute_args(args) {
+            Ok(_) => panic!("the duplicate decorator was accepted"),
+            Err(error) => error.into_iter().map(|error| error.to_string()).collect(),
+        };
+        assert_eq!(
+            messages,
+            [
+                "duplicate decorator `a`, which would wrap the function twice",
+                "decorator already specified here",
+            ]
+        );
+    }
+
+    #[test]
+    fn mock_in_tests_is_instrumented_by_default() {
+        let config_ = config("mock_in_tests(fake_fetch)");
+        let (mock, instrumented) = config_.mock_in_tests.as_ref().unwrap();
+        assert_eq!(spec(mock), "fake_fetch");
+        assert!(*instrumented);
+        let config_ = config("mock_in_tests(fake_fetch(1), instrumented = false)");
+        let (mock, instrumented) = config_.mock_in_tests.as_ref().unwrap();
+        assert_eq!(spec(mock), tokens(quote! { fake_fetch(1) }));
+        assert!(!*instrumented);
+        let error = parse_error("mock_in_tests(fake_fetch, hooks = false)");
+        assert_eq!(
+            error,
+            "expected `instrumented = true` or `instrumented = false` after the mock"
+        );
+    }
+
+    #[test]
+    fn event_ids_are_explicit_or_auto() {
+        assert_eq!(config("event_id = auto").event_id, Some(EventId::Auto));
+        assert_eq!(
+            config("event_id = 0x1042").event_id,
+            Some(EventId::Explicit(0x1042))
+        );
+        assert_eq!(
+            config("event_id = 0xffff").event_id,
+            Some(EventId::Explicit(0xffff))
+        );
+        assert_eq!(
+            parse_error("event_id = 0x10000"),
+            "event ids are `u16`, so they range from 0 to 0xffff"
+        );
+        assert_eq!(
+            parse_error("event_id = manual"),
+            "expected `auto` or an integer literal as the event id"
+        );
+    }
+
+    #[test]
+    fn malformed_arguments_are_rejected() {
+        assert_eq!(
+            parse_error("decorator"),
+            "unexpected end of input, expected parentheses"
+        );
+        assert_eq!(parse_error("slow_log(LIMIT)"), "expected `,`");
+        assert_eq!(parse_error("count_calls(1)"), "expected identifier");
+    }
+
+    /// The arguments of the hook or decorator spec in `config`.
+    fn spec_args(spec: &FunctionSpec) -> Vec<String> {
+        match spec {
+            FunctionSpec::Simple(..) => Vec::new(),
+            FunctionSpec::WithArgs(_, args, _) => args.iter().map(tokens).collect(),
+        }
+    }
+
+    #[test]
+    fn gnarly_argument_expressions_are_split_at_top_level_commas() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                r#"log(#[cfg(debug_assertions)] extra_detail(), "msg")"#,
+                &[r#"#[cfg(debug_assertions)] extra_detail()"#, r#""msg""#],
+            ),
+            (
+                r#"log(format!("a, b ({})", x), y)"#,
+                &[r#"format!("a, b ({})", x)"#, "y"],
+            ),
+            (
+                "log(vec! { 1, 2 }, matches!(x, Some(1) | None), [a, b])",
+                &["vec! { 1, 2 }", "matches!(x, Some(1) | None)", "[a, b]"],
+            ),
+            (
+                "log(|a, b| a + b, move |(x, y): (u8, u8)| x.max(y), 3)",
+                &["|a, b| a + b", "move |(x, y): (u8, u8)| x.max(y)", "3"],
+            ),
+            (
+                "log(match x { 0 => a, _ => b }, if c { d } else { e })",
+                &["match x { 0 => a, _ => b }", "if c { d } else { e }"],
+            ),
+            (
+                "log(Point { x: 1, y: 2 }, Config { verbose, ..Default::default() })",
+                &[
+                    "Point { x: 1, y: 2 }",
+                    "Config { verbose, ..Default::default() }",
+                ],
+            ),
+            (
+                "log({ let (a, b) = pair; a + b }, <Vec<(u8, u8)>>::new(), f::<u8, u16>(),)",
+                &[
+                    "{ let (a, b) = pair; a + b }",
+                    "<Vec<(u8, u8)>>::new()",
+                    "f::<u8, u16>()",
+                ],
+            ),
+            ("log(a < b, c > d, x as u8)", &["a < b", "c > d", "x as u8"]),
+        ];
+        for (hook, expected) in cases {
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|arg| tokens(syn::parse_str::<Expr>(arg).unwrap()))
+                .collect();
+
+            for position in ["on_enter", "on_exit", "on_error", "decorator"] {
+                let config = config(&format!("{}({})", position, hook));
+                let spec = match position {
+                    "on_enter" => &config.on_enter_fn.as_ref().unwrap().func,
+                    "on_exit" => &config.on_exit_fn.as_ref().unwrap().func,
+                    "on_error" => &config.on_error_fn.as_ref().unwrap().func,
+                    _ => config.decorator_fn.as_ref().unwrap(),
+                };
+                assert_eq!(spec_args(spec), expected, "{}({})", position, hook);
+            }
+
+            // Followed by modifiers and in lists of decorators
+            let config = config(&format!(
+                "on_enter({}, when = VERBOSE), decorator_select(mode(), [fast, {}])",
+                hook, hook
+            ));
+            let on_enter = config.on_enter_fn.as_ref().unwrap();
+            assert_eq!(spec_args(&on_enter.func), expected, "{}", hook);
+            assert_eq!(tokens(on_enter.when.as_ref().unwrap()), "VERBOSE");
+            let (_, decorators) = config.decorator_select.as_ref().unwrap();
+            assert_eq!(spec_args(&decorators[1]), expected, "{}", hook);
+        }
+    }
+
+    #[test]
+    fn gnarly_predicates_end_at_top_level_commas() {
+        let config = config(
+            r#"on_enter(log, when = matches!(level(), Level::Debug | Level::Trace), errors = "ignore"),
+               on_exit(log, when = result.as_ref().map_or(false, |r| { let (a, b) = r; a > b }))"#,
+        );
+        let on_enter = config.on_enter_fn.as_ref().unwrap();
+        assert_eq!(
+            tokens(on_enter.when.as_ref().unwrap()),
+            tokens(quote! { matches!(level(), Level::Debug | Level::Trace) })
+        );
+        assert_eq!(on_enter.errors, Some(HookErrorPolicy::Ignore));
+        assert_eq!(
+            tokens(config.on_exit_fn.as_ref().unwrap().when.as_ref().unwrap()),
+            tokens(quote! { result.as_ref().map_or(false, |r| { let (a, b) = r; a > b }) })
+        );
+    }
+
+    #[test]
+    fn auto_into_converts_only_suffixed_literals() {
+        let config = config(
+            r#"on_enter(log("msg", 'c', 7, 0.5, 7u64, 1.5f32, name, "a".len()), auto_into),
+               on_exit(log, auto_into), on_error(log(b"bytes"), errors = "ignore", auto_into)"#,
+        );
+        let into = |lit: TokenStream| tokens(quote! { ::core::convert::Into::into(#lit) });
+        assert_eq!(
+            spec_args(&config.on_enter_fn.as_ref().unwrap().func),
+            [
+                into(quote! { "msg" }),
+                into(quote! { 'c' }),
+                tokens(quote! { 7 }),
+                tokens(quote! { 0.5 }),
+                into(quote! { 7u64 }),
+                into(quote! { 1.5f32 }),
+                tokens(quote! { name }),
+                tokens(quote! { "a".len() }),
+            ]
+        );
+        assert_eq!(spec(&config.on_exit_fn.as_ref().unwrap().func), "log");
+        assert_eq!(
+            spec_args(&config.on_error_fn.as_ref().unwrap().func),
+            [into(quote! { b"bytes" })]
+        );
+    }
+
+    #[test]
+    fn reserved_names_are_the_names_bound_for_the_attribute() {
+        /// Collects the names bound by `let` statements, the arms of `match` expressions and `const` items.
+        struct Bindings(Vec<String>);
+
+        impl VisitMut for Bindings {
+            fn visit_local_mut(&mut self, local: &mut syn::Local) {
+                if let Pat::Ident(pat_ident) = &local.pat {
+                    self.0.push(pat_ident.ident.to_string());
+                }
+                visit_mut::visit_local_mut(self, local);
+            }
+
+            fn visit_arm_mut(&mut self, arm: &mut syn::Arm) {
+                if let Pat::TupleStruct(pat) = &arm.pat {
+                    for elem in &pat.elems {
+                        if let Pat::Ident(pat_ident) = elem {
+                            self.0.push(pat_ident.ident.to_string());
+                        }
+                    }
+                }
+                visit_mut::visit_arm_mut(self, arm);
+            }
+
+            fn visit_item_const_mut(&mut self, item: &mut syn::ItemConst) {
+                self.0.push(item.ident.to_string());
+                visit_mut::visit_item_const_mut(self, item);
+            }
+        }
+
+        let config = config(
+            "event_id = 7, on_enter(log(&__axin, __axin_event_id)), \
+             on_exit(log(__axin_body_ran, __axin_result), when = result.is_ok() && elapsed.as_secs() > 1), \
+             slow_log(THRESHOLD, slow), on_ok(log(__axin_ok)), on_err(log(__axin_err))",
+        );
+        let input_fn: ItemFn = parse_quote! {
+            fn work(x: u32) -> Result<u32, ()> {
+                Ok(x)
+            }
+        };
+        let mut generated: ItemFn =
+            syn::parse2(generate_enhanced_function(input_fn, &config)).unwrap();
+        let mut bindings = Bindings(Vec::new());
+        bindings.visit_item_fn_mut(&mut generated);
+        // Every other name the generated code binds starts with `__`, except for the inner callable, which only the
+        // decorator is given
+        let mut visible: Vec<_> = bindings
+            .0
+            .into_iter()
+            .filter(|name| {
+                (!name.starts_with("__") || name.starts_with("__axin")) && name != "original_fn"
+            })
+            .collect();
+        visible.sort();
+        let mut reserved: Vec<_> = RESERVED_NAMES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        reserved.sort();
+        assert_eq!(visible, reserved);
+    }
+
+    #[test]
+    fn exit_hooks_get_clones_of_the_parameters() {
+        let input_fn: ItemFn = parse_quote! {
+            fn work(user: String, count: &mut u32, (a, b): (u8, u8), unused: u8) -> u32 {
+                *count += 1;
+                user.len() as u32 + u32::from(a + b + unused)
+            }
+        };
+        let config = config("on_exit(log(user, count, a, other), when = !user.is_empty())");
+        assert_eq!(
+            exit_hook_params(&input_fn.sig, config.on_exit_fn.as_ref().unwrap())
+                .iter()
+                .map(|(param, mention)| (param.to_string(), mention.to_string()))
+                .collect::<Vec<_>>(),
+            [("user".to_string(), "user".to_string())]
+        );
+
+        let generated = tokens(generate_enhanced_function(input_fn, &config));
+        assert!(generated.contains(&tokens(quote! {
+            let __exit_arg0 = ::core::clone::Clone::clone(&user);
+        })));
+        assert!(generated.contains(&tokens(quote! {
+            {
+                #[allow(unused_variables)]
+                let user = __exit_arg0;
+                if {
+                    let __when: bool = !user.is_empty();
+                    __when
+                } {
+                    log(user, count, a, other);
+                }
+            }
+        })));
+    }
+
+    #[test]
+    fn panic_hooks_are_run_by_guards() {
+        let input_fn: ItemFn = parse_quote! {
+            fn work(user: String) -> usize {
+                user.len()
+            }
+        };
+        let config = config("on_panic(rollback(user)), on_exit_always(release)");
+        let generated = tokens(generate_enhanced_function(input_fn, &config));
+        let expected = tokens(quote! {
+            let __always_guard = ::axin::__private::AlwaysGuard::new(| | {
+                release();
+            });
+            let __panic_arg0 = ::core::clone::Clone::clone(&user);
+            let __panic_guard = ::axin::__private::PanicGuard::new(| | {
+                #[allow(unused_variables)]
+                let user = __panic_arg0;
+                rollback(user);
+            });
+        });
+        assert!(generated.contains(&expected));
+        assert!(generated.contains(&tokens(quote! {
+            let __result: usize = { original_fn }(user);
+            __panic_guard.disarm();
+            ::core::mem::drop(__always_guard);
+            return __result;
+        })));
+    }
+
+    #[test]
+    fn decorator_alone_is_called_inline() {
+        let input_fn: ItemFn = parse_quote! {
+            fn work(x: u32) -> u32 {
+                x + 1
+            }
+        };
+        let generated = generate_enhanced_function(input_fn.clone(), &config("decorator(timing)"));
+        assert_eq!(
+            tokens(generated),
+            tokens(quote! {
+                fn work(x: u32) -> u32 {
+                    timing(|x: u32| -> u32 { x + 1 }, x)
+                }
+            })
+        );
+
+        // Anything else around the call keeps its result bound and returned
+        for args in ["decorator(timing(3))", "decorator(timing), on_exit(done)"] {
+            let generated: ItemFn =
+                syn::parse2(generate_enhanced_function(input_fn.clone(), &config(args))).unwrap();
+            assert!(matches!(
+                generated.block.stmts.last(),
+                Some(Stmt::Expr(Expr::Return(_), _))
+            ));
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_documented() {
+        let docs = include_str!("../../src/lib.rs");
+        let section = docs
+            .split("//! ### Reserved Names")
+            .nth(1)
+            .expect("the crate documentation has no section on reserved names");
+        let documented: Vec<_> = section
+            .lines()
+            .skip_while(|line| !line.starts_with("//! - "))
+            .take_while(|line| line.starts_with("//! - "))
+            .collect();
+        let expected: Vec<_> = RESERVED_NAMES
+            .iter()
+            .map(|(name, meaning)| format!("//! - `{}`: {}", name, meaning))
+            .collect();
+        assert_eq!(documented, expected);
+    }
+
+    #[test]
+    fn paths_to_the_function_itself_are_rejected() {
+        let recurses = |args: &str, input_fn: &ItemFn| {
+            check_recursion(input_fn, &config(args), false).is_err()
+        };
+        let free: ItemFn = parse_quote! { fn flush() {} };
+        let method: ItemFn = parse_quote! { fn flush(&mut self) {} };
+        let constructor: ItemFn = parse_quote! { fn flush() -> Option<Self> { None } };
+
+        for args in [
+            "on_enter(flush)",
+            "on_exit(self::flush)",
+            "on_error(Self::flush)",
+            "decorator(flush::<u8>(1))",
+            "decorator_select(MODE, [trace, flush])",
+            "slow_log(LIMIT, flush)",
+            "hook_errors = \"log\", hook_error_sink = flush",
+            "phase_timing(flush)",
+            "mock_in_tests(flush)",
+        ] {
+            assert!(recurses(args, &free), "`{}` was accepted", args);
+        }
+        for args in [
+            "on_enter(cache::flush)",
+            "on_enter(::flush)",
+            "on_enter(super::flush)",
+            "on_enter(flushed)",
+        ] {
+            assert!(!recurses(args, &free), "`{}` was rejected", args);
+        }
+
+        assert!(recurses("adapt, on_exit(Self::flush)", &method));
+        for args in ["adapt, on_exit(flush)", "adapt, on_exit(self::flush)"] {
+            assert!(!recurses(args, &method), "`{}` was rejected", args);
+        }
+
+        // Associated functions without a receiver, told by `Self` in their signature or by the `impl` block
+        assert!(recurses("on_enter(Self::flush)", &constructor));
+        for args in ["on_enter(flush)", "on_exit(self::flush)"] {
+            assert!(!recurses(args, &constructor), "`{}` was rejected", args);
+            assert!(
+                check_recursion(&free, &config(args), true).is_ok(),
+                "`{}` was rejected in an `impl` block",
+                args
+            );
+        }
+        assert!(check_recursion(&free, &config("on_enter(Self::flush)"), true).is_err());
+    }
+
+    #[test]
+    fn stubbed_hooks_call_stubs_under_their_names() {
+        let mut stubbed = config(
+            "stub_missing(audit, failed), on_enter(audit, with_args), on_exit(audit(x, 1) as exit_audit), \
+             on_error(failed)",
+        );
+        let stmts = stub_missing_hooks(&mut stubbed).unwrap();
+        let on_enter = &stubbed.on_enter_fn.as_ref().unwrap().func;
+        assert_eq!(tokens(on_enter.path()), "__axin_stub_audit");
+        assert_eq!(on_enter.reported_name(), "audit");
+        assert_eq!(
+            stubbed.on_exit_fn.unwrap().func.reported_name(),
+            "exit_audit"
+        );
+        let stubs: Vec<_> = stmts[..2].iter().map(tokens).collect();
+        assert_eq!(
+            stubs,
+            [
+                tokens(quote! { fn __axin_stub_audit<__A0, __A1>(_: __A0, _: __A1) {} }),
+                tokens(quote! { fn __axin_stub_failed<__A0>(_: __A0) {} }),
+            ]
+        );
+        assert_eq!(stmts.len(), 3);
+
+        let mut qualified = config("stub_missing(audit), on_enter(metrics::audit)");
+        assert!(stub_missing_hooks(&mut qualified).is_err());
+    }
+
+    #[test]
+    fn only_exit_hooks_see_the_result() {
+        let check = |args: &str| check_result_placeholder(&config(args)).is_ok();
+        for args in [
+            "on_exit(log(__axin_result))",
+            "on_exit(log(format!(\"{:?}\", __axin_result)), when = __axin_result.is_ok())",
+            "on_exit(log, when = *__axin_result > 0)",
+            "on_enter(log(__axin_results))",
+        ] {
+            assert!(check(args), "`{}` was rejected", args);
+        }
+        for args in [
+            "on_enter(log(__axin_result))",
+            "on_enter(log, when = __axin_result.is_ok())",
+            "decorator(cached(__axin_result))",
+            "decorator_select(__axin_result.len(), [trace, cached])",
+            "prologue(let seen = __axin_result;)",
+            "epilogue(drop(__axin_result);)",
+            "init(log(__axin_result))",
+            "on_error(log(__axin_result))",
+            "slow_log(LIMIT, log(__axin_result))",
+            "raw_body, on_exit(log(__axin_result))",
+        ] {
+            assert!(!check(args), "`{}` was accepted", args);
+        }
+    }
+
+    #[test]
+    fn reserved_names_are_only_rejected_when_referred_to() {
+        let check = |args: &str, input_fn: ItemFn| {
+            check_reserved_names(&input_fn, &config(args)).map_err(|error| error.to_string())
+        };
+        let with_result: ItemFn = parse_quote! {
+            fn store(result: u32, (__axin, _): (u8, u8)) -> bool { true }
+        };
+        assert_eq!(check("on_exit(log, when = *result)", with_result.clone()), Err(
+            "`result` is reserved for a reference to the return value, in the `when` predicate of `on_exit`, which the \
+             attribute refers to; rename the parameter".to_string()
+        ));
+        assert_eq!(
+            check("on_enter(log(&__axin))", with_result.clone())
+                .unwrap_err()
+                .split(',')
+                .next(),
+            Some("`__axin` is reserved for the call context")
+        );
+        assert_eq!(
+            check(
+                "on_enter(log(result)), on_exit(log, when = true)",
+                with_result.clone()
+            ),
+            Ok(())
+        );
+        // The event id is only bound with `event_id`
+        let with_event_id: ItemFn = parse_quote! {
+            fn store(__axin_event_id: u16) {}
+        };
+        assert_eq!(
+            check("on_enter(log(__axin_event_id))", with_event_id.clone()),
+            Ok(())
+        );
+        assert!(check(
+            "event_id = 1, on_enter(log(__axin_event_id))",
+            with_event_id.clone()
+        )
+        .is_err());
+        assert_eq!(
+            check("event_id = 1, prologue(let elapsed = 0;), on_exit(log, when = elapsed > ZERO)", with_event_id),
+            Err("`elapsed` is reserved for the time the decorator and the body took, in the `when` predicate of \
+                 `on_exit`, which the attribute refers to; rename the binding".to_string())
+        );
+        let with_body_ran: ItemFn = parse_quote! {
+            fn store(__axin_body_ran: bool) {}
+        };
+        assert!(check("on_exit(log(__axin_body_ran))", with_body_ran.clone()).is_err());
+        assert_eq!(check("on_exit(log)", with_body_ran), Ok(()));
+    }
+
+    #[test]
+    fn generated_prefix_renames_the_placeholders_and_generated_names() {
+        let args = r#"generated_prefix = "__obs_", event_id = 7, on_enter(log(&__obs, __obs_event_id)),
+            on_exit(log(__obs_body_ran, __obs_result)), on_ok(ok(__obs_ok)), on_err(err(__obs_err)),
+            decorator(trace), stub_missing(err)"#;
+        let generated = |args: &str| {
+            let input_fn: ItemFn = parse_quote! {
+                fn work(x: impl Copy) -> Result<u32, ()> { Ok(1) }
+            };
+            let mut config = config(args);
+            let mut stmts = stub_missing_hooks(&mut config).unwrap();
+            stmts.truncate(1);
+            let generated = generate_enhanced_function(input_fn, &config);
+            quote! { #(#stmts)* #generated }
+        };
+        let names = [
+            "__obs",
+            "__obs_event_id",
+            "__obs_body_ran",
+            "__obs_result",
+            "__obs_ok",
+            "__obs_err",
+            "__obs_typed",
+            "__obs_stub_err",
+        ];
+        let packed = ["__obs_args", "__ObsArgsWork"];
+        for (args, names) in [
+            (args.to_string(), &names[..]),
+            (format!("{}, packed = struct", args), &packed[..]),
+        ] {
+            let generated = generated(&args);
+            assert!(!generated.to_string().contains("__axin"), "{}", generated);
+            for name in names {
+                assert!(
+                    mentions_ident(generated.clone(), name),
+                    "{}: {}",
+                    name,
+                    generated
+                );
+            }
+        }
+
+        // The names with the default prefix are free for the function to use
+        let with_old_names: ItemFn = parse_quote! {
+            fn store(__axin_result: u32, __axin: u8) {}
+        };
+        let check = |args: &str, input_fn: &ItemFn| {
+            check_reserved_names(input_fn, &self::config(args)).map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            check(
+                r#"generated_prefix = "__obs_", on_exit(log(__obs_result, &__obs))"#,
+                &with_old_names
+            ),
+            Ok(())
+        );
+        let with_new_names: ItemFn = parse_quote! {
+            fn store(__obs_result: u32) {}
+        };
+        assert_eq!(
+            check(
+                r#"generated_prefix = "__obs_", on_exit(log(__obs_result))"#,
+                &with_new_names
+            )
+            .unwrap_err()
+            .split(',')
+            .next(),
+            Some("`__obs_result` is reserved for a reference to the return value")
+        );
+        assert_eq!(
+            check_result_placeholder(&self::config(r#"generated_prefix = "__obs_", on_enter(log(__obs_result))"#))
+                .unwrap_err()
+                .to_string(),
+            "`__obs_result` refers to the return value, which only the arguments and the predicate of `on_exit` can \
+             use; this runs before the function returns"
+        );
+
+        for prefix in ["obs", "_", "__obs-", "fn_"] {
+            assert_eq!(
+                parse_error(&format!("generated_prefix = {:?}", prefix)),
+                "expected a prefix ending in `_` that makes identifiers with and without the `_`, like \"__obs_\""
+            );
+        }
+    }
+}
@@ -0,0 +1,1197 @@
+//! Procedural macros of [Axin](https://docs.rs/axin).
+//!
+//! This crate is an implementation detail of `axin`; depend on `axin` instead and use the re-exported macros from
+//! there.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    braced,
+    parse::{Parse, ParseStream, Parser as _},
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    Attribute, Ident, ImplItem, Item, ItemFn, ItemImpl, ItemMod, Meta, Token, Type, TypePath,
+};
+
+mod diagnostics;
+mod generator;
+mod manifest;
+
+use args::{check_duplicate_decorators, check_strict_prologue, AxinArg, AxinArgs, OptionArg};
+use axin_args::{args, defaults};
+use defaults::{crate_defaults, tracking_stmt};
+use diagnostics::{attach_diagnostics, check_trivial_decorator};
+use generator::{
+    check_body_imports, check_hook_args, check_instrument_compat, check_outcome_placeholders,
+    check_packed_args, check_recursion, check_reserved_names, check_result_placeholder,
+    generate_enhanced_function, generate_mock_function, generate_order_const, keeps_state,
+    may_implement_trait, process_attribute_args, reset_accessor, stub_missing_hooks, ExecutionPlan,
+    SignatureProfile,
+};
+
+/// An attribute procedural macro that enhances functions with entry and exit hooks, decorators, and prologue statements.
+///
+/// It can also be applied to a module with a body, instrumenting the free functions directly inside it, or to an `impl`
+/// block, instrumenting its methods.
+///
+/// With the `disable` feature or `--cfg axin_disable`, the arguments are checked, so that mistakes in them are still
+/// reported, and functions are emitted as written, next to their twins and the other items the arguments generate.
+///
+/// For more details, see the [Axin documentation](https://docs.rs/axin).
+///
+/// ## Example
+///
+/// ```
+/// use axin::axin;
+///
+/// fn setup() {
+///     println!("Starting function");
+/// }
+///
+/// fn cleanup() {
+///     println!("Function completed");
+/// }
+///
+/// fn timing_decorator<F, R>(func: F) -> R
+/// where F: FnOnce() -> R
+/// {
+///     let start = std::time::Instant::now();
+///     let result = func();
+///     println!("Execution time: {:?}", start.elapsed());
+///     result
+/// }
+///
+/// #[axin(
+///     prologue(println!("Initializing");),
+///     on_enter(setup),
+///     decorator(timing_decorator),
+///     on_exit(cleanup)
+/// )]
+/// fn instrumented_function() -> i32 {
+///     println!("Core logic");
+///     42
+/// }
+///
+/// fn main() {
+///     let result = instrumented_function();
+///     println!("Result: {}", result);
+///
+///     // Output:
+///     // Starting function
+///     // Initializing
+///     // Core logic
+///     // Execution time: ...
+///     // Function completed
+///     // Result: 42
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn axin(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as Item);
+
+    // Parse attribute parameters
+    let attribute_args = parse_macro_input!(args as AxinArgs);
+
+    expand_item(attribute_args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// An attribute procedural macro applying presets, named sets of arguments defined in the crate's `axin.toml`.
+///
+/// `#[axin_use(a, b)]` is the same as `#[axin(preset(a), preset(b))]`, and can be stacked with `axin` attributes like
+/// another of them.
+///
+/// ## Example
+///
+/// With an `axin.toml` at the root of the crate defining the preset `service`:
+///
+/// ```toml
+/// [presets.service]
+/// args = "on_enter(crate::trace(&__axin)), count_calls(crate::CALLS)"
+/// ```
+///
+/// Functions can use it by name:
+///
+/// ```ignore
+/// use axin::axin_use;
+///
+/// #[axin_use(service)]
+/// fn handle(request: &str) -> usize {
+///     request.len()
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn axin_use(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as Item);
+    let attribute_args = parse_macro_input!(args with AxinArgs::parse_presets);
+    expand_item(attribute_args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Instrument the item an attribute is applied to.
+fn expand_item(attribute_args: AxinArgs, item: Item) -> syn::Result<TokenStream2> {
+    match item {
+        Item::Fn(input_fn) => with_defaults(attribute_args, &input_fn)
+            .and_then(|attribute_args| expand_fn(attribute_args, input_fn, None, true, false)),
+        Item::Mod(item_mod) => expand_mod(attribute_args, item_mod),
+        Item::Impl(item_impl) => expand_impl(attribute_args, item_impl),
+        _ => Err(syn::Error::new(
+            Span::call_site(),
+            "`axin` can only be applied to functions, modules and `impl` blocks",
+        )),
+    }
+}
+
+/// A function-like procedural macro applying the same `axin` arguments to a pair of functions, such as a sync and an
+/// async variant of one operation, so that their instrumentation cannot drift apart.
+///
+/// The arguments are given in braces as in `#[axin(...)]`, followed by `=>` and the two functions in braces. Each
+/// function is instrumented as if the attribute was applied to it, together with any `axin` attributes of its own.
+///
+/// ## Example
+///
+/// ```
+/// use axin::{axin_pair, AxinCtx};
+///
+/// fn trace(ctx: &AxinCtx) {
+///     println!("Fetching with {}", ctx.fn_name);
+/// }
+///
+/// axin_pair!({ on_enter(trace(&__axin)) } => {
+///     fn fetch(id: u32) -> String {
+///         format!("item {}", id)
+///     }
+///
+///     async fn fetch_async(id: u32) -> String {
+///         format!("item {}", id)
+///     }
+/// });
+///
+/// fn main() {
+///     assert_eq!(fetch(1), "item 1");
+/// }
+/// ```
+#[proc_macro]
+pub fn axin_pair(input: TokenStream) -> TokenStream {
+    let PairInput { args, fns } = parse_macro_input!(input as PairInput);
+    expand_pair(args, fns)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The input of [`axin_pair!`](macro@axin_pair): the arguments and the functions to apply them to.
+struct PairInput {
+    args: AxinArgs,
+    fns: Vec<ItemFn>,
+}
+
+impl Parse for PairInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let args;
+        braced!(args in input);
+        let args = args.parse()?;
+        input.parse::<Token![=>]>()?;
+        let items;
+        let braces = braced!(items in input);
+        let mut fns = Vec::new();
+        while !items.is_empty() {
+            fns.push(items.parse()?);
+        }
+        if fns.len() != 2 {
+            return Err(syn::Error::new(
+                braces.span.join(),
+                format!(
+                    "`axin_pair!` takes two functions, such as a sync and an async variant, but is given {}; use \
+                     `#[axin(...)]` on a single function",
+                    fns.len()
+                ),
+            ));
+        }
+        Ok(PairInput { args, fns })
+    }
+}
+
+/// Instrument both functions of a pair with the same arguments.
+fn expand_pair(args: AxinArgs, fns: Vec<ItemFn>) -> syn::Result<TokenStream2> {
+    if let Some(keyword) = args.explicit_event_id() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "an explicit `event_id` would be shared by both functions of the pair; use `event_id = auto`, or give \
+             each function its own attribute",
+        ));
+    }
+    if let Some(keyword) = args.twin_keyword() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "a `twin` would be shared by both functions of the pair; give the function its own attribute",
+        ));
+    }
+
+    let mut output = TokenStream2::new();
+    for input_fn in fns {
+        let fn_args = with_defaults(args.clone(), &input_fn)?;
+        output.extend(expand_fn(fn_args, input_fn, None, true, false)?);
+    }
+    Ok(output)
+}
+
+/// Instrument a single function, together with the `axin` attributes stacked below this one, and emit the twins any of
+/// these attributes ask for.
+///
+/// Twins are copies of the function as written, so they are made before any attribute instruments it, and none of the
+/// attributes instruments them. If manifests are written, the function's one is named after `owner`, the `impl` type or
+/// module the attribute was applied to, if any. Unless `siblings` is false, as in a trait `impl` block, items can be
+/// emitted next to the function: the accessor resetting its state, see [`reset_accessor`], and its order constant. The
+/// function is `associated` if it is a method of the `impl` block the attribute was applied to; a method the attribute
+/// is applied to directly gets no accessor if it may be in a trait `impl` block, see [`may_implement_trait`], and
+/// cannot have an order constant.
+fn expand_fn(
+    attribute_args: AxinArgs,
+    input_fn: ItemFn,
+    owner: Option<&str>,
+    siblings: bool,
+    associated: bool,
+) -> syn::Result<TokenStream2> {
+    let stacked = stacked_args(&input_fn);
+    check_strict_prologue(std::iter::once(&attribute_args).chain(&stacked))?;
+    check_duplicate_decorators(std::iter::once(&attribute_args).chain(&stacked))?;
+    manifest::write_manifest(
+        owner,
+        &input_fn.sig.ident.to_string(),
+        std::iter::once(&attribute_args).chain(&stacked),
+    )?;
+    let order_const = match std::iter::once(&attribute_args)
+        .chain(&stacked)
+        .find_map(AxinArgs::order_const_keyword)
+    {
+        Some(keyword) if !siblings => {
+            return Err(syn::Error::new_spanned(
+                keyword,
+                "`order_const` cannot be used in a trait `impl` block, which cannot have items the trait does not \
+                 declare",
+            ));
+        }
+        Some(keyword) if !associated && may_implement_trait(&input_fn) => {
+            return Err(syn::Error::new_spanned(
+                keyword,
+                "`order_const` cannot tell whether this method is in a trait `impl` block, which cannot have items \
+                 the trait does not declare; give the method a visibility, or apply the attribute to its inherent \
+                 `impl` block",
+            ));
+        }
+        Some(_) => {
+            let layers: Vec<&AxinArgs> = std::iter::once(&attribute_args).chain(&stacked).collect();
+            order_consts(&input_fn, &layers)?
+        }
+        None => TokenStream2::new(),
+    };
+    let mut twins = TokenStream2::new();
+    for name in std::iter::once(&attribute_args)
+        .chain(&stacked)
+        .flat_map(AxinArgs::twins)
+    {
+        twins.extend(twin_fn(&input_fn, name)?);
+    }
+
+    // The attributes below the attribute of another macro are expanded on their own, and emit the accessor instead
+    let deferred = input_fn
+        .attrs
+        .iter()
+        .filter(|attr| is_axin_attribute(attr))
+        .count()
+        > stacked_attributes(&input_fn).count();
+    let placeable = siblings && (associated || !may_implement_trait(&input_fn));
+    let accessor = (placeable && !deferred)
+        .then(|| reset_accessor(&input_fn))
+        .flatten();
+
+    // With the `disable` feature or `cfg(axin_disable)`, the function is still instrumented, so that mistakes in the
+    // arguments are reported and the same items are emitted next to it, but it is then emitted as written instead
+    let written = (cfg!(feature = "disable") || cfg!(axin_disable)).then(|| written_fn(&input_fn));
+    let mut output = expand_layers(attribute_args, input_fn, associated)?;
+    let keeps_state = keeps_state(output.clone());
+    if let Some(written) = written {
+        output = written.into_token_stream();
+    }
+    if keeps_state {
+        output.extend(accessor.into_token_stream());
+    }
+    output.extend(order_const);
+    output.extend(twins);
+    Ok(output)
+}
+
+/// The order constants of a function instrumented by the given attributes, the first outermost.
+///
+/// The attributes given `debug_only` or `cfg(...)` leave the function alone in the builds their predicate does not hold
+/// for, and so do the phases they add: with such attributes, there is one constant for every combination of their
+/// predicates, each under the `cfg` of its combination.
+fn order_consts(input_fn: &ItemFn, layers: &[&AxinArgs]) -> syn::Result<TokenStream2> {
+    let gates: Vec<(usize, TokenStream2)> = layers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, args)| Some((index, args.gate()?)))
+        .collect();
+    if gates.is_empty() {
+        return Ok(
+            generate_order_const(input_fn, &order_plan(layers.iter().copied())?)
+                .into_token_stream(),
+        );
+    }
+    let mut consts = TokenStream2::new();
+    for combination in 0..1usize << gates.len() {
+        let holds = |bit: usize| combination >> bit & 1 == 1;
+        let applied = layers.iter().enumerate().filter(|(index, _)| {
+            !gates
+                .iter()
+                .enumerate()
+                .any(|(bit, (gated, _))| gated == index && !holds(bit))
+        });
+        let mut order_const =
+            generate_order_const(input_fn, &order_plan(applied.map(|(_, args)| *args))?);
+        let predicates = gates.iter().enumerate().map(|(bit, (_, gate))| {
+            if holds(bit) {
+                gate.clone()
+            } else {
+                quote! { not(#gate) }
+            }
+        });
+        order_const
+            .attrs
+            .push(parse_quote! { #[cfg(all(#(#predicates),*))] });
+        consts.extend(order_const.into_token_stream());
+    }
+    Ok(consts)
+}
+
+/// The plan of the phases a call of a function instrumented by the given attributes runs, the first outermost. Without
+/// any, the call runs the body only.
+fn order_plan<'a>(
+    layers: impl DoubleEndedIterator<Item = &'a AxinArgs>,
+) -> syn::Result<ExecutionPlan> {
+    let mut plan: Option<ExecutionPlan> = None;
+    for args in layers.rev() {
+        let outer = ExecutionPlan::new(&process_attribute_args(args.clone())?);
+        plan = Some(match plan {
+            Some(inner) => outer.nest(inner),
+            None => outer,
+        });
+    }
+    match plan {
+        Some(plan) => Ok(plan),
+        None => Ok(ExecutionPlan::new(&process_attribute_args(
+            AxinArgs::default(),
+        )?)),
+    }
+}
+
+/// Instrument a single function, together with the `axin` attributes stacked below this one.
+///
+/// The compiler resolves `cfg_attr` before expanding this attribute, so the attributes left are the ones that apply.
+/// The next one instruments the function first, recursively, and this one instruments the result, so that attributes
+/// wrap the ones below them: the first attribute's entry hooks run first, its exit hooks last, and its decorator wraps
+/// the decorators of the later attributes. The attributes below the attribute of another macro are left in place, see
+/// [`defer_layers`].
+fn expand_layers(
+    mut attribute_args: AxinArgs,
+    mut input_fn: ItemFn,
+    associated: bool,
+) -> syn::Result<TokenStream2> {
+    let Some(index) = stacked_attributes(&input_fn).next().map(|(index, _)| index) else {
+        defer_layers(&mut input_fn);
+        return instrument_fn(attribute_args, input_fn, associated);
+    };
+    // The first `generated_prefix` of the layers applies to those giving none, so that they all bind the same
+    // placeholders
+    let stacked = stacked_args(&input_fn);
+    let prefix = std::iter::once(&attribute_args)
+        .chain(&stacked)
+        .find_map(AxinArgs::generated_prefix)
+        .cloned();
+    attribute_args.inherit_prefix(prefix.as_ref());
+    let inner = input_fn.attrs.remove(index);
+    let mut inner_args = parse_attribute(&inner)?;
+    inner_args.inherit_prefix(prefix.as_ref());
+
+    // Functions replaced by `debug_only`, `cfg` or a mock under some configuration come with their alternatives, each of
+    // which gets instrumented
+    let expanded: syn::File = syn::parse2(expand_layers(inner_args, input_fn, associated)?)?;
+    let mut output = TokenStream2::new();
+    for item in expanded.items {
+        match item {
+            Item::Fn(item_fn) => {
+                output.extend(instrument_fn(attribute_args.clone(), item_fn, associated)?)
+            }
+            item => output.extend(item.into_token_stream()),
+        }
+    }
+    Ok(output)
+}
+
+/// Merge the crate's default arguments from its `axin.toml` under the arguments of the attribute applied to a function.
+///
+/// Defaults are merged once per function, into the outermost attribute, and left out if that attribute or one stacked
+/// below it gives `no_defaults`. A default is also left out if one of these attributes gives the same parameter, so
+/// that explicit arguments replace the defaults rather than add to them. The options these attributes give with
+/// `opt(...)` are substituted into the defaults.
+fn with_defaults(attribute_args: AxinArgs, input_fn: &ItemFn) -> syn::Result<AxinArgs> {
+    let stacked = stacked_args(input_fn);
+    let layers = || std::iter::once(&attribute_args).chain(&stacked);
+    if layers().any(AxinArgs::has_no_defaults) {
+        return Ok(attribute_args);
+    }
+    let options: Vec<&OptionArg> = layers().flat_map(AxinArgs::options).collect();
+    let defaults = match crate_defaults() {
+        Ok(Some(defaults)) => defaults,
+        Ok(None) => return match options.first() {
+            Some(option) => Err(syn::Error::new_spanned(
+                &option.name,
+                "`opt` sets options of the defaults in `axin.toml`, which this crate does not have",
+            )),
+            None => Ok(attribute_args),
+        },
+        Err(error) => return Err(syn::Error::new(Span::call_site(), error)),
+    };
+
+    let given: Vec<&str> = layers()
+        .flat_map(|args| args.args.iter().map(AxinArg::name))
+        .collect();
+    let mut merged = AxinArgs::default();
+    merged.args.extend(
+        defaults
+            .parse_args(&options)?
+            .args
+            .into_iter()
+            .filter(|arg| !given.contains(&arg.name())),
+    );
+    merged.args.extend(attribute_args.args);
+    merged.validate().map_err(|error| {
+        syn::Error::new(
+            error.span(),
+            format!(
+                "{}, with the defaults from `{}`; add `no_defaults` to opt out of them",
+                error,
+                defaults.path.display()
+            ),
+        )
+    })?;
+    merged.defaults_file = Some(defaults.path);
+    Ok(merged)
+}
+
+/// The arguments of the `axin` attributes stacked below the one being expanded.
+///
+/// Stacked attributes are only parsed to look at their parameters, and their errors reported when expanding them.
+fn stacked_args(input_fn: &ItemFn) -> Vec<AxinArgs> {
+    stacked_attributes(input_fn)
+        .filter_map(|(_, attr)| parse_attribute(attr).ok())
+        .collect()
+}
+
+/// The `axin` attributes stacked below the one being expanded, with their indices, up to the first attribute of another
+/// macro.
+fn stacked_attributes(input_fn: &ItemFn) -> impl Iterator<Item = (usize, &Attribute)> {
+    input_fn
+        .attrs
+        .iter()
+        .enumerate()
+        .take_while(|(_, attr)| is_axin_attribute(attr) || is_builtin_attribute(attr))
+        .filter(|(_, attr)| is_axin_attribute(attr))
+}
+
+/// Leave the `axin` attributes below the attribute of another macro to be expanded after it, on whatever it makes of
+/// the function, so that the macros apply in the order of their attributes. The compiler expands them as if they were
+/// the outermost, so they are given `no_defaults`: the crate's defaults are merged into the outermost attribute already.
+fn defer_layers(input_fn: &mut ItemFn) {
+    let mut deferred = false;
+    for attr in &mut input_fn.attrs {
+        if is_axin_attribute(attr) {
+            if deferred {
+                without_defaults(attr);
+            }
+        } else if !is_builtin_attribute(attr) {
+            deferred = true;
+        }
+    }
+}
+
+/// Add `no_defaults` to an `axin` attribute, rewriting `#[axin_use(a, b)]` as `#[axin(preset(a), preset(b))]`.
+/// Attributes that do not parse are left for their own expansion to report.
+fn without_defaults(attr: &mut Attribute) {
+    let mut path = attr.path().clone();
+    let args = match &attr.meta {
+        _ if is_axin_use(attr) => {
+            let Ok(names) = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)
+            else {
+                return;
+            };
+            if let Some(segment) = path.segments.last_mut() {
+                segment.ident = Ident::new("axin", segment.ident.span());
+            }
+            let names = names.iter();
+            quote! { #(preset(#names),)* }
+        }
+        Meta::Path(_) => TokenStream2::new(),
+        Meta::List(list) => {
+            let tokens = &list.tokens;
+            match tokens.clone().into_iter().last() {
+                None => TokenStream2::new(),
+                Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => tokens.clone(),
+                Some(_) => quote! { #tokens, },
+            }
+        }
+        Meta::NameValue(_) => return,
+    };
+    *attr = parse_quote! { #[#path(#args no_defaults)] };
+}
+
+/// Whether an attribute is one the compiler knows, such as `#[doc]`, `#[inline]` or a lint level, or one of a tool, such
+/// as `#[rustfmt::skip]`, rather than the attribute of another macro.
+fn is_builtin_attribute(attr: &Attribute) -> bool {
+    const BUILTIN: &[&str] = &[
+        "allow",
+        "cfg",
+        "cfg_attr",
+        "cold",
+        "deny",
+        "deprecated",
+        "doc",
+        "expect",
+        "export_name",
+        "forbid",
+        "ignore",
+        "inline",
+        "link_section",
+        "must_use",
+        "no_mangle",
+        "should_panic",
+        "target_feature",
+        "test",
+        "track_caller",
+        "unsafe",
+        "warn",
+    ];
+    const TOOLS: &[&str] = &["clippy", "diagnostic", "rustdoc", "rustfmt"];
+    let segments: Vec<_> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| &segment.ident)
+        .collect();
+    match segments.as_slice() {
+        [name] => BUILTIN.iter().any(|builtin| *name == builtin),
+        [tool, _, ..] => TOOLS.iter().any(|known| *tool == known),
+        [] => false,
+    }
+}
+
+/// The arguments of another `axin` attribute, those of an `axin_use` attribute being the presets it names.
+fn parse_attribute(attr: &Attribute) -> syn::Result<AxinArgs> {
+    let uses_presets = is_axin_use(attr);
+    match &attr.meta {
+        Meta::Path(_) if uses_presets => AxinArgs::parse_presets.parse2(TokenStream2::new()),
+        Meta::Path(_) => Ok(AxinArgs::default()),
+        _ if uses_presets => attr.parse_args_with(AxinArgs::parse_presets),
+        _ => attr.parse_args(),
+    }
+}
+
+/// Make the twin `name` of a function: a copy of it as written, without its `axin` attributes, and documented as such.
+///
+/// Attributes giving the function its symbol name, such as `#[no_mangle]`, stay with the function only, since the twin
+/// would otherwise export a second symbol of the same name.
+fn twin_fn(input_fn: &ItemFn, name: &Ident) -> syn::Result<TokenStream2> {
+    if *name == input_fn.sig.ident {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "the twin of `{}` needs a name of its own",
+                input_fn.sig.ident
+            ),
+        ));
+    }
+    let mut twin = input_fn.clone();
+    twin.attrs
+        .retain(|attr| !is_axin_attribute(attr) && !names_symbol(attr));
+    twin.sig.ident = name.clone();
+    let note = format!(
+        " Same as `{}`, without its instrumentation.",
+        input_fn.sig.ident
+    );
+    if twin.attrs.iter().any(|attr| attr.path().is_ident("doc")) {
+        twin.attrs.push(parse_quote! { #[doc = ""] });
+    }
+    twin.attrs.push(parse_quote! { #[doc = #note] });
+    Ok(twin.into_token_stream())
+}
+
+/// Whether an attribute is an `axin_use` attribute, naming presets.
+fn is_axin_use(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "axin_use")
+}
+
+/// Whether an attribute is another `axin` attribute, as `#[axin(...)]`, `#[axin::axin(...)]` or `#[axin_use(...)]`.
+fn is_axin_attribute(attr: &Attribute) -> bool {
+    let path = attr.path();
+    let segments: Vec<_> = path.segments.iter().map(|segment| &segment.ident).collect();
+    matches!(segments.as_slice(), [name] | [_, name] if *name == "axin" || *name == "axin_use")
+}
+
+/// Whether an attribute names the symbol of a function, as `#[no_mangle]` or `#[export_name = "..."]`, also when wrapped
+/// in `#[unsafe(...)]`.
+fn names_symbol(attr: &Attribute) -> bool {
+    let is_symbol_attribute =
+        |path: &syn::Path| path.is_ident("no_mangle") || path.is_ident("export_name");
+    match &attr.meta {
+        Meta::List(list) if list.path.is_ident("unsafe") => list
+            .parse_args_with(Meta::parse)
+            .is_ok_and(|meta| is_symbol_attribute(meta.path())),
+        meta => is_symbol_attribute(meta.path()),
+    }
+}
+
+/// Instrument a single function with the arguments of one attribute. The function is `associated` if it is a method of
+/// the `impl` block the attribute was applied to.
+fn instrument_fn(
+    mut attribute_args: AxinArgs,
+    input_fn: ItemFn,
+    associated: bool,
+) -> syn::Result<TokenStream2> {
+    if let Some(keyword) = attribute_args.take_module_filters().first_keyword() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            format!(
+                "`{}` can only be used when applying `axin` to a module or an `impl` block",
+                keyword
+            ),
+        ));
+    }
+
+    let gate = attribute_args.take_gate();
+    attribute_args.take_no_defaults();
+    attribute_args.remove_options();
+    attribute_args.remove_twins();
+    let defaults_file = attribute_args.defaults_file.take();
+    if attribute_args.args.is_empty() {
+        return Ok(quote! {
+            #input_fn
+        });
+    }
+    let original_fn = gate.is_some().then(|| input_fn.clone());
+
+    let mut config = process_attribute_args(attribute_args)?;
+    config.associated = associated;
+
+    if let Some(options) = &config.instrument {
+        check_instrument_compat(&input_fn, options)?;
+    }
+    if let Some(on_enter) = &config.on_enter_fn {
+        check_hook_args(&input_fn, on_enter)?;
+    }
+    if config.packed {
+        check_packed_args(&input_fn)?;
+    }
+    check_reserved_names(&input_fn, &config)?;
+    check_result_placeholder(&config)?;
+    check_outcome_placeholders(&config)?;
+    check_body_imports(&input_fn, &config)?;
+    check_recursion(&input_fn, &config, associated)?;
+    let stubs = stub_missing_hooks(&mut config)?;
+
+    // Without a decorator, only the generated code calls the inner callable, which can capture a `self` receiver as if
+    // adapted without changing what any hook sees
+    let decorated = config.decorator_fn.is_some() || config.decorator_select.is_some();
+    if input_fn.sig.receiver().is_some() && !decorated {
+        config.adapt = true;
+    }
+
+    // Modifiers and receivers the instrumentation cannot handle would otherwise be reported as errors inside the
+    // generated code
+    SignatureProfile::new(&input_fn.sig).check(&input_fn.sig.ident, &config)?;
+
+    // The statements making Cargo track the defaults file and the manifest directory are attached to the function like
+    // the warnings
+    let warnings = check_trivial_decorator(&input_fn, decorated, &config.trivial_policy)?
+        .into_iter()
+        .chain(defaults_file.as_deref().and_then(tracking_stmt))
+        .chain(manifest::tracking_stmt())
+        .chain(stubs)
+        .collect();
+
+    // A mock that is not instrumented replaces the whole function in test builds
+    let mock_fn = match &config.mock_in_tests {
+        Some((mock, false)) => Some(generate_mock_function(&input_fn, mock)),
+        _ => None,
+    };
+
+    // Process function enhancement according to the new design
+    let generated = generate_enhanced_function(input_fn, &config);
+
+    let instrumented = attach_diagnostics(generated, warnings, config.explain.as_ref());
+
+    // Without `debug_assertions` or where the `cfg` predicate does not hold, the function is emitted exactly as written,
+    // in test builds too
+    let gated = gate.as_ref().map(|gate| quote! { #gate, });
+    let instrumented = match mock_fn {
+        Some(mock_fn) => quote! {
+            #[cfg(all(#gated not(test)))]
+            #instrumented
+            #[cfg(all(#gated test))]
+            #mock_fn
+        },
+        None => match &gate {
+            Some(gate) => quote! {
+                #[cfg(#gate)]
+                #instrumented
+            },
+            None => instrumented,
+        },
+    };
+    match (gate, original_fn) {
+        (Some(gate), Some(original_fn)) => Ok(quote! {
+            #instrumented
+            #[cfg(not(#gate))]
+            #original_fn
+        }),
+        _ => Ok(instrumented),
+    }
+}
+
+/// Instrument the free functions directly inside a module, as selected by the `skip` and `only` filters.
+///
+/// Nested modules, `impl` blocks and other items are left untouched.
+fn expand_mod(mut attribute_args: AxinArgs, mut item_mod: ItemMod) -> syn::Result<TokenStream2> {
+    let filters = attribute_args.take_module_filters();
+    if let Some(keyword) = attribute_args.explicit_event_id() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "an explicit `event_id` would be shared by all functions of the module; use `event_id = auto`, or give \
+             each function its own attribute",
+        ));
+    }
+    if let Some(keyword) = attribute_args.twin_keyword() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "a `twin` would be shared by all functions of the module; give the function its own attribute",
+        ));
+    }
+    let Some((_, items)) = &mut item_mod.content else {
+        return Err(syn::Error::new_spanned(
+            &item_mod,
+            "`axin` can only be applied to modules with a body",
+        ));
+    };
+
+    for name in filters.names() {
+        let exists = items
+            .iter()
+            .any(|item| matches!(item, Item::Fn(item_fn) if item_fn.sig.ident == *name));
+        if !exists {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("module `{}` has no function `{}`", item_mod.ident, name),
+            ));
+        }
+    }
+
+    let owner = item_mod.ident.to_string();
+    for item in items.iter_mut() {
+        if let Item::Fn(item_fn) = item {
+            if take_skip_marker(&mut item_fn.attrs) {
+                continue;
+            }
+            if filters.includes(&item_fn.sig.ident) {
+                let fn_args = with_defaults(attribute_args.clone(), item_fn)?;
+                let expanded = expand_fn(fn_args, item_fn.clone(), Some(&owner), true, false)?;
+                *item = Item::Verbatim(expanded);
+            }
+        }
+    }
+
+    Ok(quote! {
+        #item_mod
+    })
+}
+
+/// Instrument the methods of an `impl` block, as selected by the `skip` and `only` filters.
+///
+/// Associated constants, types and macro invocations are left untouched.
+fn expand_impl(mut attribute_args: AxinArgs, mut item_impl: ItemImpl) -> syn::Result<TokenStream2> {
+    let filters = attribute_args.take_module_filters();
+    if let Some(keyword) = attribute_args.explicit_event_id() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "an explicit `event_id` would be shared by all methods of the `impl` block; use `event_id = auto`, or \
+             give each method its own attribute",
+        ));
+    }
+    if let Some(keyword) = attribute_args.twin_keyword() {
+        return Err(syn::Error::new_spanned(
+            keyword,
+            "a `twin` would be shared by all methods of the `impl` block; give the method its own attribute",
+        ));
+    }
+
+    for name in filters.names() {
+        let exists = item_impl
+            .items
+            .iter()
+            .any(|item| matches!(item, ImplItem::Fn(method) if method.sig.ident == *name));
+        if !exists {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!("the `impl` block has no method `{}`", name),
+            ));
+        }
+    }
+
+    // Manifests name methods after the type without its path and generic arguments, as the type is usually referred to
+    let owner = match &*item_impl.self_ty {
+        Type::Path(TypePath { path, .. }) if !path.segments.is_empty() => {
+            path.segments[path.segments.len() - 1].ident.to_string()
+        }
+        self_ty => self_ty.to_token_stream().to_string().replace(' ', ""),
+    };
+    // A trait `impl` block cannot have items the trait does not declare
+    let siblings = item_impl.trait_.is_none();
+    for item in item_impl.items.iter_mut() {
+        if let ImplItem::Fn(method) = item {
+            if take_skip_marker(&mut method.attrs) {
+                continue;
+            }
+            if filters.includes(&method.sig.ident) {
+                let method_fn = ItemFn {
+                    attrs: method.attrs.clone(),
+                    vis: method.vis.clone(),
+                    sig: method.sig.clone(),
+                    block: Box::new(method.block.clone()),
+                };
+                let fn_args = with_defaults(attribute_args.clone(), &method_fn)?;
+                let expanded = expand_fn(fn_args, method_fn, Some(&owner), siblings, true)?;
+                *item = ImplItem::Verbatim(expanded);
+            }
+        }
+    }
+
+    Ok(quote! {
+        #item_impl
+    })
+}
+
+/// Remove the `#[axin(skip)]` markers from the attributes of a function of a module or a method of an `impl` block the
+/// attribute is applied to, returning whether there was one, in which case the function is left alone.
+///
+/// The function's other `axin` attributes stay, and instrument it on their own.
+fn take_skip_marker(attrs: &mut Vec<Attribute>) -> bool {
+    let before = attrs.len();
+    attrs.retain(|attr| !is_skip_marker(attr));
+    attrs.len() < before
+}
+
+/// Whether an attribute is `#[axin(skip)]`, marking a function the attribute of its module or `impl` block skips.
+fn is_skip_marker(attr: &Attribute) -> bool {
+    let Meta::List(list) = &attr.meta else {
+        return false;
+    };
+    let mut tokens = list.tokens.clone().into_iter();
+    is_axin_attribute(attr)
+        && !is_axin_use(attr)
+        && matches!(
+            (tokens.next(), tokens.next()),
+            (Some(TokenTree::Ident(ident)), None) if ident == "skip"
+        )
+}
+
+/// A function as written, emitted with the `disable` feature or `cfg(axin_disable)` instead of its instrumentation. The
+/// `axin` attributes stacked below the one being expanded are left out, since their twins and other items are emitted
+/// with its own, and the ones below the attribute of another macro are deferred, as when instrumenting it, see
+/// [`defer_layers`].
+fn written_fn(input_fn: &ItemFn) -> ItemFn {
+    let stacked: Vec<usize> = stacked_attributes(input_fn)
+        .map(|(index, _)| index)
+        .collect();
+    let mut written = input_fn.clone();
+    written.attrs = input_fn
+        .attrs
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !stacked.contains(index))
+        .map(|(_, attr)| attr.clone())
+        .collect();
+    defer_layers(&mut written);
+    written
+}
+
+#[cfg(all(test, not(feature = "disable")))]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    /// Expand `item` with the attribute arguments `args`, as the attribute macro would.
+    fn expand(args: &str, item: &str) -> String {
+        let args: AxinArgs = syn::parse_str(args).unwrap();
+        let item: Item = syn::parse_str(item).unwrap();
+        let expanded = match item {
+            Item::Fn(input_fn) => expand_fn(args, input_fn, None, true, false),
+            Item::Mod(item_mod) => expand_mod(args, item_mod),
+            Item::Impl(item_impl) => expand_impl(args, item_impl),
+            _ => unreachable!(),
+        };
+        expanded.unwrap().to_string()
+    }
+
+    #[test]
+    fn expansion_is_deterministic() {
+        let cases = [
+            (
+                "on_enter(trace(&__axin)), decorator(retry(3)), on_error(report), \
+                 slow_log(LIMIT, slow(&__axin)), count_calls(CALLS), document, explain",
+                "fn load(id: u32) -> Result<u32, String> { Ok(id) }",
+            ),
+            (
+                "prologue(let Some(x) = x else { return 0; };), assert_returns(1), warn_call_site(\"old\")",
+                "pub fn old(x: Option<u32>) -> u32 { x }",
+            ),
+            ("minimal, count_calls(CALLS), on_enter(hook)", "fn tiny() {}"),
+            (
+                "decorator(timing), warn_if_trivial, skip(b)",
+                "mod handlers { fn a() { b() } fn b() {} }",
+            ),
+        ];
+
+        for (args, item) in cases {
+            let first = expand(args, item);
+            let second = expand(args, item);
+            assert_eq!(first, second, "expanding `{}` twice differed", item);
+        }
+    }
+
+    #[test]
+    fn attributes_and_docs_are_kept_in_order() {
+        let item = "/// Load an id.\n///\n/// Twice.\n#[inline]\n#[must_use]\n#[no_mangle]\n#[allow(clippy::all)]\n\
+                    pub extern \"C\" fn load(id: u32) -> u32 { id }";
+        let original: ItemFn = syn::parse_str(item).unwrap();
+        let original: Vec<_> = original
+            .attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+
+        for args in [
+            "on_enter(trace)",
+            "decorator(timing), on_exit(done)",
+            "minimal, on_enter(trace)",
+        ] {
+            let expanded: ItemFn = syn::parse_str(&expand(args, item)).unwrap();
+            let attrs: Vec<_> = expanded
+                .attrs
+                .iter()
+                .map(|attr| attr.to_token_stream().to_string())
+                .collect();
+            assert_eq!(
+                attrs, original,
+                "expanding with `{}` changed the attributes",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn attributes_below_other_macros_are_deferred() {
+        let item = "#[inline]\n#[axin(on_enter(inner))]\n#[cached(size = 1)]\n#[axin(on_enter(a),)]\n#[doc = \"x\"]\n\
+                    #[axin::axin_use(b, c)]\n#[axin]\nfn load(id: u32) -> u32 { id }";
+        let expanded: ItemFn = syn::parse_str(&expand("on_enter(outer)", item)).unwrap();
+        let attrs: Vec<_> = expanded
+            .attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+        assert_eq!(
+            attrs,
+            [
+                "# [inline]",
+                "# [cached (size = 1)]",
+                "# [axin (on_enter (a) , no_defaults)]",
+                "# [doc = \"x\"]",
+                "# [axin :: axin (preset (b) , preset (c) , no_defaults)]",
+                "# [axin (no_defaults)]",
+            ]
+        );
+        let body = expanded.block.to_token_stream().to_string();
+        assert!(body.contains("outer ()") && body.contains("inner ()"));
+    }
+
+    #[test]
+    fn written_functions_keep_the_attributes_below_other_macros() {
+        let input_fn: ItemFn = syn::parse_str(
+            "#[inline]\n#[axin(on_enter(inner))]\n#[cached(size = 1)]\n#[axin(on_enter(a))]\n\
+             fn load(id: u32) -> u32 { id }",
+        )
+        .unwrap();
+        let written = written_fn(&input_fn);
+        let attrs: Vec<_> = written
+            .attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+        assert_eq!(
+            attrs,
+            [
+                "# [inline]",
+                "# [cached (size = 1)]",
+                "# [axin (on_enter (a) , no_defaults)]",
+            ]
+        );
+        assert_eq!(
+            written.block.to_token_stream().to_string(),
+            input_fn.block.to_token_stream().to_string()
+        );
+    }
+
+    #[test]
+    fn twins_leave_symbol_names_to_the_function() {
+        let item = "/// Load an id.\n#[no_mangle]\n#[inline]\n#[export_name = \"load_id\"]\n#[unsafe(no_mangle)]\n\
+                    pub extern \"C\" fn load(id: u32) -> u32 { id }";
+        let expanded = expand("twin = \"load_fast\", on_enter(trace)", item);
+        let file: syn::File = syn::parse_str(&expanded).unwrap();
+        let [Item::Fn(function), Item::Fn(twin)] = file.items.as_slice() else {
+            panic!("expected the function and its twin");
+        };
+        assert_eq!(
+            function
+                .attrs
+                .iter()
+                .filter(|attr| names_symbol(attr))
+                .count(),
+            3
+        );
+        let twin_attrs: Vec<_> = twin
+            .attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+        assert_eq!(
+            twin_attrs,
+            [
+                "# [doc = \" Load an id.\"]",
+                "# [inline]",
+                "# [doc = \"\"]",
+                "# [doc = \" Same as `load`, without its instrumentation.\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_only_release_variant_is_unchanged() {
+        let item = "/// Docs.\n#[inline]\nfn load(id: u32) -> u32 { id }";
+        let expanded = expand("debug_only, on_enter(trace), decorator(timing)", item);
+        let file: syn::File = syn::parse_str(&expanded).unwrap();
+        assert_eq!(file.items.len(), 2);
+
+        let release_cfg = quote! { #[cfg(not(debug_assertions))] };
+        let Item::Fn(mut release_fn) = file.items[1].clone() else {
+            panic!("expected the release variant to be a function");
+        };
+        assert_eq!(
+            release_fn.attrs.remove(0).to_token_stream().to_string(),
+            release_cfg.to_string()
+        );
+
+        let original: ItemFn = syn::parse_str(item).unwrap();
+        assert_eq!(
+            release_fn.to_token_stream().to_string(),
+            original.to_token_stream().to_string()
+        );
+    }
+
+    #[test]
+    fn cfg_predicates_gate_the_instrumentation() {
+        let item = "fn load(id: u32) -> u32 { id }";
+        let expanded = expand(
+            "cfg(any(test, feature = \"trace\")), debug_only, on_enter(trace)",
+            item,
+        );
+        let file: syn::File = syn::parse_str(&expanded).unwrap();
+        let gates: Vec<String> = file
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Fn(item_fn) => item_fn.attrs[0].to_token_stream().to_string(),
+                _ => panic!("expected both variants to be functions"),
+            })
+            .collect();
+        let expected = [
+            quote! { #[cfg(all(any(test, feature = "trace"), debug_assertions))] },
+            quote! { #[cfg(not(all(any(test, feature = "trace"), debug_assertions)))] },
+        ];
+        assert_eq!(gates, expected.map(|gate| gate.to_string()));
+    }
+
+    #[test]
+    fn raw_body_is_not_wrapped() {
+        let item = "fn load(id: u32) -> u32 { #[cfg(unix)] if id == 0 { return 1; } id + 1 }";
+        let expanded = expand("raw_body, on_enter(trace), on_exit(done)", item);
+        let expected = quote! {
+            fn load(id: u32) -> u32 {
+                trace();
+                let __exit_guard = ::axin::__private::ExitGuard::new(|| {
+                    done();
+                });
+                #[cfg(unix)]
+                if id == 0 {
+                    return 1;
+                }
+                id + 1
+            }
+        };
+        let expected: ItemFn = syn::parse2(expected).unwrap();
+        assert_eq!(expanded, expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn stacked_attributes_are_expanded_innermost_first() {
+        let item =
+            "#[axin::axin(debug_only, on_enter(deep))]\n#[inline]\nfn load(id: u32) -> u32 { id }";
+        let expanded = expand("on_enter(base)", item);
+        let file: syn::File = syn::parse_str(&expanded).unwrap();
+
+        // Both variants of the inner `debug_only` layer are wrapped by the outer attribute
+        assert_eq!(file.items.len(), 2);
+        for item in &file.items {
+            let Item::Fn(item_fn) = item else {
+                panic!("expected a function");
+            };
+            assert!(!item_fn.attrs.iter().any(is_axin_attribute));
+            let body = item_fn.block.to_token_stream().to_string();
+            assert!(body.starts_with("{ base () ;"), "unexpected body: {}", body);
+        }
+    }
+
+    #[test]
+    fn skip_markers_leave_methods_as_written() {
+        let item = "impl Store {
+            #[axin(skip)]\n#[inline]\nfn len(&self) -> usize { 0 }
+            #[axin::axin(skip)]\n#[axin(on_enter(own))]\nfn own(&self) {}
+            fn get(&self) -> u32 { 1 }
+        }";
+        let expanded = expand("on_enter(trace)", item);
+        let ItemImpl { items, .. } = syn::parse_str(&expanded).unwrap();
+        let methods: Vec<String> = items
+            .iter()
+            .map(|item| item.to_token_stream().to_string())
+            .collect();
+        assert_eq!(methods[0], "# [inline] fn len (& self) -> usize { 0 }");
+        assert_eq!(methods[1], "# [axin (on_enter (own))] fn own (& self) { }");
+        assert!(
+            methods[2].contains("trace ()"),
+            "unexpected method: {}",
+            methods[2]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Manifests of the expansion, written for editors and other tools when `AXIN_MANIFEST_DIR` is set.
+//!
+//! Each instrumented function gets one JSON file in that directory, overwritten whenever the function is expanded
+//! again, describing the arguments of its `axin` attributes, outermost first:
+//!
+//! ```json
+//! {
+//!   "schema": 1,
+//!   "crate": "my_crate",
+//!   "function": "Store::get",
+//!   "layers": [
+//!     {
+//!       "features": ["on_enter", "decorator"],
+//!       "hooks": [
+//!         { "kind": "on_enter", "path": "crate::trace" },
+//!         { "kind": "decorator", "path": "timing", "alias": "request_timing" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `alias` is only present for hooks given one with `as alias`.
+//!
+//! The JSON is written by hand, which is all a format this small needs. `schema` is increased on changes that tools
+//! reading older manifests would misread.
+
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+use syn::{parse_quote, Stmt};
+
+use crate::args::{AxinArg, AxinArgs, FunctionSpec};
+
+/// Name of the environment variable holding the directory to write manifests to.
+pub const ENV_VAR: &str = "AXIN_MANIFEST_DIR";
+/// Version of the manifests' format.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The directory to write manifests to, if any.
+fn manifest_dir() -> Option<PathBuf> {
+    env::var_os(ENV_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// A statement making Cargo rebuild the crate when the manifest directory changes, if manifests are written.
+///
+/// Reading the variable with `option_env!` records it in the crate's dependency info, which the proc macro reading it
+/// does not.
+pub fn tracking_stmt() -> Option<Stmt> {
+    manifest_dir()?;
+    Some(parse_quote! {
+        const _: ::core::option::Option<&str> = ::core::option_env!(#ENV_VAR);
+    })
+}
+
+/// Write the manifest of the function `name` of `owner`, the `impl` type or module the attribute was applied to, if
+/// any, instrumented by the attributes with the arguments `layers`, outermost first.
+///
+/// The manifest is written to a temporary file first and then renamed, so that tools never read half of one.
+pub fn write_manifest<'a>(
+    owner: Option<&str>,
+    name: &str,
+    layers: impl IntoIterator<Item = &'a AxinArgs>,
+) -> syn::Result<()> {
+    let Some(dir) = manifest_dir() else {
+        return Ok(());
+    };
+    let crate_name = env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "crate".to_string());
+    let function = match owner {
+        Some(owner) => format!("{}::{}", owner, name),
+        None => name.to_string(),
+    };
+    let manifest = render(&crate_name, &function, layers);
+
+    let path = dir.join(format!(
+        "{}.{}.json",
+        crate_name,
+        function.replace("::", ".")
+    ));
+    let temporary = dir.join(format!(".{}.{}.tmp", crate_name, process::id()));
+    let write = |path: &Path| {
+        fs::create_dir_all(&dir)?;
+        fs::write(&temporary, manifest.as_bytes())?;
+        fs::rename(&temporary, path)
+    };
+    write(&path).map_err(|error| {
+        syn::Error::new(
+            Span::call_site(),
+            format!(
+                "failed to write the expansion manifest `{}` ({} is set): {}",
+                path.display(),
+                ENV_VAR,
+                error
+            ),
+        )
+    })
+}
+
+/// Render the manifest of a function as JSON.
+fn render<'a>(
+    crate_name: &str,
+    function: &str,
+    layers: impl IntoIterator<Item = &'a AxinArgs>,
+) -> String {
+    let layers: Vec<String> = layers.into_iter().map(render_layer).collect();
+    format!(
+        "{{\n  \"schema\": {},\n  \"crate\": {},\n  \"function\": {},\n  \"layers\": [{}]\n}}\n",
+        SCHEMA_VERSION,
+        json_string(crate_name),
+        json_string(function),
+        indented_list(&layers, "    ")
+    )
+}
+
+/// Render the parameters and hooks of one attribute as a JSON object.
+fn render_layer(args: &AxinArgs) -> String {
+    let mut features: Vec<&str> = Vec::new();
+    for arg in &args.args {
+        if !features.contains(&arg.name()) {
+            features.push(arg.name());
+        }
+    }
+    let features: Vec<String> = features.into_iter().map(json_string).collect();
+    let hooks: Vec<String> = args
+        .args
+        .iter()
+        .flat_map(|arg| hooks(arg).into_iter().map(move |hook| (arg.name(), hook)))
+        .map(|(kind, hook)| {
+            let path = hook.path().to_token_stream().to_string().replace(' ', "");
+            let alias = match hook {
+                FunctionSpec::Simple(_, Some(alias))
+                | FunctionSpec::WithArgs(_, _, Some(alias)) => {
+                    format!(", \"alias\": {}", json_string(&alias.to_string()))
+                }
+                _ => String::new(),
+            };
+            format!(
+                "{{ \"kind\": {}, \"path\": {}{} }}",
+                json_string(kind),
+                json_string(&path),
+                alias
+            )
+        })
+        .collect();
+    format!(
+        "{{\n      \"features\": [{}],\n      \"hooks\": [{}]\n    }}",
+        features.join(", "),
+        indented_list(&hooks, "        ")
+    )
+}
+
+/// The functions an argument calls: hooks, decorators, mocks and sinks.
+fn hooks(arg: &AxinArg) -> Vec<&FunctionSpec> {
+    match arg {
+        AxinArg::OnEnter { func, .. }
+        | AxinArg::OnExit { func, .. }
+        | AxinArg::OnError { func, .. }
+        | AxinArg::OnOk { func, .. }
+        | AxinArg::OnErr { func, .. }
+        | AxinArg::Decorator { func, .. }
+        | AxinArg::SlowLog { func, .. }
+        | AxinArg::HookErrorSink { func }
+        | AxinArg::MockInTests { func, .. }
+        | AxinArg::PhaseTiming { func }
+        | AxinArg::OnPanic { func }
+        | AxinArg::OnExitAlways { func } => vec![func],
+        AxinArg::DecoratorSelect { decorators, .. } => decorators.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Lay out the elements of a JSON array one per line, with the given indentation, closing it one level less indented.
+fn indented_list(elements: &[String], indent: &str) -> String {
+    if elements.is_empty() {
+        return String::new();
+    }
+    format!(
+        "\n{}{}\n{}",
+        indent,
+        elements.join(&format!(",\n{}", indent)),
+        &indent[2..]
+    )
+}
+
+/// Quote a string as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_layers_and_escapes_strings() {
+        let instrumented: AxinArgs =
+            syn::parse_quote!(decorator_select(level, [fast, slow::path as slow]), adapt);
+        let manifest = render(
+            "my_crate",
+            "quoted\"\\name\n",
+            [&instrumented, &AxinArgs::default()],
+        );
+        assert_eq!(
+            manifest,
+            r#"{
+  "schema": 1,
+  "crate": "my_crate",
+  "function": "quoted\"\\name\n",
+  "layers": [
+    {
+      "features": ["decorator_select", "adapt"],
+      "hooks": [
+        { "kind": "decorator_select", "path": "fast" },
+        { "kind": "decorator_select", "path": "slow::path", "alias": "slow" }
+      ]
+    },
+    {
+      "features": [],
+      "hooks": []
+    }
+  ]
+}
+"#
+        );
+    }
+}
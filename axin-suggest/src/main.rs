@@ -0,0 +1,91 @@
+//! `cargo axin-suggest`: find functions wrapping their bodies by hand, by timing them, catching their panics or logging
+//! their entry and exit, and print the `axin` attributes doing the same.
+//!
+//! ```text
+//! cargo axin-suggest [PATH]...
+//! ```
+//!
+//! Paths are Rust files, or directories searched for them, `src` by default. Functions already instrumented, or in an
+//! instrumented `impl` block or module, are left out. The suggestions are a starting point: the hooks they name still
+//! have to be written, as the notes printed with them say, and the hand-rolled code removed.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+mod suggest;
+
+fn main() -> ExitCode {
+    let mut paths: Vec<PathBuf> = env::args_os().skip(1).map(PathBuf::from).collect();
+    // Cargo passes the name of the subcommand first
+    if paths
+        .first()
+        .is_some_and(|path| path == Path::new("axin-suggest"))
+    {
+        paths.remove(0);
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("src"));
+    }
+
+    let mut files = Vec::new();
+    let mut failed = false;
+    for path in &paths {
+        if let Err(error) = collect_files(path, &mut files) {
+            eprintln!("error: cannot read `{}`: {}", path.display(), error);
+            failed = true;
+        }
+    }
+    files.sort();
+
+    for file in &files {
+        let parsed = fs::read_to_string(file)
+            .map_err(|error| error.to_string())
+            .and_then(|source| syn::parse_file(&source).map_err(|error| error.to_string()));
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                eprintln!("error: cannot parse `{}`: {}", file.display(), error);
+                failed = true;
+                continue;
+            }
+        };
+        for suggestion in suggest::suggest_file(&parsed) {
+            println!("{}: fn {}", file.display(), suggestion.function);
+            println!("    {}", suggestion.attribute());
+            for note in &suggestion.notes {
+                println!("    note: {}", note);
+            }
+            println!();
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Collect the Rust files at `path`, searching directories recursively but for hidden ones and `target`.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path.to_path_buf());
+        } else if !path.exists() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() && (name.starts_with('.') || name == "target") {
+            continue;
+        }
+        collect_files(&path, files)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,755 @@
+//! Matchers recognizing hand-rolled wrappers in function bodies, and the attributes replacing them.
+//!
+//! Each matcher looks at the statements of a body for one shape of wrapper:
+//!
+//! - timing: `let start = Instant::now();` first, and `start.elapsed()` printed or logged last, possibly only when it
+//!   exceeds a threshold, replaced by `profile` or `profile(slow = ...)`
+//! - panic catching: a body that is a single `catch_unwind` call, replaced by a decorator
+//! - entry and exit logs: a print or log macro first and last, replaced by hooks
+//!
+//! Statements the matchers do not fully understand, such as a timer that is used for more than the report, make them
+//! suggest nothing rather than something doing less than the code it replaces.
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    parse_quote, visit::Visit, Attribute, BinOp, Block, Expr, ExprCall, ExprLit, Ident, ImplItemFn,
+    ItemFn, ItemImpl, ItemMod, Lit, Pat, Path, Signature, Stmt, Type,
+};
+
+use axin_args::args::{AxinArgs, FunctionSpec};
+
+/// Print macros that may report a timer or log entry and exit.
+const PRINT_MACROS: &[&str] = &["print", "println", "eprint", "eprintln"];
+/// Macros of `log` and `tracing` that may report a timer or log entry and exit, alone or prefixed with their crate.
+const LOG_MACROS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+/// Constructors of `Duration` and the units of the thresholds of `profile` they correspond to.
+const DURATION_UNITS: &[(&str, &str)] = &[
+    ("from_nanos", "ns"),
+    ("from_micros", "us"),
+    ("from_millis", "ms"),
+    ("from_secs", "s"),
+];
+
+/// An argument of a suggested attribute.
+pub enum Suggested {
+    /// `on_enter(hook)`
+    OnEnter(FunctionSpec),
+    /// `decorator(function)`
+    Decorator(FunctionSpec),
+    /// `adapt`
+    Adapt,
+    /// `on_exit(hook)`
+    OnExit(FunctionSpec),
+    /// `profile` or `profile(slow = threshold)`, with the threshold as written in the attribute
+    Profile { slow: Option<String> },
+}
+
+impl Suggested {
+    /// Render the argument as written in the attribute.
+    fn render(&self) -> String {
+        let spec = |spec: &FunctionSpec| render_tokens(spec.to_token_stream());
+        match self {
+            Suggested::OnEnter(hook) => format!("on_enter({})", spec(hook)),
+            Suggested::Decorator(decorator) => format!("decorator({})", spec(decorator)),
+            Suggested::Adapt => "adapt".to_string(),
+            Suggested::OnExit(hook) => format!("on_exit({})", spec(hook)),
+            Suggested::Profile { slow: None } => "profile".to_string(),
+            Suggested::Profile { slow: Some(slow) } => format!("profile(slow = {})", slow),
+        }
+    }
+}
+
+/// A suggested attribute for one function.
+pub struct Suggestion {
+    /// The name of the function, prefixed with the type of its `impl` block for methods.
+    pub function: String,
+    /// The arguments of the attribute, in the order they are written in.
+    pub args: Vec<Suggested>,
+    /// What to write or change besides adding the attribute.
+    pub notes: Vec<String>,
+}
+
+impl Suggestion {
+    /// The attribute as written above the function.
+    pub fn attribute(&self) -> String {
+        format!("#[axin({})]", self.rendered_args())
+    }
+
+    /// Parse the arguments of the attribute as the macro does, checking that it would accept them.
+    pub fn check(&self) -> syn::Result<AxinArgs> {
+        syn::parse_str(&self.rendered_args())
+    }
+
+    fn rendered_args(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(Suggested::render).collect();
+        args.join(", ")
+    }
+}
+
+/// What one matcher recognized: the arguments replacing the code, notes on it, and the statements it replaces.
+struct Match {
+    args: Vec<Suggested>,
+    notes: Vec<String>,
+    stmts: Vec<usize>,
+}
+
+/// Suggest attributes for the functions and methods of a file that are not instrumented yet.
+pub fn suggest_file(file: &syn::File) -> Vec<Suggestion> {
+    let mut finder = Finder::default();
+    finder.visit_file(file);
+    finder.suggestions
+}
+
+/// Collects suggestions while walking a file, keeping track of the `impl` block it is in.
+#[derive(Default)]
+struct Finder {
+    owner: Option<String>,
+    instrumented: bool,
+    suggestions: Vec<Suggestion>,
+}
+
+impl Finder {
+    fn function(&mut self, attrs: &[Attribute], sig: &Signature, block: &Block) {
+        if self.instrumented || is_instrumented(attrs) {
+            return;
+        }
+        let function = match &self.owner {
+            Some(owner) => format!("{}::{}", owner, sig.ident),
+            None => sig.ident.to_string(),
+        };
+        self.suggestions
+            .extend(suggest_function(function, sig, block));
+    }
+
+    /// Visit the items of an `impl` block or module, which are left out if the attributes instrument them.
+    fn nested(
+        &mut self,
+        attrs: &[Attribute],
+        owner: Option<String>,
+        visit: impl FnOnce(&mut Self),
+    ) {
+        let instrumented = self.instrumented;
+        let outer = std::mem::replace(&mut self.owner, owner);
+        self.instrumented |= is_instrumented(attrs);
+        visit(self);
+        self.owner = outer;
+        self.instrumented = instrumented;
+    }
+}
+
+impl<'ast> Visit<'ast> for Finder {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.function(&item.attrs, &item.sig, &item.block);
+        // Functions nested in the body belong to no `impl` block
+        self.nested(&[], None, |finder| syn::visit::visit_item_fn(finder, item));
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        self.function(&item.attrs, &item.sig, &item.block);
+        self.nested(&[], None, |finder| {
+            syn::visit::visit_impl_item_fn(finder, item)
+        });
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        let owner = match &*item.self_ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string()),
+            _ => None,
+        };
+        self.nested(&item.attrs, owner, |finder| {
+            syn::visit::visit_item_impl(finder, item)
+        });
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        let owner = self.owner.clone();
+        self.nested(&item.attrs, owner, |finder| {
+            syn::visit::visit_item_mod(finder, item)
+        });
+    }
+}
+
+/// Whether one of the attributes is an `axin` attribute.
+fn is_instrumented(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| path_ends_with(attr.path(), &["axin"]))
+}
+
+/// Suggest an attribute for one function, if its body matches any of the wrappers.
+pub fn suggest_function(function: String, sig: &Signature, block: &Block) -> Option<Suggestion> {
+    // Profiling and catching panics do not support async functions
+    let matches = match sig.asyncness {
+        Some(_) => match_log_pair(&block.stmts, &[]).into_iter().collect(),
+        None => match match_catch_unwind(&block.stmts) {
+            Some(catching) => vec![catching],
+            None => {
+                let timing = match_timing(&block.stmts);
+                let used = timing.as_ref().map_or(&[][..], |timing| &timing.stmts);
+                let logs = match_log_pair(&block.stmts, used);
+                logs.into_iter().chain(timing).collect::<Vec<_>>()
+            }
+        },
+    };
+    if matches.is_empty() {
+        return None;
+    }
+
+    let mut suggestion = Suggestion {
+        function,
+        args: Vec::new(),
+        notes: Vec::new(),
+    };
+    for matched in matches {
+        suggestion.args.extend(matched.args);
+        suggestion.notes.extend(matched.notes);
+    }
+    suggestion.args.sort_by_key(|arg| match arg {
+        Suggested::OnEnter(_) => 0,
+        Suggested::Decorator(_) => 1,
+        Suggested::Adapt => 2,
+        Suggested::OnExit(_) => 3,
+        Suggested::Profile { .. } => 4,
+    });
+    // Suggesting attributes the macro rejects would only get in the way
+    suggestion.check().ok()?;
+    Some(suggestion)
+}
+
+/// Match a timer started first and reported last, by a print or log macro or only when slower than a threshold.
+fn match_timing(stmts: &[Stmt]) -> Option<Match> {
+    let timer = timer_binding(stmts.first()?)?;
+    let (report, slow) = stmts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find_map(|(index, stmt)| Some((index, elapsed_report(stmt, &timer)?)))?;
+    // The timer must be used for the report only, and the report must come last but for the returned value
+    let others_use_timer = stmts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .any(|(index, stmt)| index != report && mentions(stmt.to_token_stream(), &timer));
+    let report_is_last = stmts[report + 1..]
+        .iter()
+        .all(|stmt| matches!(stmt, Stmt::Expr(_, None)));
+    if others_use_timer || !report_is_last {
+        return None;
+    }
+
+    let note = match &slow {
+        None => "report the timings from a `record` sink registered with `axin::set_profile_sinks`",
+        Some(_) => "report slow calls from a `slow` sink registered with `axin::set_profile_sinks`",
+    };
+    Some(Match {
+        args: vec![Suggested::Profile { slow }],
+        notes: vec![note.to_string()],
+        stmts: vec![0, report],
+    })
+}
+
+/// The name of the timer started by a statement like `let start = Instant::now();`.
+fn timer_binding(stmt: &Stmt) -> Option<Ident> {
+    let Stmt::Local(local) = stmt else {
+        return None;
+    };
+    let Pat::Ident(binding) = &local.pat else {
+        return None;
+    };
+    let init = local.init.as_ref()?;
+    match &*init.expr {
+        Expr::Call(call) if call.args.is_empty() && is_call_to(call, &["Instant", "now"]) => {
+            Some(binding.ident.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Whether a statement reports the elapsed time of the timer, with `Some(threshold)` if only slower calls are.
+fn elapsed_report(stmt: &Stmt, timer: &Ident) -> Option<Option<String>> {
+    let Stmt::Expr(Expr::If(condition), _) = stmt else {
+        let tokens = report_macro(stmt)?;
+        return mentions_elapsed(tokens, timer).then_some(None);
+    };
+    let Expr::Binary(comparison) = &*condition.cond else {
+        return None;
+    };
+    let is_elapsed = |expr: &Expr| match expr {
+        Expr::MethodCall(call) => {
+            call.method == "elapsed"
+                && call.args.is_empty()
+                && matches!(&*call.receiver, Expr::Path(path) if path.path.is_ident(timer))
+        }
+        _ => false,
+    };
+    let reports_only = condition.else_branch.is_none()
+        && condition
+            .then_branch
+            .stmts
+            .iter()
+            .all(|stmt| report_macro(stmt).is_some());
+    match comparison.op {
+        BinOp::Gt(_) | BinOp::Ge(_) if reports_only && is_elapsed(&comparison.left) => {
+            Some(Some(threshold(&comparison.right)))
+        }
+        _ => None,
+    }
+}
+
+/// Write a threshold as `profile` takes it: whole numbers of a unit as such, and other expressions as they are.
+fn threshold(expr: &Expr) -> String {
+    if let Expr::Call(call) = expr {
+        for (constructor, unit) in DURATION_UNITS {
+            if !is_call_to(call, &["Duration", *constructor]) {
+                continue;
+            }
+            if let [Expr::Lit(ExprLit {
+                lit: Lit::Int(amount),
+                ..
+            })] = call.args.iter().collect::<Vec<_>>()[..]
+            {
+                if amount.suffix().is_empty() {
+                    return format!("{}{}", amount.base10_digits(), unit);
+                }
+            }
+        }
+    }
+    render_tokens(expr.to_token_stream())
+}
+
+/// Match a body consisting of a single call of `catch_unwind` on a closure.
+fn match_catch_unwind(stmts: &[Stmt]) -> Option<Match> {
+    let [Stmt::Expr(Expr::Call(call), None)] = stmts else {
+        return None;
+    };
+    if !is_call_to(call, &["catch_unwind"]) || call.args.len() != 1 {
+        return None;
+    }
+    let closure = match &call.args[0] {
+        Expr::Call(wrapper)
+            if is_call_to(wrapper, &["AssertUnwindSafe"]) && wrapper.args.len() == 1 =>
+        {
+            &wrapper.args[0]
+        }
+        closure => closure,
+    };
+    match closure {
+        Expr::Closure(closure) if closure.inputs.is_empty() => {}
+        _ => return None,
+    }
+
+    Some(Match {
+        args: vec![
+            Suggested::Decorator(parse_quote!(catch_panics)),
+            Suggested::Adapt,
+        ],
+        notes: vec![
+            "move the body of the closure into the function, returning its value in `Ok`"
+                .to_string(),
+            "write `catch_panics<F: FnOnce() -> std::thread::Result<T>, T>(f: F)`, returning \
+             `std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).and_then(|result| result)`"
+                .to_string(),
+        ],
+        stmts: vec![0],
+    })
+}
+
+/// Match a print or log macro as the first statement not used by other matchers, and one as the last but for the
+/// returned value, mentioning none of the body's local bindings.
+fn match_log_pair(stmts: &[Stmt], used: &[usize]) -> Option<Match> {
+    let remaining: Vec<usize> = (0..stmts.len())
+        .filter(|index| !used.contains(index))
+        .filter(|&index| !matches!(stmts[index], Stmt::Expr(_, None)))
+        .collect();
+    let (&enter, &exit) = (remaining.first()?, remaining.last()?);
+    let returned_after_exit = stmts[exit + 1..].iter().enumerate().all(|(offset, stmt)| {
+        used.contains(&(exit + 1 + offset)) || matches!(stmt, Stmt::Expr(_, None))
+    });
+    let locals: Vec<Ident> = stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Local(local) => Some(local.pat.to_token_stream()),
+            _ => None,
+        })
+        .flat_map(idents)
+        .collect();
+    let logs = |index: usize| {
+        report_macro(&stmts[index])
+            .is_some_and(|tokens| !locals.iter().any(|local| mentions(tokens.clone(), local)))
+    };
+    if enter == exit || !returned_after_exit || !logs(enter) || !logs(exit) {
+        return None;
+    }
+
+    Some(Match {
+        args: vec![
+            Suggested::OnEnter(parse_quote!(log_enter(&__axin))),
+            Suggested::OnExit(parse_quote!(log_exit(&__axin))),
+        ],
+        notes: vec![
+            "write `log_enter` and `log_exit` taking the call context, an `&axin::AxinCtx` naming the function, and \
+             pass them the parameters the messages mention"
+                .to_string(),
+        ],
+        stmts: vec![enter, exit],
+    })
+}
+
+/// The arguments of a statement calling a print or log macro.
+fn report_macro(stmt: &Stmt) -> Option<TokenStream> {
+    let mac = match stmt {
+        Stmt::Macro(stmt) => &stmt.mac,
+        Stmt::Expr(Expr::Macro(expr), Some(_)) => &expr.mac,
+        _ => return None,
+    };
+    let segments: Vec<String> = mac
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let known = match &segments[..] {
+        [name] => PRINT_MACROS.contains(&&**name) || LOG_MACROS.contains(&&**name),
+        [krate, name] => (krate == "log" || krate == "tracing") && LOG_MACROS.contains(&&**name),
+        _ => false,
+    };
+    known.then(|| mac.tokens.clone())
+}
+
+/// Whether a call calls a function whose path ends with the given segments.
+fn is_call_to(call: &ExprCall, segments: &[&str]) -> bool {
+    matches!(&*call.func, Expr::Path(path) if path_ends_with(&path.path, segments))
+}
+
+fn path_ends_with(path: &Path, segments: &[&str]) -> bool {
+    path.segments.len() >= segments.len()
+        && path
+            .segments
+            .iter()
+            .rev()
+            .zip(segments.iter().rev())
+            .all(|(segment, name)| segment.ident == name)
+}
+
+/// The identifiers in some tokens, at any depth.
+fn idents(tokens: TokenStream) -> Vec<Ident> {
+    tokens
+        .into_iter()
+        .flat_map(|tree| match tree {
+            TokenTree::Ident(ident) => vec![ident],
+            TokenTree::Group(group) => idents(group.stream()),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    idents(tokens).contains(ident)
+}
+
+/// Whether some tokens call `elapsed` on the timer, at any depth.
+fn mentions_elapsed(tokens: TokenStream, timer: &Ident) -> bool {
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    trees.iter().enumerate().any(|(index, tree)| match tree {
+        TokenTree::Group(group) => mentions_elapsed(group.stream(), timer),
+        TokenTree::Ident(ident) if ident == timer => matches!(
+            &trees[index + 1..],
+            [TokenTree::Punct(dot), TokenTree::Ident(method), ..]
+                if dot.as_char() == '.' && method == "elapsed"
+        ),
+        _ => false,
+    })
+}
+
+/// Render tokens the way they would be written by hand, rather than with spaces between all of them.
+fn render_tokens(tokens: TokenStream) -> String {
+    let mut rendered = String::new();
+    render_into(tokens, &mut rendered);
+    rendered
+}
+
+fn render_into(tokens: TokenStream, rendered: &mut String) {
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::None => ("", ""),
+                };
+                rendered.push_str(open);
+                render_into(group.stream(), rendered);
+                rendered.push_str(close);
+            }
+            TokenTree::Punct(punct) => {
+                rendered.push(punct.as_char());
+                if punct.as_char() == ',' {
+                    rendered.push(' ');
+                }
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if rendered.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '"') {
+                    rendered.push(' ');
+                }
+                rendered.push_str(&tree.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The attributes and notes suggested for a source file, one line per function.
+    fn suggest(source: &str) -> Vec<String> {
+        let file = syn::parse_file(source).unwrap();
+        suggest_file(&file)
+            .into_iter()
+            .map(|suggestion| format!("{}: {}", suggestion.function, suggestion.attribute()))
+            .collect()
+    }
+
+    #[test]
+    fn timers_reported_at_the_end() {
+        let suggestions = suggest(
+            r#"
+            fn load(id: u32) -> Data {
+                let start = std::time::Instant::now();
+                let data = fetch(id);
+                println!("load took {:?}", start.elapsed());
+                data
+            }
+
+            fn save(data: &Data) {
+                let timer = Instant::now();
+                write(data);
+                log::debug!("saved in {}ms", timer.elapsed().as_millis());
+            }
+            "#,
+        );
+        assert_eq!(
+            suggestions,
+            ["load: #[axin(profile)]", "save: #[axin(profile)]"]
+        );
+    }
+
+    #[test]
+    fn timers_reported_when_slow() {
+        let suggestions = suggest(
+            r#"
+            fn query() {
+                let start = Instant::now();
+                run();
+                if start.elapsed() > Duration::from_millis(250) {
+                    warn!("slow query");
+                }
+            }
+
+            fn flush(&self) {
+                let start = Instant::now();
+                self.write();
+                if start.elapsed() >= self.limit * 2 {
+                    eprintln!("slow flush: {:?}", start.elapsed());
+                }
+            }
+            "#,
+        );
+        assert_eq!(
+            suggestions,
+            [
+                "query: #[axin(profile(slow = 250ms))]",
+                "flush: #[axin(profile(slow = self.limit*2))]"
+            ]
+        );
+    }
+
+    #[test]
+    fn timers_used_for_more_than_the_report() {
+        let suggestions = suggest(
+            r#"
+            fn retried() {
+                let start = Instant::now();
+                while start.elapsed() < TIMEOUT {
+                    attempt();
+                }
+                println!("gave up after {:?}", start.elapsed());
+            }
+
+            fn early() {
+                let start = Instant::now();
+                println!("{:?}", start.elapsed());
+                finish();
+            }
+
+            fn otherwise() {
+                let start = Instant::now();
+                run();
+                if start.elapsed() > LIMIT {
+                    warn!("slow");
+                } else {
+                    retry();
+                }
+            }
+            "#,
+        );
+        assert!(suggestions.is_empty(), "{:?}", suggestions);
+    }
+
+    #[test]
+    fn panics_caught_around_the_body() {
+        let file = syn::parse_file(
+            r#"
+            fn guarded() -> std::thread::Result<u32> {
+                std::panic::catch_unwind(AssertUnwindSafe(|| compute()))
+            }
+
+            fn plain() -> std::thread::Result<()> {
+                catch_unwind(|| { run(); })
+            }
+            "#,
+        )
+        .unwrap();
+        let suggestions = suggest_file(&file);
+        let attributes: Vec<_> = suggestions.iter().map(Suggestion::attribute).collect();
+        assert_eq!(
+            attributes,
+            [
+                "#[axin(decorator(catch_panics), adapt)]",
+                "#[axin(decorator(catch_panics), adapt)]"
+            ]
+        );
+        assert_eq!(suggestions[0].notes.len(), 2);
+
+        // Closures taking arguments, and bodies doing more than catching panics, are left alone
+        assert!(suggest(
+            r#"
+            fn with_argument() -> std::thread::Result<()> {
+                catch_unwind(apply)
+            }
+
+            fn more() -> std::thread::Result<()> {
+                setup();
+                catch_unwind(|| run())
+            }
+            "#
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn entry_and_exit_logs() {
+        let suggestions = suggest(
+            r#"
+            fn handle(request: Request) -> Response {
+                tracing::info!("handling {}", request.id);
+                let response = route(request);
+                info!("handled");
+                response
+            }
+
+            fn mentions_locals() {
+                println!("start");
+                let count = work();
+                println!("done: {}", count);
+            }
+
+            fn only_entry() {
+                println!("start");
+                work();
+            }
+            "#,
+        );
+        assert_eq!(
+            suggestions,
+            ["handle: #[axin(on_enter(log_enter(&__axin)), on_exit(log_exit(&__axin)))]"]
+        );
+    }
+
+    #[test]
+    fn wrappers_combined() {
+        let suggestions = suggest(
+            r#"
+            impl Store {
+                fn get(&self, key: &str) -> Option<Value> {
+                    let start = Instant::now();
+                    debug!("get {}", key);
+                    self.lookup(key);
+                    debug!("got {}", key);
+                    if start.elapsed() > Duration::from_secs(1) {
+                        warn!("slow get");
+                    }
+                    self.last()
+                }
+            }
+            "#,
+        );
+        assert_eq!(
+            suggestions,
+            [
+                "Store::get: #[axin(on_enter(log_enter(&__axin)), on_exit(log_exit(&__axin)), \
+                 profile(slow = 1s))]"
+            ]
+        );
+    }
+
+    #[test]
+    fn async_functions_only_get_logs() {
+        let suggestions = suggest(
+            r#"
+            async fn fetch() {
+                let start = Instant::now();
+                info!("fetching");
+                download().await;
+                info!("fetched");
+                println!("{:?}", start.elapsed());
+            }
+
+            async fn logged() {
+                info!("fetching");
+                download().await;
+                info!("fetched");
+            }
+            "#,
+        );
+        assert_eq!(
+            suggestions,
+            ["logged: #[axin(on_enter(log_enter(&__axin)), on_exit(log_exit(&__axin)))]"]
+        );
+    }
+
+    #[test]
+    fn instrumented_functions_are_skipped() {
+        let suggestions = suggest(
+            r#"
+            #[axin(on_enter(trace))]
+            fn instrumented() {
+                let start = Instant::now();
+                println!("{:?}", start.elapsed());
+            }
+
+            #[axin::axin(on_enter(trace))]
+            impl Store {
+                fn method() {
+                    let start = Instant::now();
+                    println!("{:?}", start.elapsed());
+                }
+            }
+
+            mod nested {
+                fn timed() {
+                    let start = Instant::now();
+                    println!("{:?}", start.elapsed());
+                }
+            }
+            "#,
+        );
+        assert_eq!(suggestions, ["timed: #[axin(profile)]"]);
+    }
+}
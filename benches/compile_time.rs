@@ -0,0 +1,184 @@
+//! Compile-time cost of the generated code.
+//!
+//! Generates crates with `N` functions, left plain or instrumented with one of the strategies below, and measures how
+//! long `cargo build` takes to rebuild each of them, best of a few runs. The crates share a target directory under
+//! `target/compile-time`, so dependencies are only built once, before any measurement. The results are printed and
+//! written to `target/compile-time/PERFORMANCE.md`.
+//!
+//! Run with `cargo bench --bench compile_time`. The function counts default to 100 and 500, and can be set as a
+//! comma-separated list in `AXIN_BENCH_FNS`.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Rebuilds measured per crate, of which the fastest counts.
+const RUNS: usize = 3;
+
+/// The strategies compared: a name, and the attribute put on every function, if any.
+const STRATEGIES: &[(&str, Option<&str>)] = &[
+    ("baseline", None),
+    ("hooks", Some("on_enter(hook), on_exit(hook)")),
+    ("decorator", Some("decorator(decorator)")),
+    (
+        "full",
+        Some(
+            "count_calls(CALLS), on_enter(context_hook(&__axin)), decorator(decorator), \
+             slow_log(Duration::from_millis(1), slow_hook), on_error(error_hook), \
+             on_exit(hook, when = result.is_ok())",
+        ),
+    ),
+    (
+        "minimal",
+        Some("minimal, count_calls(CALLS), on_enter(hook)"),
+    ),
+    ("adapt", Some("decorator(adapted_decorator), adapt")),
+];
+
+/// Items the instrumentation of every strategy refers to.
+const PRELUDE: &str = r#"use axin::{axin, AxinCtx, CallCounter};
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn hook() {}
+
+fn context_hook(_: &AxinCtx) {}
+
+fn error_hook<E>(_: &E) {}
+
+fn slow_hook(_: Duration) {}
+
+fn decorator<F: FnOnce(u64, u64) -> R, R>(f: F, a: u64, b: u64) -> R {
+    f(a, b)
+}
+
+fn adapted_decorator<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+"#;
+
+fn main() {
+    let counts: Vec<usize> = match std::env::var("AXIN_BENCH_FNS") {
+        Ok(counts) => counts
+            .split(',')
+            .map(|count| count.trim().parse().expect("invalid function count"))
+            .collect(),
+        Err(_) => vec![100, 500],
+    };
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let bench_dir = root.join("target").join("compile-time");
+    let target_dir = bench_dir.join("target");
+
+    let mut results = Vec::new();
+    for &count in &counts {
+        for &(name, attribute) in STRATEGIES {
+            let crate_dir = bench_dir.join(format!("{}-{}", name, count));
+            generate_crate(&crate_dir, root, count, attribute);
+            // Build once without measuring, for the dependencies
+            build(&crate_dir, &target_dir);
+            let best = (0..RUNS)
+                .map(|_| {
+                    touch(&crate_dir.join("src").join("lib.rs"));
+                    build(&crate_dir, &target_dir)
+                })
+                .min()
+                .unwrap();
+            println!("{:>10} x {:<5} {:>8.2?}", name, count, best);
+            results.push((name, count, best));
+        }
+    }
+
+    let summary = summarize(&results);
+    let summary_path = bench_dir.join("PERFORMANCE.md");
+    fs::write(&summary_path, &summary).expect("failed to write the summary");
+    println!("\n{}", summary);
+    println!("written to {}", summary_path.display());
+}
+
+/// Write a crate with `count` functions of two parameters, each with the attribute if there is one.
+fn generate_crate(dir: &Path, axin_dir: &Path, count: usize, attribute: Option<&str>) {
+    fs::create_dir_all(dir.join("src")).expect("failed to create the crate");
+    let manifest = format!(
+        "[package]\nname = \"axin-compile-time\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n\
+         [dependencies]\naxin = {{ path = {:?} }}\n\n[workspace]\n",
+        axin_dir
+    );
+    fs::write(dir.join("Cargo.toml"), manifest).expect("failed to write the manifest");
+
+    let mut source = String::from("#![allow(dead_code, unused_imports)]\n\n");
+    source.push_str(PRELUDE);
+    for index in 0..count {
+        if let Some(attribute) = attribute {
+            writeln!(source, "\n#[axin({})]", attribute).unwrap();
+        } else {
+            source.push('\n');
+        }
+        writeln!(
+            source,
+            "pub fn function_{index}(a: u64, b: u64) -> Result<u64, String> {{\n    \
+             let sum = a.checked_add(b).ok_or(\"overflow\")?;\n    \
+             Ok(sum.wrapping_mul({index}))\n}}",
+        )
+        .unwrap();
+    }
+    fs::write(dir.join("src").join("lib.rs"), source).expect("failed to write the source");
+}
+
+/// Build the crate in `dir`, returning how long it took.
+fn build(dir: &Path, target_dir: &Path) -> Duration {
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--quiet"])
+        .current_dir(dir)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .env_remove("RUSTFLAGS")
+        .output()
+        .expect("failed to run cargo");
+    let elapsed = start.elapsed();
+    assert!(
+        output.status.success(),
+        "building {} failed:\n{}",
+        dir.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    elapsed
+}
+
+/// Mark a file as modified, so that the next build compiles its crate again.
+fn touch(path: &Path) {
+    let source = fs::read(path).expect("failed to read the source");
+    fs::write(path, source).expect("failed to write the source");
+}
+
+/// Render the results as a Markdown table, with each strategy's time relative to the baseline of the same size.
+fn summarize(results: &[(&str, usize, Duration)]) -> String {
+    let mut summary = String::from(
+        "# Compile-time overhead\n\n\
+         Time to rebuild a crate of `N` functions, best of several runs.\n\n\
+         | Strategy | N | Build time | vs. baseline |\n\
+         |----------|--:|-----------:|-------------:|\n",
+    );
+    for &(name, count, time) in results {
+        let baseline = results
+            .iter()
+            .find(|&&(other, other_count, _)| other == "baseline" && other_count == count)
+            .map(|&(_, _, baseline)| baseline)
+            .unwrap();
+        writeln!(
+            summary,
+            "| {} | {} | {:.2?} | {:.2}x |",
+            name,
+            count,
+            time,
+            time.as_secs_f64() / baseline.as_secs_f64()
+        )
+        .unwrap();
+    }
+    summary
+}
@@ -0,0 +1,84 @@
+//! Overhead of `minimal` mode on a tiny trait method.
+//!
+//! Compares a plain `Iterator::next`, one counting its calls by hand, and one counting its calls with
+//! `#[axin(minimal, count_calls(...))]`. The last two should be indistinguishable: minimal mode adds nothing but the
+//! counter itself.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::{axin, CallCounter};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const LEN: u32 = 1024;
+
+struct Plain(u32);
+
+impl Iterator for Plain {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.0 < LEN {
+            self.0 += 1;
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+}
+
+static HAND_CALLS: CallCounter = CallCounter::new();
+
+struct HandCounted(u32);
+
+impl Iterator for HandCounted {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        HAND_CALLS.increment();
+        if self.0 < LEN {
+            self.0 += 1;
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+}
+
+static NEXT_CALLS: CallCounter = CallCounter::new();
+
+struct Counted(u32);
+
+impl Iterator for Counted {
+    type Item = u32;
+
+    #[axin(minimal, count_calls(NEXT_CALLS))]
+    fn next(&mut self) -> Option<u32> {
+        if self.0 < LEN {
+            self.0 += 1;
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+}
+
+fn drain(iter: impl Iterator<Item = u32>) {
+    for item in iter {
+        black_box(item);
+    }
+}
+
+fn bench_next(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterator_next");
+    group.bench_function("plain", |b| b.iter(|| drain(black_box(Plain(0)))));
+    group.bench_function("hand_counted", |b| {
+        b.iter(|| drain(black_box(HandCounted(0))))
+    });
+    group.bench_function("minimal_count_calls", |b| {
+        b.iter(|| drain(black_box(Counted(0))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_next);
+criterion_main!(benches);
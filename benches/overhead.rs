@@ -0,0 +1,152 @@
+//! Runtime overhead of the generation strategies.
+//!
+//! Every strategy instruments the same two functions: a trivial one, where any overhead shows, and one doing about a
+//! microsecond of work, where it should vanish in the noise. The uninstrumented functions are the baseline. The hooks
+//! and decorators do as little as possible, so that the numbers measure the generated code rather than the hooks.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::{axin, AxinCtx, CallCounter};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Iterations of [`work`], taking about a microsecond.
+const WORK_ROUNDS: u64 = 640;
+
+static HOOK_CALLS: AtomicU64 = AtomicU64::new(0);
+static CALLS: CallCounter = CallCounter::new();
+
+fn hook() {
+    HOOK_CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn context_hook(ctx: &AxinCtx) {
+    black_box(ctx.call_id);
+}
+
+fn error_hook<E>(error: &E) {
+    black_box(error);
+}
+
+fn slow_hook(elapsed: Duration) {
+    black_box(elapsed);
+}
+
+fn decorator<F: FnOnce(u64) -> R, R>(f: F, x: u64) -> R {
+    f(black_box(x))
+}
+
+fn adapted_decorator<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+fn trivial(x: u64) -> u64 {
+    x.wrapping_add(1)
+}
+
+fn work(x: u64) -> u64 {
+    (0..WORK_ROUNDS).fold(x, |acc, round| {
+        black_box(acc.wrapping_mul(6364136223846793005).wrapping_add(round))
+    })
+}
+
+/// Define a trivial and a working variant of a strategy, instrumented with the given attribute arguments.
+macro_rules! strategy {
+    ($trivial:ident, $work:ident, $($args:tt)*) => {
+        #[axin($($args)*)]
+        fn $trivial(x: u64) -> Result<u64, ()> {
+            Ok(trivial(x))
+        }
+
+        #[axin($($args)*)]
+        fn $work(x: u64) -> Result<u64, ()> {
+            Ok(work(x))
+        }
+    };
+}
+
+fn baseline_trivial(x: u64) -> Result<u64, ()> {
+    Ok(trivial(x))
+}
+
+fn baseline_work(x: u64) -> Result<u64, ()> {
+    Ok(work(x))
+}
+
+strategy!(hooks_trivial, hooks_work, on_enter(hook), on_exit(hook));
+strategy!(decorator_trivial, decorator_work, decorator(decorator));
+
+// Written out, since `__axin` and `result` would not be visible to the arguments given through `strategy!`
+#[axin(
+    count_calls(CALLS),
+    on_enter(context_hook(&__axin)),
+    decorator(decorator),
+    slow_log(Duration::from_millis(1), slow_hook),
+    on_error(error_hook),
+    on_exit(hook, when = result.is_ok())
+)]
+fn full_trivial(x: u64) -> Result<u64, ()> {
+    Ok(trivial(x))
+}
+
+#[axin(
+    count_calls(CALLS),
+    on_enter(context_hook(&__axin)),
+    decorator(decorator),
+    slow_log(Duration::from_millis(1), slow_hook),
+    on_error(error_hook),
+    on_exit(hook, when = result.is_ok())
+)]
+fn full_work(x: u64) -> Result<u64, ()> {
+    Ok(work(x))
+}
+
+strategy!(
+    minimal_trivial,
+    minimal_work,
+    minimal,
+    count_calls(CALLS),
+    on_enter(hook)
+);
+strategy!(
+    adapt_trivial,
+    adapt_work,
+    decorator(adapted_decorator),
+    adapt
+);
+
+fn bench_strategies(c: &mut Criterion) {
+    type Strategy = (
+        &'static str,
+        fn(u64) -> Result<u64, ()>,
+        fn(u64) -> Result<u64, ()>,
+    );
+    let strategies: [Strategy; 6] = [
+        ("baseline", baseline_trivial, baseline_work),
+        ("hooks", hooks_trivial, hooks_work),
+        ("decorator", decorator_trivial, decorator_work),
+        ("full", full_trivial, full_work),
+        ("minimal", minimal_trivial, minimal_work),
+        ("adapt", adapt_trivial, adapt_work),
+    ];
+
+    let mut group = c.benchmark_group("trivial");
+    for (name, trivial, _) in strategies {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| trivial(black_box(1)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("work_1us");
+    for (name, _, work) in strategies {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| work(black_box(1)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_strategies);
+criterion_main!(benches);
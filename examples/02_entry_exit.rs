@@ -1,5 +1,7 @@
 //! Entry and exit hooks allow you to execute custom functions when entering or exiting a target function.
 
+#![cfg_attr(feature = "disable", allow(unused))]
+
 use axin::axin;
 
 fn setup() {
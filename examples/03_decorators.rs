@@ -1,6 +1,8 @@
 //! Decorators allow you to modify the behavior of functions by wrapping them with additional functionality. Unlike
 //! decorators in some other languages, Axin decorators are called every time the function is executed, not just once.
 
+#![cfg_attr(feature = "disable", allow(unused))]
+
 use axin::axin;
 
 fn timing_decorator<F>(func: F) -> i32
@@ -1,6 +1,8 @@
 //! Prologues, entry/exit hooks, and decorators can be combined to enhance function behavior in Rust. As far, only one
 //! can be specified for each group.
 
+#![cfg_attr(feature = "disable", allow(unused))]
+
 use axin::axin;
 
 fn initialize() {
@@ -1,5 +1,7 @@
 //! Simulating a real-world scenario using Axin.
 
+#![cfg_attr(feature = "disable", allow(unused))]
+
 use axin::axin;
 
 use std::time::Instant;
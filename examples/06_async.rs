@@ -0,0 +1,80 @@
+//! Async functions are instrumented like any other: hooks run around the awaited body, and decorators are passed a
+//! callable returning the body's future.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::axin;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+/// A minimal executor polling a future to completion on the current thread, so that this example needs no runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: the vtable functions ignore the data pointer, so any pointer is valid for them.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn request_started() {
+    println!("🌐 Request started");
+}
+
+fn request_finished() {
+    println!("🌐 Request finished");
+}
+
+fn request_failed(error: &String) {
+    println!("❌ Request failed: {}", error);
+}
+
+async fn traced<F: FnOnce() -> Fut, Fut: Future>(f: F) -> Fut::Output {
+    println!("📋 Decorator: before awaiting the body");
+    let output = f().await;
+    println!("📋 Decorator: after awaiting the body");
+    output
+}
+
+async fn fetch_from_database(id: u32) -> Option<String> {
+    (id == 1).then(|| "Alice".to_string())
+}
+
+#[axin(on_enter(request_started), on_exit(request_finished))]
+async fn fetch_user(id: u32) -> Option<String> {
+    println!("👤 Looking up user {}", id);
+    fetch_from_database(id).await
+}
+
+#[axin(decorator(traced), adapt, on_error(request_failed))]
+async fn rename_user(id: u32, name: &str) -> Result<String, String> {
+    let old = fetch_from_database(id)
+        .await
+        .ok_or(format!("no user {}", id))?;
+    println!("✏️ Renaming {} to {}", old, name);
+    Ok(name.to_string())
+}
+
+fn main() {
+    println!("=== Async Functions Demo ===");
+
+    println!("\n--- Async function with hooks ---");
+    let user = block_on(fetch_user(1));
+    println!("Result: {:?}", user);
+
+    println!("\n--- Async decorator and error hook ---");
+    let renamed = block_on(rename_user(1, "Bob"));
+    println!("Result: {:?}", renamed);
+    let missing = block_on(rename_user(2, "Carol"));
+    println!("Result: {:?}", missing);
+}
@@ -0,0 +1,78 @@
+//! Methods can be instrumented one by one, or all methods of an `impl` block at once. Methods taking `self` need
+//! `adapt`, `minimal` or `raw_body`, since the receiver can only be captured rather than passed to a decorator.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::{axin, CallCounter};
+
+fn audit(action: &str) {
+    println!("📝 Audit: {}", action);
+}
+
+fn transaction<F: FnOnce() -> R, R>(f: F) -> R {
+    println!("🔒 Transaction: begin");
+    let result = f();
+    println!("🔒 Transaction: commit");
+    result
+}
+
+static BALANCE_READS: CallCounter = CallCounter::new();
+
+struct Account {
+    owner: String,
+    balance: u64,
+}
+
+// Every method of the block gets the entry hook, except the skipped ones with attributes of their own
+#[axin(on_enter(audit("account method")), adapt, skip(balance, withdraw))]
+impl Account {
+    fn open(owner: &str) -> Self {
+        println!("🏦 Opening an account for {}", owner);
+        Account {
+            owner: owner.to_string(),
+            balance: 0,
+        }
+    }
+
+    #[axin(decorator(transaction), adapt)]
+    fn deposit(&mut self, amount: u64) -> u64 {
+        println!("💰 Depositing {} for {}", amount, self.owner);
+        self.balance += amount;
+        self.balance
+    }
+
+    // `minimal` emits the body unchanged, so it suits tiny, hot methods
+    #[axin(minimal, count_calls(BALANCE_READS))]
+    fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    // `raw_body` keeps the body as written, early returns included, and runs the exit hook however it returns
+    #[axin(raw_body, on_exit(audit("withdrawal attempted")))]
+    fn withdraw(&mut self, amount: u64) -> Result<u64, String> {
+        if amount > self.balance {
+            return Err(format!("insufficient funds for {}", amount));
+        }
+        self.balance -= amount;
+        Ok(self.balance)
+    }
+}
+
+fn main() {
+    println!("=== Methods Demo ===");
+
+    println!("\n--- Associated function ---");
+    let mut account = Account::open("Alice");
+
+    println!("\n--- Method with a decorator ---");
+    println!("Balance: {}", account.deposit(100));
+
+    println!("\n--- Minimal method ---");
+    println!("Balance: {}", account.balance());
+    println!("Balance: {}", account.balance());
+    println!("Balance reads: {}", BALANCE_READS.get());
+
+    println!("\n--- Raw body method ---");
+    println!("Result: {:?}", account.withdraw(30));
+    println!("Result: {:?}", account.withdraw(500));
+}
@@ -0,0 +1,121 @@
+//! Errors can be observed with error hooks and conditional exit hooks, and handled with decorators such as retries or
+//! panic guards. Hooks may fail themselves, and the attribute decides what happens with their errors.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::axin;
+use std::cell::Cell;
+use std::fmt::Display;
+
+fn report<E: Display>(operation: &str, error: &E) {
+    println!("❌ {} failed: {}", operation, error);
+}
+
+fn alert() {
+    println!("🚨 Alert: payment rejected");
+}
+
+/// A decorator retrying the function up to three times while it fails.
+fn retry<F: Fn() -> Result<T, E>, T, E: Display>(f: F) -> Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(error) if attempt < 3 => {
+                println!("🔁 Attempt {} failed: {}, retrying", attempt, error);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A decorator turning a panic into a default value.
+fn catch_panic<F: FnOnce() -> T, T: Default>(f: F) -> T {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        println!("🛡️ Caught a panic, returning the default");
+        T::default()
+    })
+}
+
+fn open_audit_log() -> Result<(), String> {
+    Err("audit log unavailable".to_string())
+}
+
+fn log_hook_error<E: Display>(error: &E) {
+    println!("⚠️ Hook failed: {}", error);
+}
+
+#[axin(on_error(report("parse")))]
+fn parse_amount(input: &str) -> Result<u32, std::num::ParseIntError> {
+    input.trim().parse()
+}
+
+#[axin(on_exit(alert, when = result.is_err()))]
+fn charge(amount: u32) -> Result<u32, String> {
+    if amount > 1000 {
+        return Err(format!("{} exceeds the limit", amount));
+    }
+    Ok(amount)
+}
+
+thread_local! {
+    static CONNECTION_ATTEMPTS: Cell<u32> = const { Cell::new(0) };
+}
+
+#[axin(decorator(retry), adapt)]
+fn connect() -> Result<&'static str, String> {
+    let attempt = CONNECTION_ATTEMPTS.with(|attempts| {
+        attempts.set(attempts.get() + 1);
+        attempts.get()
+    });
+    if attempt < 3 {
+        return Err("connection refused".to_string());
+    }
+    Ok("connected")
+}
+
+#[axin(decorator(catch_panic), adapt)]
+fn divide(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        panic!("division by zero");
+    }
+    a / b
+}
+
+#[axin(on_enter(open_audit_log), hook_errors = "log", hook_error_sink = log_hook_error)]
+fn transfer(amount: u32) -> u32 {
+    println!("💸 Transferring {}", amount);
+    amount
+}
+
+#[axin(on_enter(open_audit_log), hook_errors = "propagate")]
+fn strict_transfer(amount: u32) -> Result<u32, String> {
+    println!("💸 Transferring {}", amount);
+    Ok(amount)
+}
+
+fn main() {
+    // Keep the panic message of `divide` out of the output
+    std::panic::set_hook(Box::new(|_| {}));
+
+    println!("=== Error Handling Demo ===");
+
+    println!("\n--- Error hook ---");
+    println!("Result: {:?}", parse_amount(" 42 "));
+    println!("Result: {:?}", parse_amount("forty-two").is_err());
+
+    println!("\n--- Conditional exit hook ---");
+    println!("Result: {:?}", charge(10));
+    println!("Result: {:?}", charge(5000));
+
+    println!("\n--- Retry decorator ---");
+    println!("Result: {:?}", connect());
+
+    println!("\n--- Panic guard decorator ---");
+    println!("Result: {}", divide(10, 2));
+    println!("Result: {}", divide(1, 0));
+
+    println!("\n--- Failing hooks ---");
+    println!("Result: {}", transfer(5));
+    println!("Result: {:?}", strict_transfer(5));
+}
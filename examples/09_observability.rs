@@ -0,0 +1,75 @@
+//! Hooks can describe the call they observe: the call context names the function and numbers its calls, counters and
+//! compact event ids feed metrics and binary logs, and slow calls are reported with their duration.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::{axin, AxinCtx, CallCounter};
+use std::time::Duration;
+
+fn install_logger() {
+    println!("🔧 Logger installed");
+}
+
+fn trace(ctx: &AxinCtx) {
+    println!(
+        "🔍 Call #{} to {} in {}",
+        ctx.call_id, ctx.fn_name, ctx.module_path
+    );
+}
+
+fn trace_event(id: u16) {
+    println!("📦 Event {:#06x}", id);
+}
+
+fn report_slow(name: &str, elapsed: Duration) {
+    // The duration differs from run to run, so only whether it was measured is shown
+    println!("🐢 {} was slow: {}", name, elapsed > Duration::ZERO);
+}
+
+fn log_details(details: String) {
+    println!("📋 {}", details);
+}
+
+static LOOKUPS: CallCounter = CallCounter::new();
+
+#[axin(init(install_logger()), on_enter(trace(&__axin)))]
+fn handle_request(path: &str) -> usize {
+    path.len()
+}
+
+#[axin(count_calls(LOOKUPS), event_id = auto, on_enter(trace_event(__axin_event_id)))]
+fn lookup(key: u32) -> Option<u32> {
+    key.checked_mul(2)
+}
+
+#[axin(slow_log(Duration::ZERO, report_slow("compress")))]
+fn compress(data: &[u8]) -> usize {
+    std::thread::sleep(Duration::from_millis(1));
+    data.len() / 2
+}
+
+// The details are only formatted if the caller asks for them
+#[axin(on_enter(log_details(format!("processing {:?}", items)), when = VERBOSE))]
+fn process<const VERBOSE: bool>(items: &[u32]) -> u32 {
+    items.iter().sum()
+}
+
+fn main() {
+    println!("=== Observability Demo ===");
+
+    println!("\n--- Call context and one-time initialization ---");
+    handle_request("/users");
+    handle_request("/orders");
+
+    println!("\n--- Call counting and event ids ---");
+    lookup(1);
+    lookup(2);
+    println!("Lookups: {}", LOOKUPS.get());
+
+    println!("\n--- Slow call logging ---");
+    println!("Result: {}", compress(&[1, 2, 3, 4]));
+
+    println!("\n--- Entry hook on request ---");
+    println!("Result: {}", process::<true>(&[1, 2]));
+    println!("Result: {}", process::<false>(&[3, 4]));
+}
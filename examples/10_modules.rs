@@ -0,0 +1,60 @@
+//! An attribute on a module instruments every free function directly inside it, and decorators can be chosen on every
+//! call from a list.
+
+#![cfg_attr(feature = "disable", allow(unused))]
+
+use axin::axin;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+fn trace_handler(ctx: &axin::AxinCtx) {
+    println!("🔍 Handling {}", ctx.fn_name);
+}
+
+static DEBUGGING: AtomicBool = AtomicBool::new(false);
+
+fn fast<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    f(value)
+}
+
+fn debug<F: FnOnce(T) -> R, T: std::fmt::Debug, R: std::fmt::Debug>(f: F, value: T) -> R {
+    println!("🐞 Called with {:?}", value);
+    let result = f(value);
+    println!("🐞 Returned {:?}", result);
+    result
+}
+
+#[axin(on_enter(trace_handler(&__axin)), skip(health))]
+mod handlers {
+    use super::trace_handler;
+
+    pub fn list_users() -> Vec<&'static str> {
+        vec!["alice", "bob"]
+    }
+
+    pub fn create_user(name: &str) -> String {
+        format!("created {}", name)
+    }
+
+    pub fn health() -> &'static str {
+        "ok"
+    }
+}
+
+#[axin(decorator_select(DEBUGGING.load(Ordering::Relaxed) as usize, [fast, debug]))]
+fn square(x: i64) -> i64 {
+    x * x
+}
+
+fn main() {
+    println!("=== Modules and Decorator Selection Demo ===");
+
+    println!("\n--- Instrumented module ---");
+    println!("Result: {:?}", handlers::list_users());
+    println!("Result: {}", handlers::create_user("carol"));
+    println!("Result: {}", handlers::health());
+
+    println!("\n--- Decorator selected on every call ---");
+    println!("Result: {}", square(3));
+    DEBUGGING.store(true, Ordering::Relaxed);
+    println!("Result: {}", square(4));
+}
@@ -0,0 +1,44 @@
+//! Call counters for the `count_calls` parameter.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// A thread-safe counter of function calls, meant to be declared as a `static` and passed to `count_calls(...)`.
+///
+/// ```
+/// use axin::{axin, CallCounter};
+///
+/// static CALLS: CallCounter = CallCounter::new();
+///
+/// #[axin(count_calls(CALLS))]
+/// fn work() {}
+///
+/// work();
+/// work();
+/// # #[cfg(not(feature = "disable"))]
+/// assert_eq!(CALLS.get(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct CallCounter(AtomicU64);
+
+impl CallCounter {
+    /// Create a counter starting at zero.
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    /// Record one call.
+    #[inline]
+    pub fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The number of calls recorded so far.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Reset the counter to zero.
+    pub fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,41 @@
+//! The call context exposed to hooks and decorators as `__axin`.
+
+/// Information about a single call of an instrumented function.
+///
+/// A value of this type is bound to `__axin` in the generated code whenever a hook or decorator argument mentions
+/// `__axin`, so hooks can take a single `&AxinCtx` parameter instead of several loose values:
+///
+/// ```
+/// use axin::{axin, AxinCtx};
+///
+/// fn log_call(ctx: &AxinCtx) {
+///     println!("{}::{} (call #{})", ctx.module_path, ctx.fn_name, ctx.call_id);
+/// }
+///
+/// #[axin(on_enter(log_call(&__axin)))]
+/// fn work() {}
+///
+/// work();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AxinCtx {
+    /// The name of the instrumented function.
+    pub fn_name: &'static str,
+    /// The module path of the instrumented function, as given by [`module_path!`].
+    pub module_path: &'static str,
+    /// The 1-based sequence number of this call among all calls of the instrumented function in this process, or since
+    /// its state was last reset through `axin::test_support`.
+    pub call_id: u64,
+}
+
+impl AxinCtx {
+    #[doc(hidden)]
+    pub const fn new(fn_name: &'static str, module_path: &'static str, call_id: u64) -> Self {
+        Self {
+            fn_name,
+            module_path,
+            call_id,
+        }
+    }
+}
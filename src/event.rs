@@ -0,0 +1,37 @@
+//! Compact event ids for the `event_id` parameter.
+
+/// The id `event_id = auto` assigns to a function named `name`.
+///
+/// The id is the 32-bit FNV-1a hash of the name, folded to 16 bits by xoring its halves. It only depends on the name
+/// without the module path or the `impl` block, so it stays the same across builds, crates and refactorings that move
+/// the function, and functions sharing a name share the id. As every attribute only sees its own function, colliding
+/// ids are not detected; a test can check the names of a crate's functions with this function, and give one of the
+/// colliding functions an explicit `event_id = 0x...`.
+///
+/// ```
+/// use axin::{axin, event_id};
+///
+/// fn trace_event(id: u16) {
+///     println!("event {:#06x}", id);
+/// }
+///
+/// #[axin(event_id = auto, on_enter(trace_event(__axin_event_id)))]
+/// fn fetch_user() {}
+///
+/// fetch_user(); // event 0x8b2b
+/// assert_eq!(event_id("fetch_user"), 0x8b2b);
+/// ```
+pub const fn event_id(name: &str) -> u16 {
+    const OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+
+    let bytes = name.as_bytes();
+    let mut hash = OFFSET_BASIS;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u32;
+        hash = hash.wrapping_mul(PRIME);
+        index += 1;
+    }
+    ((hash >> 16) ^ (hash & 0xffff)) as u16
+}
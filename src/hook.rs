@@ -0,0 +1,72 @@
+//! Outcomes of hooks, for the `hook_errors` parameter.
+
+use core::convert::Infallible;
+
+/// The outcome of an entry, exit or error hook, as seen by the `hook_errors` policies.
+///
+/// Hooks returning `()` never fail, and hooks returning `Result` fail with its error. Implement this trait for other
+/// return types to use them with `hook_errors`:
+///
+/// ```
+/// use axin::{axin, HookResult};
+/// use std::fmt::Display;
+///
+/// enum Status {
+///     Done,
+///     Failed(String),
+/// }
+///
+/// impl HookResult for Status {
+///     type Error = String;
+///
+///     fn into_hook_result(self) -> Result<(), String> {
+///         match self {
+///             Status::Done => Ok(()),
+///             Status::Failed(reason) => Err(reason),
+///         }
+///     }
+/// }
+///
+/// fn connect() -> Status {
+///     Status::Failed("no connection".to_string())
+/// }
+///
+/// fn cleanup() {}
+///
+/// // The sink is called with the errors of all hooks, so it takes any error type
+/// fn report<E: Display>(error: &E) {
+///     eprintln!("hook failed: {}", error);
+/// }
+///
+/// #[axin(on_enter(connect), on_exit(cleanup), hook_errors = "log", hook_error_sink = report)]
+/// fn work() -> u32 {
+///     42
+/// }
+///
+/// assert_eq!(work(), 42);
+/// ```
+pub trait HookResult {
+    /// The error of a failed hook.
+    type Error;
+
+    /// Convert the outcome into `Ok(())` for success or the hook's error.
+    fn into_hook_result(self) -> Result<(), Self::Error>;
+}
+
+impl HookResult for () {
+    type Error = Infallible;
+
+    #[inline]
+    fn into_hook_result(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<T, E> HookResult for Result<T, E> {
+    type Error = E;
+
+    #[inline]
+    fn into_hook_result(self) -> Result<(), Self::Error> {
+        self.map(|_| ())
+    }
+}
@@ -11,10 +11,25 @@
 //! - insert statements at the beginning of function execution, and
 //! - wrap functions with decorators.
 //!
+//! The types and traits hooks and decorators commonly refer to, such as [`AxinCtx`] and [`CallCounter`], can be
+//! imported at once with `use axin::prelude::*;`, next to `use axin::axin;`, see [`prelude`].
+//!
 //! ### Entry and Exit Hooks
 //!
 //! These hooks allow you to execute custom functions when entering or exiting the target function. It's also possible
-//! to specify arguments for these hooks, which can be used to pass context or configuration.
+//! to specify arguments for these hooks, which can be used to pass context or configuration. Arguments may refer to
+//! the function's parameters, and always see their original values, even if the function body shadows them (as in
+//! `let data = data.trim();`). The parameters have been moved into the function body by the time exit hooks run, so
+//! these get clones of the ones they use, made before the call, which requires them to be `Clone`; references are not
+//! cloned but passed on, so a `&mut` parameter shows the hook what the body did through it. The exit hooks of methods
+//! may use a `&self` or `&mut self` receiver, which the body only borrows, as in `on_exit(log_state(&self.stats))`, but
+//! not one taken by value; neither can those of async methods and `raw_body` functions use `&mut self`, which the
+//! body's future or the guard running them keeps. A hook, like a decorator, can be any path, such as
+//! `Metrics::<Http>::enter`; it is called exactly as written, so the parameters of a generic type like
+//! `Recorder::<T>::record` can be left to be inferred from the hook's arguments. Hook and decorator paths are resolved
+//! where the function is defined, so the imports of its module apply to them, renamed with `as` or by a glob alike, but
+//! `use` items in the function body do not. A path starting with a name the body imports is rejected; globs in the body
+//! cannot be checked and are best avoided for names hooks use.
 //!
 //! ```
 //! use axin::axin;
@@ -50,11 +65,205 @@
 //! }
 //! ```
 //!
+//! An exit hook can be made conditional with `on_exit(hook, when = predicate)`. The predicate may refer to `result`, a
+//! reference to the return value, and to `elapsed`, the `Duration` the decorator and the function body took, and the
+//! hook is only called if it holds. `result` has the function's return type even when a decorator's output type is
+//! generic, so methods can be called on it; only an `impl Trait` return type is left to be inferred.
+//!
+//! ```
+//! use axin::axin;
+//! use std::time::Duration;
+//!
+//! fn alert_oncall() {
+//!     println!("Paging the on-call engineer");
+//! }
+//!
+//! fn report_slow() {
+//!     println!("That took a while");
+//! }
+//!
+//! #[axin(on_exit(alert_oncall, when = result.is_err()))]
+//! fn charge(amount: u32) -> Result<u32, String> {
+//!     if amount > 0 { Ok(amount) } else { Err("nothing to charge".to_string()) }
+//! }
+//!
+//! #[axin(on_exit(report_slow, when = elapsed > Duration::from_secs(1)))]
+//! fn sync() {}
+//!
+//! fn main() {
+//!     let _ = charge(0);
+//!     sync();
+//!     // Output:
+//!     // Paging the on-call engineer
+//! }
+//! ```
+//!
+//! The exit hook's arguments may refer to the return value as `__axin_result`, a reference to it, within any
+//! expression, so that hooks can log results or record metrics without taking them from the caller. The value is only
+//! borrowed and returned as is afterwards. Everything running before the function returns, such as entry hooks and
+//! decorators, cannot refer to it, and neither can the exit hook of a `raw_body` function.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn log_result(fn_name: &str, result: &impl std::fmt::Debug) {
+//!     println!("{} returned {:?}", fn_name, result);
+//! }
+//!
+//! #[axin(on_exit(log_result("parse", __axin_result)))]
+//! fn parse(input: &str) -> Result<u32, String> {
+//!     input.parse().map_err(|_| format!("not a number: {}", input))
+//! }
+//!
+//! fn main() {
+//!     let _ = parse("12");
+//!     // Output:
+//!     // parse returned Ok(12)
+//! }
+//! ```
+//!
+//! A decorator may return without calling the function body, e.g. on a cache hit. Exit hooks that run either way can
+//! tell these calls apart by `__axin_body_ran`, a `bool` their arguments and predicate may refer to. It is set as soon
+//! as the decorator calls the body, so it holds as well when the body panics and the decorator catches the panic; for
+//! async functions, it is set when the decorator creates the body's future. Without a decorator, it always holds.
+//!
+//! ```
+//! use axin::axin;
+//! use std::collections::HashMap;
+//! use std::sync::Mutex;
+//!
+//! static CACHE: Mutex<Option<HashMap<u32, u32>>> = Mutex::new(None);
+//!
+//! fn cached<F: FnOnce(u32) -> u32>(f: F, key: u32) -> u32 {
+//!     let cached = CACHE.lock().unwrap().get_or_insert_with(HashMap::new).get(&key).copied();
+//!     cached.unwrap_or_else(|| {
+//!         let value = f(key);
+//!         CACHE.lock().unwrap().get_or_insert_with(HashMap::new).insert(key, value);
+//!         value
+//!     })
+//! }
+//!
+//! fn count_lookup(miss: bool) {
+//!     println!("cache {}", if miss { "miss" } else { "hit" });
+//! }
+//!
+//! #[axin(decorator(cached), on_exit(count_lookup(__axin_body_ran)))]
+//! fn square(x: u32) -> u32 {
+//!     x * x
+//! }
+//!
+//! fn main() {
+//!     square(3);
+//!     square(3);
+//!     // Output:
+//!     // cache miss
+//!     // cache hit
+//! }
+//! ```
+//!
+//! Exit hooks run once the function body has returned and everything it was given is gone: its locals, its arguments,
+//! including ones it does not use, and the decorator wrapping it have all been dropped, and only the result is left to
+//! return. Locks and files held by locals are thus released when an exit hook runs. `on_exit(hook, after_drop)` states
+//! that a hook relies on this, and is rejected where it does not hold, with `raw_body`, whose exit hook runs before the
+//! function's arguments are dropped.
+//!
+//! An entry hook takes a predicate as well, with `on_enter(hook, when = predicate)`. It is checked before the hook's
+//! arguments are evaluated, so expensive arguments are only computed if the hook is called. The predicate may refer to
+//! the function's parameters and generic parameters, which lets the caller decide, e.g. with a const generic parameter
+//! that the compiler folds away.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn log_details(details: String) {
+//!     println!("{}", details);
+//! }
+//!
+//! #[axin(on_enter(log_details(format!("processing {:?}", items)), when = VERBOSE))]
+//! fn process<const VERBOSE: bool>(items: &[u32]) -> u32 {
+//!     items.iter().sum()
+//! }
+//!
+//! fn main() {
+//!     process::<true>(&[1, 2]);
+//!     process::<false>(&[3, 4]); // nothing is formatted
+//!     // Output:
+//!     // processing [1, 2]
+//! }
+//! ```
+//!
+//! With `on_enter(hook, with_args)`, the function's name and a borrowed tuple of references to its arguments are
+//! passed to the entry hook, after any arguments given to it. A `self` receiver is left out, and so are the parameters
+//! listed in `with_args(skip(name, ...))`, such as ones the hook cannot format. One generic hook can thus audit
+//! functions of any shape. Exit hooks cannot be given `with_args`, since the arguments are moved into the function
+//! before they run.
+//!
+//! ```
+//! use axin::axin;
+//! use std::fmt::Debug;
+//!
+//! fn audit<A: Debug>(fn_name: &str, args: &A) {
+//!     println!("{}{:?}", fn_name, args);
+//! }
+//!
+//! struct Connection;
+//!
+//! #[axin(on_enter(audit, with_args))]
+//! fn greet(name: String, times: usize) -> String {
+//!     name.repeat(times)
+//! }
+//!
+//! #[axin(on_enter(audit, with_args(skip(connection))))]
+//! fn query(connection: &Connection, table: &str) -> usize {
+//!     table.len()
+//! }
+//!
+//! fn main() {
+//!     greet("axin".to_string(), 2);
+//!     query(&Connection, "users");
+//!     // Output:
+//!     // greet("axin", 2)
+//!     // query("users",)
+//! }
+//! ```
+//!
+//! A hook given `auto_into` has each literal argument wrapped in `Into::into`, so that the hook's parameter types
+//! decide what the literal becomes, and the same string literal can be passed to hooks taking `&str`, `String` or
+//! `Cow<str>`. Only literals are converted, never other expressions, and numbers without a suffix are left as they are,
+//! since their type is already inferred from the parameter.
+//!
+//! ```
+//! use axin::axin;
+//! use std::borrow::Cow;
+//!
+//! fn log(message: String, code: u64) {
+//!     println!("{} ({})", message, code);
+//! }
+//!
+//! fn label(name: Cow<'static, str>) {
+//!     println!("{}", name);
+//! }
+//!
+//! #[axin(on_enter(log("starting", 1), auto_into), on_exit(label("done"), auto_into))]
+//! fn run() {}
+//!
+//! fn main() {
+//!     run();
+//!     // Output:
+//!     // starting (1)
+//!     // done
+//! }
+//! ```
+//!
 //! ### Prologue Statements
 //!
 //! Prologue statements allow you to insert arbitrary Rust code at the beginning of the function body. This can be very
 //! useful sometimes, as the inserted code shares the same scope as the function, though hooks and decorators are better
-//! choices for most use cases.
+//! choices for most use cases. Any statement is accepted, including `let ... else` and labeled blocks, and a
+//! semicolon may be omitted after the last one. The function body follows them as one block, exactly as written, so its
+//! labels, `return`s and inner attributes such as `#![allow(unused_variables)]` keep applying to what they did. Items
+//! the body declares, such as nested functions and constants, are visible to the prologue too, unless the body has inner
+//! attributes or defines a `macro_rules!` macro.
 //!
 //! ```
 //! use axin::axin;
@@ -74,6 +283,61 @@
 //! }
 //! ```
 //!
+//! Prologue statements may leave the function early with `return` or `?`, which makes them a good place for guard
+//! clauses. An early return only skips the rest of the function body: it produces the function's return value as if
+//! the body had returned it, so decorators and exit hooks still run normally.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! #[axin(prologue(
+//!     if input.is_empty() {
+//!         return 0;
+//!     }
+//! ))]
+//! fn first_byte(input: &str) -> u8 {
+//!     input.as_bytes()[0]
+//! }
+//!
+//! fn main() {
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!(first_byte(""), 0);
+//!     assert_eq!(first_byte("A"), 65);
+//! }
+//! ```
+//!
+//! Epilogue statements, given as `epilogue(statement1; statement2; ...)`, are the mirror image: they run after the
+//! function body, in the same scope as the prologue, before the body's value is returned to the decorator and the exit
+//! hooks. They see the parameters the body did not move, the prologue's bindings and the items the body declares, but
+//! not the body's own bindings. An early `return` or `?` in the body leaves it directly and skips the epilogue, which
+//! only runs when the body completes.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! #[axin(
+//!     prologue(let mut steps = Vec::new();),
+//!     epilogue(steps.push("done"); println!("{:?}", steps);)
+//! )]
+//! fn process(input: &str) -> usize {
+//!     input.len()
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(process("abc"), 3);
+//!     // Output:
+//!     // ["done"]
+//! }
+//! ```
+//!
+//! Since prologues run code that is easy to overlook in an attribute, `strict_prologue` rejects prologue statements
+//! containing `unsafe` or `static mut`, such as a mutable static counter bumped in an `unsafe` block, and with
+//! `strict_prologue(deny = [std::process::exit, std::env::set_var])` also those mentioning any of the given paths.
+//! The statements are checked as written, including the arguments of macros, but not what they call: a denied
+//! function called through a `use` of it is not caught. The check covers the prologues of all `axin` attributes of the
+//! function, so adding `strict_prologue` to the crate-wide defaults enforces it everywhere. Epilogue statements are
+//! checked the same way.
+//!
 //! ### Decorators
 //!
 //! Decorators allow you to wrap the function with additional behavior. This is useful for cross-cutting concerns like
@@ -139,105 +403,1443 @@
 //! }
 //! ```
 //!
+//! The arguments of a parameterized decorator are evaluated on every call, after the entry hook and before the
+//! function's own arguments are moved into the decorator. They may therefore borrow the function's parameters, e.g.
+//! `decorator(authorize(&request))`, as long as the decorator returned does not keep the borrow alive. Blocks are no
+//! exception: `decorator(with_init(async { load_config().await }))` creates a new future on every call, which only runs
+//! if the decorator awaits it, and should be an `async move` block if it uses parameters, so that it holds a copy
+//! rather than a borrow. `decorator(with_raw(unsafe { &*PTR }))` is evaluated on every call as well, and the hooks'
+//! arguments and predicates take such blocks alike.
+//!
+//! To let a decorator inspect all arguments, e.g. for a label, while the body still takes ownership of them, add
+//! `borrow_args`: `decorator(label_by("GET"), borrow_args)` sets the decorator up with `label_by("GET", &route)`, and
+//! calls the decorator it returns with the function and `route` itself, as usual. Every argument is used both ways:
+//! borrowed for the setup, then moved into the call. The borrows end with the setup, so a decorator returned that
+//! still holds one is rejected by the borrow checker. With `adapt`, the decorator returned takes the function only.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn label_by<F: FnOnce(String) -> usize>(method: &str, route: &str) -> impl FnOnce(F, String) -> usize {
+//!     let label = format!("{} {}", method, route);
+//!     move |f: F, route: String| {
+//!         let len = f(route);
+//!         println!("{}: {} bytes", label, len);
+//!         len
+//!     }
+//! }
+//!
+//! #[axin(decorator(label_by("GET"), borrow_args))]
+//! fn handle(route: String) -> usize {
+//!     route.into_bytes().len()
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(handle("/users".to_string()), 6);
+//!     // Output:
+//!     // GET /users: 6 bytes
+//! }
+//! ```
+//!
+//! Several decorators are nested, the first given outermost: `#[axin(decorator(a), decorator(b), decorator(c))]` calls
+//! `a` with a callable calling `b`, whose callable calls `c`, whose callable runs the body, as if written
+//! `a(|x| b(|x| c(|x| body(x), x), x), x)`. Every decorator is passed the arguments, or none with `adapt`, and returns
+//! what the callable it was given returns. A parameterized decorator after the first is set up when the one around it
+//! calls into it, so only the first can take `borrow_args`. The same decorator given twice, with the same setup call if
+//! any, is rejected as a likely mistake, pointing at both, also when given by different attributes of the function:
+//! stacked ones, or the ones of its `impl` block and of the method.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn trace<F: FnOnce(u32) -> u32>(name: &'static str) -> impl FnOnce(F, u32) -> u32 {
+//!     move |f, x| {
+//!         println!("{} before {}", name, x);
+//!         let result = f(x);
+//!         println!("{} after {}", name, result);
+//!         result
+//!     }
+//! }
+//!
+//! #[axin(decorator(trace("a")), decorator(trace("b")), decorator(trace("c")))]
+//! fn double(x: u32) -> u32 {
+//!     println!("body");
+//!     x * 2
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(double(21), 42);
+//!     // Output:
+//!     // a before 21
+//!     // b before 21
+//!     // c before 21
+//!     // body
+//!     // c after 42
+//!     // b after 42
+//!     // a after 42
+//! }
+//! ```
+//!
+//! The inner callable is defined inside the instrumented function, so it sees the function's type and const generic
+//! parameters and `where` clauses as they are, including bounds on associated types such as `S::Output: Send`, and
+//! `S::Output` may be the return type. Elided lifetimes keep tying the return type to the parameters, as in
+//! `fn largest<T: PartialOrd>(items: &[T]) -> &T`. A decorator of a generic function is generic over the same types,
+//! and only relies on the bounds it states itself.
+//!
+//! The inner callable is declared with the function's return type, so a body returning a `Box<dyn Trait>`, an
+//! `Arc<dyn Trait>` or the like coerces its tail and `return` expressions to it as the function itself would, including
+//! in an `async fn`. The trait object is then the decorator's `R`, which satisfies a bound like `R: 'static` unless the
+//! trait object borrows, as a `Box<dyn Trait + '_>` does. Such a borrowed return type needs `adapt`, so that the inner
+//! callable captures the parameters it borrows from.
+//!
+//! Parameters and the return type may both be `impl Trait`, as in the iterator adapter
+//! `fn transform(input: impl Iterator<Item = u8>) -> impl Iterator<Item = u8>`. A decorator's `A` is then the type of
+//! the argument, and its `R` the hidden type the body returns, which meets bounds like `+ Send` whenever the body's
+//! does. Since `R` is only known from the callable's output, a decorator converting the output to a type of its own
+//! cannot decorate a function returning `impl Trait`, whose return type names no type to convert to.
+//!
 //! Decorators do not support variadic arguments, due to the limitation of Rust.
 //!
-//! ## Order of Execution
+//! `decorator_select(selector, [decorator1, decorator2, ...])` picks the decorator on every call: `selector` is
+//! evaluated after the entry hook and converted with `as usize`, so it can be an index or a field-less enum whose
+//! variants map to the decorators by position. All decorators are called the same way and must therefore share the
+//! same shape; `explain` describes it. A selector past the end of the list panics.
 //!
-//! The order of execution for the various Axin features is as follows:
-//! 1. Entry hook function (if specified) is executed first, then
-//! 2. Decorator function (if specified) is called, and when it calls the original function,
-//! 3. Prologue statements (if specified) are executed, and then
-//! 4. The original function body is executed, after which
-//! 5. The control flow returns to the decorator, and after it completes,
-//! 6. The exit hook function (if specified) is executed last.
-
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, ItemFn};
-
-mod args;
-mod generator;
-
-use args::AxinArgs;
-use generator::{generate_enhanced_function, process_attribute_args};
-
-/// An attribute procedural macro that enhances functions with entry and exit hooks, decorators, and prologue statements.
-///
-/// For more details, see the [Axin documentation](crate).
-///
-/// ## Example
-///
-/// ```
-/// use axin::axin;
-///
-/// fn setup() {
-///     println!("Starting function");
-/// }
-///
-/// fn cleanup() {
-///     println!("Function completed");
-/// }
-///
-/// fn timing_decorator<F, R>(func: F) -> R
-/// where F: FnOnce() -> R
-/// {
-///     let start = std::time::Instant::now();
-///     let result = func();
-///     println!("Execution time: {:?}", start.elapsed());
-///     result
-/// }
-///
-/// #[axin(
-///     prologue(println!("Initializing");),
-///     on_enter(setup),
-///     decorator(timing_decorator),
-///     on_exit(cleanup)
-/// )]
-/// fn instrumented_function() -> i32 {
-///     println!("Core logic");
-///     42
-/// }
-///
-/// fn main() {
-///     let result = instrumented_function();
-///     println!("Result: {}", result);
-///
-///     // Output:
-///     // Starting function
-///     // Initializing
-///     // Core logic
-///     // Execution time: ...
-///     // Function completed
-///     // Result: 42
-/// }
-/// ```
-#[proc_macro_attribute]
-pub fn axin(args: TokenStream, input: TokenStream) -> TokenStream {
-    let input_fn = parse_macro_input!(input as ItemFn);
-
-    // Parse attribute parameters
-    if !args.is_empty() {
-        let attribute_args = match syn::parse::<AxinArgs>(args) {
-            Ok(args) => args,
-            Err(e) => return e.to_compile_error().into(),
-        };
-
-        let (prologue_stmts, decorator_fn, on_enter_fn, on_exit_fn) =
-            process_attribute_args(attribute_args);
-
-        // Process function enhancement according to the new design
-        generate_enhanced_function(
-            input_fn,
-            prologue_stmts,
-            decorator_fn,
-            on_enter_fn,
-            on_exit_fn,
-        )
-        .into()
-    } else {
-        quote! {
-            #input_fn
-        }
-        .into()
+//! ```
+//! use axin::axin;
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//!
+//! static DEBUG: AtomicBool = AtomicBool::new(false);
+//!
+//! enum Mode {
+//!     Fast,
+//!     Debug,
+//! }
+//!
+//! fn mode() -> Mode {
+//!     if DEBUG.load(Ordering::Relaxed) { Mode::Debug } else { Mode::Fast }
+//! }
+//!
+//! fn fast<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+//!     f(x)
+//! }
+//!
+//! fn debug<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+//!     let result = f(x);
+//!     println!("square({}) = {}", x, result);
+//!     result
+//! }
+//!
+//! #[axin(decorator_select(mode(), [fast, debug]))]
+//! fn square(x: u32) -> u32 {
+//!     x * x
+//! }
+//!
+//! fn main() {
+//!     square(2);
+//!     DEBUG.store(true, Ordering::Relaxed);
+//!     square(3);
+//!     // Output:
+//!     // square(3) = 9
+//! }
+//! ```
+//!
+//! By default, a decorator receives the function as a callable taking the function's parameters, followed by their
+//! values. With the `adapt` flag, the callable captures the parameters instead and takes no arguments, so a decorator
+//! written once as `fn deco<F: FnOnce() -> R, R>(f: F) -> R` wraps functions of any arity, parameterized decorators
+//! included. `decorator(timing_decorator, no_args)` says the same next to the decorator, so the `timing_decorator`
+//! above wraps `fn square(x: i32) -> i32` as it is. Since a `self` receiver can only be captured, decorated methods
+//! taking `self` need `adapt`; without a decorator, the callable captures the receiver anyway. Hooks and decorator
+//! arguments may still refer to parameters, but the parameters are moved into the callable before the decorator runs,
+//! so exit hooks only see clones of them, and none of the `&mut` ones.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn logged<F: FnOnce() -> R, R>(f: F) -> R {
+//!     println!("Calling");
+//!     f()
+//! }
+//!
+//! struct Counter(u32);
+//!
+//! #[axin(decorator(logged), adapt)]
+//! impl Counter {
+//!     fn get(&self) -> u32 {
+//!         self.0
+//!     }
+//!
+//!     fn add(&mut self, by: u32, times: u32) {
+//!         self.0 += by * times;
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut counter = Counter(0);
+//!     counter.add(2, 3);
+//!     assert_eq!(counter.get(), 6);
+//! }
+//! ```
+//!
+//! A decorator may call the callable more than once, e.g. to retry a failed call, by bounding it with `FnMut` rather
+//! than `FnOnce`, which the callable implements as long as its body can run again. The arguments are the decorator's to
+//! reuse as their types allow, so none of them has to be `Clone` for the attribute: a `&mut` argument can be reborrowed
+//! as `&mut *arg` for every attempt, a shared reference copied, and only arguments passed by value need to be cloned.
+//!
+//! With `packed = struct`, the decorator gets the arguments as one value instead, of a struct generated in the
+//! function's body with a field for each parameter, so a decorator written once as
+//! `fn deco<F: FnOnce(A) -> R, A: Debug, R>(f: F, args: A) -> R` can log the arguments of functions of any arity by
+//! name. The struct is named after the function, such as `__AxinArgsFetchReport` for `fetch_report`, and its `Debug`
+//! representation shows the field names. Parameters bound by patterns are packed under generated names. Packed
+//! parameters cannot have `impl Trait` types or mention `Self`.
+//!
+//! ```
+//! use axin::axin;
+//! use std::fmt::Debug;
+//!
+//! fn logged<F: FnOnce(A) -> R, A: Debug, R>(f: F, args: A) -> R {
+//!     println!("Calling with {:?}", args);
+//!     f(args)
+//! }
+//!
+//! #[axin(decorator(logged), packed = struct)]
+//! fn fetch_report(user_id: u64, format: &str, limit: Option<usize>) -> String {
+//!     format!("{} as {}, {:?} rows", user_id, format, limit)
+//! }
+//!
+//! fn main() {
+//!     fetch_report(7, "csv", Some(10));
+//!     // Output:
+//!     // Calling with __AxinArgsFetchReport { user_id: 7, format: "csv", limit: Some(10) }
+//! }
+//! ```
+//!
+//! A decorator on a function that merely forwards to another one usually belongs on the callee instead. The
+//! `warn_if_trivial` flag reports a warning when the decorated function's body is empty or a single call with plain
+//! arguments, and `deny_trivial_decorator` turns it into an error. Add `allow_trivial` to silence both for a function
+//! that is trivial on purpose.
+//!
+//! ### Async Functions and Patterns
+//!
+//! An `async fn` can be instrumented like any other: hooks run synchronously around the awaited body, and a decorator
+//! is passed a callable returning the body's future and is awaited itself, so it is typically an `async fn` as well.
+//! Without a decorator, the future captures the parameters, so borrowed ones work too. Parameters may be patterns,
+//! such as the extractors of web handlers, and the return type may be `impl Trait`, so a handler keeps satisfying the
+//! bounds of the framework it is registered with.
+//!
+//! ```
+//! use axin::axin;
+//! use std::future::Future;
+//!
+//! struct Json<T>(T);
+//!
+//! fn enter() {
+//!     println!("Handling request");
+//! }
+//!
+//! fn failed(error: &String) {
+//!     println!("Request failed: {}", error);
+//! }
+//!
+//! async fn timed<F: FnOnce() -> Fut, Fut: Future>(f: F) -> Fut::Output {
+//!     let start = std::time::Instant::now();
+//!     let output = f().await;
+//!     println!("Handled in {:?}", start.elapsed());
+//!     output
+//! }
+//!
+//! #[axin(on_enter(enter), decorator(timed), adapt, on_error(failed))]
+//! async fn create(Json(body): Json<String>) -> Result<impl std::fmt::Display, String> {
+//!     let count: u32 = body.parse().map_err(|_| "not a number")?;
+//!     Ok(count * 2)
+//! }
+//! ```
+//!
+//! `async` combines with `unsafe`, an `extern` ABI, generic parameters and receivers in any way, as in
+//! `pub async unsafe extern "C" fn`: the function keeps its signature as written, and the body runs in the unsafe
+//! context of an `unsafe fn` as before. Only `const fn`s and C-variadic functions cannot be instrumented, and are
+//! rejected, as are async functions with `phase_timing` or `profile`.
+//!
+//! ### Slow Call Logging
+//!
+//! `slow_log(threshold, hook)` measures how long the decorator and the function body take, and calls the hook with
+//! the elapsed [`Duration`](std::time::Duration) only when it exceeds `threshold`. The duration is appended after
+//! any arguments given to the hook, which may include the call context.
+//!
+//! ```
+//! use axin::{axin, AxinCtx};
+//! use std::time::Duration;
+//!
+//! fn report_slow_call(ctx: &AxinCtx, elapsed: Duration) {
+//!     println!("{} took {:?}", ctx.fn_name, elapsed);
+//! }
+//!
+//! #[axin(slow_log(Duration::from_millis(100), report_slow_call(&__axin)))]
+//! fn query(delay_ms: u64) {
+//!     std::thread::sleep(Duration::from_millis(delay_ms));
+//! }
+//!
+//! fn main() {
+//!     query(1); // nothing reported
+//!     query(150);
+//!     // Output:
+//!     // query took 150.1ms
+//! }
+//! ```
+//!
+//! To find out where the time of a slow call goes, `phase_timing(hook)` measures the phases of every call separately,
+//! and calls the hook with a [`PhaseTimings`] after everything else: the time the entry hook took, the time the
+//! decorator took outside of the body, the body's own time, and the time of the hooks after it. Arguments given to the
+//! hook come first, as with `slow_log`. Async functions are not supported, as their phases interleave with other
+//! tasks.
+//!
+//! ```
+//! use axin::{axin, PhaseTimings};
+//!
+//! fn report(name: &str, timings: PhaseTimings) {
+//!     println!("{}: {:?} in the body, {:?} in total", name, timings.body, timings.total());
+//! }
+//!
+//! #[axin(on_enter(setup), phase_timing(report("query")))]
+//! fn query() {}
+//! # fn setup() {}
+//! #
+//! # fn main() {
+//! #     query();
+//! # }
+//! ```
+//!
+//! `profile` bundles both for functions that should just be measured: every call is timed by phase, and calls taking
+//! longer than 100ms, or the threshold given with `profile(slow = 250ms)`, are reported as slow. Thresholds are whole
+//! numbers of `ns`, `us`, `ms` or `s`, or expressions of type `Duration`. Instead of hooks of its own, each profiled
+//! function reports to the sinks registered once for the process with [`set_profile_sinks`], which get the call context,
+//! whose `call_id` counts the function's calls. Until sinks are registered, profiled functions report nothing.
+//! `profile` cannot be combined with `phase_timing` or `slow_log`, and does not support async functions.
+//!
+//! ```
+//! use axin::{axin, set_profile_sinks, AxinCtx, PhaseTimings, ProfileSinks};
+//! use std::time::Duration;
+//!
+//! fn record(ctx: &AxinCtx, timings: PhaseTimings) {
+//!     println!("{} #{}: {:?}", ctx.fn_name, ctx.call_id, timings.total());
+//! }
+//!
+//! fn warn_slow(ctx: &AxinCtx, elapsed: Duration) {
+//!     eprintln!("{} took {:?}", ctx.fn_name, elapsed);
+//! }
+//!
+//! #[axin(profile(slow = 50ms))]
+//! fn query() {}
+//!
+//! fn main() {
+//!     set_profile_sinks(ProfileSinks::new().record(record).slow(warn_slow)).unwrap();
+//!     query();
+//! }
+//! ```
+//!
+//! ### Tracing Spans
+//!
+//! With the `tracing` feature enabled, `instrument_compat(...)` enters a [`tracing`](https://docs.rs/tracing) span
+//! for the whole call, hooks and decorator included. It accepts the commonly used options of `#[tracing::instrument]`
+//! with the same meaning: `name`, `target`, `level`, `skip`, `skip_all` and `fields`. Existing `instrument`
+//! attributes can thus be migrated by renaming them, and then combined with the other parameters. Options axin does
+//! not support, such as `err` and `ret`, are rejected with a hint at the parameter to use instead. In an `async fn`,
+//! the span is entered whenever the function's future is polled, like with `#[tracing::instrument]`.
+//!
+//! ```
+//! # #[cfg(feature = "tracing")]
+//! # mod example {
+//! use axin::axin;
+//!
+//! #[axin(instrument_compat(skip(password), fields(user = user_id), level = "debug", name = "db.load"))]
+//! fn load(user_id: u64, password: &str) -> bool {
+//!     !password.is_empty()
+//! }
+//! # }
+//! ```
+//!
+//! ### Call Site Warnings
+//!
+//! `warn_call_site("message")` marks the function `#[deprecated]` with the given note, so every caller gets a
+//! compile-time warning. Combined with a counter or hook, this tracks the migration away from a function both at
+//! compile time and at run time.
+//!
+//! ```
+//! use axin::{axin, CallCounter};
+//!
+//! static LEGACY_CALLS: CallCounter = CallCounter::new();
+//!
+//! #[axin(warn_call_site("use `new_api` instead"), count_calls(LEGACY_CALLS))]
+//! fn old_api() {}
+//!
+//! fn main() {
+//!     #[allow(deprecated)]
+//!     old_api();
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!(LEGACY_CALLS.get(), 1);
+//! }
+//! ```
+//!
+//! ### One-Time Initialization
+//!
+//! `init(expr)` evaluates `expr` on the first call of the function, before anything else the instrumentation does,
+//! which suits installing the global logger or subscriber the hooks report to. Each function evaluates its own
+//! `init` once, even if other functions give the same expression, so the initialization itself should tolerate
+//! running again, as installing a global default usually does by failing quietly. Concurrent first calls wait until
+//! the expression has been evaluated. If it panics, the next call evaluates it again, and so does the first call after
+//! the function's state is reset in tests, see [Resetting State in Tests](#resetting-state-in-tests).
+//!
+//! ```
+//! use axin::axin;
+//! use std::sync::OnceLock;
+//!
+//! static LOG_PREFIX: OnceLock<String> = OnceLock::new();
+//!
+//! fn install_logger() {
+//!     let _ = LOG_PREFIX.set("[app]".to_string());
+//! }
+//!
+//! fn log_enter() {
+//!     println!("{} entering", LOG_PREFIX.get().unwrap());
+//! }
+//!
+//! #[axin(init(install_logger()), on_enter(log_enter))]
+//! fn handler() {}
+//!
+//! #[axin(init(install_logger()), on_enter(log_enter))]
+//! fn other_handler() {}
+//!
+//! fn main() {
+//!     handler();
+//!     other_handler();
+//! }
+//! ```
+//!
+//! ### Call Counting and Minimal Mode
+//!
+//! `count_calls(COUNTER)` increments a static [`CallCounter`] on every call, before any hook runs.
+//!
+//! For tiny, hot functions such as `Iterator::next`, the `minimal` flag skips the usual wrapping entirely: the original
+//! body is emitted unchanged, preceded only by the initialization, the counter and entry hooks. To keep that
+//! straight-line code correct, `minimal` can only be combined with `init`, `count_calls` and argument-less `on_enter`
+//! hooks; anything else is rejected at compile time. Unlike the other features, `minimal` works on methods with a
+//! `self` receiver.
+//!
+//! ```
+//! use axin::{axin, CallCounter};
+//!
+//! static NEXT_CALLS: CallCounter = CallCounter::new();
+//!
+//! struct Countdown(u32);
+//!
+//! impl Iterator for Countdown {
+//!     type Item = u32;
+//!
+//!     #[axin(minimal, count_calls(NEXT_CALLS))]
+//!     fn next(&mut self) -> Option<u32> {
+//!         self.0 = self.0.checked_sub(1)?;
+//!         Some(self.0)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Countdown(3).count(), 3);
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!(NEXT_CALLS.get(), 4);
+//! }
+//! ```
+//!
+//! ### Raw Bodies
+//!
+//! Some bodies cannot be moved into a callable, such as inline assembly relying on being emitted once, or `#[cfg]`d
+//! early returns meant to leave the function itself. `raw_body` emits the body exactly as written, like `minimal`, but
+//! keeps the hooks: entry hooks, with any arguments, are put in front of the body, and the exit hook is run by a guard
+//! dropped when the body returns, whether through its end, `return` or `?`. Like other exit hooks, it does not run when
+//! the body panics. Since nothing wraps the body, decorators, prologue statements and everything inspecting the result
+//! are rejected, as are `when` on the exit hook and the handling of hook errors. The arguments of the exit hook are
+//! borrowed for the whole body, and the body may refer to `__axin` and `__axin_event_id` itself.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn enter() {}
+//!
+//! fn exit() {}
+//!
+//! #[axin(raw_body, on_enter(enter), on_exit(exit))]
+//! fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+//!     #[cfg(debug_assertions)]
+//!     if input.is_empty() {
+//!         return Ok(0);
+//!     }
+//!     input.parse()
+//! }
+//! #
+//! # fn main() {
+//! #     assert_eq!(parse("7"), Ok(7));
+//! # }
+//! ```
+//!
+//! ### Error Hooks
+//!
+//! `on_error(hook)` calls the hook with a reference to the error whenever the function returns `Err`. The error is
+//! appended after any arguments given in the attribute, and its type is left to the hook to bound, so the same hook
+//! works for concrete error types, `Box<dyn Error>` and `anyhow::Error` alike.
+//!
+//! ```
+//! use axin::axin;
+//! use std::fmt::Display;
+//!
+//! fn report<E: Display + ?Sized>(label: &str, error: &E) {
+//!     println!("{} failed: {}", label, error);
+//! }
+//!
+//! #[axin(on_error(report("parse")))]
+//! fn parse(input: &str) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+//!     Ok(input.parse()?)
+//! }
+//!
+//! fn main() {
+//!     assert!(parse("x").is_err());
+//!     // Output:
+//!     // parse failed: invalid digit found in string
+//! }
+//! ```
+//!
+//! `on_ok(hook)` and `on_err(hook)` run their hooks when the function returns `Ok` or `Err`, after the error hook and
+//! before the exit hook, which still runs in either case. In their arguments, `__axin_ok` and `__axin_err` refer to the
+//! success value and the error, borrowed. They match the result as a `Result`, so using them on a function returning
+//! anything else fails to compile with a type mismatch, and they see errors returned early with `?` like any other.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn cache(user: &String) {
+//!     println!("caching {}", user);
+//! }
+//!
+//! fn retry_later(error: &String) {
+//!     println!("retrying later: {}", error);
+//! }
+//!
+//! fn lookup(id: u32) -> Result<String, String> {
+//!     if id == 0 {
+//!         Err("no such user".to_string())
+//!     } else {
+//!         Ok(format!("user {}", id))
+//!     }
+//! }
+//!
+//! #[axin(on_ok(cache(__axin_ok)), on_err(retry_later(__axin_err)))]
+//! fn fetch(id: u32) -> Result<String, String> {
+//!     let user = lookup(id)?;
+//!     Ok(user.to_uppercase())
+//! }
+//!
+//! fn main() {
+//!     assert!(fetch(1).is_ok());
+//!     assert!(fetch(0).is_err());
+//!     // Output:
+//!     // caching USER 1
+//!     // retrying later: no such user
+//! }
+//! ```
+//!
+//! ### Failing Hooks
+//!
+//! Entry, exit, error, success and failure hooks may return a `Result`. By default its value is discarded like any
+//! other return value, but `hook_errors = "..."` chooses what to do with the errors of all hooks, and `errors = "..."`
+//! after a single hook, as in `on_exit(flush, errors = "ignore")`, overrides it for that hook:
+//!
+//! - `"log"` passes a reference to the error to the function given as `hook_error_sink = function`.
+//! - `"ignore"` drops the error.
+//! - `"propagate"` returns the error from the instrumented function with `?`, so the function must return a `Result`
+//!   whose error type the hook's error converts into. A failing entry hook thus skips the function body.
+//!
+//! Under a policy, hooks returning `()` count as hooks that never fail, and other return types can take part by
+//! implementing [`HookResult`]. The sink is called with the errors of all hooks, so it should accept any error type. A
+//! hook returning `()` under `"propagate"` needs an error type convertible from `Infallible`, which most are not; give
+//! such hooks `errors = "ignore"`.
+//!
+//! ```
+//! use axin::axin;
+//! use std::fmt::Display;
+//!
+//! fn open_audit_log() -> Result<(), String> {
+//!     Err("audit log unavailable".to_string())
+//! }
+//!
+//! fn report<E: Display>(error: &E) {
+//!     println!("hook failed: {}", error);
+//! }
+//!
+//! #[axin(on_enter(open_audit_log), hook_errors = "log", hook_error_sink = report)]
+//! fn transfer(amount: u32) -> u32 {
+//!     amount
+//! }
+//!
+//! #[axin(on_enter(open_audit_log), hook_errors = "propagate")]
+//! fn strict_transfer(amount: u32) -> Result<u32, String> {
+//!     Ok(amount)
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(transfer(5), 5);
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!(strict_transfer(5), Err("audit log unavailable".to_string()));
+//!     // Output:
+//!     // hook failed: audit log unavailable
+//! }
+//! ```
+//!
+//! ### Panic Hooks
+//!
+//! Exit hooks run after the call returns, so a panicking body skips them like the rest of the function. `on_panic(hook)`
+//! runs its hook as the call unwinds instead, and `on_exit_always(hook)` runs its hook in either case: after the exit
+//! hook when the call returns, and after the panic hook when it unwinds. Both are run by guards dropped by the
+//! unwinding, without catching the panic, so the body need not be `UnwindSafe`, and the panic goes on to the caller with
+//! its payload once the hooks have run. A panic the decorator catches does not reach them, since the call returns then.
+//!
+//! Like exit hooks, these hooks get clones of the parameters they refer to, made before the call. The guards hold on to
+//! what else they refer to while the body runs, which rules out a receiver other than `&self` and parameters that are
+//! mutable references. A hook panicking while the call unwinds aborts the process, like any destructor would. An async
+//! function's future is not dropped as its panic unwinds, but once an executor caught it, if at all, so `on_panic` is
+//! rejected there, while `on_exit_always` also runs when the future is dropped before it completes.
+//!
+//! ```
+//! use axin::axin;
+//! use std::panic::catch_unwind;
+//!
+//! fn rollback(id: u32) {
+//!     println!("rolling back {}", id);
+//! }
+//!
+//! fn release(id: u32) {
+//!     println!("releasing {}", id);
+//! }
+//!
+//! #[axin(on_panic(rollback(id)), on_exit_always(release(id)))]
+//! fn commit(id: u32) {
+//!     if id == 0 {
+//!         panic!("nothing to commit");
+//!     }
+//! }
+//!
+//! fn main() {
+//!     commit(1);
+//!     assert!(catch_unwind(|| commit(0)).is_err());
+//!     // Output:
+//!     // releasing 1
+//!     // rolling back 0
+//!     // releasing 0
+//! }
+//! ```
+//!
+//! ### Stubbing Missing Hooks
+//!
+//! When a function is instrumented before its hooks exist, e.g. while they are written in another change,
+//! `stub_missing(hook, ...)` lets the crate build without them. The calls of the hooks listed go to generated functions
+//! that do nothing, and return `()`, while their arguments are still evaluated and type checked. Every function using
+//! it gets a warning naming the stubbed hooks, as a reminder to remove them from `stub_missing` once they exist, after
+//! which the real hooks are called. Entry, exit, error, slow call and phase timing hooks named by a single identifier
+//! can be stubbed; decorators cannot, since the function would not run without them. The stubs are named apart from
+//! the hooks, so the function body does not see them.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn log(message: &str) {
+//!     println!("{}", message);
+//! }
+//!
+//! // `audit` is yet to be written
+//! #[allow(deprecated)]
+//! #[axin(stub_missing(audit), on_enter(audit("transfer", amount)), on_exit(log("done")))]
+//! fn transfer(amount: u32) -> u32 {
+//!     amount
+//! }
+//!
+//! fn main() {
+//!     transfer(5);
+//!     // Output:
+//!     // done
+//! }
+//! ```
+//!
+//! ### Return Value Assertions
+//!
+//! `assert_returns(expr)` checks, after every call, that the function returned a value equal to `expr`. The check is
+//! only compiled in `cfg(test)` builds, so it costs nothing elsewhere. The return type must implement `PartialEq` and
+//! `Debug`.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! #[axin(assert_returns(42))]
+//! fn answer() -> i32 {
+//!     6 * 7
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(answer(), 42);
+//! }
+//! ```
+//!
+//! ### Mocking in Tests
+//!
+//! `mock_in_tests(mock)` makes the function call `mock` with all of its arguments instead of running its body in
+//! `cfg(test)` builds, while all other builds keep the body. The hooks and decorators still apply to the mock, unless
+//! `mock_in_tests(mock, instrumented = false)` is given, which replaces the whole function with the call in tests.
+//! The mock must take the same arguments and return the same type, with the receiver first for methods; a mismatch is
+//! reported at the mock's call, and `explain` shows the call as `in tests`. Since the mock is only called in test
+//! builds, it may be `#[cfg(test)]` itself.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn trace() {
+//!     println!("fetching");
+//! }
+//!
+//! #[cfg(test)]
+//! fn fake_fetch_user(id: u32) -> String {
+//!     format!("user {}", id)
+//! }
+//!
+//! #[axin(on_enter(trace), mock_in_tests(fake_fetch_user))]
+//! fn fetch_user(id: u32) -> String {
+//!     // Tests never get here
+//!     format!("user {} from the network", id)
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(fetch_user(1), "user 1 from the network");
+//! }
+//! ```
+//!
+//! ### Documenting the Instrumentation
+//!
+//! The `document` flag appends a list of the active instrumentation to the function's doc comments, so the hooks
+//! and decorators show up in rustdoc next to the function's own documentation.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn setup() {}
+//!
+//! /// Handles a request.
+//! ///
+//! /// Below this line, rustdoc shows "Instrumented by axin:" followed by "- entry hook `setup`".
+//! #[axin(document, on_enter(setup))]
+//! pub fn handler() {}
+//! #
+//! # fn main() {
+//! #     handler();
+//! # }
+//! ```
+//!
+//! Functions are listed by their path. When paths alone do not tell the hooks apart, e.g. `db::flush` and
+//! `cache::flush`, any hook, decorator or mock can be given a name to be listed under instead with `as name`, here and
+//! in [expansion manifests](#expansion-manifests). The name is only reported: the function is called exactly as
+//! without it. Each hook still takes one function, so two functions on exit need two stacked attributes.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! mod db {
+//!     pub fn flush() {}
+//! }
+//!
+//! mod cache {
+//!     pub fn flush() {}
+//! }
+//!
+//! /// Lists "entry hook `flush_cache`" and "exit hook `flush_db`".
+//! #[axin(document, on_enter(cache::flush as flush_cache), on_exit(db::flush as flush_db))]
+//! pub fn checkpoint() {}
+//! #
+//! # fn main() {
+//! #     checkpoint();
+//! # }
+//! ```
+//!
+//! ### Debug-Only Instrumentation
+//!
+//! With the `debug_only` flag, the whole instrumentation is only compiled with `debug_assertions`, i.e. in the default
+//! development profile. Otherwise the function is emitted exactly as written, without any generated statement, so
+//! release builds pay nothing for it. Hooks and decorators used by such functions only should be gated with
+//! `#[cfg(debug_assertions)]` as well, or they will be reported as unused in release builds.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! #[cfg(debug_assertions)]
+//! fn trace() {
+//!     println!("Checking");
+//! }
+//!
+//! #[axin(debug_only, on_enter(trace))]
+//! fn check(x: u32) -> bool {
+//!     x % 2 == 0
+//! }
+//! #
+//! # fn main() {
+//! #     assert!(check(2));
+//! # }
+//! ```
+//!
+//! `cfg(predicate)` does the same for any configuration predicate, in the grammar of `#[cfg]`, such as
+//! `cfg(feature = "telemetry")` or `cfg(all(debug_assertions, not(test)))`: the instrumented function is emitted under
+//! `#[cfg(predicate)]`, and the function as written under `#[cfg(not(predicate))]`, so that the predicate is evaluated by
+//! the compiler. Given several times, or with `debug_only`, all predicates must hold. An attribute stacked on others
+//! leaves them alone where its predicate does not hold, and order constants list the phases of each configuration.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! #[cfg(feature = "telemetry")]
+//! mod telemetry {
+//!     pub fn enter() {}
+//! }
+//!
+//! #[axin(cfg(feature = "telemetry"), on_enter(telemetry::enter))]
+//! fn load(id: u32) -> u32 {
+//!     id
+//! }
+//! #
+//! # fn main() {
+//! #     assert_eq!(load(1), 1);
+//! # }
+//! ```
+//!
+//! `cfg_attr` gates a whole attribute instead, as in
+//! `#[cfg_attr(feature = "telemetry", axin(on_enter(telemetry::enter)))]`. Stacked on other `axin` attributes, such an
+//! attribute is layered under them. On a function of a module or a method of an `impl` block the attribute is applied
+//! to, it is only resolved after the module or the block is instrumented, and then instruments the function again,
+//! around that instrumentation.
+//!
+//! To compile out the instrumentation of a whole build, such as a release of the final binary, enable the `disable`
+//! feature instead of touching the attributes. All `axin` attributes, `axin_use` attributes and `axin_pair!`
+//! invocations then still check their arguments, so that mistakes in them are reported, but emit their functions
+//! exactly as written: hooks, prologues, decorators and mocks are left out. What the attributes generate next to the
+//! functions is still emitted, so that twins, order constants and state accessors can still be referred to. The feature
+//! is not additive: code referring to bindings of the instrumentation, such as the ones a prologue makes, does not build
+//! with it, so enable it from the final binary only. Hooks and decorators that only the instrumentation calls are then
+//! reported as unused.
+//!
+//! ```toml
+//! [dependencies]
+//! axin = { version = "0.1", features = ["disable"] }
+//! ```
+//!
+//! Passing `--cfg axin_disable` to the compiler has the same effect, for builds that cannot change features. Cargo
+//! passes `RUSTFLAGS` to procedural macros only when no `--target` is given.
+//!
+//! ### Uninstrumented Twins
+//!
+//! `twin = "name"` emits a copy of the function next to it, under the given name, that is not instrumented at all. The
+//! twin keeps the function's visibility, attributes, documentation and generics, so callers that must skip the
+//! instrumentation, e.g. on a hot path during a migration, can call it as a regular part of the API. No `axin` attribute
+//! instruments the twin, including the ones stacked on the function. Only `#[no_mangle]` and `#[export_name]` are left
+//! out, so that the instrumented function keeps its symbol to itself.
+//!
+//! A hook, decorator or mock referring to the instrumented function itself, as `f`, `self::f` or `Self::f`, is
+//! rejected, since the function would call itself on every call until the stack overflows. Its twin can be referred to
+//! instead, to call the function without the instrumentation.
+//!
+//! The body of a recursive function calls the function by its name, so every recursive call is instrumented like the
+//! first, running the hooks and the decorator again. The body runs inside a closure of the function, which backtraces
+//! show as `fib::{{closure}}` for a function `fib`. With `nested_body`, it runs inside a nested function with the same
+//! signature and generic parameters instead, shown as `fib::__axin_original`. A nested function cannot see the generic
+//! parameters of an `impl` block, so `nested_body` must not be given to a method using them, which the attribute cannot
+//! tell from a free function. The closure is kept for methods of an `impl` block the attribute is applied to, for
+//! functions taking a receiver, async and unsafe functions, and bodies referring to `Self`, `__axin` or
+//! `__axin_event_id`. To instrument only the outermost call, the body can recurse through a twin instead, whose own body
+//! then recurses through itself.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn trace() {
+//!     println!("Fetching");
+//! }
+//!
+//! /// Fetch the name of a user.
+//! #[axin(twin = "fetch_user_fast", on_enter(trace))]
+//! pub fn fetch_user(id: u32) -> String {
+//!     format!("user {}", id)
+//! }
+//!
+//! fn main() {
+//!     fetch_user(1);
+//!     fetch_user_fast(2); // nothing is printed
+//!     // Output:
+//!     // Fetching
+//! }
+//! ```
+//!
+//! ### Instrumenting Modules
+//!
+//! Applied to a module, the attribute instruments every free function directly inside it, as if it was put on each
+//! of them. Nested modules, `impl` blocks and other items are left untouched. `skip(a, b)` excludes functions, and
+//! `only(a, b)` restricts the attribute to the functions listed. Since custom inner attributes are unstable, the
+//! attribute has to be written on the `mod` item rather than as `#![axin(...)]` inside it.
+//!
+//! Applied to an `impl` block, the attribute likewise instruments every method in it, leaving associated constants and
+//! types alone, and `skip` and `only` select methods by name. Decorated methods taking `self` need `adapt`, see
+//! [Decorators](#decorators). A function of the module or a method of the block marked with `#[axin(skip)]` is left
+//! alone too, but for its own `axin` attributes, which instrument it as usual. A `const fn` cannot be instrumented at
+//! all, as hooks and decorators cannot run in const contexts, so the attribute rejects it instead of failing inside the
+//! generated code; constructors such as `const fn new` have to be skipped.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn trace() {
+//!     println!("Handling a request");
+//! }
+//!
+//! #[axin(on_enter(trace), skip(health))]
+//! mod handlers {
+//!     use super::trace;
+//!
+//!     pub fn list() {}
+//!     pub fn create() {}
+//!     pub fn health() {}
+//! }
+//! #
+//! # fn main() {
+//! #     handlers::list();
+//! #     handlers::health();
+//! # }
+//! ```
+//!
+//! APIs offering a sync and an async variant of the same operation can instrument both alike with [`axin_pair!`],
+//! which takes the arguments once, in braces, and applies them to both functions, so that the variants cannot drift
+//! apart. Each function gets its own call context and counters, as with separate attributes, and may have `axin`
+//! attributes of its own, layered under the shared ones. A shared decorator has to suit both: one passing through the
+//! value of the callable, like the `pass` below, returns the async variant's future, which is then awaited.
+//!
+//! ```
+//! use axin::{axin_pair, AxinCtx};
+//!
+//! fn trace(ctx: &AxinCtx) {
+//!     println!("{} called", ctx.fn_name);
+//! }
+//!
+//! fn pass<F: FnOnce() -> R, R>(f: F) -> R {
+//!     f()
+//! }
+//!
+//! axin_pair!({ on_enter(trace(&__axin)), decorator(pass), adapt } => {
+//!     pub fn fetch(id: u32) -> String {
+//!         format!("item {}", id)
+//!     }
+//!
+//!     pub async fn fetch_async(id: u32) -> String {
+//!         format!("item {}", id)
+//!     }
+//! });
+//! #
+//! # fn main() {
+//! #     assert_eq!(fetch(1), "item 1");
+//! #     drop(fetch_async(1));
+//! # }
+//! ```
+//!
+//! ### Combining with Other Macros
+//!
+//! Attributes of other macros, such as `#[cached]` of the `cached` crate, are kept in place, neither reordered nor
+//! duplicated, and the macros apply in the order of their attributes. Below `#[cached]`, `axin` instruments the function
+//! `#[cached]` makes, which looks up the cache, so its hooks run on every call. Above `#[cached]`, it instruments the
+//! function as written, which `#[cached]` then only calls on a cache miss. What the other macro does with the `axin`
+//! attributes below it is up to that macro: `#[cached]` also puts them on the function priming the cache.
+//!
+//! Stacked `axin` attributes are layered into one expansion only as long as no attribute of another macro comes between
+//! them. The ones below such an attribute are expanded after its macro, as the attributes of whatever function it
+//! makes, except that the crate's defaults are not merged into them again. Attributes the compiler knows, such as
+//! `#[doc]`, `#[inline]` or lint levels, and those of tools, such as `#[rustfmt::skip]`, do not come between them.
+//!
+//! ```
+//! use axin::{axin, CallCounter};
+//! use cached::proc_macro::cached;
+//!
+//! static LOOKUPS: CallCounter = CallCounter::new();
+//! static MISSES: CallCounter = CallCounter::new();
+//!
+//! #[axin(count_calls(MISSES))]
+//! #[cached]
+//! #[axin(count_calls(LOOKUPS))]
+//! fn square(x: u64) -> u64 {
+//!     x * x
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(square(3) + square(3), 18);
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!((LOOKUPS.get(), MISSES.get()), (2, 1));
+//! }
+//! ```
+//!
+//! ### Crate-Wide Defaults
+//!
+//! Arguments every attribute of a crate should get, such as a span or an entry hook, can be given once in an
+//! `axin.toml` next to the crate's `Cargo.toml`, or at the root of its workspace:
+//!
+//! ```toml
+//! [defaults]
+//! args = ["on_enter(crate::telemetry::trace(&__axin))", "count_calls(crate::CALLS)"]
+//! ```
+//!
+//! `args` is a string or an array of strings holding arguments as written in `#[axin(...)]`, with paths as seen from
+//! the instrumented functions. The defaults are merged under the arguments of each attribute, once per function: a
+//! default is left out if the function's attributes give the same parameter, and an attribute on a module or an
+//! `impl` block passes them on to every function it instruments. `no_defaults` opts a function out of all of them,
+//! e.g. `#[axin(no_defaults)]` on a function inside an instrumented module. The file is read once per build, and
+//! changing it rebuilds the crate.
+//!
+//! Defaults can leave values to each function with a placeholder `__axin_opt("name", default)`, replaced by the value
+//! a function gives with `opt(name = value)`, or by `default` otherwise. Values are literals or identifiers, and have
+//! to be of the same kind as the default, e.g. strings for a string default. With these defaults, a function with
+//! `#[axin(opt(component = "billing"))]` traces its calls as billing, and any other one as unknown:
+//!
+//! ```toml
+//! [defaults]
+//! args = ['on_enter(crate::telemetry::trace_enter(__axin_opt("component", "unknown")))']
+//! ```
+//!
+//! ### Presets
+//!
+//! Sets of arguments only some functions should get can be named in the same file, each in a `[presets.name]` table
+//! with `args` as in `[defaults]`:
+//!
+//! ```toml
+//! [presets.service]
+//! args = ["on_enter(crate::telemetry::trace(&__axin))", "on_error(crate::alert)"]
+//!
+//! [presets.metrics]
+//! args = "count_calls(crate::CALLS)"
+//! ```
+//!
+//! `preset(service)` in an attribute stands for the arguments of the preset, which are checked and merged with the
+//! defaults as if written out in its place, and `#[axin_use(service, metrics)]` is short for
+//! `#[axin(preset(service), preset(metrics))]`. `axin_use` attributes stack with `axin` ones like any other of them.
+//! Presets are the same for every function using them, so they take no options, and neither the defaults nor other
+//! presets can use them.
+//!
+//! ### Call Context
+//!
+//! Hook and decorator arguments may refer to `__axin`, an [`AxinCtx`] value describing the current call. It is only
+//! constructed when some argument actually mentions it.
+//!
+//! ```
+//! use axin::{axin, AxinCtx};
+//!
+//! fn trace(ctx: &AxinCtx) {
+//!     println!("call #{} to {}", ctx.call_id, ctx.fn_name);
+//! }
+//!
+//! #[axin(on_enter(trace(&__axin)))]
+//! fn handler() {
+//!     println!("Handling");
+//! }
+//!
+//! fn main() {
+//!     handler();
+//!     handler();
+//!     // Output:
+//!     // call #1 to handler
+//!     // Handling
+//!     // call #2 to handler
+//!     // Handling
+//! }
+//! ```
+//!
+//! The `call_id` counter is a `static` declared inside the instrumented function, like every other item Axin generates
+//! to instrument it, so none of them shows up in rustdoc or pollutes the enclosing module. The accessor resetting the
+//! state of the function with the `test-support` feature and its `order_const` constant are emitted next to it, and
+//! hidden from rustdoc instead, see [Resetting State in Tests](#resetting-state-in-tests). Being a single item, the
+//! counter is shared by all copies of the function: an `#[inline]` function inlined into other crates and a generic
+//! function instantiated with different types count their calls together. Nothing outside the function refers to its
+//! items either, and Axin registers no function globally, so an instrumented function that is never called is left out
+//! of the binary with its statics, hooks and decorators, like any other dead code.
+//!
+//! ### Resetting State in Tests
+//!
+//! The call counter and the record that `init` ran are kept in statics, so tests calling the same functions see each
+//! other's calls, in whatever order they happen to run. With the `test-support` feature, which is meant to be enabled
+//! for tests only, the state of a function is registered on its first call, and can be reset with
+//! `axin::test_support::reset_all()`, or with the accessor `reset_<name>_axin_state()` the function gets next to it. The
+//! `test_support` module describes both.
+//!
+//! ### Compact Event Ids
+//!
+//! Where function names and formatting are too heavy, as on embedded targets, `event_id = 0x1042` binds the `u16`
+//! constant `__axin_event_id` for hook and decorator arguments, so that hooks can log compact binary events. With
+//! `event_id = auto`, the id is derived from the function's name by [`event_id`]: a deterministic hash of the name alone,
+//! without its module path, so the same function gets the same id in every build.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn trace_event(id: u16) {
+//!     println!("event {:#06x}", id);
+//! }
+//!
+//! #[axin(event_id = 0x1042, on_enter(trace_event(__axin_event_id)))]
+//! fn start_motor() {}
+//!
+//! #[axin(event_id = auto, on_enter(trace_event(__axin_event_id)))]
+//! fn stop_motor() {}
+//! #
+//! # fn main() {
+//! #     start_motor();
+//! #     stop_motor();
+//! # }
+//! ```
+//!
+//! Automatic ids are not checked for collisions, as every attribute only sees its own function: functions sharing a
+//! name share an id, and with 16 bits, a crate of a few hundred functions likely has a pair of different names hashing
+//! to the same id. A test can compute the ids of all names with [`event_id`], and an explicit id resolves a collision.
+//! When the attribute is applied to a module or an `impl` block, only `event_id = auto` is accepted, since an explicit id
+//! would be shared by all of its functions.
+//!
+//! ### Explaining the Expansion
+//!
+//! Adding the `explain` flag makes the macro report the code it generates as a compile-time warning at the attribute,
+//! highlighting the inner callable and how it is called, with one argument per line when the call is long. This is
+//! handy when a decorator's signature does not match what the macro expects. Enable the `diagnostics-pretty` feature to
+//! get the code pretty-printed instead of rendered as raw tokens.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn twice<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+//!     f(x) * 2
+//! }
+//!
+//! #[axin(explain, decorator(twice))]
+//! fn square(x: i32) -> i32 {
+//!     x * x
+//! }
+//! #
+//! # fn main() {
+//! #     #[cfg(not(feature = "disable"))]
+//! #     assert_eq!(square(3), 18);
+//! # }
+//! ```
+//!
+//! The expansion is deterministic: expanding the same input always produces the same tokens. Generated items have
+//! fixed names or names derived from the instrumented function, never from spans or random suffixes. Builds using Axin
+//! thus stay reproducible, and unchanged functions do not cause incremental recompilation.
+//!
+//! ### Expansion Manifests
+//!
+//! Editors and other tools can learn how functions are instrumented without parsing their attributes: when the
+//! environment variable `AXIN_MANIFEST_DIR` is set while building, every instrumented function gets a JSON file in that
+//! directory, listing the parameters and the hook, decorator and sink paths of each of its `axin` attributes, outermost
+//! first. The files are named after the crate, the `impl` type or module the attribute was applied to, if any, and the
+//! function, e.g. `my_crate.Store.get.json`, and are overwritten whenever the function is expanded again:
+//!
+//! ```json
+//! {
+//!   "schema": 1,
+//!   "crate": "my_crate",
+//!   "function": "Store::get",
+//!   "layers": [
+//!     {
+//!       "features": ["on_enter", "decorator"],
+//!       "hooks": [
+//!         { "kind": "on_enter", "path": "trace" },
+//!         { "kind": "decorator", "path": "timing", "alias": "request_timing" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Hooks given a name with `as name` carry it as `alias`. `schema` is the version of the format, increased on changes
+//! that would break tools reading it. Macros cannot tell which file they expand on stable Rust, so functions of the
+//! same name and owner in different files of a crate share a manifest. Changing the variable rebuilds the crates built
+//! with it set; crates built before it was first set have to be rebuilt, e.g. after `cargo clean`, to get their
+//! manifests.
+//!
+//! ### Reserved Names
+//!
+//! The generated code binds the following names for the arguments of the attribute to refer to. A parameter of the
+//! function or a binding of its prologue named like one the attribute refers to is rejected, since one would shadow the
+//! other:
+//!
+//! - `__axin`: the call context, an `AxinCtx`, in the arguments of hooks and decorators
+//! - `__axin_event_id`: the id given with `event_id`, in the arguments of hooks and decorators
+//! - `result`: a reference to the return value, in the `when` predicate of `on_exit`
+//! - `elapsed`: the time the decorator and the body took, in the `when` predicate of `on_exit`
+//! - `__axin_body_ran`: whether the function body was called, in the arguments and the `when` predicate of `on_exit`
+//! - `__axin_result`: a reference to the return value, in the arguments and the `when` predicate of `on_exit`
+//! - `__axin_ok`: a reference to the success value, in the arguments of `on_ok`
+//! - `__axin_err`: a reference to the error, in the arguments of `on_err`
+//!
+//! No other name is reserved, and the `__axin` prefix is not: a crate may have its own items named with it, such as
+//! bindings generated for a C library, and use them in hooks, decorators and bodies. The statics Axin declares for
+//! `init` and the call context are scoped to the generated code. The inner callable and the result, bound as
+//! `original_fn` and `__result`, are hygienic, like the locals of a `macro_rules!` macro: parameters, bindings and items
+//! of the same names keep referring to what they were declared as, wherever the attribute puts them. The items
+//! declared in the function for `packed = struct` and `impl Trait` parameters, named like `__AxinArgsFetchReport` and
+//! `__axin_typed`, are visible to the body and shadow items of the same names, like any item declared in a body.
+//!
+//! `generated_prefix = "__obs_"` gives all of these names another prefix than `__axin_`, for crates whose own
+//! `__axin_` names should not be shadowed or rejected: the call context becomes `__obs`, the placeholders `__obs_result`
+//! and the like, the generated items `__ObsArgsFetchReport`, `__obs_typed`, `__obs_original` and the stubs of
+//! `stub_missing` `__obs_stub_name`, and names with the `__axin_` prefix are no longer reserved. Given in the
+//! `[defaults]` of `axin.toml`, it applies to every function of the crate; given by one of the attributes stacked on a
+//! function, it applies to those giving none. The prefix must end in `_`, and both it and its stem without the `_` must
+//! be identifiers. The `__axin_opt` placeholders of `axin.toml` keep their name.
+//!
+//! ```
+//! use axin::{axin, AxinCtx};
+//!
+//! // A name from a binding generator, unrelated to Axin
+//! #[allow(non_upper_case_globals)]
+//! static __axin_version: &str = "1.2";
+//!
+//! fn log(ctx: &AxinCtx, value: &u32) {
+//!     println!("{} returned {} with version {}", ctx.fn_name, value, __axin_version);
+//! }
+//!
+//! #[axin(generated_prefix = "__obs_", on_exit(log(&__obs, __obs_result)))]
+//! fn compute(__axin_result: u32) -> u32 {
+//!     __axin_result * 2
+//! }
+//!
+//! assert_eq!(compute(21), 42);
+//! ```
+//!
+//! The runtime types passed to hooks, such as [`AxinCtx`] and [`PhaseTimings`], are `#[non_exhaustive]`, so that later
+//! versions can add to them.
+//!
+//! ## Order of Execution
+//!
+//! The order of execution for the various Axin features is as follows:
+//! 1. The initialization expression (if specified) is evaluated first, on the function's first call only, then
+//! 2. The call counter (if specified) is incremented, then
+//! 3. Entry hook function (if specified) is executed, then
+//! 4. Decorator functions (if specified) are called, the first outermost, and when the innermost calls the original
+//!    function,
+//! 5. Prologue statements (if specified) are executed, and then
+//! 6. The original function body is executed, after which
+//! 7. Epilogue statements (if specified) are executed, unless the body returned early, and then
+//! 8. The control flow returns to the decorator, and after it completes,
+//! 9. The return value assertion (if specified, test builds only) is checked, then
+//! 10. The slow call hook function (if specified) is executed if the call was slow, then
+//! 11. The error hook function (if specified) is executed if the function returned `Err`, then
+//! 12. The success or failure hook function (if specified) is executed, whichever the result calls for, then
+//! 13. The exit hook function (if specified) is executed, if its predicate (if specified) holds, and
+//! 14. The always-run exit hook function (if specified) is executed last.
+//!
+//! If the call unwinds instead, the panic hook function (if specified) and then the always-run exit hook function (if
+//! specified) are executed as the panic propagates.
+//!
+//! To keep this order checked as instrumentation is combined, `assert_order("...")` traces the phases every call runs
+//! in test builds, and asserts before returning that they are the comma-separated phases given: `init`, `count`,
+//! `enter`, `decorator`, `prologue`, `body`, `epilogue`, `assert`, `slow_log`, `error`, `ok`, `err`, `exit` and
+//! `exit_always`, in the order above. Hooks that are skipped, such as an exit hook whose predicate does not hold, are
+//! not traced. The phases of instrumented functions called during a call are part of its trace, and
+//! [`take_order_trace`] returns all phases traced on the current thread since it was last called, without cutting
+//! short the traces of the calls running. Nothing is traced outside of test builds.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn hook() {}
+//!
+//! fn pass<F: FnOnce() -> R, R>(f: F) -> R {
+//!     f()
+//! }
+//!
+//! #[axin(on_enter(hook), decorator(pass), on_exit(hook), assert_order("enter,decorator,body,exit"))]
+//! fn work() {}
+//! #
+//! # fn main() {
+//! #     work();
+//! # }
+//! ```
+//!
+//! For tooling and debugging, `order_const` emits a constant `<NAME>_AXIN_ORDER` next to the function, with its
+//! visibility, listing the phases its calls run, in the order above, each followed by the hooks or decorators run in
+//! it, as in `"enter:setup"`. Nested decorators are separated by `>`, and decorators to select from by `|`. Phases that
+//! run depending on the call, such as the error hook, are listed as well, so a call traces the phases of the list it
+//! runs; the panic hook, which only runs as the call unwinds, is not listed. The constant lists the phases of all
+//! attributes stacked on the function, whichever of them gives `order_const`, except those of attributes given
+//! `debug_only` or `cfg(...)` in the builds they leave the function alone in. Like the accessor resetting the
+//! function's state, the constant is hidden from rustdoc. It cannot be emitted in a trait `impl` block, which cannot
+//! have items the trait does not declare, nor by an attribute applied directly to a method without visibility taking
+//! `self` or referring to `Self`, which cannot tell whether its `impl` block implements a trait.
+//!
+//! ```
+//! use axin::axin;
+//!
+//! fn setup() {}
+//!
+//! fn cleanup() {}
+//!
+//! fn timing<F: FnOnce() -> R, R>(f: F) -> R {
+//!     f()
+//! }
+//!
+//! # #[cfg(not(feature = "disable"))]
+//! #[axin(order_const, on_enter(setup), decorator(timing), adapt, prologue(let doubled = id * 2;), on_exit(cleanup))]
+//! pub fn fetch_user(id: u32) -> u32 {
+//!     doubled
+//! }
+//!
+//! fn main() {
+//! #   #[cfg(not(feature = "disable"))]
+//!     assert_eq!(
+//!         FETCH_USER_AXIN_ORDER,
+//!         ["enter:setup", "decorator:timing", "prologue", "body", "exit:cleanup"]
+//!     );
+//! }
+//! ```
+//!
+//! Several `axin` attributes on one function, including those added by `cfg_attr`, are layered in their order: each
+//! attribute's instrumentation wraps that of the attributes below it, as if the later ones were part of the original
+//! function body. With `#[axin(on_enter(base))]` followed by `#[cfg_attr(feature = "deep", axin(on_enter(deep)))]`,
+//! `base` runs before `deep` when the feature is enabled, and a decorator of the first attribute wraps the decorator
+//! of the second one. The attribute of another macro between them splits them, see
+//! [Combining with Other Macros](#combining-with-other-macros).
+//!
+//! An entry hook that panics ends the call where it is, like a panicking body: nothing after it in the order above
+//! runs, in its attribute or in the attributes above it, and in particular none of their exit, error or slow call
+//! hooks. Nothing before it is undone either. The initialization is complete, the call counters of its attribute and
+//! of the attributes above it have counted the call, and the entry hooks of the attributes above it have run. Their
+//! values, guards included, were dropped as soon as each hook returned, so none is left to drop. What the
+//! instrumentation holds itself is dropped as the panic unwinds, in reverse order, without running anything but the
+//! panic hooks and the always-run exit hooks of the attributes above, whose guards are set up before their decorators:
+//! tracing spans are exited, and the guards of `raw_body` exit hooks do not run them. A decorator of an attribute above
+//! sees the panic as coming from the function it wraps, and may catch it like any other; the call then goes on from the
+//! decorator's return, with `__axin_body_ran` set, since the decorator called the function.
+
+extern crate self as axin;
+
+pub use axin_macros::{axin, axin_pair, axin_use};
+
+mod counter;
+mod ctx;
+mod event;
+mod hook;
+mod order;
+pub mod prelude;
+mod profile;
+mod state;
+pub mod test_support;
+mod timing;
+
+pub use counter::CallCounter;
+pub use ctx::AxinCtx;
+pub use event::event_id;
+pub use hook::HookResult;
+pub use order::take_order_trace;
+pub use profile::{set_profile_sinks, ProfileSinks};
+pub use timing::PhaseTimings;
+
+/// Items used by the code generated by the [`axin`](macro@axin) macro. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::order::{assert_order, order_mark, order_start};
+    pub use crate::profile::{profile_record, profile_slow};
+    pub use crate::state::FnState;
+    pub use core::cell::Cell;
+    pub use core::sync::atomic::{AtomicU64, Ordering};
+    pub use core::time::Duration;
+    pub use std::time::Instant;
+    #[cfg(feature = "tracing")]
+    pub use tracing;
+
+    /// Return `future` unchanged, fixing its output type to `T`, so that `?` in an async body knows its error type.
+    pub fn typed_future<T, F: core::future::Future<Output = T>>(future: F) -> F {
+        future
+    }
+
+    /// Runs the exit hook of a `raw_body` function when dropped at the end of its body, however the body returns. Like
+    /// other exit hooks, it is not run if the body panics, but it is if the function is called while the thread is
+    /// panicking already, such as by a destructor run as another panic unwinds, and returns.
+    pub struct ExitGuard<F: FnOnce()> {
+        hook: Option<F>,
+        panicking: bool,
+    }
+
+    impl<F: FnOnce()> ExitGuard<F> {
+        pub fn new(hook: F) -> Self {
+            ExitGuard {
+                hook: Some(hook),
+                panicking: std::thread::panicking(),
+            }
+        }
+    }
+
+    impl<F: FnOnce()> Drop for ExitGuard<F> {
+        fn drop(&mut self) {
+            if let Some(hook) = self.hook.take() {
+                if self.panicking || !std::thread::panicking() {
+                    hook();
+                }
+            }
+        }
+    }
+
+    /// Runs the `on_panic` hook when dropped as the call unwinds, unless disarmed once the call returns. The thread is
+    /// not panicking when the guard is dropped with a future that is cancelled, or with the frame of a call made while
+    /// the thread is unwinding already, and returning.
+    pub struct PanicGuard<F: FnOnce()> {
+        hook: Option<F>,
+        panicking: bool,
+    }
+
+    impl<F: FnOnce()> PanicGuard<F> {
+        pub fn new(hook: F) -> Self {
+            PanicGuard {
+                hook: Some(hook),
+                panicking: std::thread::panicking(),
+            }
+        }
+
+        pub fn disarm(mut self) {
+            self.hook = None;
+        }
+    }
+
+    impl<F: FnOnce()> Drop for PanicGuard<F> {
+        fn drop(&mut self) {
+            if let Some(hook) = self.hook.take() {
+                if !self.panicking && std::thread::panicking() {
+                    hook();
+                }
+            }
+        }
+    }
+
+    /// Runs the `on_exit_always` hook when dropped, after the exit hook or as the call unwinds.
+    pub struct AlwaysGuard<F: FnOnce()> {
+        hook: Option<F>,
+    }
+
+    impl<F: FnOnce()> AlwaysGuard<F> {
+        pub fn new(hook: F) -> Self {
+            AlwaysGuard { hook: Some(hook) }
+        }
+    }
+
+    impl<F: FnOnce()> Drop for AlwaysGuard<F> {
+        fn drop(&mut self) {
+            if let Some(hook) = self.hook.take() {
+                hook();
+            }
+        }
+    }
+
+    /// Never called: returning its value from an async body behind `if false` fixes the body's output type to `T` before
+    /// the rest of the body is checked, so that its tail and `return` expressions are coerced to `T`, as in a function.
+    pub fn async_output<T>() -> T {
+        unreachable!()
     }
 }
@@ -0,0 +1,99 @@
+//! Traces of the phases of calls for the `assert_order` parameter.
+
+use std::cell::RefCell;
+
+/// The phases traced on a thread: those not taken yet, and those of each call checking its order that runs.
+struct Trace {
+    untaken: Vec<&'static str>,
+    frames: Vec<Vec<&'static str>>,
+}
+
+thread_local! {
+    static ORDER_TRACE: RefCell<Trace> = const {
+        RefCell::new(Trace {
+            untaken: Vec::new(),
+            frames: Vec::new(),
+        })
+    };
+}
+
+/// Take the phases traced on the current thread since the last call, in the order they ran.
+///
+/// Functions instrumented with `assert_order("...")` trace every phase of their calls in test builds, such as
+/// `"enter"`, `"body"` or `"exit"`, and each call asserts that it ran the given phases. The phases of calls made while a
+/// call runs, such as those of instrumented functions its body calls, are part of its trace, so that the order can be
+/// checked across several functions or stacked attributes. Taking the trace while calls run, e.g. in a test decorator or
+/// a function called by an instrumented one, leaves the traces of these calls whole. Nothing is traced outside of test
+/// builds.
+///
+/// ```
+/// use axin::{axin, take_order_trace};
+///
+/// fn trace() {}
+///
+/// #[axin(on_enter(trace), assert_order("enter,body"))]
+/// fn work() {}
+///
+/// // Called by a test
+/// fn work_enters_first() {
+///     work();
+///     assert_eq!(take_order_trace(), ["enter", "body"]);
+/// }
+/// ```
+pub fn take_order_trace() -> Vec<&'static str> {
+    ORDER_TRACE.with(|trace| std::mem::take(&mut trace.borrow_mut().untaken))
+}
+
+/// Trace that `phase` of a call runs.
+pub fn order_mark(phase: &'static str) {
+    ORDER_TRACE.with(|trace| {
+        let trace = &mut *trace.borrow_mut();
+        trace.untaken.push(phase);
+        for frame in &mut trace.frames {
+            frame.push(phase);
+        }
+    });
+}
+
+/// The trace of a call checking its order, from its start. Dropping it ends the trace, also when the call panics.
+pub struct OrderFrame {
+    depth: usize,
+}
+
+impl Drop for OrderFrame {
+    fn drop(&mut self) {
+        // Ignored when the thread-local is gone, as the thread exits
+        let _ = ORDER_TRACE.try_with(|trace| trace.borrow_mut().frames.truncate(self.depth));
+    }
+}
+
+/// Start the trace of a call.
+pub fn order_start() -> OrderFrame {
+    ORDER_TRACE.with(|trace| {
+        let frames = &mut trace.borrow_mut().frames;
+        frames.push(Vec::new());
+        OrderFrame {
+            depth: frames.len() - 1,
+        }
+    })
+}
+
+/// Assert that the phases traced since `start` are `expected`.
+#[track_caller]
+pub fn assert_order(start: OrderFrame, expected: &[&str], fn_name: &str) {
+    let actual = ORDER_TRACE.with(|trace| {
+        trace
+            .borrow_mut()
+            .frames
+            .get_mut(start.depth)
+            .map(std::mem::take)
+    });
+    let actual = actual.unwrap_or_default();
+    assert!(
+        actual == expected,
+        "`{}` ran the phases {:?}, expected {:?}",
+        fn_name,
+        actual,
+        expected
+    );
+}
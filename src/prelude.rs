@@ -0,0 +1,38 @@
+//! The types and traits instrumented code commonly refers to, for a single glob import.
+//!
+//! ```
+//! use axin::axin;
+//! use axin::prelude::*;
+//!
+//! static CALLS: CallCounter = CallCounter::new();
+//!
+//! fn trace(ctx: &AxinCtx) {
+//!     println!("call #{} of {}", ctx.call_id, ctx.fn_name);
+//! }
+//!
+//! #[axin(on_enter(trace(&__axin)), count_calls(CALLS))]
+//! fn work() {}
+//! #
+//! # fn main() {
+//! #     work();
+//! #     #[cfg(not(feature = "disable"))]
+//! #     assert_eq!(CALLS.get(), 1);
+//! # }
+//! ```
+//!
+//! The [`axin`](macro@crate::axin) attribute is not part of the prelude, so that glob-importing it next to the prelude
+//! of another crate exporting a macro of the same name is not ambiguous; import it by name. Functions such as
+//! [`set_profile_sinks`](crate::set_profile_sinks) and the [`test_support`](crate::test_support) module are not part of
+//! it either, and are referred to by their paths.
+//!
+//! # Stability
+//!
+//! Every item of the prelude is a re-export of an item at the crate root. Items are only removed from the prelude, or
+//! replaced by items of the same name, in releases that are breaking under Cargo's rules for semantic versioning, i.e.
+//! with a new `0.x` version before 1.0. Other releases may add types and traits, named after what they are in `axin`;
+//! names a module defines or imports by name take precedence over the glob import, so an addition only conflicts with
+//! an item of the same name glob-imported from another crate. Traits are imported by name, so that implementing them
+//! needs no further import.
+
+#[doc(no_inline)]
+pub use crate::{AxinCtx, CallCounter, HookResult, PhaseTimings, ProfileSinks};
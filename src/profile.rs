@@ -0,0 +1,89 @@
+//! Sinks for the `profile` parameter.
+
+use crate::{AxinCtx, PhaseTimings};
+use core::time::Duration;
+use std::sync::OnceLock;
+
+static SINKS: OnceLock<ProfileSinks> = OnceLock::new();
+
+/// The functions receiving what `profile` measures, registered once per process with [`set_profile_sinks`].
+///
+/// Both sinks get the call context of the call, whose `call_id` counts the calls of the function so far. Sinks left
+/// unset do nothing, as do both until sinks are registered.
+///
+/// ```
+/// use axin::{axin, set_profile_sinks, AxinCtx, PhaseTimings, ProfileSinks};
+/// use std::time::Duration;
+///
+/// fn record(ctx: &AxinCtx, timings: PhaseTimings) {
+///     println!("call #{} of {} took {:?}", ctx.call_id, ctx.fn_name, timings.total());
+/// }
+///
+/// fn warn_slow(ctx: &AxinCtx, elapsed: Duration) {
+///     eprintln!("{} was slow: {:?}", ctx.fn_name, elapsed);
+/// }
+///
+/// #[axin(profile(slow = 250ms))]
+/// fn work() {}
+///
+/// set_profile_sinks(ProfileSinks::new().record(record).slow(warn_slow)).unwrap();
+/// work();
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct ProfileSinks {
+    /// Called after every call with the time each phase took.
+    pub record: fn(&AxinCtx, PhaseTimings),
+    /// Called after calls slower than the function's threshold, with the time the decorator and the body took.
+    pub slow: fn(&AxinCtx, Duration),
+}
+
+impl ProfileSinks {
+    /// Sinks doing nothing.
+    pub const fn new() -> Self {
+        Self {
+            record: |_, _| {},
+            slow: |_, _| {},
+        }
+    }
+
+    /// Set the sink receiving the timings of every call.
+    pub const fn record(mut self, record: fn(&AxinCtx, PhaseTimings)) -> Self {
+        self.record = record;
+        self
+    }
+
+    /// Set the sink receiving slow calls.
+    pub const fn slow(mut self, slow: fn(&AxinCtx, Duration)) -> Self {
+        self.slow = slow;
+        self
+    }
+}
+
+impl Default for ProfileSinks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Register the sinks of all functions instrumented with `profile`, for the rest of the process.
+///
+/// Sinks can only be registered once, so this is meant to be called early in `main`, next to setting up logging.
+/// Registering them again returns the given sinks as the error, leaving the registered ones in place.
+pub fn set_profile_sinks(sinks: ProfileSinks) -> Result<(), ProfileSinks> {
+    SINKS.set(sinks)
+}
+
+/// Report the timings of a call to the registered sinks, for the code generated for `profile`.
+pub fn profile_record(ctx: &AxinCtx, timings: PhaseTimings) {
+    if let Some(sinks) = SINKS.get() {
+        (sinks.record)(ctx, timings);
+    }
+}
+
+/// Report a slow call to the registered sinks, for the code generated for `profile`.
+pub fn profile_slow(ctx: &AxinCtx, elapsed: Duration) {
+    if let Some(sinks) = SINKS.get() {
+        (sinks.slow)(ctx, elapsed);
+    }
+}
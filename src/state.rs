@@ -0,0 +1,63 @@
+//! Static state kept by instrumented functions, which tests can reset through `axin::test_support`.
+
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// The state of one instrumented function: whether its `init` expression ran, and how many times it was called, for
+/// the call context. Declared as a `static` in the function by the generated code.
+#[derive(Debug, Default)]
+pub struct FnState {
+    calls: AtomicU64,
+    initialized: AtomicBool,
+    init_lock: Mutex<()>,
+    registered: AtomicBool,
+}
+
+impl FnState {
+    pub const fn new() -> Self {
+        FnState {
+            calls: AtomicU64::new(0),
+            initialized: AtomicBool::new(false),
+            init_lock: Mutex::new(()),
+            registered: AtomicBool::new(false),
+        }
+    }
+
+    /// Run `init` unless it ran since the state was created or last reset. Concurrent first calls wait until it ran. An
+    /// `init` that panics has not run, and runs again on the next call.
+    pub fn init(&self, init: impl FnOnce()) {
+        if self.initialized.load(Ordering::Acquire) {
+            return;
+        }
+        let _lock = self
+            .init_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if !self.initialized.load(Ordering::Acquire) {
+            init();
+            self.initialized.store(true, Ordering::Release);
+        }
+    }
+
+    /// Count a call, returning its number, starting at 1.
+    pub fn next_call(&self) -> u64 {
+        self.calls.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Forget the calls so far, and that `init` ran.
+    pub fn reset(&self) {
+        let _lock = self
+            .init_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.calls.store(0, Ordering::Relaxed);
+        self.initialized.store(false, Ordering::Release);
+    }
+
+    /// Register `reset`, which resets this state, under the path of the function, unless it is registered already.
+    pub fn register(&self, path: &'static str, reset: fn()) {
+        if !self.registered.swap(true, Ordering::AcqRel) {
+            crate::test_support::register(path, reset);
+        }
+    }
+}
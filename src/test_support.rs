@@ -0,0 +1,91 @@
+//! Resetting the static state instrumented functions keep, so that tests do not depend on the order they run in.
+//!
+//! An instrumented function keeps state in a `static` of its own when it has an `init` expression, which runs on its
+//! first call only, or refers to the call context `__axin`, whose `call_id` counts its calls. With the `test-support`
+//! feature, such a function registers its state here on its first call, and gets an accessor
+//! `reset_<name>_axin_state()` next to it, with the same visibility. Without the feature, nothing is registered, and
+//! resetting does nothing.
+//!
+//! ```
+//! # #[cfg(feature = "test-support")]
+//! # fn main() {
+//! use axin::{axin, AxinCtx};
+//!
+//! fn log(ctx: &AxinCtx) {
+//!     println!("call {}", ctx.call_id);
+//! }
+//!
+//! #[axin(on_enter(log(&__axin)))]
+//! fn handler() {}
+//!
+//! handler();
+//! handler();
+//! reset_handler_axin_state();
+//! handler(); // prints "call 1" again
+//! # }
+//! # #[cfg(not(feature = "test-support"))]
+//! # fn main() {}
+//! ```
+//!
+//! [`reset_all`] resets the state of all functions called so far. Counters passed to `count_calls` are not the
+//! instrumentation's own, and are reset with [`CallCounter::reset`](crate::CallCounter::reset).
+//!
+//! Accessors are emitted for functions and associated functions, but not for methods taking `self`, nor in trait
+//! `impl` blocks, which cannot have items the trait does not declare; [`reset_all`] resets their state as well. An
+//! attribute applied to a method directly cannot tell whether its `impl` block implements a trait, so methods without
+//! visibility that refer to `Self` in their signature get no accessor either. The accessors are hidden from rustdoc. A function instrumented on both sides of the attribute of another macro, such as `#[cached]`,
+//! gets its accessor from the `axin` attributes below that attribute only, so that it gets one at most. The accessor
+//! resets the state of all functions of the same path, including the state kept for the attributes above, and of
+//! methods of the same name in the module.
+//!
+//! Enable the feature for tests only, as a dev-dependency:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! axin = { version = "0.1", features = ["test-support"] }
+//! ```
+
+use std::sync::{Mutex, PoisonError};
+
+/// A function resetting the state of an instrumented function, with the path of the instrumented function.
+type Reset = (&'static str, fn());
+
+/// The reset functions registered so far.
+static RESETS: Mutex<Vec<Reset>> = Mutex::new(Vec::new());
+
+pub(crate) fn register(path: &'static str, reset: fn()) {
+    RESETS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((path, reset));
+}
+
+/// The reset functions registered for which `select` holds, copied so that the lock is not held while they run.
+fn resets(select: impl Fn(&str) -> bool) -> Vec<fn()> {
+    RESETS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .filter(|(path, _)| select(path))
+        .map(|(_, reset)| *reset)
+        .collect()
+}
+
+/// Reset the state of all instrumented functions called so far.
+///
+/// The state is shared by all threads, so tests running in parallel that reset the state of the same functions
+/// should be serialized, e.g. with a `Mutex` they lock.
+pub fn reset_all() {
+    for reset in resets(|_| true) {
+        reset();
+    }
+}
+
+/// Reset the state of the instrumented functions at `path`, `module_path!()` followed by `::` and the name. Called by
+/// the generated `reset_<name>_axin_state()` accessors.
+#[doc(hidden)]
+pub fn reset(path: &str) {
+    for reset in resets(|registered| registered == path) {
+        reset();
+    }
+}
@@ -0,0 +1,54 @@
+//! Phase timings for the `phase_timing` parameter.
+
+use core::time::Duration;
+
+/// The time each phase of a call to an instrumented function took, passed to the hook given to `phase_timing(...)`
+/// after everything else the instrumentation does.
+///
+/// ```
+/// use axin::{axin, PhaseTimings};
+///
+/// fn report(timings: PhaseTimings) {
+///     println!("{:?} of {:?} in the body", timings.body, timings.total());
+/// }
+///
+/// #[axin(phase_timing(report))]
+/// fn work() {}
+///
+/// work();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PhaseTimings {
+    /// The time the entry hook took.
+    pub enter: Duration,
+    /// The time the decorator took outside of the function body. Without a decorator, this is only the overhead of
+    /// calling the body.
+    pub decorator_overhead: Duration,
+    /// The time the function body took, including its prologue.
+    pub body: Duration,
+    /// The time the hooks after the body took, from the return value assertion to the exit hook.
+    pub exit: Duration,
+}
+
+impl PhaseTimings {
+    #[doc(hidden)]
+    pub const fn new(
+        enter: Duration,
+        decorator_overhead: Duration,
+        body: Duration,
+        exit: Duration,
+    ) -> Self {
+        Self {
+            enter,
+            decorator_overhead,
+            body,
+            exit,
+        }
+    }
+
+    /// The time of all phases together.
+    pub fn total(&self) -> Duration {
+        self.enter + self.decorator_overhead + self.body + self.exit
+    }
+}
@@ -0,0 +1,191 @@
+//! The generated code allocates nothing: instrumented functions with hooks, predicates, prologue statements and
+//! decorators, and with the built-ins measuring and counting calls, allocate exactly as much as the uninstrumented
+//! function, counted by a global allocator. Features that may allocate, such as `assert_order` or `tracing` spans, are
+//! opt-in and not covered here.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, AxinCtx, CallCounter, PhaseTimings};
+use common::block_on;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Counts the allocations of threads that asked for it, leaving the test harness's own threads out.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations `f` makes on this thread when called 1000 times.
+fn allocations(mut f: impl FnMut()) -> usize {
+    // A first call outside of the count, for anything initialized once
+    f();
+    COUNTING.with(|counting| counting.set(true));
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..1000 {
+        f();
+    }
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|counting| counting.set(false));
+    after - before
+}
+
+fn enter(x: u64) {
+    black_box(x);
+}
+
+fn exit() {
+    black_box(());
+}
+
+fn with_context(ctx: &AxinCtx) {
+    black_box(ctx);
+}
+
+fn report<E>(error: &E) {
+    black_box(error);
+}
+
+fn timing<F: FnOnce(u64) -> R, R>(f: F, x: u64) -> R {
+    f(black_box(x))
+}
+
+fn scaled<F: FnOnce(u64) -> u64>(factor: u64) -> impl FnOnce(F, u64) -> u64 {
+    move |f, x| f(x) * factor
+}
+
+fn adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+fn slow(elapsed: Duration) {
+    black_box(elapsed);
+}
+
+fn timings(timings: PhaseTimings) {
+    black_box(timings);
+}
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn plain(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(
+    prologue(let y = x * 2;),
+    on_enter(enter(x), when = x < u64::MAX),
+    on_exit(exit, when = *result > 0)
+)]
+fn hooked(x: u64) -> u64 {
+    y / 2 + 1
+}
+
+#[axin(decorator(timing), on_enter(with_context(&__axin)), on_exit(exit))]
+fn decorated(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(decorator(scaled(1)), on_exit(exit))]
+fn parameterized(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(decorator(adapted), adapt, on_error(report))]
+fn fallible(x: u64) -> Result<u64, u64> {
+    if x > 0 {
+        Ok(x + 1)
+    } else {
+        Err(x)
+    }
+}
+
+#[axin(decorator_select(x % 2, [timing, timing]))]
+fn selected(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(on_enter(enter(x)), on_exit(exit))]
+async fn asynchronous(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(
+    count_calls(CALLS),
+    event_id = auto,
+    slow_log(Duration::from_secs(60), slow),
+    on_enter(enter(__axin_event_id as u64))
+)]
+fn counted(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(phase_timing(timings), on_exit(exit, when = elapsed < Duration::from_secs(60)))]
+fn measured(x: u64) -> u64 {
+    x + 1
+}
+
+#[axin(profile)]
+fn profiled(x: u64) -> u64 {
+    x + 1
+}
+
+#[test]
+fn the_baseline_allocates_nothing() {
+    assert_eq!(allocations(|| assert_eq!(plain(black_box(1)), 2)), 0);
+    // While allocations are indeed counted
+    assert_eq!(allocations(|| drop(black_box(vec![1u8]))), 1000);
+}
+
+#[test]
+fn hooks_and_decorators_allocate_nothing() {
+    assert_eq!(allocations(|| assert_eq!(hooked(black_box(1)), 2)), 0);
+    assert_eq!(allocations(|| assert_eq!(decorated(black_box(1)), 2)), 0);
+    assert_eq!(
+        allocations(|| assert_eq!(parameterized(black_box(1)), 2)),
+        0
+    );
+    assert_eq!(allocations(|| assert_eq!(fallible(black_box(1)), Ok(2))), 0);
+    assert_eq!(allocations(|| assert_eq!(selected(black_box(1)), 2)), 0);
+    let future = || asynchronous(black_box(1));
+    assert_eq!(allocations(|| assert_eq!(block_on(future()), 2)), 0);
+}
+
+#[test]
+fn built_ins_allocate_nothing() {
+    assert_eq!(allocations(|| assert_eq!(counted(black_box(1)), 2)), 0);
+    assert_eq!(allocations(|| assert_eq!(measured(black_box(1)), 2)), 0);
+    assert_eq!(allocations(|| assert_eq!(profiled(black_box(1)), 2)), 0);
+}
@@ -0,0 +1,107 @@
+//! Hook and decorator arguments written as expressions that contain commas of their own, such as macro calls, closures,
+//! `match` expressions and struct literals, or attributes, which must reach the hook as the arguments written.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn log(detail: String, message: &str) {
+    record(format!("{} {}", message, detail));
+}
+
+fn log_all(parts: Vec<&str>) {
+    record(parts.join("|"));
+}
+
+fn extra_detail() -> String {
+    "detail".to_string()
+}
+
+fn labeled<F: FnOnce(u32) -> R, R>(
+    label: String,
+    pick: impl Fn(u32, u32) -> u32,
+) -> impl FnOnce(F, u32) -> R {
+    move |f, x| {
+        record(format!("{} {}", label, pick(x, 10)));
+        f(x)
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    verbose: bool,
+    depth: u8,
+}
+
+fn describe(options: Options, level: u8) {
+    record(format!("{} {} {}", options.verbose, options.depth, level));
+}
+
+// Attributes on arguments apply to them as in any call
+#[axin(on_enter(log(#[cfg(debug_assertions)] extra_detail(), #[cfg(not(debug_assertions))] "release".into(), "msg")))]
+fn attributed() -> u32 {
+    1
+}
+
+// Macro calls with commas and parentheses in strings, and brace-delimited macro calls
+#[axin(on_enter(log(format!("a, b ({})", x), "fmt")), on_exit(log_all(vec! { "x", "y" })))]
+fn macros(x: u32) -> u32 {
+    x
+}
+
+// Closures with several parameters, and `match` expressions with several arms
+#[axin(
+    decorator(labeled(
+        match x % 3 { 0 => "zero".to_string(), n => format!("rest {}", n) },
+        |a, b| a.max(b),
+    )),
+    on_exit(log_all(vec![match x { 4 => "four", _ => "other" }, "done"]))
+)]
+fn closures(x: u32) -> u32 {
+    x + 1
+}
+
+// Struct literals, with and without functional update
+#[axin(on_enter(describe(Options { verbose: true, ..Default::default() }, 2)), on_exit(describe(Options { verbose: false, depth: 3 }, 4)))]
+fn literals() {}
+
+#[test]
+fn attributes_and_macros() {
+    assert_eq!(attributed(), 1);
+    assert_eq!(macros(7), 7);
+    let detail = if cfg!(debug_assertions) {
+        "detail"
+    } else {
+        "release"
+    };
+    assert_eq!(
+        take_events(),
+        [
+            format!("msg {}", detail),
+            "fmt a, b (7)".to_string(),
+            "x|y".to_string()
+        ]
+    );
+}
+
+#[test]
+fn closures_matches_and_literals() {
+    assert_eq!(closures(4), 5);
+    assert_eq!(closures(12), 13);
+    literals();
+    assert_eq!(
+        take_events(),
+        [
+            "rest 1 10",
+            "four|done",
+            "zero 12",
+            "other|done",
+            "true 0 2",
+            "false 3 4"
+        ]
+    );
+}
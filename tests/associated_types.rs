@@ -0,0 +1,138 @@
+//! Generic functions whose `where` clauses bound associated types of their parameters, with those associated types in
+//! parameter and return positions, instrumented together with parameterized decorators.
+
+#![cfg(not(feature = "disable"))]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+
+trait Store {
+    type Key;
+    type Output;
+
+    fn load(&self, key: Self::Key) -> Self::Output;
+}
+
+/// A store of numbers, keyed by their position.
+#[derive(Clone)]
+struct Numbers(Vec<u32>);
+
+impl Store for Numbers {
+    type Key = usize;
+    type Output = Option<u32>;
+
+    fn load(&self, key: usize) -> Option<u32> {
+        self.0.get(key).copied()
+    }
+}
+
+/// A store of names, keyed by themselves.
+struct Names;
+
+impl Store for Names {
+    type Key = &'static str;
+    type Output = String;
+
+    fn load(&self, key: &'static str) -> String {
+        key.to_uppercase()
+    }
+}
+
+fn enter(name: &str) {
+    record(format!("enter {}", name));
+}
+
+fn exit() {
+    record("exit".to_string());
+}
+
+/// A parameterized decorator forwarding a store and a key, which only knows about them what the function's bounds
+/// say.
+fn traced<F, S, R>(label: &'static str) -> impl FnOnce(F, S, S::Key) -> R
+where
+    F: FnOnce(S, S::Key) -> R,
+    S: Store,
+    S::Key: Debug,
+{
+    move |f: F, store: S, key: S::Key| {
+        record(format!("{} {:?}", label, key));
+        f(store, key)
+    }
+}
+
+/// A parameterized decorator for functions adapted to take no arguments.
+fn labeled<F: FnOnce() -> R, R>(label: &'static str) -> impl FnOnce(F) -> R {
+    move |f: F| {
+        record(format!("{} start", label));
+        f()
+    }
+}
+
+#[axin(on_enter(enter("fetch")), decorator(traced("fetch")), on_exit(exit))]
+fn fetch<S>(store: S, key: S::Key) -> S::Output
+where
+    S: Store,
+    S::Key: Debug + Clone,
+    S::Output: Debug + Send,
+{
+    let output = store.load(key.clone());
+    record(format!("loaded {:?} = {:?}", key, output));
+    output
+}
+
+/// Runs lookups against a store, with the associated types appearing only in the methods' `where` clauses.
+struct Runner {
+    name: &'static str,
+}
+
+impl Runner {
+    #[axin(on_enter(enter(self.name)), decorator(labeled("run")), adapt)]
+    fn run<S>(&self, store: S, keys: Vec<S::Key>) -> Vec<S::Output>
+    where
+        S: Store,
+        S::Output: Send,
+    {
+        keys.into_iter().map(|key| store.load(key)).collect()
+    }
+
+    #[axin(on_enter(enter(self.name)), decorator(labeled("first")), adapt)]
+    fn first<'a, S: Store + 'a>(&self, stores: &'a [S], key: S::Key) -> Option<S::Output>
+    where
+        S::Key: Copy,
+    {
+        stores.first().map(|store| store.load(key))
+    }
+}
+
+#[test]
+fn associated_types_in_parameters_and_return_type() {
+    assert_eq!(fetch(Numbers(vec![1, 2, 3]), 1), Some(2));
+    assert_eq!(fetch(Names, "ada"), "ADA");
+    assert_eq!(
+        take_events(),
+        [
+            "enter fetch",
+            "fetch 1",
+            "loaded 1 = Some(2)",
+            "exit",
+            "enter fetch",
+            "fetch \"ada\"",
+            "loaded \"ada\" = \"ADA\"",
+            "exit",
+        ]
+    );
+}
+
+#[test]
+fn associated_types_in_method_where_clauses() {
+    let runner = Runner { name: "runner" };
+    assert_eq!(runner.run(Numbers(vec![4, 5]), vec![1, 2]), [Some(5), None]);
+    assert_eq!(runner.first(&[Names], "bob"), Some("BOB".to_string()));
+    assert_eq!(
+        take_events(),
+        ["enter runner", "run start", "enter runner", "first start"]
+    );
+}
@@ -0,0 +1,67 @@
+//! Attributes and doc comments of instrumented functions, which the expansion keeps as written: a `#[no_mangle]`
+//! function is still exported under its own name, and its twin, which cannot share the symbol, is not.
+//!
+//! With warnings denied, as in the other test crates, lints allowed on the function must keep applying to it.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn forward<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    record("decorator");
+    f(x)
+}
+
+/// Double a value, for C callers.
+#[axin(
+    on_enter(record("enter")),
+    decorator(forward),
+    twin = "axin_double_fast"
+)]
+#[no_mangle]
+pub extern "C" fn axin_double(x: u32) -> u32 {
+    x * 2
+}
+
+#[axin(on_exit(record("exit")))]
+#[export_name = "axin_triple_exported"]
+pub extern "C" fn axin_triple(x: u32) -> u32 {
+    x * 3
+}
+
+// Declared again under the exported names, so that calls go through the symbols
+mod exported {
+    extern "C" {
+        pub fn axin_double(x: u32) -> u32;
+        pub fn axin_triple_exported(x: u32) -> u32;
+    }
+}
+
+#[axin(on_enter(record("enter")))]
+#[inline(always)]
+#[allow(non_snake_case)]
+fn Quadruple(x: u32) -> u32 {
+    x * 4
+}
+
+#[test]
+fn symbols_are_exported_under_their_names() {
+    unsafe {
+        assert_eq!(exported::axin_double(2), 4);
+        assert_eq!(exported::axin_triple_exported(2), 6);
+    }
+    assert_eq!(take_events(), ["enter", "decorator", "exit"]);
+
+    assert_eq!(axin_double_fast(3), 6);
+    assert!(take_events().is_empty());
+}
+
+#[test]
+fn lint_attributes_apply_to_the_expansion() {
+    assert_eq!(Quadruple(2), 8);
+    assert_eq!(take_events(), ["enter"]);
+}
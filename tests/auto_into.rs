@@ -0,0 +1,90 @@
+//! Hooks given `auto_into`, whose literal arguments are converted to the hook's parameter types, so that one string
+//! literal in the attribute fits hooks taking `String`, `&str` or `Cow<str>` alike.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+fn owned(message: String) {
+    record(format!("owned {}", message));
+}
+
+fn borrowed(message: &str) {
+    record(format!("borrowed {}", message));
+}
+
+fn cow(message: Cow<'static, str>) {
+    let kind = match message {
+        Cow::Borrowed(_) => "borrowed",
+        Cow::Owned(_) => "owned",
+    };
+    record(format!("cow {} {}", kind, message));
+}
+
+fn detailed(label: String, path: PathBuf, code: u64, ratio: f32, id: i64) {
+    record(format!(
+        "{} {} {} {} {}",
+        label,
+        path.display(),
+        code,
+        ratio,
+        id
+    ));
+}
+
+fn failed<E: std::fmt::Debug>(context: String, error: &E) {
+    record(format!("{}: {:?}", context, error));
+}
+
+#[axin(
+    on_enter(owned("call"), auto_into),
+    on_exit(borrowed("call"), auto_into)
+)]
+fn owned_and_borrowed() {}
+
+#[axin(on_enter(cow("call"), auto_into))]
+fn copy_on_write() {}
+
+// Unsuffixed numbers keep the parameter's type, suffixed ones are converted, and other expressions are left alone
+#[axin(
+    on_enter(detailed("call", "/tmp/log", 7, 0.5, 3i32), auto_into),
+    on_exit(borrowed(label), auto_into)
+)]
+fn mixed(label: &str) -> usize {
+    label.len()
+}
+
+#[axin(on_error(failed("parsing failed"), auto_into, errors = "ignore"))]
+fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    input.parse()
+}
+
+#[test]
+fn one_literal_fits_every_string_type() {
+    owned_and_borrowed();
+    copy_on_write();
+    assert_eq!(
+        take_events(),
+        ["owned call", "borrowed call", "cow borrowed call"]
+    );
+}
+
+#[test]
+fn only_literals_are_converted() {
+    assert_eq!(mixed("done"), 4);
+    assert!(parse("x").is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "call /tmp/log 7 0.5 3",
+            "borrowed done",
+            "parsing failed: ParseIntError { kind: InvalidDigit }"
+        ]
+    );
+}
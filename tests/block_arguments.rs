@@ -0,0 +1,129 @@
+//! `async` and `unsafe` blocks as arguments of decorators and hooks. Like any other argument, they are evaluated on
+//! every call, where the decorator is set up or the hook called: an `async` block creates a new future each time, which
+//! only runs when the decorator awaits it.
+//!
+//! With warnings denied, as in the other test crates, the expansion must not introduce unnecessary `unsafe` blocks
+//! either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+use std::future::Future;
+use std::pin::Pin;
+
+async fn load_offset() -> u32 {
+    record("load");
+    10
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = u32>>>;
+
+/// A decorator awaiting its configuration before calling the function.
+fn with_init<F, Fut>(init: impl Future<Output = u32> + 'static) -> impl FnOnce(F, u32) -> BoxFuture
+where
+    F: FnOnce(u32) -> Fut + 'static,
+    Fut: Future<Output = u32>,
+{
+    record("setup");
+    move |f, x| {
+        Box::pin(async move {
+            let offset = init.await;
+            f(x + offset).await
+        })
+    }
+}
+
+/// A decorator of synchronous functions, configured with a string.
+fn with_raw<F: FnOnce(u32) -> u32>(label: &'static str) -> impl FnOnce(F, u32) -> u32 {
+    move |f, x| {
+        record(label);
+        f(x)
+    }
+}
+
+fn enter() {
+    record("enter");
+}
+
+fn log(value: u32) {
+    record(format!("log {}", value));
+}
+
+fn poll_now(future: impl Future<Output = u32>) {
+    record(format!("polled {}", block_on(future)));
+}
+
+static RAW: &[u8] = b"raw";
+
+fn raw_value() -> *const u32 {
+    static VALUE: u32 = 7;
+    &VALUE
+}
+
+#[axin(on_enter(enter), decorator(with_init(async { load_offset().await })))]
+async fn offset(x: u32) -> u32 {
+    record("body");
+    x
+}
+
+// A parameter is copied into the block, which outlives the setup in the decorator returned
+#[axin(decorator(with_init(async move { x * 2 })))]
+async fn doubled(x: u32) -> u32 {
+    x + 1
+}
+
+#[axin(decorator(with_raw(unsafe { std::str::from_utf8_unchecked(RAW) })))]
+fn raw(x: u32) -> u32 {
+    x + 1
+}
+
+// Predicates may start with a block too
+#[axin(
+    on_enter(log(unsafe { *raw_value() }), when = unsafe { *raw_value() } > 0),
+    on_exit(poll_now(async move { x + 1 }), when = x > 0),
+    decorator(with_raw(unsafe { std::str::from_utf8_unchecked(RAW) }))
+)]
+fn hooked(x: u32) -> u32 {
+    x
+}
+
+// In an `unsafe fn`, blocks in the arguments are still needed for unsafe operations, and not unused
+#[axin(on_enter(log(unsafe { *raw_value() })))]
+unsafe fn raw_read(pointer: *const u32) -> u32 {
+    unsafe { *pointer }
+}
+
+#[test]
+fn async_blocks_are_created_on_every_call() {
+    assert_eq!(block_on(offset(1)), 11);
+    assert_eq!(block_on(offset(2)), 12);
+    assert_eq!(
+        take_events(),
+        ["enter", "setup", "load", "body", "enter", "setup", "load", "body"]
+    );
+
+    // The future is created when the function is called, but only runs when awaited
+    let future = offset(3);
+    assert!(take_events().is_empty());
+    assert_eq!(block_on(future), 13);
+    assert_eq!(take_events(), ["enter", "setup", "load", "body"]);
+
+    assert_eq!(block_on(doubled(4)), 13);
+    assert_eq!(take_events(), ["setup"]);
+}
+
+#[test]
+fn unsafe_blocks_as_arguments() {
+    assert_eq!(raw(1), 2);
+    assert_eq!(hooked(1), 1);
+    assert_eq!(hooked(0), 0);
+    assert_eq!(unsafe { raw_read(raw_value()) }, 7);
+    assert_eq!(
+        take_events(),
+        ["raw", "log 7", "raw", "polled 2", "log 7", "raw", "log 7"]
+    );
+}
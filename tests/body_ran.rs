@@ -0,0 +1,85 @@
+//! Exit hooks seeing whether the body ran, through `__axin_body_ran`: with decorators calling it, short-circuiting
+//! without calling it, or catching its panic, in predicates, and without decorators.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+thread_local! {
+    static CACHE: RefCell<HashMap<u32, u32>> = RefCell::new(HashMap::new());
+}
+
+fn lookup(hit: bool) {
+    record(if hit { "miss" } else { "hit" });
+}
+
+/// Returns cached results without calling the function.
+fn cached<F: FnOnce(u32) -> u32>(f: F, key: u32) -> u32 {
+    if let Some(value) = CACHE.with(|cache| cache.borrow().get(&key).copied()) {
+        return value;
+    }
+    let value = f(key);
+    CACHE.with(|cache| cache.borrow_mut().insert(key, value));
+    value
+}
+
+/// Returns `None` instead of panicking.
+fn catching<F: FnOnce() -> Option<u32>>(f: F) -> Option<u32> {
+    catch_unwind(AssertUnwindSafe(f)).ok().flatten()
+}
+
+#[axin(decorator(cached), on_exit(lookup(__axin_body_ran)))]
+fn square(x: u32) -> u32 {
+    record("body");
+    x * x
+}
+
+#[axin(decorator(cached), on_exit(record("computed"), when = __axin_body_ran && *result > 10))]
+fn cube(x: u32) -> u32 {
+    x * x * x
+}
+
+#[axin(decorator(catching), adapt, on_exit(lookup(__axin_body_ran)))]
+fn fragile(fail: bool) -> Option<u32> {
+    if fail {
+        panic!("failed");
+    }
+    Some(1)
+}
+
+#[axin(on_exit(lookup(__axin_body_ran)))]
+fn undecorated() {}
+
+#[axin(raw_body, on_exit(lookup(__axin_body_ran)))]
+fn raw() {}
+
+#[test]
+fn short_circuiting_decorators_skip_the_body() {
+    assert_eq!(square(3), 9);
+    assert_eq!(square(3), 9);
+    assert_eq!(take_events(), ["body", "miss", "hit"]);
+
+    assert_eq!(cube(2) + cube(4) + cube(4), 136);
+    assert_eq!(take_events(), ["computed"]);
+}
+
+#[test]
+fn bodies_caught_panicking_ran() {
+    assert_eq!(fragile(false), Some(1));
+    assert_eq!(fragile(true), None);
+    assert_eq!(take_events(), ["miss", "miss"]);
+}
+
+#[test]
+fn bodies_without_decorators_always_run() {
+    undecorated();
+    raw();
+    assert_eq!(take_events(), ["miss", "miss"]);
+}
@@ -0,0 +1,68 @@
+//! `axin` combined with the `#[cached]` attribute of the `cached` crate. Macros apply in the order of their attributes:
+//! `axin` below `#[cached]` instruments the caching function, whose hooks run on every call, and `axin` above it
+//! instruments the function as written, which `#[cached]` only calls on a cache miss. `axin` attributes below the other
+//! attribute are not folded into those above it.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, CallCounter};
+use cached::proc_macro::cached;
+use common::{record, take_events};
+
+#[cached]
+#[axin(on_enter(record("enter")), on_exit(record("exit")))]
+fn square(x: u64) -> u64 {
+    record(format!("square {}", x));
+    x * x
+}
+
+#[axin(on_enter(record("enter")), on_exit(record("exit")))]
+#[cached]
+fn cube(x: u64) -> u64 {
+    record(format!("cube {}", x));
+    x * x * x
+}
+
+static MISSES: CallCounter = CallCounter::new();
+static CALLS: CallCounter = CallCounter::new();
+
+/// Computes the double of `x`, once.
+#[axin(count_calls(MISSES))]
+#[cached(size = 8)]
+#[inline]
+#[axin(count_calls(CALLS))]
+fn double(x: u64) -> u64 {
+    x * 2
+}
+
+#[test]
+fn hooks_below_cached_run_on_every_call() {
+    assert_eq!(square(3), 9);
+    assert_eq!(square(3), 9);
+    assert_eq!(
+        take_events(),
+        ["enter", "square 3", "exit", "enter", "exit"]
+    );
+}
+
+#[test]
+fn hooks_above_cached_run_on_cache_misses() {
+    assert_eq!(cube(2), 8);
+    assert_eq!(cube(2), 8);
+    assert_eq!(cube(3), 27);
+    assert_eq!(
+        take_events(),
+        ["enter", "cube 2", "exit", "enter", "cube 3", "exit"]
+    );
+}
+
+#[test]
+fn stacked_attributes_keep_their_place_around_cached() {
+    for _ in 0..3 {
+        assert_eq!(double(21), 42);
+    }
+    assert_eq!((MISSES.get(), CALLS.get()), (1, 3));
+}
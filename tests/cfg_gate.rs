@@ -0,0 +1,91 @@
+//! Instrumentation restricted with `cfg(...)` to the builds a configuration predicate holds for, such as debug builds
+//! or builds with a feature of this crate, leaving the function as written in all others.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+// Hooks only compiled where the instrumentation referring to them is
+#[cfg(feature = "tracing")]
+mod spans {
+    pub fn enter(name: &str) {
+        super::record(format!("span {}", name));
+    }
+}
+
+#[cfg(debug_assertions)]
+mod checks {
+    pub fn timing<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+        super::record("timing");
+        f(x)
+    }
+}
+
+#[axin(cfg(feature = "tracing"), on_enter(spans::enter("load")))]
+fn load(id: u32) -> u32 {
+    record("body");
+    id
+}
+
+#[axin(cfg(debug_assertions), decorator(checks::timing), order_const)]
+fn checked(x: u32) -> u32 {
+    x + 1
+}
+
+// The full predicate grammar, combined with `debug_only`, which must hold as well
+#[axin(
+    cfg(all(test, not(feature = "tracing"), any(unix, windows, target_os = "none"))),
+    debug_only,
+    on_exit(record("exit"))
+)]
+fn combined() {}
+
+// A stacked attribute with a predicate that never holds leaves the others alone
+#[axin(on_enter(record("outer")), order_const)]
+#[axin(cfg(any()), on_enter(missing::hook))]
+fn layered() {}
+
+#[test]
+fn feature_gated_instrumentation_applies_with_the_feature() {
+    assert_eq!(load(1), 1);
+    let expected: &[&str] = if cfg!(feature = "tracing") {
+        &["span load", "body"]
+    } else {
+        &["body"]
+    };
+    assert_eq!(take_events(), expected);
+}
+
+#[test]
+fn debug_gated_instrumentation_applies_in_debug_builds() {
+    assert_eq!(checked(1), 2);
+    let (events, order): (&[&str], &[&str]) = if cfg!(debug_assertions) {
+        (&["timing"], &["decorator:checks::timing", "body"])
+    } else {
+        (&[], &["body"])
+    };
+    assert_eq!(take_events(), events);
+    assert_eq!(CHECKED_AXIN_ORDER, order);
+}
+
+#[test]
+fn predicates_are_combined() {
+    combined();
+    let expected: &[&str] = if cfg!(all(debug_assertions, not(feature = "tracing"))) {
+        &["exit"]
+    } else {
+        &[]
+    };
+    assert_eq!(take_events(), expected);
+}
+
+#[test]
+fn layers_whose_predicate_does_not_hold_are_left_out() {
+    layered();
+    assert_eq!(take_events(), ["outer"]);
+    assert_eq!(LAYERED_AXIN_ORDER, ["enter:record", "body"]);
+}
@@ -0,0 +1,46 @@
+//! Helpers shared by the integration tests, included with `mod common;` by the test crates using them.
+//!
+//! The test crates of instrumented functions deny warnings, so that the expansion must not introduce unused bindings
+//! or the like; a test crate checking that it does not introduce some other warning says so. Since every test crate
+//! uses only some of the helpers, unused ones are allowed.
+
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Record that something happened on the current thread, for the test to check with [`take_events`].
+pub fn record(event: impl Into<String>) {
+    EVENTS.with(|events| events.borrow_mut().push(event.into()));
+}
+
+/// Take the events recorded on the current thread so far, in order.
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+/// Poll a future to completion on the current thread. The waker does nothing, so a pending future is polled again right
+/// away. The future is pinned on the stack, so that polling it allocates nothing.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: the vtable functions ignore the data pointer, so any pointer is valid for them.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Bodies with labeled loops and blocks, `return` inside nested closures and async blocks, and `?` inside closures,
+//! instrumented with every kind of wrapping. Labels, `return` and `?` must keep referring to what they refer to in the
+//! original body: a `return` in a closure returns from the closure, not from the function.
+//!
+//! With warnings denied, as in the other test crates, the expansion must not introduce unreachable code either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, CallCounter};
+use common::{block_on, record, take_events};
+use std::future::Future;
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn enter() {
+    record("enter");
+}
+
+fn exit() {
+    record("exit");
+}
+
+fn failed<E>(_: &E) {
+    record("failed");
+}
+
+fn slow(_: Duration) {
+    record("slow");
+}
+
+fn forward<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("decorator");
+    f(value)
+}
+
+fn forward_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+fn forward_borrowing<F: FnOnce(T) -> R, T, R>(_: &T) -> impl FnOnce(F, T) -> R {
+    |f: F, value: T| {
+        record("decorator");
+        f(value)
+    }
+}
+
+async fn forward_async<F: FnOnce(T) -> Fut, Fut: Future<Output = R>, T, R>(f: F, value: T) -> R {
+    record("decorator");
+    f(value).await
+}
+
+const GRID: [[i32; 3]; 3] = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+
+/// Define a module of functions with tricky control flow, each instrumented with the given attribute arguments, and a
+/// test checking their results and the events they recorded per call.
+macro_rules! torture {
+    ($name:ident, [$($events:literal),*], $($args:tt)*) => {
+        #[axin($($args)*)]
+        mod $name {
+            use super::*;
+
+            /// A value breaking out of nested loops by label.
+            pub fn find(target: i32) -> Option<(usize, usize)> {
+                let mut passes = 0;
+                let found = 'outer: loop {
+                    passes += 1;
+                    for (i, row) in GRID.iter().enumerate() {
+                        for (j, &value) in row.iter().enumerate() {
+                            if value == target {
+                                break 'outer Some((i, j));
+                            }
+                        }
+                    }
+                    if passes == 2 {
+                        break None;
+                    }
+                };
+                found
+            }
+
+            /// `return` in closures returns from the closure.
+            pub fn evens(values: Vec<i32>) -> Vec<i32> {
+                let keep = |value: &i32| {
+                    if value % 2 != 0 {
+                        return false;
+                    }
+                    true
+                };
+                let doubled: Vec<i32> = values
+                    .into_iter()
+                    .filter(keep)
+                    .map(|value| {
+                        if value > 100 {
+                            return value;
+                        }
+                        value * 2
+                    })
+                    .collect();
+                if doubled.is_empty() {
+                    return vec![0];
+                }
+                doubled
+            }
+
+            /// `?` in closures returns from the closure, and `?` in the body from the function.
+            pub fn parse_all(inputs: Vec<&'static str>) -> Result<Vec<i32>, String> {
+                let values = inputs
+                    .iter()
+                    .map(|input| -> Result<i32, String> {
+                        let value = input.trim().parse::<i32>().map_err(|error| error.to_string())?;
+                        Ok(value)
+                    })
+                    .filter_map(|parsed| parsed.ok())
+                    .collect::<Vec<_>>();
+                let first = values.first().ok_or("nothing parsed")?;
+                Ok(vec![*first, values.len() as i32])
+            }
+
+            /// Labeled blocks, `continue` by label and a `return` in an async block.
+            pub fn classify(limit: i32) -> &'static str {
+                let mut count = 0;
+                'rows: for row in GRID {
+                    for value in row {
+                        if value > limit {
+                            continue 'rows;
+                        }
+                        count += 1;
+                    }
+                }
+                let pending = async move {
+                    if count == 0 {
+                        return "none";
+                    }
+                    "some"
+                };
+                let label = 'label: {
+                    if count == 9 {
+                        break 'label "all";
+                    }
+                    block_on(pending)
+                };
+                label
+            }
+
+            /// Inner attributes apply to the whole body.
+            pub fn absolute(value: i32) -> i32 {
+                #![allow(unused_variables)]
+                let unused = value;
+                'block: {
+                    if value < 0 {
+                        break 'block -value;
+                    }
+                    value
+                }
+            }
+        }
+
+        #[test]
+        fn $name() {
+            let per_call: &[&str] = &[$($events),*];
+            let events_of = |calls: usize| -> Vec<&str> { per_call.repeat(calls) };
+
+            assert_eq!($name::find(6), Some((1, 2)));
+            assert_eq!($name::find(10), None);
+            assert_eq!(take_events(), events_of(2));
+
+            assert_eq!($name::evens(vec![1, 2, 3, 4, 102]), [4, 8, 102]);
+            assert_eq!($name::evens(vec![1]), [0]);
+            assert_eq!(take_events(), events_of(2));
+
+            assert_eq!($name::parse_all(vec!["7", "x", " 9 "]), Ok(vec![7, 2]));
+            assert_eq!($name::parse_all(vec!["x"]), Err("nothing parsed".to_string()));
+            assert_eq!(take_events(), events_of(2));
+
+            assert_eq!($name::classify(0), "none");
+            assert_eq!($name::classify(5), "some");
+            assert_eq!($name::classify(9), "all");
+            assert_eq!(take_events(), events_of(3));
+
+            assert_eq!($name::absolute(-3), 3);
+            assert_eq!(take_events(), events_of(1));
+        }
+    };
+}
+
+torture!(hooks, ["enter", "exit"], on_enter(enter), on_exit(exit));
+torture!(decorated, ["decorator"], decorator(forward));
+torture!(
+    adapted,
+    ["enter", "decorator"],
+    on_enter(enter),
+    decorator(forward_adapted),
+    adapt
+);
+torture!(
+    borrowing,
+    ["decorator"],
+    decorator(forward_borrowing, borrow_args)
+);
+torture!(
+    selected,
+    ["decorator"],
+    decorator_select(0, [forward, forward])
+);
+torture!(prologue, ["enter"], prologue(enter();));
+torture!(
+    timed,
+    ["decorator", "exit"],
+    count_calls(CALLS),
+    slow_log(Duration::MAX, slow),
+    decorator(forward),
+    on_exit(exit)
+);
+torture!(minimal, ["enter"], minimal, on_enter(enter));
+torture!(
+    raw,
+    ["enter", "exit"],
+    raw_body,
+    on_enter(enter),
+    on_exit(exit)
+);
+
+#[axin(on_error(failed), decorator(forward), on_exit(exit))]
+fn parse_strict(inputs: Vec<&'static str>) -> Result<Vec<i32>, String> {
+    'check: {
+        for input in &inputs {
+            if input.is_empty() {
+                break 'check;
+            }
+        }
+        return inputs
+            .iter()
+            .map(|input| input.parse::<i32>().map_err(|error| error.to_string()))
+            .collect();
+    }
+    Err("empty input".to_string())
+}
+
+#[test]
+fn errors_from_labeled_blocks() {
+    assert_eq!(parse_strict(vec!["1", "2"]), Ok(vec![1, 2]));
+    assert_eq!(parse_strict(vec!["1", ""]), Err("empty input".to_string()));
+    assert!(parse_strict(vec!["x"]).is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "decorator",
+            "exit",
+            "decorator",
+            "failed",
+            "exit",
+            "decorator",
+            "failed",
+            "exit"
+        ]
+    );
+}
+
+/// Async bodies with the same control flow, where the body is an async block of its own.
+#[axin(on_enter(enter), decorator(forward_async), on_exit(exit))]
+async fn find_async(target: i32) -> Option<(usize, usize)> {
+    let mut passes = 0;
+    let found = 'outer: loop {
+        passes += 1;
+        for (i, row) in GRID.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                if value == target {
+                    break 'outer Some((i, j));
+                }
+            }
+        }
+        if passes == 2 {
+            break None;
+        }
+    };
+    let keep = |position: &(usize, usize)| {
+        if position.0 > 1 {
+            return false;
+        }
+        true
+    };
+    if let Some(position) = found.filter(keep) {
+        return Some(position);
+    }
+    None
+}
+
+#[axin(on_enter(enter), on_error(failed))]
+async fn parse_async(inputs: Vec<&'static str>) -> Result<i32, String> {
+    #![allow(unused_variables)]
+    let unused = inputs.len();
+    let values: Vec<i32> = inputs
+        .iter()
+        .map(|input| -> Result<i32, String> {
+            input.parse::<i32>().map_err(|error| error.to_string())
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(values.iter().sum())
+}
+
+/// Bodies written on one line, which the generated code must not wrap in a block of its own: the compiler reports
+/// unnecessary braces around a block on one line only.
+#[rustfmt::skip]
+#[axin(on_enter(enter))]
+async fn next_entered(a: i32) -> i32 { a + 1 }
+
+#[rustfmt::skip]
+#[axin(on_exit(exit))]
+async fn next_exited(a: i32) -> i32 { a + 1 }
+
+#[rustfmt::skip]
+#[axin(decorator(forward_async))]
+async fn next_decorated(a: i32) -> i32 { a + 1 }
+
+#[rustfmt::skip]
+#[axin(prologue(record("prologue");))]
+async fn next_with_prologue(a: i32) -> i32 { a + 1 }
+
+#[rustfmt::skip]
+#[axin(minimal, on_enter(enter))]
+async fn next_minimal(a: i32) -> i32 { a + 1 }
+
+#[rustfmt::skip]
+#[axin(raw_body, on_enter(enter), on_exit(exit))]
+fn next_raw(a: i32) -> i32 { a + 1 }
+
+#[test]
+fn async_expression_bodies() {
+    assert_eq!(block_on(next_entered(1)), 2);
+    assert_eq!(block_on(next_exited(2)), 3);
+    assert_eq!(block_on(next_decorated(3)), 4);
+    assert_eq!(block_on(next_with_prologue(4)), 5);
+    assert_eq!(block_on(next_minimal(5)), 6);
+    assert_eq!(next_raw(6), 7);
+    assert_eq!(
+        take_events(),
+        [
+            "enter",
+            "exit",
+            "decorator",
+            "prologue",
+            "enter",
+            "enter",
+            "exit"
+        ]
+    );
+}
+
+#[test]
+fn async_bodies() {
+    assert_eq!(block_on(find_async(5)), Some((1, 1)));
+    assert_eq!(block_on(find_async(8)), None);
+    assert_eq!(block_on(parse_async(vec!["1", "2"])), Ok(3));
+    assert!(block_on(parse_async(vec!["x"])).is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "enter",
+            "decorator",
+            "exit",
+            "enter",
+            "decorator",
+            "exit",
+            "enter",
+            "enter",
+            "failed"
+        ]
+    );
+}
@@ -0,0 +1,153 @@
+//! Conversion impls, whose bodies are a single tail expression, instrumented with hooks and decorators.
+//!
+//! With warnings denied, as in the other test crates, the expansion must not introduce needless returns either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt;
+use std::num::{ParseIntError, TryFromIntError};
+
+fn enter() {
+    record("enter");
+}
+
+fn exit() {
+    record("exit");
+}
+
+fn failed<E: fmt::Display>(error: &E) {
+    record(format!("error: {}", error));
+}
+
+fn traced<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("decorator");
+    f(value)
+}
+
+#[derive(Debug, PartialEq)]
+struct Celsius(f64);
+
+#[derive(Debug, PartialEq)]
+struct Fahrenheit(f64);
+
+impl From<Celsius> for Fahrenheit {
+    #[axin(on_enter(enter), decorator(traced), on_exit(exit))]
+    fn from(celsius: Celsius) -> Self {
+        Fahrenheit(celsius.0 * 9.0 / 5.0 + 32.0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Port(u16);
+
+#[axin(on_enter(enter), decorator(traced), on_error(failed), on_exit(exit))]
+impl TryFrom<i64> for Port {
+    type Error = TryFromIntError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(Port(u16::try_from(value)?))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Small(u8);
+
+#[derive(Debug, PartialEq)]
+enum ConversionError {
+    Parse(ParseIntError),
+    Range(TryFromIntError),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::Parse(error) => write!(f, "parse: {}", error),
+            ConversionError::Range(error) => write!(f, "range: {}", error),
+        }
+    }
+}
+
+impl From<ParseIntError> for ConversionError {
+    fn from(error: ParseIntError) -> Self {
+        ConversionError::Parse(error)
+    }
+}
+
+impl From<TryFromIntError> for ConversionError {
+    fn from(error: TryFromIntError) -> Self {
+        ConversionError::Range(error)
+    }
+}
+
+impl TryFrom<&str> for Small {
+    type Error = ConversionError;
+
+    // A conversion chain whose `?`s convert different errors through `From`
+    #[axin(decorator(traced), on_error(failed))]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(Small(u8::try_from(value.parse::<i64>()?)?))
+    }
+}
+
+#[test]
+fn from_with_hooks_and_decorator() {
+    assert_eq!(Fahrenheit::from(Celsius(100.0)), Fahrenheit(212.0));
+    let fahrenheit: Fahrenheit = Celsius(0.0).into();
+    assert_eq!(fahrenheit, Fahrenheit(32.0));
+    assert_eq!(
+        take_events(),
+        ["enter", "decorator", "exit", "enter", "decorator", "exit"]
+    );
+}
+
+#[test]
+fn try_from_impl_with_hooks_and_decorator() {
+    assert_eq!(Port::try_from(8080), Ok(Port(8080)));
+    assert!(Port::try_from(-1).is_err());
+    let port: Result<Port, _> = 70000i64.try_into();
+    assert!(port.is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "enter",
+            "decorator",
+            "exit",
+            "enter",
+            "decorator",
+            "error: out of range integral type conversion attempted",
+            "exit",
+            "enter",
+            "decorator",
+            "error: out of range integral type conversion attempted",
+            "exit",
+        ]
+    );
+}
+
+#[test]
+fn try_from_conversion_chain() {
+    assert_eq!(Small::try_from("42"), Ok(Small(42)));
+    assert!(matches!(
+        Small::try_from("300"),
+        Err(ConversionError::Range(_))
+    ));
+    assert!(matches!(
+        Small::try_from("x"),
+        Err(ConversionError::Parse(_))
+    ));
+    assert_eq!(
+        take_events(),
+        [
+            "decorator",
+            "decorator",
+            "error: range: out of range integral type conversion attempted",
+            "decorator",
+            "error: parse: invalid digit found in string",
+        ]
+    );
+}
@@ -0,0 +1,160 @@
+//! When exit hooks run relative to the drops of the function's locals and arguments: after all of them, whether the
+//! body is called directly or by a decorator, captured when adapting, or asynchronous, and with or without `after_drop`.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+
+/// Records its drop, like a lock guard or an open file would release what it holds.
+struct Tracked(&'static str);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        record(format!("drop {}", self.0));
+    }
+}
+
+fn exit() {
+    record("exit");
+}
+
+fn pass<F: FnOnce(A, B) -> R, A, B, R>(f: F, a: A, b: B) -> R {
+    f(a, b)
+}
+
+fn pass_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+/// A decorator holding a guard of its own, which it does not give up when called.
+fn holding<F: FnOnce(A, B) -> R, A, B, R>(name: &'static str) -> impl Fn(F, A, B) -> R {
+    let guard = Tracked(name);
+    move |f, a, b| {
+        let _ = &guard;
+        f(a, b)
+    }
+}
+
+#[axin(on_exit(exit))]
+fn direct(used: Tracked, _unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+#[axin(on_exit(exit, after_drop))]
+fn direct_after_drop(used: Tracked, _unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+#[axin(decorator(pass), on_exit(exit, after_drop))]
+fn decorated(used: Tracked, _unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+#[axin(decorator(holding("decorator")), on_exit(exit, after_drop))]
+fn decorated_holding(used: Tracked, _unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+// Captured parameters that the body only borrows or does not use at all
+#[axin(adapt, on_exit(exit, after_drop))]
+fn adapted(used: Tracked, unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+#[axin(decorator(pass_adapted), adapt, on_exit(exit, after_drop))]
+fn adapted_decorated(used: Tracked, (_first, _): (Tracked, Tracked)) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+#[axin(on_exit(exit, after_drop))]
+async fn asynchronous(used: Tracked, unused: Tracked) -> &'static str {
+    let _local = Tracked("local");
+    used.0
+}
+
+struct Owner(Tracked);
+
+impl Owner {
+    #[axin(adapt, on_exit(exit, after_drop))]
+    fn consume(self) {
+        let _local = Tracked("local");
+    }
+}
+
+// Captured parameters of bodies written on one line, which the callable must not wrap in a block of its own: the
+// compiler reports unnecessary braces around a block on one line only
+#[rustfmt::skip]
+#[axin(decorator(pass_adapted), adapt)]
+fn adapted_sum(a: i32, b: i32) -> i32 { a + b }
+
+impl Owner {
+    #[rustfmt::skip]
+    #[axin(decorator(pass_adapted), adapt)]
+    fn name(&self) -> &'static str { self.0 .0 }
+}
+
+/// Check that the exit hook ran after exactly the given values were dropped, in any order.
+fn assert_dropped_before_exit(drops: &[&str]) {
+    let mut events = take_events();
+    assert_eq!(events.pop().as_deref(), Some("exit"), "{:?}", events);
+    events.sort();
+    let mut drops: Vec<_> = drops.iter().map(|name| format!("drop {}", name)).collect();
+    drops.sort();
+    assert_eq!(events, drops);
+}
+
+#[test]
+fn exit_hooks_run_after_locals_and_arguments_are_dropped() {
+    assert_eq!(direct(Tracked("used"), Tracked("unused")), "used");
+    assert_dropped_before_exit(&["local", "used", "unused"]);
+    assert_eq!(
+        direct_after_drop(Tracked("used"), Tracked("unused")),
+        "used"
+    );
+    assert_dropped_before_exit(&["local", "used", "unused"]);
+    assert_eq!(decorated(Tracked("used"), Tracked("unused")), "used");
+    assert_dropped_before_exit(&["local", "used", "unused"]);
+}
+
+#[test]
+fn decorators_are_dropped_before_exit_hooks() {
+    let used = Tracked("used");
+    assert_eq!(decorated_holding(used, Tracked("unused")), "used");
+    assert_dropped_before_exit(&["local", "used", "unused", "decorator"]);
+}
+
+#[test]
+fn captured_arguments_are_dropped_before_exit_hooks() {
+    assert_eq!(adapted(Tracked("used"), Tracked("unused")), "used");
+    assert_dropped_before_exit(&["local", "used", "unused"]);
+    let pair = (Tracked("first"), Tracked("second"));
+    assert_eq!(adapted_decorated(Tracked("used"), pair), "used");
+    assert_dropped_before_exit(&["local", "used", "first", "second"]);
+    let future = asynchronous(Tracked("used"), Tracked("unused"));
+    assert_eq!(block_on(future), "used");
+    assert_dropped_before_exit(&["local", "used", "unused"]);
+
+    let owner = Owner(Tracked("self"));
+    assert_eq!(owner.0 .0, "self");
+    owner.consume();
+    assert_dropped_before_exit(&["local", "self"]);
+}
+
+#[test]
+fn captured_expression_bodies() {
+    assert_eq!(adapted_sum(1, 2), 3);
+    let owner = Owner(Tracked("self"));
+    assert_eq!(owner.name(), "self");
+    drop(owner);
+    assert_eq!(take_events(), ["drop self"]);
+}
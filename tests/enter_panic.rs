@@ -0,0 +1,119 @@
+//! Entry hooks panicking in the middle of stacked attributes. The call ends at the panicking hook: the hooks and
+//! counters before it stay run and counted, and nothing after it runs, exit hooks included, while guards dropped by the
+//! unwinding run nothing either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, CallCounter};
+use common::{record, take_events};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Records its drop, to tell when the value an entry hook returns is dropped.
+struct Tracked(&'static str);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        record(format!("drop {}", self.0));
+    }
+}
+
+fn enter(layer: &'static str) -> Tracked {
+    record(format!("enter {}", layer));
+    Tracked(layer)
+}
+
+fn fail(layer: &str) {
+    record(format!("fail {}", layer));
+    panic!("entry hook of the {} layer failed", layer);
+}
+
+fn exit(layer: &str) {
+    record(format!("exit {}", layer));
+}
+
+/// Returns `None` if the function panics.
+fn catching<F: FnOnce() -> Option<u32>>(f: F) -> Option<u32> {
+    let result = catch_unwind(AssertUnwindSafe(f)).ok().flatten();
+    record("caught");
+    result
+}
+
+fn log_ran(body_ran: bool) {
+    record(format!("body ran: {}", body_ran));
+}
+
+static OUTER: CallCounter = CallCounter::new();
+static MIDDLE: CallCounter = CallCounter::new();
+static INNER: CallCounter = CallCounter::new();
+
+#[axin(count_calls(OUTER), on_enter(enter("outer")), on_exit(exit("outer")))]
+#[axin(count_calls(MIDDLE), on_enter(fail("middle")), on_exit(exit("middle")))]
+#[axin(count_calls(INNER), on_enter(enter("inner")), on_exit(exit("inner")))]
+fn layered(x: u32) -> u32 {
+    record("body");
+    x
+}
+
+// The guard of the outer exit hook is dropped as the panic unwinds, without running it
+#[axin(raw_body, on_enter(enter("outer")), on_exit(exit("outer")))]
+#[axin(on_enter(fail("inner")), on_exit(exit("inner")))]
+fn raw(x: u32) -> u32 {
+    record("body");
+    x
+}
+
+// A decorator above catches the panic like one of the body
+#[axin(decorator(catching), adapt, on_exit(log_ran(__axin_body_ran)))]
+#[axin(on_enter(fail("inner")), on_exit(exit("inner")))]
+fn caught(x: u32) -> Option<u32> {
+    record("body");
+    Some(x)
+}
+
+/// Calls a `raw_body` function while the thread is panicking.
+struct CallsOnDrop;
+
+impl Drop for CallsOnDrop {
+    fn drop(&mut self) {
+        assert!(std::thread::panicking());
+        assert_eq!(unwinding(1), 1);
+    }
+}
+
+#[axin(raw_body, on_exit(exit("unwinding")))]
+fn unwinding(x: u32) -> u32 {
+    x
+}
+
+#[test]
+fn hooks_after_a_panicking_entry_hook_do_not_run() {
+    assert!(catch_unwind(|| layered(1)).is_err());
+    assert_eq!(take_events(), ["enter outer", "drop outer", "fail middle"]);
+    assert_eq!(
+        (OUTER.get(), MIDDLE.get(), INNER.get()),
+        (1, 1, 0),
+        "the layers up to the panicking hook count the call"
+    );
+
+    assert!(catch_unwind(|| raw(1)).is_err());
+    assert_eq!(take_events(), ["enter outer", "drop outer", "fail inner"]);
+}
+
+#[test]
+fn decorators_above_may_catch_the_panic() {
+    assert_eq!(caught(1), None);
+    assert_eq!(take_events(), ["fail inner", "caught", "body ran: true"]);
+}
+
+#[test]
+fn exit_guards_run_in_calls_made_while_unwinding() {
+    let result = catch_unwind(|| {
+        let _calls_on_drop = CallsOnDrop;
+        panic!("unwinding");
+    });
+    assert!(result.is_err());
+    assert_eq!(take_events(), ["exit unwinding"]);
+}
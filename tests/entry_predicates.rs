@@ -0,0 +1,112 @@
+//! Entry hooks gated by a predicate, such as a const generic parameter the caller picks, whose arguments are only
+//! evaluated when the hook is called.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::cell::Cell;
+
+thread_local! {
+    static FORMATTED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The argument of the entry hooks, counting how often it is computed.
+fn details(items: &[u32]) -> String {
+    FORMATTED.with(|formatted| formatted.set(formatted.get() + 1));
+    format!("{:?}", items)
+}
+
+fn formatted() -> usize {
+    FORMATTED.with(|formatted| formatted.take())
+}
+
+fn log_details(details: String) {
+    record(details);
+}
+
+fn checked_details(details: String) -> Result<(), String> {
+    record(details);
+    Err("rejected".to_string())
+}
+
+fn traced<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("decorator".to_string());
+    f(value)
+}
+
+#[axin(on_enter(log_details(details(items)), when = VERBOSE))]
+fn process<const VERBOSE: bool>(items: &[u32]) -> u32 {
+    items.iter().sum()
+}
+
+#[axin(on_enter(log_details(details(&[level])), when = VERBOSE && level > 1), decorator(traced))]
+fn leveled<const VERBOSE: bool>(level: u32) -> u32 {
+    level * 2
+}
+
+#[axin(on_enter(checked_details(details(&[value])), when = T::CHECKED, errors = "propagate"))]
+fn validated<T: Mode>(value: u32) -> Result<u32, String> {
+    Ok(value)
+}
+
+trait Mode {
+    const CHECKED: bool;
+}
+
+struct Checked;
+
+impl Mode for Checked {
+    const CHECKED: bool = true;
+}
+
+struct Unchecked;
+
+impl Mode for Unchecked {
+    const CHECKED: bool = false;
+}
+
+#[axin(raw_body, on_enter(log_details(details(items)), when = VERBOSE))]
+fn raw_process<const VERBOSE: bool>(items: &[u32]) -> usize {
+    items.len()
+}
+
+#[test]
+fn const_generic_predicates() {
+    assert_eq!(process::<true>(&[1, 2]), 3);
+    assert_eq!(formatted(), 1);
+    assert_eq!(process::<false>(&[3, 4]), 7);
+    assert_eq!(formatted(), 0);
+    assert_eq!(take_events(), ["[1, 2]"]);
+}
+
+#[test]
+fn predicates_on_parameters() {
+    assert_eq!(leveled::<true>(2), 4);
+    assert_eq!(leveled::<true>(1), 2);
+    assert_eq!(leveled::<false>(3), 6);
+    assert_eq!(formatted(), 1);
+    assert_eq!(
+        take_events(),
+        ["[2]", "decorator", "decorator", "decorator"]
+    );
+}
+
+#[test]
+fn predicates_on_associated_constants() {
+    assert_eq!(validated::<Checked>(1), Err("rejected".to_string()));
+    assert_eq!(validated::<Unchecked>(2), Ok(2));
+    assert_eq!(formatted(), 1);
+    assert_eq!(take_events(), ["[1]"]);
+}
+
+#[test]
+fn raw_bodies() {
+    assert_eq!(raw_process::<true>(&[5]), 1);
+    assert_eq!(raw_process::<false>(&[6, 7]), 2);
+    assert_eq!(formatted(), 1);
+    assert_eq!(take_events(), ["[5]"]);
+}
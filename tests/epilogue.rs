@@ -0,0 +1,160 @@
+//! Epilogue statements, run after the function body in the scope of the prologue, before the body's value is returned
+//! to the decorator and the exit hooks.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, take_order_trace, CallCounter};
+use common::{block_on, record, take_events};
+use std::fmt::{Debug, Display};
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn traced<F: FnOnce(u32) -> R, R: Debug>(f: F, x: u32) -> R {
+    record("decorator");
+    let result = f(x);
+    record(format!("decorator got {:?}", result));
+    result
+}
+
+fn adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+fn error_hook<E: Display>(error: &E) {
+    record(format!("error {}", error));
+}
+
+fn slow_hook(_: Duration) {
+    record("slow");
+}
+
+#[axin(
+    init(record("init")),
+    count_calls(CALLS),
+    on_enter(record("enter")),
+    decorator(traced),
+    prologue(let started = x; record("prologue");),
+    epilogue(record(format!("epilogue {} {}", started, x));),
+    assert_returns(Err(2)),
+    slow_log(Duration::ZERO, slow_hook),
+    on_error(error_hook),
+    on_exit(record("exit")),
+    assert_order("init,count,enter,decorator,prologue,body,epilogue,assert,slow_log,error,exit")
+)]
+fn everything(x: u32) -> Result<u32, u32> {
+    record("body");
+    Err(x)
+}
+
+// The body's value is returned after the epilogue, which sees the parameters the body did not move and the items it
+// declares
+#[axin(
+    prologue(let mut log = Vec::new();),
+    epilogue(log.push(LABEL); record(log.join(" "));),
+    decorator(adapted),
+    adapt
+)]
+fn joined(first: String, second: &str) -> String {
+    const LABEL: &str = "joined";
+    first + second
+}
+
+// An early `return` or `?` leaves the body and skips the epilogue, which only runs when the body completes
+#[axin(epilogue(record(format!("parsed {}", input))), on_exit(record("exit")))]
+fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+    if input.is_empty() {
+        return Ok(0);
+    }
+    let value = input.parse()?;
+    Ok(value)
+}
+
+// The body's tail is coerced to the return type, as without an epilogue
+#[axin(epilogue(record("boxed");))]
+fn boxed(flag: bool) -> Box<dyn Display> {
+    if flag {
+        Box::new(1)
+    } else {
+        Box::new("one")
+    }
+}
+
+#[axin(epilogue(record(format!("async {}", x))), on_enter(record("enter")))]
+async fn doubled(x: u32) -> u32 {
+    x * 2
+}
+
+#[axin(epilogue(record("inner attributes")))]
+fn with_inner_attributes() -> u32 {
+    #![allow(unused_variables)]
+    let unused = 1;
+    2
+}
+
+#[test]
+fn epilogue_runs_between_body_and_decorator() {
+    assert_eq!(everything(2), Err(2));
+    assert_eq!(
+        take_events(),
+        [
+            "init",
+            "enter",
+            "decorator",
+            "prologue",
+            "body",
+            "epilogue 2 2",
+            "decorator got Err(2)",
+            "slow",
+            "error 2",
+            "exit"
+        ]
+    );
+    assert_eq!(CALLS.get(), 1);
+    assert_eq!(
+        take_order_trace(),
+        [
+            "init",
+            "count",
+            "enter",
+            "decorator",
+            "prologue",
+            "body",
+            "epilogue",
+            "assert",
+            "slow_log",
+            "error",
+            "exit"
+        ]
+    );
+}
+
+#[test]
+fn epilogue_sees_the_prologue_and_the_body_items() {
+    assert_eq!(joined("a".to_string(), "b"), "ab");
+    assert_eq!(take_events(), ["decorator", "joined"]);
+}
+
+#[test]
+fn early_returns_skip_the_epilogue() {
+    assert_eq!(parse("12"), Ok(12));
+    assert_eq!(parse(""), Ok(0));
+    assert!(parse("x").is_err());
+    assert_eq!(take_events(), ["parsed 12", "exit", "exit", "exit"]);
+}
+
+#[test]
+fn epilogue_keeps_the_body_as_written() {
+    assert_eq!(boxed(true).to_string(), "1");
+    assert_eq!(boxed(false).to_string(), "one");
+    assert_eq!(with_inner_attributes(), 2);
+    assert_eq!(block_on(doubled(2)), 4);
+    assert_eq!(
+        take_events(),
+        ["boxed", "boxed", "inner attributes", "enter", "async 2"]
+    );
+}
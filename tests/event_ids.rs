@@ -0,0 +1,113 @@
+//! Compact event ids bound to `__axin_event_id`, given explicitly or derived from the function's name.
+
+#![cfg(not(feature = "disable"))]
+
+use axin::{axin, event_id};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<u16>> = const { RefCell::new(Vec::new()) };
+}
+
+fn trace_event(id: u16) {
+    EVENTS.with(|events| events.borrow_mut().push(id));
+}
+
+fn take_events() -> Vec<u16> {
+    EVENTS.with(|events| events.take())
+}
+
+#[axin(event_id = 0x1042, on_enter(trace_event(__axin_event_id)))]
+fn explicit() {}
+
+#[axin(event_id = auto, on_enter(trace_event(__axin_event_id)), on_exit(trace_event(!__axin_event_id)))]
+fn fetch_user() {}
+
+#[axin(event_id = auto, on_enter(super::trace_event(__axin_event_id)))]
+mod handlers {
+    pub fn create() {}
+
+    pub fn delete() {}
+}
+
+/// The id is a constant, so it can be used where constants are required.
+#[axin(event_id = 7, prologue(const _: () = assert!(__axin_event_id == 7);))]
+fn constant() -> u16 {
+    __axin_event_id
+}
+
+#[test]
+fn explicit_ids_are_passed_through() {
+    explicit();
+    assert_eq!(take_events(), [0x1042]);
+    assert_eq!(constant(), 7);
+}
+
+#[test]
+fn auto_ids_are_derived_from_the_name() {
+    fetch_user();
+    handlers::create();
+    handlers::delete();
+    assert_eq!(
+        take_events(),
+        [
+            event_id("fetch_user"),
+            !event_id("fetch_user"),
+            event_id("create"),
+            event_id("delete"),
+        ]
+    );
+}
+
+#[test]
+fn auto_ids_are_stable() {
+    // Changing these breaks the decoding of events logged by earlier builds
+    let ids = ["fetch_user", "create", "delete", "main", ""].map(event_id);
+    assert_eq!(ids, [0x8b2b, 0x7fe6, 0x2388, 0x0898, 0x1cd9]);
+}
+
+/// Find the names sharing an id, as a crate would check its functions.
+fn collisions<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    let mut seen: HashMap<u16, &str> = HashMap::new();
+    let mut collisions = Vec::new();
+    for name in names {
+        if let Some(other) = seen.insert(event_id(name), name) {
+            if other != name {
+                collisions.push((other.to_string(), name.to_string()));
+            }
+        }
+    }
+    collisions
+}
+
+#[test]
+fn collisions_over_a_word_list() {
+    const VERBS: &[&str] = &[
+        "get", "set", "load", "store", "read", "write", "open", "close", "send", "recv", "parse",
+        "format", "create", "delete", "update", "find", "insert", "remove", "start", "stop",
+    ];
+    const NOUNS: &[&str] = &[
+        "user", "item", "order", "config", "file", "socket", "buffer", "frame", "packet", "sensor",
+        "motor", "timer", "event", "state", "key", "value", "page", "block", "record", "message",
+    ];
+    let names: Vec<String> = VERBS
+        .iter()
+        .flat_map(|verb| NOUNS.iter().map(move |noun| format!("{}_{}", verb, noun)))
+        .collect();
+    assert_eq!(names.len(), 400);
+
+    // A 16-bit id makes a few collisions among hundreds of names likely, so they are detected rather than ruled out
+    let found = collisions(names.iter().map(String::as_str));
+    let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+    assert_eq!(
+        found,
+        [
+            pair("parse_key", "delete_config"),
+            pair("send_value", "find_user")
+        ]
+    );
+
+    // The same name is no collision, as it is the same function instrumented in several places
+    assert!(collisions(["fetch_user", "fetch_user"]).is_empty());
+}
@@ -0,0 +1,378 @@
+//! Tests running every example under `examples` as its own process and comparing what it prints with the output
+//! expected here, like `single_threaded_test` does for functions in `integration_test.rs`.
+//!
+//! A new example is covered by adding a test calling [`assert_example_output`] with its expected output. Parts of a
+//! line which differ from run to run, like durations, are written as `[..]`.
+
+#![cfg(not(feature = "disable"))]
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+/// Build all examples once, returning the directory containing their binaries.
+///
+/// The examples get a target directory of their own, so building them does not wait for the lock on the one of the
+/// running test.
+fn examples_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = root.join("target").join("examples");
+
+        let output = Command::new(env!("CARGO"))
+            .args(["build", "--quiet", "--examples"])
+            .current_dir(root)
+            .env("CARGO_TARGET_DIR", &target_dir)
+            .env_remove("RUSTFLAGS")
+            .output()
+            .expect("failed to run cargo");
+        assert!(
+            output.status.success(),
+            "building the examples failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        target_dir.join("debug").join("examples")
+    })
+}
+
+/// Whether `actual` matches the `expected` line, in which every `[..]` stands for any text.
+fn line_matches(expected: &str, actual: &str) -> bool {
+    let mut parts = expected.split("[..]");
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = actual.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.len() >= part.len() && rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// Run the example `name` and assert that it succeeds, printing `expected`.
+fn assert_example_output(name: &str, expected: &str) {
+    let binary = examples_dir().join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+    let output = Command::new(&binary)
+        .output()
+        .unwrap_or_else(|error| panic!("failed to run {}: {}", binary.display(), error));
+    assert!(
+        output.status.success(),
+        "example {} failed:\n{}",
+        name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let actual = String::from_utf8(output.stdout).expect("the example printed invalid UTF-8");
+    let matches = actual.lines().count() == expected.lines().count()
+        && expected
+            .lines()
+            .zip(actual.lines())
+            .all(|(expected, actual)| line_matches(expected, actual));
+    assert!(
+        matches,
+        "Output of example {} did not match expected:\nExpected:\n{}\nActual:\n{}",
+        name, expected, actual
+    );
+}
+
+#[test]
+fn wildcard_lines() {
+    assert!(line_matches("took [..]", "took 1.5ms"));
+    assert!(line_matches("took [..]ms", "took 1.5ms"));
+    assert!(line_matches("[..] and [..]", "this and that"));
+    assert!(line_matches("exact", "exact"));
+    assert!(!line_matches("exact", "exactly"));
+    assert!(!line_matches("took [..]ms", "took 1.5s"));
+    assert!(!line_matches("a[..]ab", "ab"));
+}
+
+#[test]
+fn example_basic_prologue() {
+    assert_example_output(
+        "01_basic_prologue",
+        r#"=== Basic Prologue Demo ===
+
+--- Simple prologue ---
+Initializing function
+Function body executing
+
+--- Multi-step prologue ---
+Step 1: Setup
+Step 2: Initialize
+Step 3: Ready
+Main function logic
+"#,
+    );
+}
+
+#[test]
+fn example_entry_exit() {
+    assert_example_output(
+        "02_entry_exit",
+        r#"=== Entry/Exit Functions Demo ===
+
+--- Function with setup ---
+🚀 Function is starting...
+Doing some work
+
+--- Function with cleanup ---
+Doing some work
+✅ Function completed!
+
+--- Function with both and hooks with args ---
+📝 Logging function entry: with_both
+Doing some work with full logging
+📝 Logging function exit: with_both
+"#,
+    );
+}
+
+#[test]
+fn example_decorators() {
+    assert_example_output(
+        "03_decorators",
+        r#"=== Decorator Functions Demo ===
+
+--- Timing decorator ---
+⏱️  Starting timer...
+Calculating important result...
+⏱️  Function took: [..]
+Final result: 42
+
+--- Logging decorator ---
+📋 Logging decorator: Before function
+Performing string operation
+📋 Logging decorator: After function, result: Hello, World!
+Final result: Hello, World!
+
+--- Doubling decorator ---
+Computing base value
+🔢 Doubling result from 21 to 42
+Final result: 42
+"#,
+    );
+}
+
+#[test]
+fn example_combined_features() {
+    assert_example_output(
+        "04_combined_features",
+        r#"=== Combined Features Demo ===
+
+--- Complex operation with all features ---
+🔧 System initialization
+📊 Performance monitor: Starting
+📝 Setting up variables
+📝 Configuring environment
+💼 Executing core business logic
+💼 Processing data
+📊 Performance monitor: Completed in [..]
+🏁 System finalization
+Final result: 100
+
+--- Secure operation ---
+🔧 System initialization
+🔒 Security check: Validating permissions
+🔐 Preparing secure context
+🔑 Handling sensitive data
+🔒 Security check: Operation authorized
+🏁 System finalization
+Final result: Classified Information
+
+--- Execution order demonstration ---
+2️⃣ OnEnter: Begin
+3️⃣ Decorator: Before
+1️⃣ Prologue: Setup
+4️⃣ Function Body: Main work
+5️⃣ Decorator: After
+6️⃣ OnExit: End
+Final result: 42
+"#,
+    );
+}
+
+#[test]
+fn example_real_world() {
+    assert_example_output(
+        "05_real_world",
+        r#"=== Real-world Usage Examples ===
+
+--- API Endpoint with full monitoring ---
+🌐 API Request started
+📊 Performance: Monitoring API call
+🔧 Initializing request context
+🔧 Request ID: req_12345
+👤 Fetching user profile from database
+📊 Performance: API call took [..]
+🌐 API Request completed
+✅ Profile: User: John Doe, Email: john@example.com
+
+--- Data processing with error handling ---
+🛡️ Error Handler: Wrapping function
+📊 Preparing data processing pipeline
+🔄 Processing large dataset
+🛡️ Error Handler: Function completed successfully
+📈 Processing result: 42
+
+--- Expensive calculation with caching ---
+💾 Cache: Checking cache...
+💾 Cache: Cache miss, executing function
+🧮 Setting up calculation parameters
+🧮 Using precision: 0.001
+💰 Performing expensive mathematical operation
+💾 Cache: Storing result in cache
+🎯 Calculation result: Result: 3.14159265359
+"#,
+    );
+}
+
+#[test]
+fn example_async() {
+    assert_example_output(
+        "06_async",
+        r#"=== Async Functions Demo ===
+
+--- Async function with hooks ---
+🌐 Request started
+👤 Looking up user 1
+🌐 Request finished
+Result: Some("Alice")
+
+--- Async decorator and error hook ---
+📋 Decorator: before awaiting the body
+✏️ Renaming Alice to Bob
+📋 Decorator: after awaiting the body
+Result: Ok("Bob")
+📋 Decorator: before awaiting the body
+📋 Decorator: after awaiting the body
+❌ Request failed: no user 2
+Result: Err("no user 2")
+"#,
+    );
+}
+
+#[test]
+fn example_methods() {
+    assert_example_output(
+        "07_methods",
+        r#"=== Methods Demo ===
+
+--- Associated function ---
+📝 Audit: account method
+🏦 Opening an account for Alice
+
+--- Method with a decorator ---
+📝 Audit: account method
+🔒 Transaction: begin
+💰 Depositing 100 for Alice
+🔒 Transaction: commit
+Balance: 100
+
+--- Minimal method ---
+Balance: 100
+Balance: 100
+Balance reads: 2
+
+--- Raw body method ---
+📝 Audit: withdrawal attempted
+Result: Ok(70)
+📝 Audit: withdrawal attempted
+Result: Err("insufficient funds for 500")
+"#,
+    );
+}
+
+#[test]
+fn example_error_handling() {
+    assert_example_output(
+        "08_error_handling",
+        r#"=== Error Handling Demo ===
+
+--- Error hook ---
+Result: Ok(42)
+❌ parse failed: invalid digit found in string
+Result: true
+
+--- Conditional exit hook ---
+Result: Ok(10)
+🚨 Alert: payment rejected
+Result: Err("5000 exceeds the limit")
+
+--- Retry decorator ---
+🔁 Attempt 1 failed: connection refused, retrying
+🔁 Attempt 2 failed: connection refused, retrying
+Result: Ok("connected")
+
+--- Panic guard decorator ---
+Result: 5
+🛡️ Caught a panic, returning the default
+Result: 0
+
+--- Failing hooks ---
+⚠️ Hook failed: audit log unavailable
+💸 Transferring 5
+Result: 5
+Result: Err("audit log unavailable")
+"#,
+    );
+}
+
+#[test]
+fn example_observability() {
+    assert_example_output(
+        "09_observability",
+        r#"=== Observability Demo ===
+
+--- Call context and one-time initialization ---
+🔧 Logger installed
+🔍 Call #1 to handle_request in 09_observability
+🔍 Call #2 to handle_request in 09_observability
+
+--- Call counting and event ids ---
+📦 Event 0x72d3
+📦 Event 0x72d3
+Lookups: 2
+
+--- Slow call logging ---
+🐢 compress was slow: true
+Result: 2
+
+--- Entry hook on request ---
+📋 processing [1, 2]
+Result: 3
+Result: 7
+"#,
+    );
+}
+
+#[test]
+fn example_modules() {
+    assert_example_output(
+        "10_modules",
+        r#"=== Modules and Decorator Selection Demo ===
+
+--- Instrumented module ---
+🔍 Handling list_users
+Result: ["alice", "bob"]
+🔍 Handling create_user
+Result: created carol
+Result: ok
+
+--- Decorator selected on every call ---
+Result: 9
+🐞 Called with 4
+🐞 Returned 16
+Result: 16
+"#,
+    );
+}
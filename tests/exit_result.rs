@@ -0,0 +1,94 @@
+//! `__axin_result`: exit hooks seeing a reference to the return value, within any expression of their arguments and
+//! predicate. The result is only borrowed, and returned to the caller as is afterwards.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+use std::fmt::Debug;
+
+fn log_result<T: Debug>(result: &T) {
+    record(format!("returned {:?}", result));
+}
+
+fn log(message: String) {
+    record(message);
+}
+
+fn forward<F: FnOnce(i32) -> R, R>(f: F, x: i32) -> R {
+    record("decorator");
+    f(x)
+}
+
+#[axin(on_exit(log_result(__axin_result)))]
+fn reset(value: &mut i32) {
+    *value = 0;
+}
+
+#[axin(decorator(forward), on_exit(log(format!("{} squared is {}", x, *__axin_result))))]
+fn square(x: i32) -> i32 {
+    x * x
+}
+
+#[derive(Debug)]
+struct ParseError(String);
+
+// The predicate may refer to the result by either name
+#[axin(
+    on_exit(
+        log(match __axin_result {
+            Ok(name) => format!("parsed {}", name.len()),
+            Err(ParseError(input)) => format!("rejected {}", input),
+        }),
+        when = !__axin_result.as_ref().is_ok_and(|name| name.is_empty()) && result.is_ok() == __axin_result.is_ok()
+    )
+)]
+fn parse_name(input: &str) -> Result<String, ParseError> {
+    match input.strip_prefix("name=") {
+        Some(name) => Ok(name.to_string()),
+        None => Err(ParseError(input.to_string())),
+    }
+}
+
+// A result that is not `Copy`, nor `Clone`, is returned as the hook saw it
+#[axin(on_exit(log_result(&__axin_result.len())))]
+fn letters(word: &str) -> Vec<char> {
+    word.chars().collect()
+}
+
+#[axin(on_exit(log_result(__axin_result)))]
+async fn fetch(id: u32) -> Result<String, ParseError> {
+    Ok(format!("item {}", id))
+}
+
+#[test]
+fn unit_results() {
+    let mut value = 3;
+    reset(&mut value);
+    assert_eq!(value, 0);
+    assert_eq!(take_events(), ["returned ()"]);
+}
+
+#[test]
+fn copied_results() {
+    assert_eq!(square(-3), 9);
+    assert_eq!(take_events(), ["decorator", "-3 squared is 9"]);
+}
+
+#[test]
+fn borrowed_results() {
+    assert_eq!(parse_name("name=axin").unwrap(), "axin");
+    assert!(parse_name("name=").unwrap().is_empty());
+    assert_eq!(parse_name("id=1").unwrap_err().0, "id=1");
+    assert_eq!(letters("ab"), ['a', 'b']);
+    assert_eq!(take_events(), ["parsed 4", "rejected id=1", "returned 2"]);
+}
+
+#[test]
+fn async_results() {
+    assert_eq!(block_on(fetch(7)).unwrap(), "item 7");
+    assert_eq!(take_events(), [r#"returned Ok("item 7")"#]);
+}
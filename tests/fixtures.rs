@@ -0,0 +1,414 @@
+//! Tests building separate fixture crates under `tests/fixtures`, for setups that cannot be reproduced inside a
+//! single test target (build scripts, configuration files, environment variables, ...).
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// Run `cargo <args>` in the fixture crate `name` with the extra environment variables `envs`.
+///
+/// Fixtures share a target directory under this crate's one, so dependencies are only built once.
+fn cargo_in_fixture(name: &str, args: &[&str], envs: &[(&str, &str)]) -> Output {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir: PathBuf = root.join("target").join("fixtures");
+
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .args(args)
+        .current_dir(root.join("tests").join("fixtures").join(name))
+        .env("CARGO_TARGET_DIR", target_dir)
+        .env_remove("RUSTFLAGS");
+    for (key, value) in envs {
+        command.env(key, value);
+    }
+    command.output().expect("failed to run cargo")
+}
+
+/// Assert that a cargo invocation succeeded, showing its output otherwise.
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "cargo failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn generated_handlers() {
+    // Spans of code included from `OUT_DIR` still produce a working expansion
+    let output = cargo_in_fixture("generated-handlers", &["test", "--quiet"], &[]);
+    assert_success(&output);
+
+    // ... and a readable error pointing into the generated file
+    let output = cargo_in_fixture(
+        "generated-handlers",
+        &["build", "--quiet"],
+        &[("AXIN_FIXTURE_MISTAKE", "1")],
+    );
+    assert!(
+        !output.status.success(),
+        "the misspelled attribute compiled"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unsupported parameter: 'on_entr'"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("handlers.rs"),
+        "diagnostic does not point into the generated file:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn public_api() {
+    // Call context counters are shared with other crates, through inlining and generic instantiation
+    let output = cargo_in_fixture("public-api", &["test", "--quiet"], &[]);
+    assert_success(&output);
+
+    // The examples in the doc comments of instrumented functions are collected and run, and see the hooks' effects
+    let output = cargo_in_fixture("public-api", &["test", "--doc", "--quiet"], &[]);
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("test result: ok. 3 passed"),
+        "the doc tests were not all collected:\n{}",
+        stdout
+    );
+
+    // Every generated item stays inside the instrumented function, or is hidden, so none of them is documented
+    let output = cargo_in_fixture("public-api", &["doc", "--no-deps", "--quiet"], &[]);
+    assert_success(&output);
+    let doc_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("fixtures")
+        .join("doc");
+    let mut pending = vec![doc_dir.clone()];
+    let mut documented = String::new();
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            pending.extend(path.read_dir().unwrap().map(|entry| entry.unwrap().path()));
+        } else if let Ok(content) = std::fs::read(&path) {
+            documented.push_str(&String::from_utf8_lossy(&content));
+        }
+    }
+    assert!(
+        doc_dir
+            .join("axin_fixture_public_api")
+            .join("fn.double.html")
+            .exists(),
+        "the fixture was not documented"
+    );
+    for generated in [
+        "__AXIN_CALLS",
+        "original_fn",
+        "__result",
+        "__span",
+        "axin_explain",
+        "DOUBLE_AXIN_ORDER",
+        "reset_double_axin_state",
+    ] {
+        assert!(
+            !documented.contains(generated),
+            "`{}` shows up in the documentation",
+            generated
+        );
+    }
+}
+
+#[test]
+fn layered_attributes() {
+    // Attributes added by `cfg_attr` are layered under the unconditional ones, with and without the feature
+    for args in [
+        &["test", "--quiet"][..],
+        &["test", "--quiet", "--features", "deep"],
+    ] {
+        let output = cargo_in_fixture("layered-attributes", args, &[]);
+        assert_success(&output);
+    }
+}
+
+#[test]
+fn gated_paths() {
+    // Attributes gated by a feature or by `debug_assertions` refer to modules gated the same way, which are missing
+    // from every other configuration
+    for args in [
+        &["test", "--quiet"][..],
+        &["test", "--quiet", "--features", "telemetry"],
+        &["test", "--quiet", "--features", "audit"],
+        &["test", "--quiet", "--all-features"],
+        &["test", "--quiet", "--release"],
+    ] {
+        let output = cargo_in_fixture("gated-paths", args, &[]);
+        assert_success(&output);
+    }
+}
+
+#[test]
+fn disabled() {
+    // With the `disable` feature, functions are emitted as written, whatever their attributes
+    let output = cargo_in_fixture(
+        "disabled",
+        &["test", "--quiet", "--features", "disable"],
+        &[],
+    );
+    assert_success(&output);
+
+    // ... and with `--cfg axin_disable`
+    let disabled = [("RUSTFLAGS", "--cfg axin_disable")];
+    let output = cargo_in_fixture("disabled", &["test", "--quiet"], &disabled);
+    assert_success(&output);
+
+    // ... whose arguments are still checked
+    let output = cargo_in_fixture(
+        "disabled",
+        &["build", "--quiet", "--features", "disable,mistake"],
+        &[],
+    );
+    assert!(
+        !output.status.success(),
+        "the misspelled attribute compiled"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unsupported parameter: 'on_entr'"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn crate_defaults() {
+    // The defaults from the fixture's `axin.toml` are merged under every attribute's arguments
+    let output = cargo_in_fixture("crate-defaults", &["test", "--quiet"], &[]);
+    assert_success(&output);
+
+    // ... which Cargo knows the crate depends on, so that changing them rebuilds it
+    let output = cargo_in_fixture("crate-defaults", &["build", "--quiet"], &[]);
+    assert_success(&output);
+    let dep_info = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("fixtures")
+        .join("debug")
+        .join("libaxin_fixture_crate_defaults.d");
+    let dep_info = std::fs::read_to_string(dep_info).expect("missing dependency info");
+    assert!(
+        dep_info.contains("axin.toml"),
+        "the defaults are not tracked:\n{}",
+        dep_info
+    );
+
+    // Arguments conflicting with the defaults are reported with a hint to opt out
+    let output = cargo_in_fixture(
+        "crate-defaults",
+        &["build", "--quiet", "--features", "conflict"],
+        &[],
+    );
+    assert!(!output.status.success(), "the conflict compiled");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "`minimal` mode only supports entry hooks without arguments, with the defaults from"
+        ) && stderr.contains("add `no_defaults` to opt out of them"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+
+    // Options are checked against the defaults of their placeholders
+    let output = cargo_in_fixture(
+        "crate-defaults",
+        &["build", "--quiet", "--features", "mismatch"],
+        &[],
+    );
+    assert!(!output.status.success(), "the mismatched option compiled");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("the option `component` takes a string")
+            && stderr.contains("but is given an integer"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+
+    // Presets are looked up in the same file
+    let output = cargo_in_fixture(
+        "crate-defaults",
+        &["build", "--quiet", "--features", "unknown-preset"],
+        &[],
+    );
+    assert!(!output.status.success(), "the unknown preset compiled");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown preset `exited`")
+            && stderr.contains("axin.toml` defines the presets `exiting`, `custom`"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+
+    // A strict check of prologues given by the defaults applies to every function
+    let output = cargo_in_fixture(
+        "crate-defaults",
+        &["build", "--quiet", "--features", "exiting-prologue"],
+        &[],
+    );
+    assert!(!output.status.success(), "the exiting prologue compiled");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "`strict_prologue` does not allow the denied path `std::process::exit` in prologue statements"
+        ),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn generated_prefix() {
+    // The prefix from the fixture's `axin.toml` names the placeholders and generated items, next to the crate's own
+    // `__axin_` names
+    let output = cargo_in_fixture("generated-prefix", &["test", "--quiet"], &[]);
+    assert_success(&output);
+
+    // ... and the names reserved with it are checked instead
+    let output = cargo_in_fixture(
+        "generated-prefix",
+        &["build", "--quiet", "--features", "collision"],
+        &[],
+    );
+    assert!(!output.status.success(), "the collision compiled");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("`__obs_result` is reserved for a reference to the return value"),
+        "unexpected diagnostic:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn expansion_manifest() {
+    // A directory of its own for every run, whose change makes Cargo rebuild the fixture
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("fixtures")
+        .join("manifests")
+        .join(format!("{}-{}", std::process::id(), nanos));
+    let output = cargo_in_fixture(
+        "expansion-manifest",
+        &["build", "--quiet"],
+        &[("AXIN_MANIFEST_DIR", dir.to_str().unwrap())],
+    );
+    assert_success(&output);
+
+    // One manifest per instrumented function, the skipped method getting none
+    let mut files: Vec<_> = dir
+        .read_dir()
+        .expect("no manifests were written")
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "axin_fixture_expansion_manifest.Store.get.json",
+            "axin_fixture_expansion_manifest.stacked.json"
+        ]
+    );
+
+    // Stacked attributes are listed outermost first
+    let manifest = std::fs::read_to_string(dir.join(&files[1])).unwrap();
+    assert_eq!(
+        manifest,
+        r#"{
+  "schema": 1,
+  "crate": "axin_fixture_expansion_manifest",
+  "function": "stacked",
+  "layers": [
+    {
+      "features": ["on_enter", "on_exit"],
+      "hooks": [
+        { "kind": "on_enter", "path": "trace" },
+        { "kind": "on_exit", "path": "audit", "alias": "audit_done" }
+      ]
+    },
+    {
+      "features": ["on_enter", "decorator", "count_calls"],
+      "hooks": [
+        { "kind": "on_enter", "path": "crate::trace" },
+        { "kind": "decorator", "path": "timing" }
+      ]
+    }
+  ]
+}
+"#
+    );
+
+    // Methods are named after their type, with the arguments of the attribute on the `impl` block
+    let manifest = std::fs::read_to_string(dir.join(&files[0])).unwrap();
+    assert_eq!(
+        manifest,
+        r#"{
+  "schema": 1,
+  "crate": "axin_fixture_expansion_manifest",
+  "function": "Store::get",
+  "layers": [
+    {
+      "features": ["on_enter", "on_error", "adapt"],
+      "hooks": [
+        { "kind": "on_enter", "path": "trace" },
+        { "kind": "on_error", "path": "report" }
+      ]
+    }
+  ]
+}
+"#
+    );
+}
+
+#[test]
+fn dead_code() {
+    // Fifty instrumented functions that are never called leave no trace in a release build, statics included
+    let binary = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("fixtures")
+        .join("release")
+        .join(format!(
+            "axin-fixture-dead-code{}",
+            std::env::consts::EXE_SUFFIX
+        ));
+    let mut sizes = Vec::new();
+    for args in [
+        &["build", "--release", "--quiet"][..],
+        &["build", "--release", "--quiet", "--features", "uncalled"],
+    ] {
+        let output = cargo_in_fixture("dead-code", args, &[]);
+        assert_success(&output);
+        let content = std::fs::read(&binary).expect("missing binary");
+        let contains = |marker: &str| {
+            content
+                .windows(marker.len())
+                .any(|window| window == marker.as_bytes())
+        };
+        assert!(
+            contains("axin-live-marker"),
+            "the called function is missing"
+        );
+        assert!(
+            !contains("axin-dead-code"),
+            "the uncalled functions were linked"
+        );
+        sizes.push(content.len());
+    }
+    // Calling them would add tens of kilobytes; what is left is at most alignment
+    assert!(
+        sizes[1] <= sizes[0] + 4096,
+        "the uncalled functions grew the binary from {} to {} bytes",
+        sizes[0],
+        sizes[1]
+    );
+}
@@ -0,0 +1,165 @@
+//! Functions instrumented with the defaults from the crate's `axin.toml`, which adds an entry hook and a call counter
+//! to every attribute, alone, next to explicit arguments, and opted out of with `no_defaults`. The entry hook takes the
+//! component of the function as an option. The presets of the file add to the defaults of the functions using them.
+
+use axin::{axin, axin_use, AxinCtx, CallCounter};
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: String) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+pub static CALLS: CallCounter = CallCounter::new();
+
+fn trace(ctx: &AxinCtx, component: &str) {
+    record(format!("enter {} in {}", ctx.fn_name, component));
+}
+
+fn custom() {
+    record("custom enter".to_string());
+}
+
+fn exit() {
+    record("exit".to_string());
+}
+
+#[axin]
+pub fn defaulted(x: u32) -> u32 {
+    x + 1
+}
+
+#[axin(on_exit(exit), opt(component = "billing"))]
+pub fn extended(x: u32) -> u32 {
+    x + 2
+}
+
+#[axin(on_enter(custom))]
+pub fn overridden(x: u32) -> u32 {
+    x + 3
+}
+
+#[axin(no_defaults, on_exit(exit))]
+pub fn opted_out(x: u32) -> u32 {
+    x + 4
+}
+
+#[axin_use(exiting)]
+pub fn preset(x: u32) -> u32 {
+    x + 5
+}
+
+// The entry hook of a preset replaces the default one, like one written out
+#[axin(preset(exiting), preset(custom))]
+pub fn presets(x: u32) -> u32 {
+    x + 6
+}
+
+// The defaults are merged once, into the outer attribute
+#[axin(opt(component = "stacked"))]
+#[axin_use(exiting)]
+pub fn stacked_preset(x: u32) -> u32 {
+    x + 7
+}
+
+#[axin(skip(helper), opt(component = "handlers"))]
+pub mod handlers {
+    pub fn list() -> usize {
+        helper()
+    }
+
+    pub fn helper() -> usize {
+        2
+    }
+
+    #[axin::axin(no_defaults)]
+    pub fn quiet() {}
+}
+
+pub struct Point(pub i32, pub i32);
+
+#[axin]
+impl Point {
+    pub fn origin() -> Self {
+        Point(0, 0)
+    }
+}
+
+// `minimal` only allows entry hooks without arguments, which the default one is not
+#[cfg(feature = "conflict")]
+#[axin(minimal)]
+pub fn conflicting() {}
+
+// No preset of this name
+#[cfg(feature = "unknown-preset")]
+#[axin_use(exiting, exited)]
+pub fn unknown_preset() {}
+
+// The component is a string
+#[cfg(feature = "mismatch")]
+#[axin(opt(component = 42))]
+pub fn mismatched() {}
+
+// Prologues are checked strictly, and this one exits
+#[cfg(feature = "exiting-prologue")]
+#[axin(prologue(if std::env::args().count() > 9 { std::process::exit(1) }))]
+pub fn exiting() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single test, since all functions share the call counter
+    #[test]
+    fn defaults_are_merged_under_explicit_arguments() {
+        assert_eq!(defaulted(1), 2);
+        assert_eq!(extended(1), 3);
+        assert_eq!(overridden(1), 4);
+        assert_eq!(opted_out(1), 5);
+        assert_eq!(
+            take_events(),
+            [
+                "enter defaulted in unknown",
+                "enter extended in billing",
+                "exit",
+                "custom enter",
+                "exit"
+            ]
+        );
+        assert_eq!(CALLS.get(), 3);
+
+        // In modules and `impl` blocks, to every function the attribute applies to
+        assert_eq!(handlers::list(), 2);
+        handlers::quiet();
+        assert_eq!(Point::origin().0, 0);
+        assert_eq!(
+            take_events(),
+            ["enter list in handlers", "enter origin in unknown"]
+        );
+        assert_eq!(CALLS.get(), 5);
+
+        // Presets, as arguments of the attribute
+        assert_eq!(preset(1), 6);
+        assert_eq!(presets(1), 7);
+        assert_eq!(stacked_preset(1), 8);
+        assert_eq!(
+            take_events(),
+            [
+                "enter preset in unknown",
+                "exit",
+                "custom enter",
+                "exit",
+                "enter stacked_preset in stacked",
+                "exit"
+            ]
+        );
+        assert_eq!(CALLS.get(), 8);
+    }
+}
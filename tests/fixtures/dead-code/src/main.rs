@@ -0,0 +1,67 @@
+//! A binary calling one instrumented function, and with `uncalled`, defining fifty more that it never calls. Their
+//! hooks, decorators and the statics generated for them are dead code, which the release build leaves out.
+
+use axin::{axin, AxinCtx};
+use std::hint::black_box;
+
+fn trace(ctx: &AxinCtx) {
+    black_box(ctx.call_id);
+}
+
+fn mark(marker: &'static str) {
+    black_box(marker);
+}
+
+fn timed<F: FnOnce() -> R, R>(f: F) -> R {
+    black_box(f())
+}
+
+fn slow(ctx: &AxinCtx, elapsed: std::time::Duration) {
+    black_box((ctx.fn_name, elapsed));
+}
+
+// Its marker shows that strings of the functions called end up in the binary
+#[axin(
+    on_enter(trace(&__axin)),
+    on_exit(mark("axin-live-marker")),
+    decorator(timed),
+    adapt,
+    slow_log(std::time::Duration::from_millis(1), slow(&__axin))
+)]
+fn used(x: u32) -> u32 {
+    x + 1
+}
+
+/// Define instrumented functions never called, each with its own call context counter and one-time initialization.
+#[cfg(feature = "uncalled")]
+macro_rules! uncalled {
+    ($($name:ident)*) => {
+        $(
+            #[allow(dead_code)]
+            #[axin(
+                on_enter(trace(&__axin)),
+                on_exit(mark("axin-dead-code-marker")),
+                decorator(timed),
+                adapt,
+                init(mark(concat!("axin-dead-code-init-", stringify!($name)))),
+                slow_log(std::time::Duration::from_millis(1), slow(&__axin))
+            )]
+            fn $name(x: u32) -> u32 {
+                x * 2
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "uncalled")]
+uncalled! {
+    f00 f01 f02 f03 f04 f05 f06 f07 f08 f09
+    f10 f11 f12 f13 f14 f15 f16 f17 f18 f19
+    f20 f21 f22 f23 f24 f25 f26 f27 f28 f29
+    f30 f31 f32 f33 f34 f35 f36 f37 f38 f39
+    f40 f41 f42 f43 f44 f45 f46 f47 f48 f49
+}
+
+fn main() {
+    println!("{}", used(black_box(1)));
+}
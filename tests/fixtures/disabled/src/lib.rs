@@ -0,0 +1,124 @@
+//! Instrumented functions built with the `disable` feature or `--cfg axin_disable`, which emit them as written: none of
+//! the hooks and decorators run, and the functions return what their bodies do. The twins, order constants and state
+//! accessors are still emitted next to them.
+
+use axin::{axin, axin_pair, CallCounter};
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn record(event: &str) {
+    EVENTS.with(|events| events.borrow_mut().push(event.to_string()));
+}
+
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+pub fn enter() {
+    record("enter");
+}
+
+pub fn exit() {
+    record("exit");
+}
+
+pub fn fail() {
+    panic!("instrumentation ran");
+}
+
+pub fn doubled<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    record("decorator");
+    f(x) * 2
+}
+
+pub static CALLS: CallCounter = CallCounter::new();
+
+#[axin(
+    on_enter(enter),
+    decorator(doubled),
+    prologue(record("prologue");),
+    on_exit(exit),
+    count_calls(CALLS),
+    assert_returns(0),
+    init(record("init")),
+    order_const,
+    twin = "hooked_plain"
+)]
+pub fn hooked(x: u32) -> u32 {
+    x + 1
+}
+
+// Stacked attributes are left alone as well, but for their twins
+#[axin(on_enter(fail))]
+#[axin(decorator(doubled), on_exit_always(record("guard")), twin = "stacked_plain")]
+pub fn stacked(x: u32) -> u32 {
+    x
+}
+
+#[axin(on_enter(enter), on_exit(exit))]
+pub mod service {
+    pub fn load(id: u32) -> u32 {
+        id * 10
+    }
+}
+
+pub struct Store(pub u32);
+
+#[axin(on_enter(enter), on_exit(exit), adapt)]
+impl Store {
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    // The marker is removed with the attribute of the block, so that it is not expanded on its own
+    #[axin(skip)]
+    pub fn id(&self) -> u32 {
+        self.0 + 1
+    }
+}
+
+axin_pair! {
+    { on_enter(fail), decorator(doubled) } => {
+        pub fn sync_fetch(x: u32) -> u32 {
+            x + 2
+        }
+
+        pub async fn async_fetch(x: u32) -> u32 {
+            x + 2
+        }
+    }
+}
+
+#[cfg(feature = "mistake")]
+#[axin(on_entr(enter))]
+pub fn misspelled() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_runs_but_the_bodies() {
+        assert_eq!(hooked(1), 2);
+        assert_eq!(stacked(3), 3);
+        assert_eq!(service::load(4), 40);
+        assert_eq!(Store(5).get(), 5);
+        assert_eq!(Store(5).id(), 6);
+        assert_eq!(sync_fetch(6), 8);
+        drop(async_fetch(6));
+        assert_eq!(take_events(), Vec::<String>::new());
+        assert_eq!(CALLS.get(), 0);
+    }
+
+    #[test]
+    fn twins_and_companion_items_are_kept() {
+        assert_eq!(hooked_plain(1), 2);
+        assert_eq!(stacked_plain(3), 3);
+        reset_hooked_axin_state();
+        assert_eq!(HOOKED_AXIN_ORDER.len(), 8);
+        assert_eq!(take_events(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,37 @@
+//! Functions whose expansion manifests are written while `AXIN_MANIFEST_DIR` is set: a free function with stacked
+//! attributes, and the methods of an `impl` block, one of which is skipped and thus gets no manifest.
+
+use axin::{axin, AxinCtx, CallCounter};
+
+pub static CALLS: CallCounter = CallCounter::new();
+
+fn trace() {}
+
+fn audit(_: &AxinCtx, _: &str) {}
+
+fn report<E>(_: &E) {}
+
+fn timing<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    f(x)
+}
+
+#[axin(on_enter(trace), on_exit(audit(&__axin, "done") as audit_done))]
+#[axin(on_enter(crate::trace), decorator(timing), count_calls(crate::CALLS))]
+pub fn stacked(x: u32) -> u32 {
+    x + 1
+}
+
+pub struct Store {
+    value: Option<u32>,
+}
+
+#[axin(on_enter(trace), on_error(report), adapt, skip(new))]
+impl Store {
+    pub fn new(value: Option<u32>) -> Self {
+        Store { value }
+    }
+
+    pub fn get(&self) -> Result<u32, &'static str> {
+        self.value.ok_or("empty")
+    }
+}
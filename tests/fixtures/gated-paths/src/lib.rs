@@ -0,0 +1,202 @@
+//! Hooks and decorators from modules that only exist with the `telemetry` or the `audit` feature, or in debug builds,
+//! given to `axin` attributes gated the same way, by `cfg_attr`, `cfg(...)` or `debug_only`. Tested with either
+//! feature, both and neither, and built in release mode, so that every gated path is missing in some configuration.
+
+use axin::axin;
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+pub(crate) fn record(event: &str) {
+    EVENTS.with(|events| events.borrow_mut().push(event.to_string()));
+}
+
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+#[cfg(feature = "telemetry")]
+mod telemetry {
+    pub fn enter() {
+        crate::record("telemetry enter");
+    }
+
+    pub fn timing<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+        crate::record("telemetry timing");
+        f(x)
+    }
+}
+
+#[cfg(feature = "audit")]
+mod audit {
+    pub fn exit() {
+        crate::record("audit exit");
+    }
+
+    pub fn report<E>(_: &E) {
+        crate::record("audit error");
+    }
+}
+
+#[cfg(debug_assertions)]
+mod debug_tools {
+    pub fn enter() {
+        crate::record("debug enter");
+    }
+}
+
+// A gated attribute alone, with the constant and the twin it adds next to the function
+#[cfg_attr(
+    feature = "telemetry",
+    axin(on_enter(telemetry::enter), order_const, twin = "load_untraced")
+)]
+pub fn load(x: u32) -> u32 {
+    x
+}
+
+// Gated layers under an attribute that is always there
+#[axin(on_enter(record("enter")), init(record("init")), order_const)]
+#[cfg_attr(feature = "telemetry", axin(decorator(telemetry::timing)))]
+#[cfg_attr(feature = "audit", axin(on_exit(audit::exit), on_error(audit::report)))]
+pub fn store(x: u32) -> Result<u32, String> {
+    if x == 0 {
+        Err("empty".to_string())
+    } else {
+        Ok(x)
+    }
+}
+
+// Gated attributes on a method of an instrumented `impl` block and a function of an instrumented module. The compiler
+// only resolves them once the block or the module is expanded, so they apply around its instrumentation, separately.
+pub struct Cache;
+
+#[axin(on_exit(record("exit")), adapt)]
+impl Cache {
+    #[cfg_attr(feature = "telemetry", axin(on_enter(telemetry::enter), adapt))]
+    pub fn get(&self, x: u32) -> u32 {
+        x
+    }
+}
+
+#[axin(on_exit(crate::record("exit")))]
+pub mod jobs {
+    #[cfg_attr(feature = "audit", axin::axin(on_enter(crate::audit::exit)))]
+    pub fn run() {}
+}
+
+// Instrumentation for debug builds only
+#[axin(debug_only, on_enter(debug_tools::enter), order_const)]
+pub fn checked(x: u32) -> u32 {
+    x
+}
+
+// Instrumentation for the builds with both features, given a predicate on them
+#[axin(
+    cfg(all(feature = "telemetry", feature = "audit")),
+    on_enter(telemetry::enter),
+    on_exit(audit::exit),
+    order_const
+)]
+pub fn synced(x: u32) -> u32 {
+    x
+}
+
+// A gated function, under an attribute referring to the gated module
+#[axin(on_enter(telemetry::enter))]
+#[cfg(feature = "telemetry")]
+pub fn traced() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The events expected, keeping the ones only expected when `enabled` holds.
+    fn expected(events: &[(&str, bool)]) -> Vec<String> {
+        events
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(event, _)| event.to_string())
+            .collect()
+    }
+
+    const TELEMETRY: bool = cfg!(feature = "telemetry");
+    const AUDIT: bool = cfg!(feature = "audit");
+
+    #[test]
+    fn gated_attributes_apply_with_their_feature() {
+        assert_eq!(load(1), 1);
+        assert_eq!(take_events(), expected(&[("telemetry enter", TELEMETRY)]));
+        #[cfg(feature = "telemetry")]
+        {
+            assert_eq!(LOAD_AXIN_ORDER, ["enter:telemetry::enter", "body"]);
+            assert_eq!(load_untraced(2), 2);
+            traced();
+            assert_eq!(take_events(), ["telemetry enter"]);
+        }
+    }
+
+    #[test]
+    fn gated_layers_apply_under_the_others() {
+        assert_eq!(store(0), Err("empty".to_string()));
+        assert_eq!(
+            take_events(),
+            expected(&[
+                ("init", true),
+                ("enter", true),
+                ("telemetry timing", TELEMETRY),
+                ("audit error", AUDIT),
+                ("audit exit", AUDIT),
+            ])
+        );
+        let phases: Vec<&str> = STORE_AXIN_ORDER
+            .iter()
+            .map(|step| step.split(':').next().unwrap())
+            .collect();
+        assert_eq!(phases.first(), Some(&"init"));
+        assert_eq!(phases.contains(&"decorator"), TELEMETRY);
+        assert_eq!(phases.contains(&"error"), AUDIT);
+    }
+
+    #[test]
+    fn gated_attributes_apply_inside_instrumented_items() {
+        assert_eq!(Cache.get(3), 3);
+        assert_eq!(
+            take_events(),
+            expected(&[("telemetry enter", TELEMETRY), ("exit", true)])
+        );
+        jobs::run();
+        assert_eq!(
+            take_events(),
+            expected(&[("audit exit", AUDIT), ("exit", true)])
+        );
+    }
+
+    #[test]
+    fn cfg_instrumentation_applies_where_its_predicate_holds() {
+        assert_eq!(synced(5), 5);
+        let both = TELEMETRY && AUDIT;
+        assert_eq!(
+            take_events(),
+            expected(&[("telemetry enter", both), ("audit exit", both)])
+        );
+        assert_eq!(SYNCED_AXIN_ORDER.len(), if both { 3 } else { 1 });
+    }
+
+    #[test]
+    fn debug_only_instrumentation_applies_in_debug_builds() {
+        assert_eq!(checked(4), 4);
+        assert_eq!(
+            take_events(),
+            expected(&[("debug enter", cfg!(debug_assertions))])
+        );
+        // The constant lists the phases of either build
+        let order: &[&str] = if cfg!(debug_assertions) {
+            &["enter:debug_tools::enter", "body"]
+        } else {
+            &["body"]
+        };
+        assert_eq!(CHECKED_AXIN_ORDER, order);
+    }
+}
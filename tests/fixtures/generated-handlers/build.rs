@@ -0,0 +1,32 @@
+//! Generates instrumented handlers into `OUT_DIR`, like a code generator would.
+//!
+//! With `AXIN_FIXTURE_MISTAKE` set, one of the attributes is misspelled, to check the diagnostic.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=AXIN_FIXTURE_MISTAKE");
+
+    let hook = if env::var_os("AXIN_FIXTURE_MISTAKE").is_some() {
+        "on_entr"
+    } else {
+        "on_enter"
+    };
+
+    let handlers = format!(
+        r#"
+#[axin::axin({hook}(crate::trace("ping")), prologue(let start = 1;))]
+pub fn ping(count: u32) -> u32 {{
+    start + count
+}}
+
+#[axin::axin(decorator(crate::twice))]
+pub fn pong(count: u32) -> u32 {{
+    count
+}}
+"#
+    );
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("handlers.rs"), handlers).unwrap();
+}
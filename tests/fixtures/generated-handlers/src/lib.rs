@@ -0,0 +1,27 @@
+//! Instrumented functions generated by a build script.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub static TRACED: AtomicUsize = AtomicUsize::new(0);
+
+pub fn trace(_: &str) {
+    TRACED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn twice<F: FnOnce(u32) -> u32>(f: F, count: u32) -> u32 {
+    f(count) * 2
+}
+
+include!(concat!(env!("OUT_DIR"), "/handlers.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_handlers_are_instrumented() {
+        assert_eq!(ping(41), 42);
+        assert_eq!(TRACED.load(Ordering::Relaxed), 1);
+        assert_eq!(pong(21), 42);
+    }
+}
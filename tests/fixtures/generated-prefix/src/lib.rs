@@ -0,0 +1,104 @@
+//! Functions instrumented with the `__obs_` prefix from the crate's `axin.toml`, next to items of the crate named with
+//! the `__axin_` prefix, like those of a binding generator. The hooks refer to the placeholders with the crate's prefix,
+//! and parameters and prologue bindings with the default prefix are left alone.
+
+#![allow(non_upper_case_globals)]
+
+use axin::{axin, AxinCtx, CallCounter};
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: String) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+pub static CALLS: CallCounter = CallCounter::new();
+
+// Generated bindings of a C library
+pub const __axin_result: u32 = 7;
+
+pub fn __axin_typed(x: u32) -> u32 {
+    x
+}
+
+pub struct __AxinArgsSum(pub u32);
+
+fn enter(ctx: &AxinCtx, event_id: u16) {
+    record(format!("enter {} #{}", ctx.fn_name, event_id));
+}
+
+fn exit(result: &u32, body_ran: bool) {
+    record(format!("exit {} {}", result, body_ran));
+}
+
+fn ok(value: &u32) {
+    record(format!("ok {}", value));
+}
+
+fn trace<F: FnOnce(Args) -> u32, Args: std::fmt::Debug>(f: F, args: Args) -> u32 {
+    record(format!("{:?}", args));
+    f(args)
+}
+
+#[axin(
+    event_id = 7,
+    on_enter(enter(&__obs, __obs_event_id)),
+    on_exit(exit(__obs_result, __obs_body_ran)),
+)]
+pub fn scaled(__axin_value: u32) -> u32 {
+    __axin_value * __axin_result
+}
+
+#[axin(
+    on_ok(ok(__obs_ok)),
+    prologue(let __axin_base = __axin_typed(__axin_result);),
+)]
+pub fn checked(x: impl Into<u32>) -> Result<u32, String> {
+    Ok(x.into() + __axin_base)
+}
+
+// The prefix applies to the stacked attribute as well
+#[axin(decorator(trace), packed = struct)]
+#[axin(on_exit(exit(__obs_result, __obs_body_ran)))]
+pub fn sum(a: u32, b: u32) -> u32 {
+    let _ = __AxinArgsSum(a);
+    a + b
+}
+
+// The placeholder would shadow the parameter in the exit hook's arguments
+#[cfg(feature = "collision")]
+#[axin(on_exit(exit(__obs_result, true)))]
+pub fn colliding(__obs_result: u32) -> u32 {
+    __obs_result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single test, since all functions share the call counter
+    #[test]
+    fn placeholders_and_generated_names_use_the_prefix() {
+        assert_eq!(scaled(2), 14);
+        assert_eq!(checked(1u8), Ok(8));
+        assert_eq!(sum(1, 2), 3);
+        assert_eq!(
+            take_events(),
+            [
+                "enter scaled #7",
+                "exit 14 true",
+                "ok 8",
+                "__ObsArgsSum { a: 1, b: 2 }",
+                "exit 3 true"
+            ]
+        );
+        assert_eq!(CALLS.get(), 3);
+    }
+}
@@ -0,0 +1,90 @@
+//! `axin` attributes stacked on top of each other, some of them only added by `cfg_attr` when the `deep` feature is
+//! enabled. Tested with and without the feature.
+
+use axin::axin;
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: &str) {
+    EVENTS.with(|events| events.borrow_mut().push(event.to_string()));
+}
+
+pub fn take_events() -> Vec<String> {
+    EVENTS.with(|events| events.take())
+}
+
+fn base() {
+    record("base enter");
+}
+
+fn base_exit() {
+    record("base exit");
+}
+
+#[cfg(feature = "deep")]
+fn deep_trace() {
+    record("deep enter");
+}
+
+#[cfg(feature = "deep")]
+fn deep_exit() {
+    record("deep exit");
+}
+
+fn outer<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    record("outer decorator");
+    f(x)
+}
+
+#[cfg(feature = "deep")]
+fn inner<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    record("inner decorator");
+    f(x + 1)
+}
+
+#[axin(on_enter(base), on_exit(base_exit))]
+#[cfg_attr(feature = "deep", axin(on_enter(deep_trace), on_exit(deep_exit)))]
+pub fn hooks(x: u32) -> u32 {
+    record("body");
+    x
+}
+
+#[axin(on_enter(base), decorator(outer))]
+#[cfg_attr(feature = "deep", axin(decorator(inner), on_exit(deep_exit)))]
+pub fn decorated(x: u32) -> u32 {
+    record("body");
+    x * 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditional_hooks_run_inside_the_unconditional_ones() {
+        assert_eq!(hooks(1), 1);
+        let expected: &[&str] = if cfg!(feature = "deep") {
+            &["base enter", "deep enter", "body", "deep exit", "base exit"]
+        } else {
+            &["base enter", "body", "base exit"]
+        };
+        assert_eq!(take_events(), expected);
+    }
+
+    #[test]
+    fn conditional_decorator_is_wrapped_by_the_unconditional_one() {
+        let expected: (u32, &[&str]) = if cfg!(feature = "deep") {
+            (
+                20,
+                &["base enter", "outer decorator", "inner decorator", "body", "deep exit"],
+            )
+        } else {
+            (10, &["base enter", "outer decorator", "body"])
+        };
+        assert_eq!(decorated(1), expected.0);
+        assert_eq!(take_events(), expected.1);
+    }
+}
@@ -0,0 +1,65 @@
+//! Instrumented public functions, used from another crate and documented with rustdoc, with examples run as doc tests.
+//! The items emitted next to them, hidden from rustdoc, are used from another crate as well.
+
+use axin::{axin, AxinCtx};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static LAST_CALL_ID: AtomicU64 = AtomicU64::new(0);
+
+fn remember(ctx: &AxinCtx) {
+    LAST_CALL_ID.store(ctx.call_id, Ordering::Relaxed);
+}
+
+fn twice<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x) * 2
+}
+
+/// The id of the last call of an instrumented function.
+pub fn last_call_id() -> u64 {
+    LAST_CALL_ID.load(Ordering::Relaxed)
+}
+
+/// Doubles `x`, inlined into its callers.
+///
+/// ```
+/// use axin_fixture_public_api::{double, last_call_id};
+///
+/// assert_eq!(double(2), 4);
+/// // The entry hook ran, in the process of this example
+/// assert_eq!(last_call_id(), 1);
+/// ```
+#[inline]
+#[axin(on_enter(remember(&__axin)), decorator(twice), document, order_const)]
+pub fn double(x: u32) -> u32 {
+    x
+}
+
+/// A generic function, instantiated in the calling crate.
+///
+/// ```
+/// use axin_fixture_public_api::{identity, last_call_id};
+///
+/// assert_eq!(identity("one"), "one");
+/// assert_eq!(identity(2), 2);
+/// assert_eq!(last_call_id(), 2);
+/// ```
+#[axin(on_enter(remember(&__axin)))]
+pub fn identity<T>(value: T) -> T {
+    value
+}
+
+/// Handlers instrumented as a whole.
+#[axin(on_enter(remember(&__axin)))]
+pub mod handlers {
+    use super::remember;
+
+    /// Answers.
+    ///
+    /// ```
+    /// assert_eq!(axin_fixture_public_api::handlers::answer(), 42);
+    /// assert_eq!(axin_fixture_public_api::last_call_id(), 1);
+    /// ```
+    pub fn answer() -> u32 {
+        42
+    }
+}
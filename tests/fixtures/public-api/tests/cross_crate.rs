@@ -0,0 +1,31 @@
+//! Instrumented functions called from another crate share their call context counter with the defining crate, even
+//! when inlined or instantiated here.
+
+use axin_fixture_public_api::{
+    double, handlers, identity, last_call_id, reset_double_axin_state, DOUBLE_AXIN_ORDER,
+};
+
+#[test]
+fn call_ids_are_shared_across_crates() {
+    assert_eq!(double(2), 4);
+    assert_eq!(last_call_id(), 1);
+    assert_eq!(double(3), 6);
+    assert_eq!(last_call_id(), 2);
+
+    // One counter per generic function, shared by all instantiations
+    assert_eq!(identity(1u8), 1);
+    assert_eq!(identity("one"), "one");
+    assert_eq!(last_call_id(), 2);
+
+    assert_eq!(handlers::answer(), 42);
+    assert_eq!(last_call_id(), 1);
+
+    // The items emitted next to the function are hidden from rustdoc only
+    assert_eq!(
+        DOUBLE_AXIN_ORDER,
+        ["enter:remember", "decorator:twice", "body"]
+    );
+    reset_double_axin_state();
+    double(1);
+    assert_eq!(last_call_id(), 1);
+}
@@ -0,0 +1,137 @@
+//! Hooks and decorators that are associated functions of generic types, called with turbofish paths or with the
+//! type's parameters left to be inferred from their arguments.
+
+#![cfg(not(feature = "disable"))]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// A recorder for requests of type `T`, inferred from the request passed to its hooks.
+struct Recorder<T>(PhantomData<T>);
+
+impl<T: Debug> Recorder<T> {
+    fn record(request: &T) {
+        record(format!("record {:?}", request));
+    }
+
+    fn label(label: &str, request: &T) {
+        record(format!("{} {:?}", label, request));
+    }
+
+    fn wrap<F: FnOnce(T) -> R, R>(f: F, request: T) -> R {
+        record(format!("wrap {:?}", request));
+        f(request)
+    }
+}
+
+/// Metrics for a protocol, named by turbofish.
+struct Metrics<P>(PhantomData<P>);
+
+struct Http;
+
+trait Protocol {
+    const NAME: &'static str;
+}
+
+impl Protocol for Http {
+    const NAME: &'static str = "http";
+}
+
+impl<P: Protocol> Metrics<P> {
+    fn enter() {
+        record(format!("enter {}", P::NAME));
+    }
+
+    fn exit() {
+        record(format!("exit {}", P::NAME));
+    }
+
+    fn failed<E: Debug>(error: &E) {
+        record(format!("{} failed: {:?}", P::NAME, error));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Request(u32);
+
+#[axin(
+    on_enter(Recorder::record(&request)),
+    decorator(Recorder::wrap),
+    on_exit(Recorder::label("done", &request))
+)]
+fn handle(request: Request) -> u32 {
+    request.0 * 2
+}
+
+#[axin(
+    on_enter(Metrics::<Http>::enter),
+    on_error(Metrics::<Http>::failed),
+    on_exit(Metrics::<Http>::exit)
+)]
+fn fetch(id: u32) -> Result<u32, String> {
+    if id == 0 {
+        return Err("no id".to_string());
+    }
+    Ok(id)
+}
+
+/// A parameterized decorator whose item type is inferred from the decorated function.
+struct Batch<T>(PhantomData<T>);
+
+type Counter<T> = fn(Vec<T>) -> usize;
+
+impl<T: Debug + Clone> Batch<T> {
+    fn of(size: usize) -> impl FnOnce(Counter<T>, Vec<T>) -> usize {
+        move |f, items| {
+            record(format!("batch of {} {:?}", size, items));
+            items.chunks(size).map(|chunk| f(chunk.to_vec())).sum()
+        }
+    }
+}
+
+#[axin(decorator(Batch::of(2)))]
+fn count(items: Vec<char>) -> usize {
+    items.len()
+}
+
+#[axin(decorator(Recorder::<Request>::wrap))]
+fn handle_named(request: Request) -> u32 {
+    request.0 + 1
+}
+
+#[test]
+fn inferred_generic_hooks() {
+    assert_eq!(handle(Request(21)), 42);
+    assert_eq!(
+        take_events(),
+        ["record Request(21)", "wrap Request(21)", "done Request(21)"]
+    );
+    assert_eq!(handle_named(Request(1)), 2);
+    assert_eq!(take_events(), ["wrap Request(1)"]);
+}
+
+#[test]
+fn turbofish_hooks() {
+    assert_eq!(fetch(1), Ok(1));
+    assert!(fetch(0).is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "enter http",
+            "exit http",
+            "enter http",
+            "http failed: \"no id\"",
+            "exit http"
+        ]
+    );
+}
+
+#[test]
+fn inferred_generic_decorator() {
+    assert_eq!(count(vec!['a', 'b', 'c']), 3);
+    assert_eq!(take_events(), ["batch of 2 ['a', 'b', 'c']"]);
+}
@@ -0,0 +1,91 @@
+//! Generic functions with type and const parameters, `where` clauses and elided lifetimes, instrumented with hooks and
+//! decorators. The body is called with the function's own parameters, so it monomorphizes as without the attribute.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+
+fn simple_decorator<F: FnOnce(A) -> R, A, R>(f: F, args: A) -> R {
+    record("decorator");
+    f(args)
+}
+
+fn adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("adapted");
+    f()
+}
+
+fn log_len<T>(items: &[T]) {
+    record(format!("len {}", items.len()));
+}
+
+#[axin(decorator(simple_decorator))]
+fn largest<T: PartialOrd>(items: &[T]) -> &T {
+    let mut largest = &items[0];
+    for item in items {
+        if item > largest {
+            largest = item;
+        }
+    }
+    largest
+}
+
+#[axin(on_enter(log_len(items)), on_exit(record("exit")))]
+fn smallest<T: PartialOrd + Copy>(items: &[T]) -> T {
+    let mut smallest = items[0];
+    for &item in items {
+        if item < smallest {
+            smallest = item;
+        }
+    }
+    smallest
+}
+
+#[axin(on_enter(record(format!("{:?}", seed))), decorator(adapted), adapt)]
+fn filled<T, const N: usize>(seed: T) -> [T; N]
+where
+    T: Clone + Default + Debug,
+{
+    let mut items: [T; N] = std::array::from_fn(|_| T::default());
+    items[0] = seed.clone();
+    items
+}
+
+// The packed struct copies the generic parameters and the `where` clause
+#[axin(on_enter(record("pair")), decorator(simple_decorator), packed = struct)]
+fn pair<'a, A, B>(first: &'a A, second: B) -> (&'a A, B)
+where
+    A: ?Sized + Debug,
+    for<'b> &'b B: Debug,
+{
+    (first, second)
+}
+
+#[test]
+fn generic_functions_with_decorators() {
+    assert_eq!(*largest(&[3, 7, 2]), 7);
+    assert_eq!(*largest(&["b", "c", "a"]), "c");
+    assert_eq!(take_events(), ["decorator", "decorator"]);
+
+    assert_eq!(pair("a", 1), ("a", 1));
+    assert_eq!(take_events(), ["pair", "decorator"]);
+}
+
+#[test]
+fn generic_functions_with_hooks() {
+    assert_eq!(smallest(&[3.5, 1.5]), 1.5);
+    assert_eq!(smallest(&['b', 'a', 'c']), 'a');
+    assert_eq!(take_events(), ["len 2", "exit", "len 3", "exit"]);
+}
+
+#[test]
+fn const_generics_and_where_clauses() {
+    assert_eq!(filled::<String, 2>("x".to_string()), ["x", ""]);
+    assert_eq!(filled::<u8, 3>(4), [4, 0, 0]);
+    assert_eq!(take_events(), ["\"x\"", "adapted", "4", "adapted"]);
+}
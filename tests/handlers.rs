@@ -0,0 +1,236 @@
+//! Web handlers in the style of `axum`: async functions taking extractors by tuple-struct patterns and returning
+//! `impl IntoResponse`, instrumented and still accepted by a handler trait bound.
+//!
+//! The extractors and the `Handler` trait are hand-rolled equivalents of `axum`'s, which bound the handler and its
+//! future by `Send`, so that the instrumentation must not keep anything `!Send` across an `.await`.
+
+#![cfg(not(feature = "disable"))]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+struct Request {
+    user: String,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+trait IntoResponse {
+    fn into_response(self) -> Response;
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response {
+        Response {
+            status: 200,
+            body: self,
+        }
+    }
+}
+
+impl<T: IntoResponse, E: IntoResponse> IntoResponse for Result<T, E> {
+    fn into_response(self) -> Response {
+        match self {
+            Ok(response) => response.into_response(),
+            Err(error) => error.into_response(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ApiError(String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        Response {
+            status: 400,
+            body: self.0,
+        }
+    }
+}
+
+impl From<std::num::ParseIntError> for ApiError {
+    fn from(error: std::num::ParseIntError) -> Self {
+        ApiError(error.to_string())
+    }
+}
+
+/// Shared application state, like `axum::extract::State`.
+struct State<S>(S);
+
+/// The request body, like `axum::Json`.
+struct Json<T>(T);
+
+type Db = Arc<Mutex<Vec<String>>>;
+
+trait FromRequest<S>: Sized {
+    fn from_request(request: &Request, state: &S) -> Self;
+}
+
+impl<S: Clone> FromRequest<S> for State<S> {
+    fn from_request(_: &Request, state: &S) -> Self {
+        State(state.clone())
+    }
+}
+
+impl<S> FromRequest<S> for Json<String> {
+    fn from_request(request: &Request, _: &S) -> Self {
+        Json(request.body.clone())
+    }
+}
+
+/// A user name taken from the request.
+struct User(String);
+
+impl<S> FromRequest<S> for User {
+    fn from_request(request: &Request, _: &S) -> Self {
+        User(request.user.clone())
+    }
+}
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// An async function taking two extractors, like `axum::handler::Handler`.
+trait Handler<T, S>: Clone + Send + Sized + 'static {
+    fn call(self, request: Request, state: S) -> BoxFuture<Response>;
+}
+
+impl<F, Fut, A, B, S> Handler<(A, B), S> for F
+where
+    F: FnOnce(A, B) -> Fut + Clone + Send + 'static,
+    Fut: Future + Send,
+    Fut::Output: IntoResponse,
+    A: FromRequest<S> + Send + 'static,
+    B: FromRequest<S> + Send + 'static,
+    S: Send + 'static,
+{
+    fn call(self, request: Request, state: S) -> BoxFuture<Response> {
+        let a = A::from_request(&request, &state);
+        let b = B::from_request(&request, &state);
+        Box::pin(async move { self(a, b).await.into_response() })
+    }
+}
+
+fn serve<T, H: Handler<T, Db>>(handler: H, db: &Db, user: &str, body: &str) -> Response {
+    let request = Request {
+        user: user.to_string(),
+        body: body.to_string(),
+    };
+    block_on(handler.call(request, db.clone()))
+}
+
+fn enter(name: &str) {
+    record(format!("enter {}", name));
+}
+
+fn failed(error: &ApiError) {
+    record(format!("error {}", error.0));
+}
+
+/// An async decorator, awaited by the instrumented handler.
+async fn timed<F, Fut>(f: F) -> Fut::Output
+where
+    F: FnOnce() -> Fut,
+    Fut: Future,
+{
+    record("timed start".to_string());
+    let output = f().await;
+    record("timed end".to_string());
+    output
+}
+
+/// An async decorator receiving the extractors, passed through under generated names.
+async fn audited<F, Fut>(f: F, state: State<Db>, user: User) -> Fut::Output
+where
+    F: FnOnce(State<Db>, User) -> Fut,
+    Fut: Future,
+{
+    record(format!("audited {}", user.0));
+    f(state, user).await
+}
+
+async fn yield_now() {
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                context.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    YieldNow(false).await
+}
+
+#[axin(on_enter(enter("create")), decorator(timed), adapt, on_error(failed))]
+async fn create(
+    State(db): State<Db>,
+    Json(body): Json<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let count: usize = body.parse()?;
+    yield_now().await;
+    let mut db = db.lock().unwrap();
+    db.extend((0..count).map(|index| format!("item {}", index)));
+    Ok(format!("{} items", db.len()))
+}
+
+#[axin(decorator(audited), on_enter(enter("list")))]
+async fn list(State(db): State<Db>, User(user): User) -> impl IntoResponse {
+    yield_now().await;
+    format!("{}: {}", user, db.lock().unwrap().join(", "))
+}
+
+#[axin(on_enter(enter("echo")))]
+async fn echo(User(user): User, Json(body): Json<String>) -> String {
+    format!("{} said {}", user, body)
+}
+
+#[test]
+fn handler_with_hooks_and_decorator() {
+    let db = Db::default();
+
+    let response = serve(create, &db, "alice", "2");
+    assert_eq!((response.status, response.body.as_str()), (200, "2 items"));
+    let response = serve(create, &db, "alice", "two");
+    assert_eq!(response.status, 400);
+    assert_eq!(
+        take_events(),
+        [
+            "enter create",
+            "timed start",
+            "timed end",
+            "enter create",
+            "timed start",
+            "timed end",
+            "error invalid digit found in string",
+        ]
+    );
+}
+
+#[test]
+fn handler_with_pattern_arguments_passed_to_decorator() {
+    let db = Db::new(Mutex::new(vec!["a".to_string(), "b".to_string()]));
+
+    let response = serve(list, &db, "bob", "");
+    assert_eq!(response.body, "bob: a, b");
+    let response = serve(echo, &db, "carol", "hi");
+    assert_eq!(response.body, "carol said hi");
+    assert_eq!(take_events(), ["enter list", "audited bob", "enter echo"]);
+}
@@ -0,0 +1,69 @@
+//! Hooks, decorators and mocks given names with `as name`, which are only reported and leave the calls unchanged,
+//! including for hooks with the same last path segment.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+mod db {
+    pub fn flush() {
+        super::record("db");
+    }
+}
+
+mod cache {
+    pub fn flush(reason: &str) {
+        super::record(format!("cache {}", reason));
+    }
+}
+
+fn timing<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    record("timing");
+    f(x)
+}
+
+fn fast<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    record("fast");
+    f(x)
+}
+
+#[axin(
+    document,
+    on_enter(cache::flush("enter") as flush_cache),
+    decorator(timing as request_timing),
+    on_exit(db::flush as flush_db)
+)]
+fn aliased(x: u32) -> u32 {
+    record("body");
+    x + 1
+}
+
+#[axin(on_enter(cache::flush("enter")), decorator(timing), on_exit(db::flush))]
+fn plain(x: u32) -> u32 {
+    record("body");
+    x + 1
+}
+
+#[axin(decorator_select(x % 2, [fast as even, timing as odd]))]
+fn selected(x: u32) -> u32 {
+    x
+}
+
+#[test]
+fn aliases_do_not_change_the_calls() {
+    assert_eq!(aliased(1), 2);
+    let aliased_events = take_events();
+    assert_eq!(plain(1), 2);
+    assert_eq!(aliased_events, take_events());
+    assert_eq!(aliased_events, ["cache enter", "timing", "body", "db"]);
+}
+
+#[test]
+fn aliased_decorators_are_still_selected_by_index() {
+    assert_eq!(selected(2) + selected(3), 5);
+    assert_eq!(take_events(), ["fast", "timing"]);
+}
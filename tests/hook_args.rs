@@ -0,0 +1,122 @@
+//! Entry hooks given `with_args`, which are passed the function's name and a borrowed tuple of its arguments, so that
+//! one generic hook audits functions of any shape.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+
+fn audit<A: Debug>(fn_name: &str, args: &A) {
+    record(format!("{}{:?}", fn_name, args));
+}
+
+fn audit_in<A: Debug>(component: &str, fn_name: &str, args: &A) {
+    record(format!("{}::{}{:?}", component, fn_name, args));
+}
+
+fn traced<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+/// A parameter without `Debug`, which has to be skipped.
+struct Connection;
+
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[axin(on_enter(audit, with_args))]
+fn ping() -> &'static str {
+    "pong"
+}
+
+#[axin(on_enter(audit, with_args))]
+fn scale(factor: u32) -> u32 {
+    factor * 2
+}
+
+// Owned arguments are only borrowed by the hook, and still moved into the body afterwards
+#[axin(on_enter(audit, with_args))]
+fn greet(name: String, times: usize, loud: bool) -> String {
+    let greeting = format!("hello {}", name).repeat(times);
+    if loud {
+        greeting.to_uppercase()
+    } else {
+        greeting
+    }
+}
+
+#[axin(on_enter(audit_in("db"), with_args(skip(connection))))]
+fn query(connection: &Connection, table: &str, limit: Option<u32>) -> usize {
+    let _ = connection;
+    table.len() + limit.unwrap_or(0) as usize
+}
+
+// A parameter bound by a pattern is passed as a whole
+#[axin(on_enter(audit, with_args), decorator(traced), adapt)]
+fn norm(Point { x, y }: Point, mut scale: i32) -> i32 {
+    scale *= 1;
+    (x.abs() + y.abs()) * scale
+}
+
+#[axin(raw_body, on_enter(audit, with_args))]
+fn first_even((a, b): (u32, u32)) -> Option<u32> {
+    if a % 2 == 0 {
+        return Some(a);
+    }
+    (b % 2 == 0).then_some(b)
+}
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    // The receiver is not part of the tuple
+    #[axin(on_enter(audit, with_args), decorator(traced), adapt)]
+    fn add(&mut self, amount: u32) -> u32 {
+        self.count += amount;
+        self.count
+    }
+}
+
+#[test]
+fn one_hook_audits_different_shapes() {
+    assert_eq!(ping(), "pong");
+    assert_eq!(scale(21), 42);
+    assert_eq!(greet("axin".to_string(), 1, true), "HELLO AXIN");
+    assert_eq!(
+        take_events(),
+        ["ping()", "scale(21,)", r#"greet("axin", 1, true)"#]
+    );
+}
+
+#[test]
+fn skipped_arguments_and_hook_arguments() {
+    assert_eq!(query(&Connection, "users", Some(10)), 15);
+    assert_eq!(take_events(), [r#"db::query("users", Some(10))"#]);
+}
+
+#[test]
+fn patterns_raw_bodies_and_methods() {
+    assert_eq!(norm(Point { x: 3, y: -4 }, 2), 14);
+    assert_eq!(first_even((3, 8)), Some(8));
+    assert_eq!(Counter { count: 1 }.add(2), 3);
+    assert_eq!(
+        take_events(),
+        [
+            "norm(Point { x: 3, y: -4 }, 2)",
+            "decorator",
+            "first_even((3, 8),)",
+            "add(2,)",
+            "decorator"
+        ]
+    );
+}
@@ -0,0 +1,105 @@
+//! Functions whose parameters, bindings and neighbouring items are named like the inner callable and the result Axin
+//! binds, `original_fn` and `__result`. These bindings are hygienic, so the names in the input refer to what they were
+//! declared as, wherever the attribute puts them.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn forward<F: FnOnce(A) -> R, A, R>(f: F, args: A) -> R {
+    record("forward");
+    f(args)
+}
+
+fn forward_pair<F: FnOnce(A, B) -> R, A, B, R>(f: F, a: A, b: B) -> R {
+    record("forward pair");
+    f(a, b)
+}
+
+fn timing<F: FnOnce() -> R, R>(f: F) -> R {
+    record("timing");
+    f()
+}
+
+fn labeled<F: FnOnce(u32) -> u32>(label: &'static str) -> impl FnOnce(F, u32) -> u32 {
+    move |f, x| {
+        record(label);
+        f(x)
+    }
+}
+
+// A function of the module named like the inner callable
+fn original_fn() -> &'static str {
+    "module function"
+}
+
+// A closure parameter named like the inner callable, passed to the decorator with the others
+#[axin(decorator(forward_pair), on_exit(record("exit")))]
+fn apply(original_fn: fn(u32) -> u32, x: u32) -> u32 {
+    original_fn(x)
+}
+
+// The same, with the parameters captured for a decorator taking none
+#[axin(decorator(timing), adapt)]
+fn apply_adapted(original_fn: &dyn Fn(u32) -> u32, x: u32) -> u32 {
+    original_fn(x)
+}
+
+// A parameter named like the result, which the exit hook gets a copy of
+#[axin(on_exit(record(format!("exit with {}", __result))))]
+fn offset(__result: u32) -> u32 {
+    __result + 1
+}
+
+// Locals named like both in the prologue, the body and the epilogue
+#[axin(
+    prologue(let original_fn = |x: u32| x * 2; let __result = "prologue";),
+    epilogue(record(format!("epilogue with {}", __result));)
+)]
+fn locals(x: u32) -> u32 {
+    let __result = original_fn(x);
+    __result + 1
+}
+
+// The module's function called by a hook and by the setup of a nested decorator, after the inner callable is bound
+#[axin(
+    decorator(forward),
+    decorator(labeled(original_fn())),
+    on_exit(record(original_fn()))
+)]
+fn nested(x: u32) -> u32 {
+    x
+}
+
+#[test]
+fn parameters_named_like_the_inner_callable_are_passed_through() {
+    assert_eq!(apply(|x| x + 1, 1), 2);
+    assert_eq!(take_events(), ["forward pair", "exit"]);
+    assert_eq!(apply_adapted(&|x| x * 3, 2), 6);
+    assert_eq!(take_events(), ["timing"]);
+}
+
+#[test]
+fn parameters_named_like_the_result_are_not_shadowed() {
+    assert_eq!(offset(1), 2);
+    assert_eq!(take_events(), ["exit with 1"]);
+}
+
+#[test]
+fn locals_named_like_the_bindings_are_their_own() {
+    assert_eq!(locals(3), 7);
+    assert_eq!(take_events(), ["epilogue with prologue"]);
+}
+
+#[test]
+fn module_items_named_like_the_inner_callable_are_not_shadowed() {
+    assert_eq!(nested(4), 4);
+    assert_eq!(
+        take_events(),
+        ["forward", "module function", "module function"]
+    );
+}
@@ -0,0 +1,138 @@
+//! The attribute applied to `impl` blocks and modules, instrumenting their methods and functions alike, except for those
+//! marked with `#[axin(skip)]`. Associated constants and types are left alone.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn trace_enter() {
+    record("enter");
+}
+
+fn trace_exit() {
+    record("exit");
+}
+
+struct UserService {
+    names: Vec<String>,
+}
+
+#[axin(on_enter(trace_enter), on_exit(trace_exit))]
+impl UserService {
+    const LIMIT: usize = 2;
+
+    fn new() -> Self {
+        record("new");
+        UserService { names: Vec::new() }
+    }
+
+    fn add(&mut self, name: &str) -> bool {
+        record(format!("add {}", name));
+        if self.names.len() < Self::LIMIT {
+            self.names.push(name.to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(String::as_str)
+    }
+
+    #[axin(skip)]
+    fn len(&self) -> usize {
+        record("len");
+        self.names.len()
+    }
+
+    // Its own attribute instruments it on its own
+    #[axin(skip)]
+    #[axin(on_enter(record("health enter")))]
+    fn health(&self) -> bool {
+        record("health");
+        true
+    }
+
+    fn into_names(self) -> Vec<String> {
+        self.names
+    }
+}
+
+struct Countdown(u32);
+
+#[axin(on_exit(trace_exit))]
+impl Iterator for Countdown {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.0 = self.0.checked_sub(1)?;
+        Some(self.0)
+    }
+
+    #[axin(skip)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0 as usize, Some(self.0 as usize))
+    }
+}
+
+#[axin(on_enter(trace_enter))]
+mod handlers {
+    use super::{record, trace_enter};
+
+    pub fn list() {
+        record("list");
+    }
+
+    #[axin(skip)]
+    pub fn ping() {
+        record("ping");
+    }
+}
+
+#[test]
+fn methods_are_instrumented_unless_skipped() {
+    let mut service = UserService::new();
+    assert!(service.add("ada"));
+    assert_eq!(service.get(0), Some("ada"));
+    assert_eq!(service.len(), 1);
+    assert!(service.health());
+    assert_eq!(service.into_names(), ["ada"]);
+    assert_eq!(
+        take_events(),
+        [
+            "enter",
+            "new",
+            "exit",
+            "enter",
+            "add ada",
+            "exit",
+            "enter",
+            "exit",
+            "len",
+            "health enter",
+            "health",
+            "enter",
+            "exit"
+        ]
+    );
+}
+
+#[test]
+fn trait_methods_are_instrumented_unless_skipped() {
+    let countdown = Countdown(2);
+    assert_eq!(countdown.size_hint(), (2, Some(2)));
+    assert_eq!(countdown.collect::<Vec<_>>(), [1, 0]);
+    assert_eq!(take_events(), ["exit", "exit", "exit"]);
+}
+
+#[test]
+fn module_functions_are_instrumented_unless_skipped() {
+    handlers::list();
+    handlers::ping();
+    assert_eq!(take_events(), ["enter", "list", "ping"]);
+}
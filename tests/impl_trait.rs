@@ -0,0 +1,101 @@
+//! Functions taking and returning `impl Trait` at once, the shape of iterator and stream adapters, with and without
+//! decorators, `Send` bounds and return types capturing borrowed parameters.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, PhaseTimings};
+use common::{record, take_events};
+use std::fmt::Debug;
+
+fn timing<F: FnOnce(A) -> R, A, R>(f: F, input: A) -> R {
+    record("timing");
+    f(input)
+}
+
+fn timing2<F: FnOnce(A, B) -> R, A, B, R>(f: F, a: A, b: B) -> R {
+    record("timing");
+    f(a, b)
+}
+
+fn timing_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("timing");
+    f()
+}
+
+fn report(_: PhaseTimings) {
+    record("timings");
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[axin(decorator(timing))]
+fn transform(input: impl Iterator<Item = u8>) -> impl Iterator<Item = u8> {
+    input.map(|x| x + 1)
+}
+
+#[axin(decorator(timing), on_exit(record("exit")))]
+fn transform_send(input: impl Iterator<Item = u8> + Send) -> impl Iterator<Item = u8> + Send {
+    input.filter(|x| x % 2 == 0)
+}
+
+// Without a decorator, with mutable parameters, and with the exit hook's predicate using the result
+#[axin(on_exit(record("exit"), when = result.size_hint().0 > 1))]
+fn transform_with(
+    input: impl Iterator<Item = u8>,
+    mut step: impl FnMut(u8) -> u8,
+) -> impl Iterator<Item = u8> {
+    let offset = step(0);
+    input.map(move |x| x + offset)
+}
+
+// Return types capturing the lifetime of a borrowed parameter, named and elided
+#[axin(decorator(timing2))]
+fn borrowing<'a>(input: &'a [u8], label: impl Debug) -> impl Iterator<Item = &'a u8> + 'a {
+    record(format!("{:?}", label));
+    input.iter()
+}
+
+#[axin(decorator_select(input.len() % 2, [timing2, timing2]), phase_timing(report))]
+fn elided(input: &[u8], skip: impl Into<usize>) -> impl Iterator<Item = &u8> + '_ {
+    input.iter().skip(skip.into())
+}
+
+#[axin(decorator(timing_adapted), adapt)]
+fn adapted(input: impl IntoIterator<Item = u8>) -> impl Iterator<Item = u8> {
+    input.into_iter().skip(1)
+}
+
+#[test]
+fn decorated_adapters() {
+    assert_eq!(transform([1, 2].into_iter()).collect::<Vec<_>>(), [2, 3]);
+    let evens = assert_send(transform_send(1..=4));
+    assert_eq!(evens.collect::<Vec<_>>(), [2, 4]);
+    assert_eq!(adapted([1, 2, 3]).collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(take_events(), ["timing", "timing", "exit", "timing"]);
+}
+
+#[test]
+fn adapters_without_decorators() {
+    let mut calls = 0;
+    let shifted = transform_with([1, 2].into_iter(), |x| {
+        calls += 1;
+        x + 10
+    });
+    assert_eq!(shifted.collect::<Vec<_>>(), [11, 12]);
+    assert_eq!(calls, 1);
+    assert_eq!(transform_with([1].into_iter(), |x| x).count(), 1);
+    assert_eq!(take_events(), ["exit"]);
+}
+
+#[test]
+fn adapters_borrowing_their_input() {
+    let data = vec![1, 2, 3];
+    assert_eq!(borrowing(&data, "all").copied().collect::<Vec<_>>(), data);
+    assert_eq!(elided(&data, 2u8).collect::<Vec<_>>(), [&3]);
+    assert_eq!(take_events(), ["timing", "\"all\"", "timing", "timings"]);
+}
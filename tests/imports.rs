@@ -0,0 +1,71 @@
+//! Hooks and decorators named through imports where the function is defined: renamed imports and glob imports at
+//! module scope, which hook paths resolve in, next to imports in the body that only the body sees.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+mod telemetry {
+    pub fn enter() {
+        super::record("enter");
+    }
+
+    pub fn exit(label: &str) {
+        super::record(format!("exit {}", label));
+    }
+
+    pub fn timing<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+        super::record("timing");
+        f(x)
+    }
+}
+
+mod renamed {
+    use crate::telemetry::{enter as t_enter, exit as t_exit, timing as timed};
+    use axin::axin;
+
+    #[axin(on_enter(t_enter), decorator(timed), on_exit(t_exit("renamed")))]
+    pub fn work(x: u32) -> u32 {
+        x + 1
+    }
+}
+
+mod globbed {
+    use crate::telemetry::*;
+    use axin::axin;
+
+    #[axin(on_enter(enter), decorator(timing), on_exit(exit("globbed")))]
+    pub fn work(x: u32) -> u32 {
+        x + 1
+    }
+}
+
+// Imports in the body apply to the body only, and may shadow nothing the attribute refers to
+#[axin(on_enter(telemetry::enter))]
+fn body_import(x: u32) -> u32 {
+    use crate::telemetry::exit as finish;
+    finish("body");
+    x + 1
+}
+
+#[test]
+fn renamed_imports_at_module_scope() {
+    assert_eq!(renamed::work(1), 2);
+    assert_eq!(take_events(), ["enter", "timing", "exit renamed"]);
+}
+
+#[test]
+fn glob_imports_at_module_scope() {
+    assert_eq!(globbed::work(1), 2);
+    assert_eq!(take_events(), ["enter", "timing", "exit globbed"]);
+}
+
+#[test]
+fn imports_in_the_body() {
+    assert_eq!(body_import(1), 2);
+    assert_eq!(take_events(), ["enter", "exit body"]);
+}
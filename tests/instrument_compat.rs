@@ -0,0 +1,213 @@
+//! `instrument_compat` records the same spans as the `#[tracing::instrument]` attribute lines it mirrors.
+
+#![cfg(all(feature = "tracing", not(feature = "disable")))]
+
+mod common;
+
+use axin::axin;
+use common::block_on;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// A span as seen by the subscriber: its name, target, level and recorded fields.
+#[derive(Debug, PartialEq)]
+struct RecordedSpan {
+    name: &'static str,
+    target: &'static str,
+    level: Level,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for RecordedSpan {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+/// A subscriber recording every span created and counting the spans entered.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<RecordedSpan>>>,
+    entered: Arc<Mutex<usize>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let metadata = attrs.metadata();
+        let mut span = RecordedSpan {
+            name: metadata.name(),
+            target: metadata.target(),
+            level: *metadata.level(),
+            fields: Vec::new(),
+        };
+        attrs.record(&mut span);
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(span);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {
+        *self.entered.lock().unwrap() += 1;
+    }
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Run `f` with a fresh [`Recorder`], returning the spans it created and how many times spans were entered.
+fn record(f: impl FnOnce()) -> (Vec<RecordedSpan>, usize) {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), f);
+    let spans = std::mem::take(&mut *recorder.spans.lock().unwrap());
+    let entered = *recorder.entered.lock().unwrap();
+    (spans, entered)
+}
+
+/// Assert that calling the two functions records the same span, apart from its name, and that it is entered.
+fn assert_same_span(instrumented: impl FnOnce(), compat: impl FnOnce(), compat_name: &'static str) {
+    let (mut expected, expected_entered) = record(instrumented);
+    let (actual, actual_entered) = record(compat);
+    assert_eq!(expected.len(), 1);
+    expected[0].name = compat_name;
+    assert_eq!(actual, expected);
+    assert_eq!(actual_entered, expected_entered);
+}
+
+#[tracing::instrument]
+fn plain_instrument(id: u32, label: &str) -> u32 {
+    id + label.len() as u32
+}
+
+#[axin(instrument_compat())]
+fn plain_compat(id: u32, label: &str) -> u32 {
+    id + label.len() as u32
+}
+
+#[test]
+fn test_plain() {
+    assert_same_span(
+        || assert_eq!(plain_instrument(1, "ab"), 3),
+        || assert_eq!(plain_compat(1, "ab"), 3),
+        "plain_compat",
+    );
+}
+
+#[tracing::instrument(skip(password), fields(user = user_id), level = "debug", name = "db.load")]
+fn load_instrument(user_id: u64, password: &str) -> bool {
+    !password.is_empty()
+}
+
+#[axin(instrument_compat(skip(password), fields(user = user_id), level = "debug", name = "db.load"))]
+fn load_compat(user_id: u64, password: &str) -> bool {
+    !password.is_empty()
+}
+
+#[test]
+fn test_skip_fields_level_name() {
+    assert_same_span(
+        || assert!(load_instrument(7, "hunter2")),
+        || assert!(load_compat(7, "hunter2")),
+        "db.load",
+    );
+}
+
+#[tracing::instrument(skip_all, fields(len = items.len(), first = ?items.first()), target = "app::batch")]
+fn batch_instrument(items: Vec<i32>) -> i32 {
+    items.iter().sum()
+}
+
+#[axin(instrument_compat(skip_all, fields(len = items.len(), first = ?items.first()), target = "app::batch"))]
+fn batch_compat(items: Vec<i32>) -> i32 {
+    items.iter().sum()
+}
+
+#[test]
+fn test_skip_all_target_sigils() {
+    assert_same_span(
+        || assert_eq!(batch_instrument(vec![1, 2, 3]), 6),
+        || assert_eq!(batch_compat(vec![1, 2, 3]), 6),
+        "batch_compat",
+    );
+}
+
+#[tracing::instrument(level = Level::WARN, fields(path = %path, status))]
+fn request_instrument(path: &str, retries: usize) -> usize {
+    retries
+}
+
+fn retry_twice<F: FnOnce(&str, usize) -> usize>(f: F, path: &str, retries: usize) -> usize {
+    f(path, retries + 2)
+}
+
+#[axin(
+    instrument_compat(level = Level::WARN, fields(path = %path, status)),
+    decorator(retry_twice)
+)]
+fn request_compat(path: &str, retries: usize) -> usize {
+    retries
+}
+
+#[test]
+fn test_level_expression_and_decorator() {
+    assert_same_span(
+        || assert_eq!(request_instrument("/", 0), 0),
+        || assert_eq!(request_compat("/", 0), 2),
+        "request_compat",
+    );
+}
+
+/// Poll a future to completion, checking that it can be sent to another thread.
+fn block_on_send<F: std::future::Future + Send>(future: F) -> F::Output {
+    block_on(future)
+}
+
+/// Return `Pending` once, so that the span is entered again when the future is polled again.
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|context| {
+        if yielded {
+            std::task::Poll::Ready(())
+        } else {
+            yielded = true;
+            context.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    })
+    .await
+}
+
+#[tracing::instrument(fields(kind = "async"))]
+async fn fetch_instrument(id: u32) -> Result<u32, String> {
+    yield_now().await;
+    let id = id.checked_mul(2).ok_or("overflow")?;
+    Ok(id)
+}
+
+#[axin(instrument_compat(fields(kind = "async")))]
+async fn fetch_compat(id: u32) -> Result<u32, String> {
+    yield_now().await;
+    let id = id.checked_mul(2).ok_or("overflow")?;
+    Ok(id)
+}
+
+#[test]
+fn test_async() {
+    assert_same_span(
+        || assert_eq!(block_on_send(fetch_instrument(2)), Ok(4)),
+        || assert_eq!(block_on_send(fetch_compat(2)), Ok(4)),
+        "fetch_compat",
+    );
+}
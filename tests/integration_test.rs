@@ -3,10 +3,12 @@
 //! This test module is "self-explaining", it demonstrates how to use the `axin` macro with various parameters, and it
 //! also shows that how to use the macro in a test context.
 
+#![cfg(not(feature = "disable"))]
+
 /// Utilities for tests
 #[macro_use]
 mod utils {
-    use std::sync::Mutex;
+    use std::sync::{Mutex, PoisonError};
 
     /// A static variable to capture the output of the test functions, the lock here is just for interior mutability.
     static OUTPUT: Mutex<String> = Mutex::new(String::new());
@@ -14,7 +16,7 @@ mod utils {
     static TEST_LOCK: Mutex<()> = Mutex::new(());
 
     pub fn output(msg: impl Into<String>) {
-        let mut output = OUTPUT.lock().unwrap();
+        let mut output = OUTPUT.lock().unwrap_or_else(PoisonError::into_inner);
         output.push_str(&msg.into());
     }
 
@@ -32,13 +34,17 @@ mod utils {
         O: AsRef<str>,
     {
         move |f: F| {
-            let _lock = TEST_LOCK.lock().unwrap(); // Ensure single-threaded access
-            OUTPUT.lock().unwrap().clear(); // Clear previous output
+            // Ensure single-threaded access, also after another test failed while holding the lock
+            let _lock = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            OUTPUT
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear(); // Clear previous output
 
             let result = f();
 
             let expected_output = expected_output.as_ref();
-            let actual_output = OUTPUT.lock().unwrap();
+            let actual_output = OUTPUT.lock().unwrap_or_else(PoisonError::into_inner);
             assert_eq!(
                 actual_output.as_str(),
                 expected_output,
@@ -55,6 +61,9 @@ mod utils {
 /// Hooks and decorators for the tests
 mod testee {
     use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axin::AxinCtx;
 
     pub fn on_enter_hook() {
         println_test!("Entering hook");
@@ -68,6 +77,38 @@ mod testee {
         println_test!("Param hook: {}", param);
     }
 
+    pub fn context_hook(ctx: &AxinCtx) {
+        println_test!("Context: {} #{}", ctx.fn_name, ctx.call_id);
+    }
+
+    pub fn error_hook<E: fmt::Display + ?Sized>(error: &E) {
+        println_test!("Error hook: {:#}", error);
+    }
+
+    pub fn labeled_error_hook<E: fmt::Display + ?Sized>(label: &str, error: &E) {
+        println_test!("Error hook ({}): {:#}", label, error);
+    }
+
+    /// A hook failing on its second call, counted by `calls`.
+    pub fn flaky_hook(calls: &AtomicUsize) -> Result<(), String> {
+        let call = calls.fetch_add(1, Ordering::Relaxed) + 1;
+        println_test!("Flaky hook: call {}", call);
+        if call == 2 {
+            Err(format!("call {} failed", call))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn hook_error_sink<E: fmt::Display>(error: &E) {
+        println_test!("Hook error: {}", error);
+    }
+
+    pub fn slow_call_hook(ctx: &AxinCtx, elapsed: std::time::Duration) {
+        assert!(elapsed >= std::time::Duration::from_millis(20));
+        println_test!("Slow call: {}", ctx.fn_name);
+    }
+
     pub fn simple_decorator<F, R>(f: F) -> R
     where
         F: FnOnce() -> R,
@@ -102,6 +143,62 @@ mod testee {
         }
     }
 
+    pub struct Request {
+        pub user: String,
+        pub body: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Response(pub String);
+
+    pub fn authorize<F>(req: &Request) -> impl FnOnce(F, Request) -> Response
+    where
+        F: FnOnce(Request) -> Response,
+    {
+        let allowed = req.user == "admin";
+        println_test!("Authorizing: {}", req.user);
+        move |f: F, req: Request| {
+            if allowed {
+                f(req)
+            } else {
+                Response("denied".to_string())
+            }
+        }
+    }
+
+    pub fn label_by<F, R>(method: &str, route: &str) -> impl FnOnce(F, String) -> R
+    where
+        F: FnOnce(String) -> R,
+    {
+        let label = format!("{} {}", method, route);
+        println_test!("Routing: {}", label);
+        move |f: F, route: String| {
+            let result = f(route);
+            println_test!("Routed: {}", label);
+            result
+        }
+    }
+
+    pub fn authorize_adapted<F>(req: &Request) -> impl FnOnce(F) -> Response
+    where
+        F: FnOnce() -> Response,
+    {
+        let allowed = req.user == "admin";
+        println_test!("Authorizing: {}", req.user);
+        move |f: F| {
+            if allowed {
+                f()
+            } else {
+                Response("denied".to_string())
+            }
+        }
+    }
+
+    pub fn fake_fetch_user(id: u32, Request { user, .. }: Request) -> String {
+        println_test!("Faking user {} for {}", id, user);
+        format!("user {}", id)
+    }
+
     pub fn parameterized_decorator_with_param<F, P, Q, R>(param: P) -> impl FnOnce(F, Q) -> R
     where
         F: FnOnce(Q) -> R,
@@ -129,7 +226,11 @@ mod tests {
     // 2. The test function that calls the first one with the decorator to capture the output and check it.
 
     // test simple hooks
-    #[axin(on_enter(on_enter_hook), on_exit(on_exit_hook))]
+    #[axin(
+        on_enter(on_enter_hook),
+        on_exit(on_exit_hook),
+        assert_order("enter,body,exit")
+    )]
     fn test_hooks() {
         println_test!("Inside test_hooks function");
     }
@@ -143,7 +244,11 @@ mod tests {
     }
 
     // test parameterized hooks
-    #[axin(on_enter(parameterized_hook("test_param")), on_exit(on_exit_hook))]
+    #[axin(
+        on_enter(parameterized_hook("test_param")),
+        on_exit(on_exit_hook),
+        assert_order("enter,body,exit")
+    )]
     fn test_parameterized_hooks() {
         println_test!("Inside test_parameterized_hooks function");
     }
@@ -157,7 +262,10 @@ mod tests {
     }
 
     // test prologue functionality
-    #[axin(prologue(println_test!("Prologue statement executed")))]
+    #[axin(
+        prologue(println_test!("Prologue statement executed")),
+        assert_order("prologue,body")
+    )]
     fn test_prologue() {
         println_test!("Inside test_prologue function");
     }
@@ -174,7 +282,7 @@ mod tests {
     #[axin(prologue(
         println_test!("Prologue step 1 executed");
         println_test!("Prologue step 2 executed");
-    ), on_enter(on_enter_hook), on_exit(on_exit_hook))]
+    ), on_enter(on_enter_hook), on_exit(on_exit_hook), assert_order("enter,prologue,body,exit"))]
     fn test_multiple_prologue() {
         println_test!("Inside test_multiple_prologue function");
     }
@@ -193,7 +301,7 @@ Exiting hook
     }
 
     // test simple decorator
-    #[axin(decorator(simple_decorator))]
+    #[axin(decorator(simple_decorator), assert_order("decorator,body"))]
     fn test_simple_decorator() {
         println_test!("Inside test_simple_decorator function");
     }
@@ -261,4 +369,850 @@ Exiting param decorator: test_param
         let result = test_parameterized_decorator_with_param(100);
         assert_eq!(result, 101, "Expected result to be 101");
     }
+
+    // test the call context placeholder
+    #[axin(on_enter(context_hook(&__axin)))]
+    fn test_context() {
+        println_test!("Inside test_context function");
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Context: test_context #1
+Inside test_context function
+Context: test_context #2
+Inside test_context function
+"#
+    )))]
+    fn call_test_context() {
+        test_context();
+        test_context();
+    }
+
+    // test return value assertions
+    #[axin(assert_returns(43))]
+    fn test_assert_returns(i: i32) -> i32 {
+        i + 1
+    }
+
+    #[test]
+    fn call_test_assert_returns() {
+        assert_eq!(test_assert_returns(42), 43);
+
+        let result = std::panic::catch_unwind(|| test_assert_returns(0));
+        let payload = result.expect_err("Expected the assertion to fail");
+        let message = payload
+            .downcast_ref::<String>()
+            .expect("Expected a formatted panic message");
+        assert!(
+            message.contains("`test_assert_returns` returned an unexpected value"),
+            "Unexpected panic message: {}",
+            message
+        );
+    }
+
+    // test early returns from the prologue
+    #[axin(prologue(
+        if i < 0 {
+            return 0;
+        }
+    ))]
+    fn test_prologue_early_return(i: i32) -> i32 {
+        println_test!("Inside test_prologue_early_return function: {}", i);
+        i * 2
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Inside test_prologue_early_return function: 21\n")))]
+    fn call_test_prologue_early_return() {
+        assert_eq!(test_prologue_early_return(-1), 0);
+        assert_eq!(test_prologue_early_return(21), 42);
+    }
+
+    // test `?` in the prologue
+    #[axin(prologue(let value: i32 = input.parse().map_err(|_| "not a number")?;))]
+    fn test_prologue_try(input: &str) -> Result<i32, &'static str> {
+        println_test!("Inside test_prologue_try function: {}", value);
+        Ok(value + 1)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Inside test_prologue_try function: 41\n")))]
+    fn call_test_prologue_try() {
+        assert_eq!(test_prologue_try("41"), Ok(42));
+        assert_eq!(test_prologue_try("forty-one"), Err("not a number"));
+    }
+
+    // test `let ... else` in the prologue
+    static CONFIG: std::sync::OnceLock<i32> = std::sync::OnceLock::new();
+
+    #[axin(prologue(let Some(cfg) = CONFIG.get() else { return Err("uninitialized"); };))]
+    fn test_prologue_let_else(i: i32) -> Result<i32, &'static str> {
+        println_test!("Inside test_prologue_let_else function: {}", cfg);
+        Ok(i * cfg)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Inside test_prologue_let_else function: 2\n")))]
+    fn call_test_prologue_let_else() {
+        assert_eq!(test_prologue_let_else(21), Err("uninitialized"));
+        CONFIG.set(2).unwrap();
+        assert_eq!(test_prologue_let_else(21), Ok(42));
+    }
+
+    // test block-like statements in the prologue, which need no semicolon
+    #[axin(prologue(
+        let mut steps = 0;
+        'check: {
+            if i < 0 {
+                break 'check;
+            }
+            steps += 1;
+        }
+        if let Some(double) = i.checked_mul(2) {
+            steps += double;
+        }
+        for _ in 0..2 {
+            steps += 1;
+        }
+    ))]
+    fn test_prologue_block_like(i: i32) -> i32 {
+        steps
+    }
+
+    #[test]
+    fn call_test_prologue_block_like() {
+        assert_eq!(test_prologue_block_like(-1), 0);
+        assert_eq!(test_prologue_block_like(20), 43);
+    }
+
+    // test that an early return from the prologue still runs decorators and exit hooks
+    #[axin(
+        prologue(if i == 0 { return -1; }),
+        decorator(simple_decorator_with_param),
+        on_exit(on_exit_hook)
+    )]
+    fn test_prologue_early_return_decorated(i: i32) -> i32 {
+        println_test!("Inside test_prologue_early_return_decorated function");
+        i
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        "Entering decorator: 0\nExiting decorator: 0\nExiting hook\n"
+    )))]
+    fn call_test_prologue_early_return_decorated() {
+        assert_eq!(test_prologue_early_return_decorated(0), -1);
+    }
+
+    // test that hooks and decorators see the parameters, never the body's shadows of them
+    #[axin(
+        on_enter(parameterized_hook(data)),
+        decorator(simple_decorator_with_param),
+        on_exit(parameterized_hook(data))
+    )]
+    fn test_shadowed_param(data: &str) -> usize {
+        let data = data.trim_matches('*');
+        println_test!("Inside test_shadowed_param function: {}", data);
+        data.len()
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Param hook: *data*
+Entering decorator: *data*
+Inside test_shadowed_param function: data
+Exiting decorator: *data*
+Param hook: *data*
+"#
+    )))]
+    fn call_test_shadowed_param() {
+        assert_eq!(test_shadowed_param("*data*"), 4);
+    }
+
+    // test selecting the decorator on every call
+    static DECORATOR_MODE: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn decorator_mode() -> usize {
+        DECORATOR_MODE.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn noop_decorator<F: FnOnce(i32) -> i32>(f: F, i: i32) -> i32 {
+        f(i)
+    }
+
+    #[axin(decorator_select(
+        decorator_mode(),
+        [
+            simple_decorator_with_param,
+            parameterized_decorator_with_param("selected"),
+            noop_decorator,
+        ]
+    ))]
+    fn test_decorator_select(i: i32) -> i32 {
+        println_test!("Inside test_decorator_select function: {}", i);
+        i + 1
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Entering decorator: 0
+Inside test_decorator_select function: 0
+Exiting decorator: 0
+Entering param decorator: selected
+User arg: 1
+Inside test_decorator_select function: 1
+Exiting param decorator: selected
+Inside test_decorator_select function: 2
+"#
+    )))]
+    fn call_test_decorator_select() {
+        for mode in 0..3 {
+            DECORATOR_MODE.store(mode, std::sync::atomic::Ordering::Relaxed);
+            assert_eq!(test_decorator_select(mode as i32), mode as i32 + 1);
+        }
+
+        DECORATOR_MODE.store(3, std::sync::atomic::Ordering::Relaxed);
+        let result = std::panic::catch_unwind(|| test_decorator_select(3));
+        DECORATOR_MODE.store(0, std::sync::atomic::Ordering::Relaxed);
+        let payload = result.expect_err("Expected an out-of-range selection to panic");
+        let message = payload
+            .downcast_ref::<String>()
+            .expect("Expected a formatted panic message");
+        assert_eq!(
+            message,
+            "`test_decorator_select` selected decorator 3, but only 3 decorators are given"
+        );
+    }
+
+    // test instrumentation restricted to builds with `debug_assertions`
+    #[axin(debug_only, on_enter(on_enter_hook), on_exit(on_exit_hook))]
+    fn test_debug_only(i: i32) -> i32 {
+        println_test!("Inside test_debug_only function");
+        i + 1
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(if cfg!(debug_assertions) {
+        "Entering hook\nInside test_debug_only function\nExiting hook\n"
+    } else {
+        "Inside test_debug_only function\n"
+    })))]
+    fn call_test_debug_only() {
+        assert_eq!(test_debug_only(41), 42);
+    }
+
+    // test applying the attribute to the functions of a module
+    #[axin(on_enter(on_enter_hook), on_exit(on_exit_hook), skip(skipped))]
+    mod handlers {
+        use super::*;
+
+        pub fn first() {
+            println_test!("Inside first handler");
+        }
+
+        pub fn second(i: i32) -> i32 {
+            println_test!("Inside second handler: {}", i);
+            i * 2
+        }
+
+        pub fn skipped() {
+            println_test!("Inside skipped handler");
+        }
+
+        pub mod nested {
+            pub fn untouched() {
+                println_test!("Inside nested handler");
+            }
+        }
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Entering hook
+Inside first handler
+Exiting hook
+Entering hook
+Inside second handler: 21
+Exiting hook
+Inside skipped handler
+Inside nested handler
+"#
+    )))]
+    fn call_test_module() {
+        handlers::first();
+        assert_eq!(handlers::second(21), 42);
+        handlers::skipped();
+        handlers::nested::untouched();
+    }
+
+    // test selecting the functions of a module with `only`
+    static REPORT_CALLS: axin::CallCounter = axin::CallCounter::new();
+
+    #[axin(count_calls(REPORT_CALLS), only(counted))]
+    mod reports {
+        use super::*;
+
+        pub fn counted() {}
+
+        pub fn uncounted() {}
+    }
+
+    #[test]
+    fn call_test_module_only() {
+        reports::counted();
+        reports::uncounted();
+        reports::counted();
+        assert_eq!(REPORT_CALLS.get(), 2);
+    }
+
+    // test error hooks with `anyhow::Result`
+    #[cfg(feature = "anyhow")]
+    #[axin(on_error(error_hook), on_exit(on_exit_hook))]
+    fn test_on_error_anyhow(input: &str) -> anyhow::Result<i32> {
+        use anyhow::Context;
+
+        let value: i32 = input.parse().context("failed to parse input")?;
+        Ok(value)
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Exiting hook
+Error hook: failed to parse input: invalid digit found in string
+Exiting hook
+"#
+    )))]
+    fn call_test_on_error_anyhow() {
+        assert_eq!(test_on_error_anyhow("42").unwrap(), 42);
+        assert!(test_on_error_anyhow("4x2").is_err());
+    }
+
+    // test error hooks with boxed errors
+    #[axin(on_error(labeled_error_hook("boxed")))]
+    fn test_on_error_boxed(input: &str) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+        if input.is_empty() {
+            return Err("empty input".into());
+        }
+        Ok(input.len() as i32)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Error hook (boxed): empty input\n")))]
+    fn call_test_on_error_boxed() {
+        assert_eq!(test_on_error_boxed("abc").unwrap(), 3);
+        assert!(test_on_error_boxed("").is_err());
+    }
+
+    // test exit hooks that only run on errors
+    #[axin(on_exit(on_exit_hook, when = result.is_err()))]
+    fn test_on_exit_when_err(input: &str) -> Result<i32, std::num::ParseIntError> {
+        input.parse()
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Exiting hook\n")))]
+    fn call_test_on_exit_when_err() {
+        assert_eq!(test_on_exit_when_err("42"), Ok(42));
+        assert!(test_on_exit_when_err("4x2").is_err());
+    }
+
+    // test exit hooks that only run on slow calls, whatever the result
+    #[axin(on_exit(
+        parameterized_hook("slow exit"),
+        when = elapsed > std::time::Duration::from_millis(20)
+    ))]
+    fn test_on_exit_when_slow(sleep_ms: u64, fail: bool) -> Result<u64, u64> {
+        std::thread::sleep(std::time::Duration::from_millis(sleep_ms));
+        if fail {
+            Err(sleep_ms)
+        } else {
+            Ok(sleep_ms)
+        }
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Param hook: slow exit
+Param hook: slow exit
+"#
+    )))]
+    fn call_test_on_exit_when_slow() {
+        assert_eq!(test_on_exit_when_slow(0, false), Ok(0));
+        assert_eq!(test_on_exit_when_slow(0, true), Err(0));
+        assert_eq!(test_on_exit_when_slow(30, false), Ok(30));
+        assert_eq!(test_on_exit_when_slow(30, true), Err(30));
+    }
+
+    // test one decorator without parameters adapted to methods of any arity
+    struct Accumulator(i32);
+
+    #[axin(decorator(simple_decorator), adapt, skip(labeled))]
+    impl Accumulator {
+        fn new() -> Self {
+            Accumulator(0)
+        }
+
+        fn add(&mut self, i: i32) {
+            self.0 += i;
+        }
+
+        fn add_scaled(&mut self, i: i32, scale: i32, label: &str) -> i32 {
+            println_test!("Adding {}", label);
+            self.0 += i * scale;
+            self.0
+        }
+
+        #[axin(decorator(parameterized_decorator("labeled")), adapt)]
+        fn labeled(self, label: String) -> String {
+            format!("{}: {}", label, self.0)
+        }
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Entering decorator
+Exiting decorator
+Entering decorator
+Exiting decorator
+Entering decorator
+Adding twice
+Exiting decorator
+Entering param decorator: labeled
+Exiting param decorator: labeled
+"#
+    )))]
+    fn call_test_adapt() {
+        let mut acc = Accumulator::new();
+        acc.add(1);
+        assert_eq!(acc.add_scaled(2, 2, "twice"), 5);
+        assert_eq!(acc.labeled("total".to_string()), "total: 5");
+    }
+
+    // test logging hook errors, with a hook failing on its second call
+    static LOGGED_HOOK_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[axin(
+        on_enter(flaky_hook(&LOGGED_HOOK_CALLS)),
+        on_exit(on_exit_hook),
+        hook_errors = "log",
+        hook_error_sink = hook_error_sink
+    )]
+    fn test_hook_errors_log(i: i32) -> i32 {
+        i
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Flaky hook: call 1
+Exiting hook
+Flaky hook: call 2
+Hook error: call 2 failed
+Exiting hook
+Flaky hook: call 3
+Exiting hook
+"#
+    )))]
+    fn call_test_hook_errors_log() {
+        assert_eq!(test_hook_errors_log(1), 1);
+        assert_eq!(test_hook_errors_log(2), 2);
+        assert_eq!(test_hook_errors_log(3), 3);
+    }
+
+    // test ignoring hook errors, except for a hook logging its own
+    static IGNORED_HOOK_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+    static OVERRIDDEN_HOOK_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[axin(
+        on_enter(flaky_hook(&IGNORED_HOOK_CALLS)),
+        on_exit(flaky_hook(&OVERRIDDEN_HOOK_CALLS), errors = "log"),
+        hook_errors = "ignore",
+        hook_error_sink = hook_error_sink
+    )]
+    fn test_hook_errors_ignore(i: i32) -> i32 {
+        i
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Flaky hook: call 1
+Flaky hook: call 1
+Flaky hook: call 2
+Flaky hook: call 2
+Hook error: call 2 failed
+Flaky hook: call 3
+Flaky hook: call 3
+"#
+    )))]
+    fn call_test_hook_errors_ignore() {
+        assert_eq!(test_hook_errors_ignore(1), 1);
+        assert_eq!(test_hook_errors_ignore(2), 2);
+        assert_eq!(test_hook_errors_ignore(3), 3);
+    }
+
+    // test propagating hook errors, which skips the body when the entry hook fails. The infallible error hook opts out,
+    // since its `Infallible` error would not convert into `String`.
+    static PROPAGATED_HOOK_CALLS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    #[axin(
+        on_enter(flaky_hook(&PROPAGATED_HOOK_CALLS)),
+        on_error(error_hook, errors = "ignore"),
+        hook_errors = "propagate"
+    )]
+    fn test_hook_errors_propagate(i: i32) -> Result<i32, String> {
+        println_test!("Inside test_hook_errors_propagate function: {}", i);
+        Ok(i)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Flaky hook: call 1
+Inside test_hook_errors_propagate function: 1
+Flaky hook: call 2
+Flaky hook: call 3
+Inside test_hook_errors_propagate function: 3
+"#
+    )))]
+    fn call_test_hook_errors_propagate() {
+        assert_eq!(test_hook_errors_propagate(1), Ok(1));
+        assert_eq!(
+            test_hook_errors_propagate(2),
+            Err("call 2 failed".to_string())
+        );
+        assert_eq!(test_hook_errors_propagate(3), Ok(3));
+    }
+
+    // test minimal mode on a trait method
+    static NEXT_CALLS: axin::CallCounter = axin::CallCounter::new();
+
+    struct Countdown(u32);
+
+    impl Iterator for Countdown {
+        type Item = u32;
+
+        #[axin(minimal, count_calls(NEXT_CALLS), on_enter(on_enter_hook))]
+        fn next(&mut self) -> Option<u32> {
+            if self.0 == 0 {
+                return None;
+            }
+            self.0 -= 1;
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        "Entering hook\nEntering hook\nEntering hook\nEntering hook\n"
+    )))]
+    fn call_test_minimal_iterator() {
+        NEXT_CALLS.reset();
+        let items: Vec<_> = Countdown(3).collect();
+        assert_eq!(items, vec![2, 1, 0]);
+        // three items plus the final `None`
+        assert_eq!(NEXT_CALLS.get(), 4);
+    }
+
+    // test call counting alongside other features
+    static COUNTED_CALLS: axin::CallCounter = axin::CallCounter::new();
+
+    #[axin(count_calls(COUNTED_CALLS), decorator(simple_decorator_with_param))]
+    fn test_count_calls(i: i32) -> i32 {
+        i * 2
+    }
+
+    #[test]
+    fn call_test_count_calls() {
+        assert_eq!(test_count_calls(1), 2);
+        assert_eq!(test_count_calls(2), 4);
+        assert_eq!(COUNTED_CALLS.get(), 2);
+    }
+
+    // test decorator arguments borrowing the function's parameters
+    #[axin(decorator(authorize(&req)))]
+    fn test_decorator_borrowing_param(req: Request) -> Response {
+        println_test!("Handling: {}", req.body);
+        Response(req.body)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Authorizing: admin
+Handling: hello
+Authorizing: guest
+"#
+    )))]
+    fn call_test_decorator_borrowing_param() {
+        let request = |user: &str| Request {
+            user: user.to_string(),
+            body: "hello".to_string(),
+        };
+        assert_eq!(
+            test_decorator_borrowing_param(request("admin")),
+            Response("hello".to_string())
+        );
+        assert_eq!(
+            test_decorator_borrowing_param(request("guest")),
+            Response("denied".to_string())
+        );
+    }
+
+    // test decorators borrowing all arguments, which the body still gets to own
+    #[axin(decorator(label_by("GET"), borrow_args))]
+    fn test_borrow_args(route: String) -> Vec<u8> {
+        println_test!("Handling: {}", route);
+        route.into_bytes()
+    }
+
+    #[axin(decorator(authorize_adapted, borrow_args), adapt)]
+    fn test_borrow_args_adapted(Request { user, body }: Request) -> Response {
+        println_test!("Handling: {} from {}", body, user);
+        Response(body)
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Routing: GET /users
+Handling: /users
+Routed: GET /users
+Authorizing: admin
+Handling: hello from admin
+Authorizing: guest
+"#
+    )))]
+    fn call_test_borrow_args() {
+        assert_eq!(test_borrow_args("/users".to_string()), b"/users");
+        let request = |user: &str| Request {
+            user: user.to_string(),
+            body: "hello".to_string(),
+        };
+        assert_eq!(
+            test_borrow_args_adapted(request("admin")),
+            Response("hello".to_string())
+        );
+        assert_eq!(
+            test_borrow_args_adapted(request("guest")),
+            Response("denied".to_string())
+        );
+    }
+
+    // test replacing the body with a mock in test builds, with and without the instrumentation
+    #[axin(
+        on_enter(on_enter_hook),
+        mock_in_tests(fake_fetch_user),
+        on_exit(on_exit_hook)
+    )]
+    fn test_mock_in_tests(id: u32, Request { user, .. }: Request) -> String {
+        panic!("fetching user {} for {} needs the network", id, user);
+    }
+
+    #[axin(
+        on_enter(on_enter_hook),
+        mock_in_tests(fake_fetch_user, instrumented = false)
+    )]
+    fn test_mock_in_tests_uninstrumented(mut id: u32, request: Request) -> String {
+        id += 1;
+        panic!(
+            "fetching user {} for {} needs the network",
+            id, request.user
+        );
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        r#"Entering hook
+Faking user 7 for admin
+Exiting hook
+Faking user 8 for guest
+"#
+    )))]
+    fn call_test_mock_in_tests() {
+        let request = |user: &str| Request {
+            user: user.to_string(),
+            body: String::new(),
+        };
+        assert_eq!(test_mock_in_tests(7, request("admin")), "user 7");
+        assert_eq!(
+            test_mock_in_tests_uninstrumented(8, request("guest")),
+            "user 8"
+        );
+    }
+
+    // test logging slow calls only
+    #[axin(slow_log(std::time::Duration::from_millis(20), slow_call_hook(&__axin)))]
+    fn test_slow_log(sleep_ms: u64) {
+        std::thread::sleep(std::time::Duration::from_millis(sleep_ms));
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test("Slow call: test_slow_log\n")))]
+    fn call_test_slow_log() {
+        test_slow_log(0);
+        test_slow_log(30);
+    }
+
+    // test call site warnings alongside a usage counter
+    static LEGACY_CALLS: axin::CallCounter = axin::CallCounter::new();
+
+    #[axin(
+        warn_call_site("use test_count_calls instead"),
+        count_calls(LEGACY_CALLS)
+    )]
+    fn test_warn_call_site() -> u32 {
+        42
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn call_test_warn_call_site() {
+        assert_eq!(test_warn_call_site(), 42);
+        assert_eq!(test_warn_call_site(), 42);
+        assert_eq!(LEGACY_CALLS.get(), 2);
+    }
+
+    // test one-time initialization, run once per function before any of its hooks, even under concurrent first calls
+    static INITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn install_logger() {
+        INITS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    fn require_logger() {
+        assert!(
+            INITS.load(std::sync::atomic::Ordering::SeqCst) > 0,
+            "hook ran before the initialization"
+        );
+    }
+
+    #[axin(init(install_logger()), on_enter(require_logger))]
+    fn test_init_first(i: i32) -> i32 {
+        i + 1
+    }
+
+    #[axin(init(install_logger()), on_enter(require_logger))]
+    fn test_init_second(i: i32) -> i32 {
+        i * 2
+    }
+
+    #[test]
+    fn call_test_init() {
+        let barrier = std::sync::Barrier::new(8);
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    barrier.wait();
+                    assert_eq!(test_init_first(i), i + 1);
+                    assert_eq!(test_init_second(i), i * 2);
+                });
+            }
+        });
+        // once per function, although both use the same expression
+        assert_eq!(INITS.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    // test forwarding many parameters, through a decorator taking them one by one and through an adapted one
+    #[allow(clippy::too_many_arguments)]
+    fn forward_sixteen<F, R>(
+        f: F,
+        a: u8,
+        b: u8,
+        c: u8,
+        d: u8,
+        e: u8,
+        g: u8,
+        h: u8,
+        i: u8,
+        j: u8,
+        k: u8,
+        l: u8,
+        m: u8,
+        n: u8,
+        o: u8,
+        p: u8,
+        q: u16,
+    ) -> R
+    where
+        F: FnOnce(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16) -> R,
+    {
+        println_test!("Forwarding {} arguments", 16);
+        f(a, b, c, d, e, g, h, i, j, k, l, m, n, o, p, q)
+    }
+
+    #[axin(
+        on_enter(parameterized_hook(&a.to_string())),
+        decorator(forward_sixteen),
+        assert_order("enter,decorator,body")
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn test_sixteen_params(
+        a: u8,
+        b: u8,
+        c: u8,
+        d: u8,
+        e: u8,
+        g: u8,
+        h: u8,
+        i: u8,
+        j: u8,
+        k: u8,
+        l: u8,
+        m: u8,
+        n: u8,
+        o: u8,
+        p: u8,
+        q: u16,
+    ) -> Vec<u16> {
+        let head = [a, b, c, d, e, g, h, i, j, k, l, m, n, o, p];
+        head.iter().map(|&x| u16::from(x)).chain([q]).collect()
+    }
+
+    #[axin(
+        decorator(simple_decorator),
+        adapt,
+        on_exit(on_exit_hook),
+        assert_order("decorator,body,exit")
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn test_sixteen_params_adapted(
+        a: u8,
+        b: u8,
+        c: u8,
+        d: u8,
+        e: u8,
+        g: u8,
+        h: u8,
+        i: u8,
+        j: u8,
+        k: u8,
+        l: u8,
+        m: u8,
+        n: u8,
+        o: u8,
+        p: u8,
+        q: u16,
+    ) -> Vec<u16> {
+        let head = [a, b, c, d, e, g, h, i, j, k, l, m, n, o, p];
+        head.iter().map(|&x| u16::from(x)).chain([q]).collect()
+    }
+
+    #[test]
+    #[axin(decorator(single_threaded_test(
+        "Param hook: 1\nForwarding 16 arguments\nEntering decorator\nExiting decorator\nExiting hook\n"
+    )))]
+    fn call_test_sixteen_params() {
+        let expected = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 1600];
+        assert_eq!(
+            test_sixteen_params(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 1600),
+            expected
+        );
+        assert_eq!(
+            test_sixteen_params_adapted(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 1600),
+            expected
+        );
+    }
 }
@@ -0,0 +1,146 @@
+//! Several decorators on one function, nested so that the first given is the outermost: each is called with a callable
+//! calling the next one, and the last one's callable runs the body.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+
+fn trace<F: FnOnce(A) -> R, A: Debug, R: Debug>(name: &str) -> impl FnOnce(F, A) -> R + '_ {
+    move |f, args| {
+        record(format!("{} before {:?}", name, args));
+        let result = f(args);
+        record(format!("{} after {:?}", name, result));
+        result
+    }
+}
+
+fn outer<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    record("outer");
+    f(x + 1)
+}
+
+fn inner<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    record("inner");
+    f(x * 10)
+}
+
+fn label_by<F: FnOnce(u32) -> u32>(x: &u32) -> impl FnOnce(F, u32) -> u32 {
+    let label = format!("label {}", x);
+    move |f, x| {
+        record(label);
+        f(x)
+    }
+}
+
+fn wrapped<F: FnOnce() -> R, R>(f: F) -> R {
+    record("wrapped");
+    f()
+}
+
+fn framed<F: FnOnce() -> R, R>(f: F) -> R {
+    record("framed");
+    f()
+}
+
+#[axin(decorator(trace("a")), decorator(trace("b")), decorator(trace("c")))]
+fn double(x: u32) -> u32 {
+    record("body");
+    x * 2
+}
+
+// Each decorator passes the next one what it makes of the arguments
+#[axin(decorator(outer), decorator(inner))]
+fn identity(x: u32) -> u32 {
+    x
+}
+
+// Only the first decorator borrows the arguments for its setup
+#[axin(decorator(label_by, borrow_args), decorator(inner))]
+fn labeled(x: u32) -> u32 {
+    x
+}
+
+#[axin(
+    on_enter(record("enter")),
+    decorator(wrapped),
+    decorator(framed),
+    on_exit(record("exit")),
+    adapt
+)]
+fn join(first: String, second: &str) -> String {
+    first + second
+}
+
+#[axin(decorator(trace("a")), decorator(trace("b")), packed = struct)]
+fn area(width: u32, height: u32) -> u32 {
+    width * height
+}
+
+#[axin(
+    decorator(outer),
+    decorator(trace("inner")),
+    assert_order("decorator,body")
+)]
+fn ordered(x: u32) -> u32 {
+    x
+}
+
+#[test]
+fn the_first_decorator_is_the_outermost() {
+    assert_eq!(double(21), 42);
+    assert_eq!(
+        take_events(),
+        [
+            "a before 21",
+            "b before 21",
+            "c before 21",
+            "body",
+            "c after 42",
+            "b after 42",
+            "a after 42",
+        ]
+    );
+}
+
+#[test]
+fn arguments_are_passed_through_every_decorator() {
+    assert_eq!(identity(1), 20);
+    assert_eq!(take_events(), ["outer", "inner"]);
+}
+
+#[test]
+fn the_first_decorator_borrows_the_arguments() {
+    assert_eq!(labeled(4), 40);
+    assert_eq!(take_events(), ["label 4", "inner"]);
+}
+
+#[test]
+fn adapted_decorators_take_no_arguments() {
+    assert_eq!(join("a".to_string(), "b"), "ab");
+    assert_eq!(take_events(), ["enter", "wrapped", "framed", "exit"]);
+}
+
+#[test]
+fn packed_arguments_are_passed_to_every_decorator() {
+    assert_eq!(area(2, 3), 6);
+    assert_eq!(
+        take_events(),
+        [
+            "a before __AxinArgsArea { width: 2, height: 3 }",
+            "b before __AxinArgsArea { width: 2, height: 3 }",
+            "b after 6",
+            "a after 6",
+        ]
+    );
+}
+
+#[test]
+fn nested_decorators_are_one_phase() {
+    assert_eq!(ordered(1), 2);
+    assert_eq!(take_events(), ["outer", "inner before 2", "inner after 2"]);
+}
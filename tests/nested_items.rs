@@ -0,0 +1,162 @@
+//! Bodies declaring items of their own, such as functions, types with `impl` blocks and statics, which move into the
+//! inner callable along with the body and must still resolve, including `Self` and generic parameters.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn traced<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("decorator");
+    f(value)
+}
+
+fn wrapped<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+trait Named {
+    const NAME: &'static str;
+}
+
+impl Named for u8 {
+    const NAME: &'static str = "u8";
+}
+
+#[axin(on_enter(record("enter")), decorator(traced), on_exit(record("exit")))]
+fn nested_fn(x: u32) -> u32 {
+    fn double(x: u32) -> u32 {
+        x * 2
+    }
+    double(x)
+}
+
+#[axin(on_enter(record("enter")), decorator(traced), on_exit(record("exit")))]
+fn nested_type(x: u8) -> String {
+    struct Wrapper<U>(U);
+
+    impl<U: Named> Wrapper<U> {
+        const LABEL: &'static str = U::NAME;
+
+        fn label(&self) -> String {
+            format!("{}:{}", Self::LABEL, Self::LABEL.len())
+        }
+    }
+
+    Wrapper(x).label()
+}
+
+#[axin(on_enter(record("enter")), decorator(traced), on_exit(record("exit")))]
+fn nested_static(step: usize) -> usize {
+    static TOTAL: AtomicUsize = AtomicUsize::new(0);
+    TOTAL.fetch_add(step, Ordering::Relaxed) + step
+}
+
+// Generic parameters of the function are not visible to nested items, but nested items may be generic themselves
+#[axin(on_enter(record("enter")), decorator(traced))]
+fn nested_generic<T: Debug + Default>(value: T) -> String {
+    fn show<U: Debug>(value: U) -> String {
+        format!("{:?}", value)
+    }
+    const TIMES: usize = 2;
+    show(value).repeat(TIMES) + &show(T::default())
+}
+
+struct Holder<T>(T);
+
+impl<T: Named + Copy> Holder<T> {
+    // `Self` in the body and the nested items' own `Self` both survive the move into the inner callable
+    #[axin(on_enter(record(T::NAME)), decorator(wrapped), adapt)]
+    fn describe(&self) -> String {
+        struct Label<U>(U);
+
+        impl<U: Named> Label<U> {
+            fn get(&self) -> &'static str {
+                U::NAME
+            }
+        }
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let calls = CALLS.fetch_add(1, Ordering::Relaxed);
+        format!("{}{}{}", Label(self.0).get(), Self::suffix(), calls)
+    }
+
+    fn suffix() -> &'static str {
+        "#"
+    }
+}
+
+// Items declared in the body are visible to the prologue as well
+#[axin(prologue(let doubled = double(x) + OFFSET;), on_enter(record("enter")))]
+fn prologue_sees_items(x: u32) -> u32 {
+    const OFFSET: u32 = 1;
+
+    fn double(x: u32) -> u32 {
+        x * 2
+    }
+
+    doubled
+}
+
+// Inner attributes keep applying to the items
+#[axin(prologue(record("prologue");), on_enter(record("enter")))]
+fn inner_attributes(x: u32) -> u32 {
+    #![allow(dead_code)]
+
+    fn unused() {}
+
+    x
+}
+
+// A macro is only visible after its definition, so items using it stay behind it
+#[axin(prologue(record("prologue");), decorator(traced))]
+fn nested_macro(x: u32) -> u32 {
+    macro_rules! triple {
+        ($x:expr) => {
+            $x * 3
+        };
+    }
+
+    fn apply(x: u32) -> u32 {
+        triple!(x)
+    }
+
+    apply(x)
+}
+
+#[test]
+fn nested_functions_types_and_statics() {
+    assert_eq!(nested_fn(2), 4);
+    assert_eq!(nested_type(1), "u8:2");
+    assert_eq!(nested_static(2), 2);
+    assert_eq!(nested_static(3), 5);
+    assert_eq!(take_events(), ["enter", "decorator", "exit"].repeat(4));
+}
+
+#[test]
+fn generic_parameters_and_self() {
+    assert_eq!(nested_generic(1u8), "110");
+    assert_eq!(Holder(1u8).describe(), "u8#0");
+    assert_eq!(Holder(2u8).describe(), "u8#1");
+    assert_eq!(
+        take_events(),
+        ["enter", "decorator", "u8", "decorator", "u8", "decorator"]
+    );
+}
+
+#[test]
+fn prologues_and_items() {
+    assert_eq!(prologue_sees_items(2), 5);
+    assert_eq!(inner_attributes(7), 7);
+    assert_eq!(nested_macro(2), 6);
+    assert_eq!(
+        take_events(),
+        ["enter", "enter", "prologue", "decorator", "prologue"]
+    );
+}
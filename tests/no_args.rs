@@ -0,0 +1,75 @@
+//! `decorator(d, no_args)`: decorators written for functions without parameters, such as the `timing_decorator` of the
+//! crate documentation, wrapping functions with parameters, which hooks and decorator arguments still see.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+// As in the crate documentation
+fn timing_decorator<F, R>(func: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let start = std::time::Instant::now();
+    println!("Starting timer...");
+    let result = func();
+    println!("Took: {:?}", start.elapsed());
+    result
+}
+
+fn custom_logging_decorator<F, R>(msg: &'static str) -> impl FnOnce(F) -> R
+where
+    F: FnOnce() -> R,
+{
+    move |f| {
+        println!("Custom log: {}", msg);
+        f()
+    }
+}
+
+fn labeled<F: FnOnce() -> R, R>(label: String) -> impl FnOnce(F) -> R {
+    move |f| {
+        record(label);
+        f()
+    }
+}
+
+fn log(message: impl Into<String>) {
+    record(message);
+}
+
+#[axin(decorator(timing_decorator, no_args))]
+fn square(x: i32) -> i32 {
+    x * x
+}
+
+// The entry hook and the decorator's setup see all parameters, the exit hook clones of the ones it uses, since the
+// parameters themselves are moved into the body
+#[axin(
+    on_enter(log(format!("enter {} {}", name, repeat))),
+    decorator(labeled(format!("{}:{}", name.len(), separator)), no_args),
+    on_exit(log(format!("exit {}", repeat)))
+)]
+fn repeat(name: String, repeat: usize, separator: char) -> String {
+    vec![name; repeat].join(&separator.to_string())
+}
+
+#[axin(decorator(custom_logging_decorator("joining"), no_args))]
+fn join(mut parts: Vec<&str>, separator: &str, reverse: bool) -> String {
+    if reverse {
+        parts.reverse();
+    }
+    parts.join(separator)
+}
+
+#[test]
+fn decorators_without_parameters() {
+    assert_eq!(square(3), 9);
+    assert_eq!(join(vec!["a", "b"], "-", true), "b-a");
+    assert_eq!(repeat("ab".to_string(), 3, ','), "ab,ab,ab");
+    assert_eq!(take_events(), ["enter ab 3", "2:,", "exit 3"]);
+}
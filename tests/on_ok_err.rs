@@ -0,0 +1,95 @@
+//! Success and failure hooks of functions returning a `Result`, seeing the value or the error through their
+//! placeholders, and the exit hook still running after either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn ok(value: &String) {
+    record(format!("ok {}", value));
+}
+
+fn err(error: &String) {
+    record(format!("err {}", error));
+}
+
+fn parse_id(input: &str) -> Result<u32, String> {
+    input.parse().map_err(|_| format!("bad id {:?}", input))
+}
+
+#[axin(on_ok(ok(__axin_ok)), on_err(err(__axin_err)), on_exit(record("exit")))]
+fn greet(name: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("no name".to_string());
+    }
+    Ok(format!("hello {}", name))
+}
+
+// The body returns early with `?`, which the hooks see like any other error
+#[axin(on_ok(record(format!("user {}", __axin_ok))), on_err(err(__axin_err)))]
+fn user(id: &str) -> Result<String, String> {
+    let id = parse_id(id)?;
+    Ok(format!("#{}", id))
+}
+
+#[axin(on_err(record("failed")))]
+fn only_failures(fail: bool) -> Result<(), String> {
+    if fail {
+        Err("failure".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn check(value: &u32) -> Result<(), String> {
+    if *value > 10 {
+        Err(format!("{} is too large", value))
+    } else {
+        Ok(())
+    }
+}
+
+// A failing success hook turns the result into an error under the "propagate" policy
+#[axin(
+    on_ok(check(__axin_ok)),
+    hook_errors = "propagate",
+    on_exit(record("exit"), errors = "ignore")
+)]
+fn bounded(value: u32) -> Result<u32, String> {
+    Ok(value)
+}
+
+#[test]
+fn hooks_see_the_value_or_the_error() {
+    assert_eq!(greet("ada"), Ok("hello ada".to_string()));
+    assert_eq!(greet(""), Err("no name".to_string()));
+    assert_eq!(
+        take_events(),
+        ["ok hello ada", "exit", "err no name", "exit"]
+    );
+}
+
+#[test]
+fn early_returns_with_the_question_mark_are_errors() {
+    assert_eq!(user("7"), Ok("#7".to_string()));
+    assert_eq!(user("x"), Err(r#"bad id "x""#.to_string()));
+    assert_eq!(take_events(), ["user #7", r#"err bad id "x""#]);
+}
+
+#[test]
+fn hooks_without_placeholders_run_without_arguments() {
+    assert_eq!(only_failures(false), Ok(()));
+    assert_eq!(only_failures(true), Err("failure".to_string()));
+    assert_eq!(take_events(), ["failed"]);
+}
+
+#[test]
+fn failing_hooks_follow_the_policy() {
+    assert_eq!(bounded(3), Ok(3));
+    assert_eq!(bounded(30), Err("30 is too large".to_string()));
+    assert_eq!(take_events(), ["exit"]);
+}
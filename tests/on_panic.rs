@@ -0,0 +1,171 @@
+//! Panic hooks and always-run exit hooks, run by guards as the call unwinds. The panics are caught at the call sites,
+//! where they arrive with their payloads after the hooks ran.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+/// The message of a panic caught by `catch_unwind`.
+fn message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+    }
+}
+
+fn fail(layer: &str) {
+    record(format!("fail {}", layer));
+    panic!("hook of the {} layer failed", layer);
+}
+
+#[axin(
+    on_exit(record("exit")),
+    on_panic(record("panic")),
+    on_exit_always(record("always"))
+)]
+fn divide(a: u32, b: u32) -> u32 {
+    record("body");
+    a / b
+}
+
+// The body consumes the parameters, and the guards get clones of them
+#[axin(
+    on_panic(record(format!("rolled back {}", name))),
+    on_exit_always(record(format!("released {:?}", tags)))
+)]
+fn store(name: String, tags: Vec<String>) -> usize {
+    if tags.is_empty() {
+        panic!("no tags for {}", name);
+    }
+    name.len() + tags.len()
+}
+
+// Borrowing a `RefCell` keeps the body from being `UnwindSafe`, which the guards do not need
+#[axin(on_panic(record(format!("cell {}", cell.borrow()))))]
+fn bump(cell: &RefCell<u32>) {
+    *cell.borrow_mut() += 1;
+    panic!("bumped");
+}
+
+#[axin(
+    on_exit(fail("exit")),
+    on_panic(record("panic")),
+    on_exit_always(record("always"))
+)]
+fn failing_exit() {}
+
+#[axin(
+    on_panic(record("panic outer")),
+    on_exit_always(record("always outer"))
+)]
+#[axin(on_enter(fail("inner")), on_panic(record("panic inner")))]
+fn layered() {}
+
+#[axin(
+    on_exit(record("exit")),
+    on_exit_always(record("always")),
+    assert_order("body,exit,exit_always")
+)]
+fn ordered() {}
+
+/// Pending on its first poll, ready on the next.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[axin(on_exit_always(record(format!("always {}", id))))]
+async fn wait(id: u32) -> u32 {
+    YieldOnce(false).await;
+    id
+}
+
+fn poll<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: the vtable functions ignore the data pointer, so any pointer is valid for them.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+    future.poll(&mut Context::from_waker(&waker))
+}
+
+#[test]
+fn panic_hooks_run_as_the_call_unwinds() {
+    assert_eq!(divide(6, 3), 2);
+    assert_eq!(take_events(), ["body", "exit", "always"]);
+
+    let payload = catch_unwind(|| divide(1, 0)).unwrap_err();
+    assert_eq!(message(payload), "attempt to divide by zero");
+    assert_eq!(take_events(), ["body", "panic", "always"]);
+}
+
+#[test]
+fn guard_hooks_see_clones_of_the_parameters() {
+    assert_eq!(store("ada".to_string(), vec!["admin".to_string()]), 4);
+    assert_eq!(take_events(), [r#"released ["admin"]"#]);
+
+    let payload = catch_unwind(|| store("bob".to_string(), Vec::new())).unwrap_err();
+    assert_eq!(message(payload), "no tags for bob");
+    assert_eq!(take_events(), ["rolled back bob", "released []"]);
+
+    let cell = RefCell::new(1);
+    let payload = catch_unwind(AssertUnwindSafe(|| bump(&cell))).unwrap_err();
+    assert_eq!(message(payload), "bumped");
+    assert_eq!(take_events(), ["cell 2"]);
+}
+
+#[test]
+fn panic_hooks_do_not_run_once_the_call_returned() {
+    let payload = catch_unwind(failing_exit).unwrap_err();
+    assert_eq!(message(payload), "hook of the exit layer failed");
+    assert_eq!(take_events(), ["fail exit", "always"]);
+}
+
+#[test]
+fn guards_of_outer_layers_see_inner_entry_hooks_panic() {
+    let payload = catch_unwind(layered).unwrap_err();
+    assert_eq!(message(payload), "hook of the inner layer failed");
+    assert_eq!(take_events(), ["fail inner", "panic outer", "always outer"]);
+}
+
+#[test]
+fn always_run_exit_hooks_run_last() {
+    ordered();
+    assert_eq!(take_events(), ["exit", "always"]);
+}
+
+#[test]
+fn always_run_exit_hooks_run_when_a_future_is_dropped() {
+    let mut completed = Box::pin(wait(1));
+    assert!(poll(completed.as_mut()).is_pending());
+    assert_eq!(poll(completed.as_mut()), Poll::Ready(1));
+    assert_eq!(take_events(), ["always 1"]);
+
+    let mut cancelled = Box::pin(wait(2));
+    assert!(poll(cancelled.as_mut()).is_pending());
+    assert!(take_events().is_empty());
+    drop(cancelled);
+    assert_eq!(take_events(), ["always 2"]);
+}
@@ -0,0 +1,138 @@
+//! Constants listing the phases of calls with `order_const`, compared to the phases the calls trace with
+//! `assert_order`, for several combinations of instrumentation and stacked attributes.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+use axin::{axin, take_order_trace, CallCounter};
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn setup() {}
+
+fn cleanup() {}
+
+fn report<E>(_: &E) {}
+
+fn slow_hook(_: Duration) {}
+
+fn timing<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    f(value)
+}
+
+fn retry<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    f(value)
+}
+
+/// The phases of the steps of an order constant, without what runs in them.
+fn phases(order: &[&'static str]) -> Vec<&'static str> {
+    order
+        .iter()
+        .map(|step| step.split(':').next().unwrap())
+        .collect()
+}
+
+#[axin(
+    order_const,
+    on_enter(setup),
+    decorator(timing),
+    prologue(let doubled = id * 2;),
+    on_exit(cleanup),
+    assert_order("enter,decorator,prologue,body,exit")
+)]
+pub fn fetch_user(id: u32) -> u32 {
+    doubled
+}
+
+#[axin(
+    order_const,
+    init(setup()),
+    count_calls(CALLS),
+    decorator(timing),
+    decorator(retry),
+    slow_log(Duration::ZERO, slow_hook),
+    on_error(report),
+    on_ok(setup()),
+    on_err(cleanup()),
+    on_exit_always(cleanup()),
+    assert_order("init,count,decorator,body,slow_log,error,err,exit_always")
+)]
+fn load(code: i32) -> Result<u32, i32> {
+    std::thread::sleep(Duration::from_millis(1));
+    Err(code)
+}
+
+// The inner attribute's phases are part of the outer attribute's body
+#[axin(
+    order_const,
+    on_enter(setup),
+    on_exit(cleanup),
+    assert_order("enter,body,enter,body,epilogue,exit")
+)]
+#[axin(on_enter(setup), epilogue(let _ = 1;), assert_order("enter,body,epilogue"))]
+fn layered() {}
+
+#[axin(order_const, minimal, on_enter(setup))]
+fn tiny() {}
+
+#[test]
+fn order_constants_name_what_runs_in_each_phase() {
+    assert_eq!(
+        FETCH_USER_AXIN_ORDER,
+        [
+            "enter:setup",
+            "decorator:timing",
+            "prologue",
+            "body",
+            "exit:cleanup"
+        ]
+    );
+    assert_eq!(
+        LOAD_AXIN_ORDER,
+        [
+            "init",
+            "count:CALLS",
+            "decorator:timing > retry",
+            "body",
+            "slow_log:slow_hook",
+            "error:report",
+            "ok:setup",
+            "err:cleanup",
+            "exit_always:cleanup",
+        ]
+    );
+    tiny();
+    assert_eq!(TINY_AXIN_ORDER, ["enter:setup", "body"]);
+    assert_eq!(
+        LAYERED_AXIN_ORDER,
+        [
+            "enter:setup",
+            "body",
+            "enter:setup",
+            "body",
+            "epilogue",
+            "exit:cleanup"
+        ]
+    );
+}
+
+#[test]
+fn order_constants_list_the_traced_phases() {
+    fetch_user(1);
+    assert_eq!(take_order_trace(), phases(FETCH_USER_AXIN_ORDER));
+
+    layered();
+    assert_eq!(take_order_trace(), phases(LAYERED_AXIN_ORDER));
+}
+
+#[test]
+fn calls_run_the_phases_their_outcome_calls_for() {
+    // A failing call does not run the success hook
+    let _ = load(1);
+    let expected: Vec<_> = phases(LOAD_AXIN_ORDER)
+        .into_iter()
+        .filter(|phase| *phase != "ok")
+        .collect();
+    assert_eq!(take_order_trace(), expected);
+}
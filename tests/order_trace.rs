@@ -0,0 +1,160 @@
+//! Traces of the phases of calls with `assert_order`, which every call checks against the phases given, and which
+//! tests take with `take_order_trace` to check the order across calls and stacked attributes.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+use axin::{axin, take_order_trace, CallCounter};
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn hook() {}
+
+fn error_hook<E>(_: &E) {}
+
+fn slow_hook(_: Duration) {}
+
+fn pass<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    f(value)
+}
+
+fn pass_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+#[axin(
+    on_enter(hook),
+    decorator(pass),
+    prologue(let doubled = x * 2;),
+    on_exit(hook),
+    assert_order("enter,decorator,prologue,body,exit")
+)]
+fn hooks_and_decorator(x: u32) -> u32 {
+    doubled
+}
+
+#[axin(
+    init(hook()),
+    count_calls(CALLS),
+    on_enter(hook),
+    assert_order("init,count,enter,body")
+)]
+fn initialized_once() {}
+
+#[axin(
+    decorator(pass),
+    assert_returns(Err(0)),
+    on_error(error_hook),
+    on_exit(hook, when = result.is_err()),
+    assert_order("decorator,body,assert,error,exit")
+)]
+fn failing(code: i32) -> Result<u32, i32> {
+    Err(code)
+}
+
+#[axin(
+    slow_log(Duration::ZERO, slow_hook),
+    on_exit(hook),
+    assert_order("body,slow_log,exit")
+)]
+fn slow() {
+    std::thread::sleep(Duration::from_millis(1));
+}
+
+// Skipped hooks are not traced
+#[axin(
+    on_enter(hook, when = verbose),
+    decorator_select(verbose as usize, [pass_adapted, pass_adapted]),
+    adapt,
+    assert_order("decorator,body")
+)]
+fn selected(verbose: bool) -> bool {
+    verbose
+}
+
+// The inner attribute's phases are part of the outer attribute's body
+#[axin(
+    on_enter(hook),
+    on_exit(hook),
+    assert_order("enter,body,enter,decorator,body,exit")
+)]
+#[axin(on_enter(hook), decorator(pass), assert_order("enter,decorator,body"))]
+fn stacked(x: u32) -> u32 {
+    x
+}
+
+#[axin(on_enter(hook), assert_order("enter,body"))]
+fn callee() {}
+
+// Calls made by the body are part of its trace
+#[axin(
+    on_enter(hook),
+    on_exit(hook),
+    assert_order("enter,body,enter,body,exit")
+)]
+fn caller() {
+    callee();
+}
+
+#[test]
+fn hooks_decorators_and_prologues() {
+    assert_eq!(hooks_and_decorator(2), 4);
+    assert_eq!(
+        take_order_trace(),
+        ["enter", "decorator", "prologue", "body", "exit"]
+    );
+}
+
+#[test]
+fn initialization_and_counting() {
+    initialized_once();
+    assert_eq!(take_order_trace(), ["init", "count", "enter", "body"]);
+}
+
+#[test]
+fn assertions_and_error_hooks() {
+    assert_eq!(failing(0), Err(0));
+    slow();
+    assert_eq!(
+        take_order_trace(),
+        [
+            "decorator",
+            "body",
+            "assert",
+            "error",
+            "exit",
+            "body",
+            "slow_log",
+            "exit"
+        ]
+    );
+}
+
+#[test]
+fn skipped_hooks_and_selected_decorators() {
+    assert!(!selected(false));
+    assert_eq!(take_order_trace(), ["decorator", "body"]);
+}
+
+#[test]
+#[should_panic(
+    expected = "`selected` ran the phases [\"enter\", \"decorator\", \"body\"], expected [\"decorator\", \"body\"]"
+)]
+fn unexpected_orders_fail() {
+    selected(true);
+}
+
+#[test]
+fn stacked_attributes_and_nested_calls() {
+    assert_eq!(stacked(3), 3);
+    assert_eq!(
+        take_order_trace(),
+        ["enter", "body", "enter", "decorator", "body", "exit"]
+    );
+    caller();
+    assert_eq!(
+        take_order_trace(),
+        ["enter", "body", "enter", "body", "exit"]
+    );
+}
@@ -0,0 +1,108 @@
+//! Arguments passed to decorators packed into a struct generated for the function with `packed = struct`, whose `Debug`
+//! representation names every argument.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::Debug;
+
+fn logged<F: FnOnce(A) -> R, A: Debug, R>(f: F, args: A) -> R {
+    record(format!("{:?}", args));
+    f(args)
+}
+
+fn labeled<F: FnOnce(A) -> R, A: Debug, R>(label: &str) -> impl FnOnce(F, A) -> R + '_ {
+    move |f, args| {
+        record(format!("{} {:?}", label, args));
+        f(args)
+    }
+}
+
+#[derive(Debug)]
+pub struct DateRange {
+    pub from: u32,
+    pub to: u32,
+}
+
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[allow(clippy::too_many_arguments)]
+#[axin(decorator(logged), packed = struct)]
+fn fetch_report(
+    user_id: u64,
+    range: &DateRange,
+    format: &str,
+    limit: Option<usize>,
+    tags: Vec<String>,
+    mut verbose: bool,
+) -> String {
+    verbose &= limit.is_some();
+    format!(
+        "{}:{}-{}:{}:{}:{}",
+        user_id,
+        range.from,
+        range.to,
+        format,
+        tags.join("+"),
+        verbose
+    )
+}
+
+// Generic parameters, parameters bound by patterns and named lifetimes
+#[axin(decorator(labeled("pick")), packed = struct)]
+fn pick<'a, T: Clone + Debug, const N: usize>(
+    items: &'a [T; N],
+    Point { x, y }: Point,
+) -> Option<T> {
+    items.get((x + y) as usize).cloned()
+}
+
+// Type parameters that only the return type uses
+#[axin(decorator_select(raw.len() % 2, [logged, logged]), packed = struct)]
+fn parse<T: std::str::FromStr>(raw: &str) -> Option<T> {
+    raw.parse().ok()
+}
+
+#[test]
+fn decorators_see_named_fields() {
+    let range = DateRange { from: 3, to: 9 };
+    let report = fetch_report(
+        7,
+        &range,
+        "csv",
+        Some(10),
+        vec!["a".to_string(), "b".to_string()],
+        true,
+    );
+    assert_eq!(report, "7:3-9:csv:a+b:true");
+    assert_eq!(
+        take_events(),
+        [concat!(
+            r#"__AxinArgsFetchReport { user_id: 7, range: DateRange { from: 3, to: 9 }, format: "csv", "#,
+            r#"limit: Some(10), tags: ["a", "b"], verbose: true }"#
+        )]
+    );
+}
+
+#[test]
+fn generics_patterns_and_lifetimes() {
+    assert_eq!(pick(&['a', 'b', 'c'], Point { x: 1, y: 1 }), Some('c'));
+    assert_eq!(parse::<u8>("42"), Some(42));
+    assert_eq!(parse::<u8>("x"), None);
+    assert_eq!(
+        take_events(),
+        [
+            "pick __AxinArgsPick { items: ['a', 'b', 'c'], __arg1: Point { x: 1, y: 1 } }",
+            r#"__AxinArgsParse { raw: "42" }"#,
+            r#"__AxinArgsParse { raw: "x" }"#
+        ]
+    );
+}
@@ -0,0 +1,121 @@
+//! Pairs of a sync and an async variant of one function, instrumented alike by `axin_pair!`, with hooks, decorators
+//! and attributes of their own.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin_pair, AxinCtx};
+use common::{block_on, record, take_events};
+use std::future::Future;
+use std::task::{Context, Poll};
+
+fn trace(ctx: &AxinCtx, id: u32) {
+    record(format!("enter {} {}", ctx.fn_name, id));
+}
+
+fn failed(error: &String) {
+    record(format!("failed {}", error));
+}
+
+/// Passes the body through, returning its future unchanged for the async variant, which is then awaited.
+fn pass<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+/// Ready on its second poll, so that awaiting it suspends the body once.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+axin_pair!({
+    on_enter(trace(&__axin, id)),
+    on_exit(record("exit")),
+    on_error(failed),
+    decorator(pass),
+    adapt,
+} => {
+    fn fetch(id: u32) -> Result<String, String> {
+        record("body");
+        if id == 0 {
+            return Err("no id".to_string());
+        }
+        Ok(format!("item {}", id))
+    }
+
+    async fn fetch_async(id: u32) -> Result<String, String> {
+        YieldOnce(false).await;
+        record("body");
+        if id == 0 {
+            return Err("no id".to_string());
+        }
+        Ok(format!("item {}", id))
+    }
+});
+
+// Attributes of a function of the pair are layered under the shared arguments
+axin_pair!({ on_enter(record("shared")) } => {
+    #[axin::axin(on_enter(record("sync only")))]
+    pub fn store(value: &str) -> usize {
+        value.len()
+    }
+
+    pub async fn store_async(value: &str) -> usize {
+        value.len()
+    }
+});
+
+#[test]
+fn both_variants_run_the_same_hooks() {
+    assert_eq!(fetch(1).as_deref(), Ok("item 1"));
+    let sync_events = take_events();
+    assert_eq!(block_on(fetch_async(1)).as_deref(), Ok("item 1"));
+    let async_events: Vec<_> = take_events()
+        .into_iter()
+        .map(|event| event.replace("fetch_async", "fetch"))
+        .collect();
+    assert_eq!(sync_events, ["enter fetch 1", "decorator", "body", "exit"]);
+    assert_eq!(async_events, sync_events);
+}
+
+#[test]
+fn both_variants_handle_errors_alike() {
+    assert!(fetch(0).is_err());
+    assert!(block_on(fetch_async(0)).is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "enter fetch 0",
+            "decorator",
+            "body",
+            "failed no id",
+            "exit",
+            "enter fetch_async 0",
+            "decorator",
+            "body",
+            "failed no id",
+            "exit"
+        ]
+    );
+}
+
+#[test]
+fn own_attributes_are_layered_under_the_shared_arguments() {
+    assert_eq!(store("abc"), 3);
+    assert_eq!(block_on(store_async("de")), 2);
+    assert_eq!(take_events(), ["shared", "sync only", "shared"]);
+}
@@ -0,0 +1,94 @@
+//! Hook arguments referring to the function's parameters. Entry hooks see the parameters themselves, before the body
+//! gets them, and exit hooks see clones of the ones they refer to, taken before the call, as well as the references
+//! passed to the function. Names that are not parameters resolve as anywhere else.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+
+fn audit(user_id: u64, payload: &str) {
+    record(format!("audit {} {}", user_id, payload));
+}
+
+fn notify(user: String, tags: &[String]) {
+    record(format!("notify {} {:?}", user, tags));
+}
+
+fn forward<F: FnOnce(String, Vec<String>) -> R, R>(f: F, user: String, tags: Vec<String>) -> R {
+    record("decorator");
+    f(user, tags)
+}
+
+/// Not a parameter of any function below.
+const CHANNEL: &str = "mail";
+
+#[axin(on_enter(audit(user_id, payload)), on_exit(audit(user_id, payload)))]
+fn handle(user_id: u64, payload: &str) -> String {
+    format!("{}:{}", user_id, payload)
+}
+
+// The body takes the parameters by value, and the exit hook gets them as they were passed
+#[axin(
+    on_enter(notify(user.clone(), &tags)),
+    decorator(forward),
+    on_exit(notify(user, &tags), when = !tags.is_empty())
+)]
+fn tag(mut user: String, mut tags: Vec<String>) -> String {
+    user.push('!');
+    tags.clear();
+    user
+}
+
+#[axin(adapt, on_exit(notify(format!("{} via {}", user, CHANNEL), &[])))]
+fn greet(user: String) -> String {
+    format!("hello {}", user)
+}
+
+// A mutable reference is reborrowed by the call, so the exit hook sees what the body did through it
+#[axin(on_exit(record(format!("pushed {:?}", log))))]
+fn push(log: &mut Vec<String>, entry: String) {
+    log.push(entry);
+}
+
+#[axin(on_exit(notify(user, &[])))]
+async fn fetch(user: String) -> usize {
+    user.len()
+}
+
+#[test]
+fn entry_hooks_see_the_parameters() {
+    assert_eq!(handle(7, "ping"), "7:ping");
+    assert_eq!(take_events(), ["audit 7 ping", "audit 7 ping"]);
+}
+
+#[test]
+fn exit_hooks_see_clones_of_the_parameters() {
+    assert_eq!(tag("ada".to_string(), vec!["admin".to_string()]), "ada!");
+    assert_eq!(tag("bob".to_string(), Vec::new()), "bob!");
+    assert_eq!(
+        take_events(),
+        [
+            r#"notify ada ["admin"]"#,
+            "decorator",
+            r#"notify ada ["admin"]"#,
+            "notify bob []",
+            "decorator",
+        ]
+    );
+
+    assert_eq!(greet("eve".to_string()), "hello eve");
+    assert_eq!(block_on(fetch("dan".to_string())), 3);
+    assert_eq!(take_events(), ["notify eve via mail []", "notify dan []"]);
+}
+
+#[test]
+fn exit_hooks_see_referenced_parameters() {
+    let mut log = Vec::new();
+    push(&mut log, "a".to_string());
+    assert_eq!(log, ["a"]);
+    assert_eq!(take_events(), [r#"pushed ["a"]"#]);
+}
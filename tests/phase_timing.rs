@@ -0,0 +1,176 @@
+//! Timings of the phases of calls with `phase_timing`, measured around hooks, decorators and bodies that sleep for
+//! known durations.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+use axin::{axin, PhaseTimings};
+use std::cell::RefCell;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const ENTER: Duration = Duration::from_millis(20);
+const DECORATOR: Duration = Duration::from_millis(30);
+const BODY: Duration = Duration::from_millis(40);
+const EXIT: Duration = Duration::from_millis(10);
+
+thread_local! {
+    static TIMINGS: RefCell<Vec<(&'static str, PhaseTimings)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn report(timings: PhaseTimings) {
+    report_as("call", timings);
+}
+
+fn report_as(label: &'static str, timings: PhaseTimings) {
+    TIMINGS.with(|all| all.borrow_mut().push((label, timings)));
+}
+
+fn take_timings() -> Vec<(&'static str, PhaseTimings)> {
+    TIMINGS.with(|all| all.take())
+}
+
+fn slow_enter() {
+    sleep(ENTER);
+}
+
+fn slow_exit() {
+    sleep(EXIT);
+}
+
+fn slow_error<E>(_: &E) {
+    sleep(EXIT);
+}
+
+fn slow_decorator<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    sleep(DECORATOR);
+    f(value)
+}
+
+fn slow_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    let result = f();
+    sleep(DECORATOR);
+    result
+}
+
+#[axin(
+    on_enter(slow_enter),
+    decorator(slow_decorator),
+    on_exit(slow_exit),
+    phase_timing(report)
+)]
+fn work(x: u32) -> u32 {
+    sleep(BODY);
+    x + 1
+}
+
+// Without hooks or a decorator, only the body takes time
+#[axin(phase_timing(report_as("bare")))]
+fn bare(text: &str) -> usize {
+    sleep(BODY);
+    text.len()
+}
+
+// Packed arguments and errors
+#[axin(decorator(slow_decorator), packed = struct, on_error(slow_error), phase_timing(report))]
+fn parse(raw: &str, radix: u32) -> Result<u32, std::num::ParseIntError> {
+    sleep(BODY);
+    u32::from_str_radix(raw, radix)
+}
+
+struct Worker {
+    done: u32,
+}
+
+impl Worker {
+    #[axin(
+        on_enter(slow_enter),
+        decorator(slow_adapted),
+        adapt,
+        phase_timing(report_as("method"))
+    )]
+    fn step(&mut self) -> u32 {
+        sleep(BODY);
+        self.done += 1;
+        self.done
+    }
+}
+
+/// Check that every phase took at least its sleep, and that all phases together took no longer than the whole call.
+fn assert_phases(timings: &PhaseTimings, expected: [Duration; 4], call: Duration) {
+    let phases = [
+        timings.enter,
+        timings.decorator_overhead,
+        timings.body,
+        timings.exit,
+    ];
+    for (phase, expected) in phases.iter().zip(expected) {
+        assert!(
+            *phase >= expected,
+            "{:?} took less than {:?}",
+            timings,
+            expected
+        );
+    }
+    assert_eq!(timings.total(), phases.iter().sum::<Duration>());
+    assert!(
+        timings.total() <= call,
+        "{:?} took longer than the call",
+        timings
+    );
+}
+
+#[test]
+fn phases_take_at_least_their_sleeps() {
+    let start = Instant::now();
+    assert_eq!(work(1), 2);
+    let call = start.elapsed();
+
+    let timings = take_timings();
+    assert_eq!(timings.len(), 1);
+    let (label, timings) = timings[0];
+    assert_eq!(label, "call");
+    assert_phases(&timings, [ENTER, DECORATOR, BODY, EXIT], call);
+    // The body's sleep is not part of the decorator's time, nor the hooks' sleeps part of the body's
+    assert!(timings.decorator_overhead < DECORATOR + BODY);
+    assert!(timings.body < BODY + DECORATOR);
+}
+
+#[test]
+fn calls_without_hooks_and_methods() {
+    let start = Instant::now();
+    assert_eq!(bare("axin"), 4);
+    let bare_call = start.elapsed();
+
+    let mut worker = Worker { done: 0 };
+    let start = Instant::now();
+    assert_eq!(worker.step(), 1);
+    let method_call = start.elapsed();
+
+    let timings = take_timings();
+    let [("bare", bare), ("method", method)] = timings[..] else {
+        panic!("unexpected timings {:?}", timings);
+    };
+    assert_phases(
+        &bare,
+        [Duration::ZERO, Duration::ZERO, BODY, Duration::ZERO],
+        bare_call,
+    );
+    assert!(bare.enter < ENTER && bare.decorator_overhead < DECORATOR && bare.exit < EXIT);
+    assert_phases(
+        &method,
+        [ENTER, DECORATOR, BODY, Duration::ZERO],
+        method_call,
+    );
+}
+
+#[test]
+fn packed_arguments_and_errors() {
+    let start = Instant::now();
+    assert!(parse("zz", 10).is_err());
+    let call = start.elapsed();
+
+    let timings = take_timings();
+    assert_eq!(timings.len(), 1);
+    assert_phases(&timings[0].1, [Duration::ZERO, DECORATOR, BODY, EXIT], call);
+}
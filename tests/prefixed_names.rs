@@ -0,0 +1,75 @@
+//! Items of the crate named with the `__axin` prefix, as a binding generator may produce, used by instrumented functions
+//! next to the names Axin generates. Only the placeholders listed in the crate documentation are reserved, and only
+//! where the attribute refers to them, so the prefix itself stays usable.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, AxinCtx, CallCounter};
+use common::{record, take_events};
+
+// Bindings to a C library, named like the statics and functions Axin declares in instrumented functions
+extern "C" fn __axin_checksum(data: *const u8, len: usize) -> u32 {
+    // SAFETY: callers pass a pointer to `len` readable bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    bytes.iter().map(|&byte| u32::from(byte)).sum()
+}
+
+static __AXIN_CALLS: CallCounter = CallCounter::new();
+static __AXIN_INIT: &str = "library";
+
+fn log_call(ctx: &AxinCtx, handle: u32) {
+    record(format!(
+        "call #{} to {} with {}",
+        ctx.call_id, ctx.fn_name, handle
+    ));
+}
+
+fn log_event(id: u16) {
+    record(format!("event {:#x}", id));
+}
+
+// The call context and the event id are bound next to the crate's items, whose names differ from theirs
+#[axin(
+    init(record(__AXIN_INIT)),
+    count_calls(__AXIN_CALLS),
+    event_id = 0x2a,
+    on_enter(log_call(&__axin, __axin_handle)),
+    on_exit(log_event(__axin_event_id))
+)]
+fn __axin_checksum_of(__axin_handle: u32, data: &[u8]) -> u32 {
+    __axin_checksum(data.as_ptr(), data.len()) + __axin_handle
+}
+
+// A decorator and a prologue next to a parameter with the prefix
+fn forward<F: FnOnce(&[u8]) -> R, R>(f: F, data: &[u8]) -> R {
+    record("decorator");
+    f(data)
+}
+
+#[axin(decorator(forward), prologue(let __axin_len = data.len();))]
+fn __axin_length(data: &[u8]) -> usize {
+    __axin_len
+}
+
+#[test]
+fn prefixed_items_of_the_crate() {
+    assert_eq!(__axin_checksum_of(1, b"ab"), 196);
+    assert_eq!(__axin_checksum_of(2, b"a"), 99);
+    assert_eq!(__AXIN_CALLS.get(), 2);
+    assert_eq!(
+        take_events(),
+        [
+            "library",
+            "call #1 to __axin_checksum_of with 1",
+            "event 0x2a",
+            "call #2 to __axin_checksum_of with 2",
+            "event 0x2a",
+        ]
+    );
+
+    assert_eq!(__axin_length(b"abc"), 3);
+    assert_eq!(take_events(), ["decorator"]);
+}
@@ -0,0 +1,85 @@
+//! Instrumented functions using the call context, call counters, hook results, phase timings and profile sinks, with
+//! nothing imported from `axin` but the attribute and the prelude.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use axin::prelude::*;
+use common::{record, take_events};
+use std::time::Duration;
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn enter(ctx: &AxinCtx) {
+    record(format!("enter {}", ctx.fn_name));
+}
+
+/// An entry hook's outcome, which fails below a threshold.
+struct Budget(u32);
+
+impl HookResult for Budget {
+    type Error = String;
+
+    fn into_hook_result(self) -> Result<(), String> {
+        if self.0 < 10 {
+            Err(format!("budget of {} too small", self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn check_budget(_: &str, (budget,): &(&u32,)) -> Budget {
+    Budget(**budget)
+}
+
+fn report<E: ToString>(error: &E) {
+    record(error.to_string());
+}
+
+fn timed(timings: PhaseTimings) {
+    record(format!("timed {}", timings.total() >= timings.body));
+}
+
+fn profiled(ctx: &AxinCtx, _: PhaseTimings) {
+    record(format!("profiled {}", ctx.fn_name));
+}
+
+fn slow(_: &AxinCtx, _: Duration) {}
+
+#[axin(on_enter(enter(&__axin)), count_calls(CALLS), phase_timing(timed))]
+fn load(id: u32) -> u32 {
+    id + 1
+}
+
+#[axin(profile)]
+fn fetch(id: u32) -> u32 {
+    id * 2
+}
+
+#[axin(on_enter(check_budget, with_args), hook_errors = "log", hook_error_sink = report)]
+fn spend(budget: u32) -> u32 {
+    budget / 2
+}
+
+#[test]
+fn runtime_items_come_from_the_prelude() {
+    axin::set_profile_sinks(ProfileSinks::new().record(profiled).slow(slow)).unwrap();
+
+    assert_eq!(load(1), 2);
+    assert_eq!(CALLS.get(), 1);
+    assert_eq!(fetch(2), 4);
+    assert_eq!(spend(4), 2);
+    assert_eq!(
+        take_events(),
+        [
+            "enter load",
+            "timed true",
+            "profiled fetch",
+            "budget of 4 too small"
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+//! Functions instrumented with `profile`, whose timings and slow calls are reported to the sinks registered with
+//! `set_profile_sinks`, with the default and given thresholds, decorators and methods.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, set_profile_sinks, AxinCtx, PhaseTimings, ProfileSinks};
+use common::{record, take_events};
+use std::thread::sleep;
+use std::time::Duration;
+
+const BODY: Duration = Duration::from_millis(10);
+
+fn record_timings(ctx: &AxinCtx, timings: PhaseTimings) {
+    assert!(timings.total() >= timings.body);
+    record(format!("record {} #{}", ctx.fn_name, ctx.call_id));
+}
+
+fn warn_slow(ctx: &AxinCtx, elapsed: Duration) {
+    assert!(elapsed >= BODY, "{:?} is shorter than the body", elapsed);
+    record(format!("slow {} #{}", ctx.fn_name, ctx.call_id));
+}
+
+/// Register the sinks, which every test does, and only the first one succeeds at.
+fn register_sinks() {
+    let sinks = ProfileSinks::new().record(record_timings).slow(warn_slow);
+    let _ = set_profile_sinks(sinks);
+}
+
+fn pass<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    f(x)
+}
+
+#[axin(profile)]
+fn fast(x: u32) -> u32 {
+    x + 1
+}
+
+#[axin(profile(slow = 5ms))]
+fn slow(x: u32) -> u32 {
+    sleep(BODY);
+    x
+}
+
+// A threshold given as an expression, and a decorator whose time counts toward it
+#[axin(profile(slow = Duration::from_secs(1),), decorator(pass))]
+fn decorated(x: u32) -> u32 {
+    x * 2
+}
+
+struct Cache {
+    hits: u32,
+}
+
+impl Cache {
+    #[axin(profile(slow = 1s), adapt)]
+    fn hit(&mut self) -> u32 {
+        self.hits += 1;
+        self.hits
+    }
+}
+
+#[test]
+fn every_call_is_recorded_and_counted() {
+    register_sinks();
+    assert_eq!(fast(1), 2);
+    assert_eq!(fast(2), 3);
+    assert_eq!(decorated(3), 6);
+    let mut cache = Cache { hits: 0 };
+    assert_eq!(cache.hit(), 1);
+    assert_eq!(
+        take_events(),
+        [
+            "record fast #1",
+            "record fast #2",
+            "record decorated #1",
+            "record hit #1"
+        ]
+    );
+}
+
+#[test]
+fn calls_above_the_threshold_are_slow() {
+    register_sinks();
+    assert_eq!(slow(4), 4);
+    assert_eq!(take_events(), ["slow slow #1", "record slow #1"]);
+}
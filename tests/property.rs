@@ -0,0 +1,196 @@
+//! Property tests checking that the instrumentation never changes what a function returns.
+//!
+//! Every shape of function is defined twice from the same body, once instrumented and once as written, and generated
+//! inputs must make both return identical values, errors included. The hooks and decorators only have side effects, so
+//! any difference is a bug of the generated code.
+
+#![cfg(not(feature = "disable"))]
+
+use axin::{axin, CallCounter};
+use proptest::prelude::*;
+use std::cell::Cell;
+use std::num::ParseIntError;
+
+thread_local! {
+    /// Count of hook and decorator runs, so that the instrumentation is known to run.
+    static EFFECTS: Cell<usize> = const { Cell::new(0) };
+}
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn effect() {
+    EFFECTS.with(|effects| effects.set(effects.get() + 1));
+}
+
+fn observe<T>(_: &T) {
+    effect();
+}
+
+fn pass<F: FnOnce() -> R, R>(f: F) -> R {
+    effect();
+    f()
+}
+
+fn pass_1<F: FnOnce(A) -> R, A, R>(f: F, a: A) -> R {
+    effect();
+    f(a)
+}
+
+fn pass_2<F: FnOnce(A, B) -> R, A, B, R>(f: F, a: A, b: B) -> R {
+    effect();
+    f(a, b)
+}
+
+fn pass_3<F: FnOnce(A, B, C) -> R, A, B, C, R>(f: F, a: A, b: B, c: C) -> R {
+    effect();
+    f(a, b, c)
+}
+
+/// Define the function `$instrumented` with the attribute `$attr`, and `$plain` without it, with the same signature
+/// and body.
+macro_rules! pair {
+    (#[$attr:meta] fn $instrumented:ident / $plain:ident ($($arg:ident: $ty:ty),*) -> $ret:ty $body:block) => {
+        #[$attr]
+        fn $instrumented($($arg: $ty),*) -> $ret $body
+
+        fn $plain($($arg: $ty),*) -> $ret $body
+    };
+}
+
+// No arguments, a `String`
+pair! {
+    #[axin(on_enter(effect), on_exit(effect), decorator(pass))]
+    fn greeting / greeting_plain() -> String {
+        "hello".repeat(3)
+    }
+}
+
+// One argument, a number
+pair! {
+    #[axin(on_enter(observe(&x)), on_exit(effect), count_calls(CALLS))]
+    fn mix / mix_plain(x: u64) -> u64 {
+        x.rotate_left(7) ^ x.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+// One argument, a `Result` with errors of its own
+pair! {
+    #[axin(on_enter(effect), decorator(pass_1), on_error(observe))]
+    fn parse / parse_plain(input: String) -> Result<i32, ParseIntError> {
+        let value: i32 = input.trim().parse()?;
+        Ok(value.wrapping_mul(3))
+    }
+}
+
+// Two arguments, a `Vec`, returning early
+pair! {
+    #[axin(prologue(effect();), decorator(pass_2), on_exit(effect))]
+    fn windows / windows_plain(data: Vec<u8>, size: usize) -> Vec<u16> {
+        if size == 0 {
+            return Vec::new();
+        }
+        data.windows(size)
+            .map(|window| window.iter().map(|&byte| byte as u16).sum())
+            .collect()
+    }
+}
+
+// Three arguments, an `Option`, with the arguments captured by an adapted decorator
+pair! {
+    #[axin(on_enter(observe(&text)), decorator(pass), adapt, on_exit(effect))]
+    fn find / find_plain(text: String, needle: char, from: usize) -> Option<usize> {
+        text.char_indices()
+            .skip(from)
+            .find(|&(_, c)| c == needle)
+            .map(|(index, _)| index)
+    }
+}
+
+// Three arguments, a tuple, with a decorator selected on every call
+pair! {
+    #[axin(decorator_select(a as usize % 2, [pass_3, pass_3]), on_enter(effect))]
+    fn divide / divide_plain(a: i64, b: i64, fallback: i64) -> (i64, bool) {
+        match a.checked_div(b) {
+            Some(quotient) => (quotient, true),
+            None => (fallback, false),
+        }
+    }
+}
+
+// Four arguments, a `Result` with a `String` error, emitted unchanged
+pair! {
+    #[axin(raw_body, on_enter(observe(&name)), on_exit(effect))]
+    fn label / label_plain(name: String, id: u32, width: usize, strict: bool) -> Result<String, String> {
+        if strict && name.is_empty() {
+            return Err(format!("no name for {}", id));
+        }
+        Ok(format!("{:>width$}#{}", name, id, width = width % 16))
+    }
+}
+
+// Four arguments, a float, in minimal mode
+pair! {
+    #[axin(minimal, on_enter(effect), count_calls(CALLS))]
+    fn blend / blend_plain(a: f64, b: f64, weight: f32, invert: bool) -> f64 {
+        let weight = if invert { 1.0 - weight as f64 } else { weight as f64 };
+        a * weight + b * (1.0 - weight)
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn numbers(x in any::<u64>()) {
+        prop_assert_eq!(mix(x), mix_plain(x));
+    }
+
+    #[test]
+    fn results(input in prop_oneof![any::<i32>().prop_map(|n| format!(" {} ", n)), ".{0,12}"]) {
+        prop_assert_eq!(parse(input.clone()), parse_plain(input));
+    }
+
+    #[test]
+    fn vectors(data in prop::collection::vec(any::<u8>(), 0..32), size in 0usize..6) {
+        prop_assert_eq!(windows(data.clone(), size), windows_plain(data, size));
+    }
+
+    #[test]
+    fn options(text in "[a-c ]{0,16}", needle in prop::char::range('a', 'd'), from in 0usize..20) {
+        prop_assert_eq!(find(text.clone(), needle, from), find_plain(text, needle, from));
+    }
+
+    #[test]
+    fn tuples(a in any::<i64>(), b in prop_oneof![Just(0i64), Just(-1), any::<i64>()], fallback in any::<i64>()) {
+        prop_assert_eq!(divide(a, b, fallback), divide_plain(a, b, fallback));
+    }
+
+    #[test]
+    fn errors(name in "[a-z]{0,4}", id in any::<u32>(), width in any::<usize>(), strict in any::<bool>()) {
+        prop_assert_eq!(
+            label(name.clone(), id, width, strict),
+            label_plain(name, id, width, strict)
+        );
+    }
+
+    #[test]
+    fn floats(a in any::<f64>(), b in any::<f64>(), weight in any::<f32>(), invert in any::<bool>()) {
+        // Compared bit by bit, so that NaNs and signed zeros count as well
+        prop_assert_eq!(
+            blend(a, b, weight, invert).to_bits(),
+            blend_plain(a, b, weight, invert).to_bits()
+        );
+    }
+}
+
+#[test]
+fn no_arguments() {
+    assert_eq!(greeting(), greeting_plain());
+}
+
+#[test]
+fn the_instrumentation_runs() {
+    let _ = greeting();
+    let _ = find("abc".to_string(), 'c', 0);
+    assert_eq!(EFFECTS.with(Cell::get), 6);
+}
@@ -0,0 +1,131 @@
+//! Functions instrumented with `raw_body`, whose bodies are emitted unchanged, with hooks around them.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, AxinCtx, CallCounter};
+use common::{record, take_events};
+
+fn enter(name: &str) {
+    record(format!("enter {}", name));
+}
+
+fn exit() {
+    record("exit");
+}
+
+/// Early returns and `?` under `cfg`, which leave the function itself, still run the exit hook.
+#[axin(raw_body, on_enter(enter("parse")), on_exit(exit))]
+fn parse(input: &str) -> Result<u32, String> {
+    #[cfg(debug_assertions)]
+    if input.is_empty() {
+        record("empty in debug");
+        return Err("empty".to_string());
+    }
+    #[cfg(not(debug_assertions))]
+    if input.is_empty() {
+        return Err("empty".to_string());
+    }
+    let value: u32 = input.trim().parse().map_err(|_| "invalid".to_string())?;
+    #[cfg(target_pointer_width = "64")]
+    {
+        record("64-bit");
+    }
+    Ok(value)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[axin(raw_body, on_enter(enter("add_one")), on_exit(exit))]
+fn add_one(x: u64) -> u64 {
+    let y: u64;
+    // SAFETY: only moves and adds between the given registers
+    unsafe {
+        std::arch::asm!("mov {0}, {1}", "add {0}, 1", out(reg) y, in(reg) x);
+    }
+    y
+}
+
+#[cfg(target_arch = "aarch64")]
+#[axin(raw_body, on_enter(enter("add_one")), on_exit(exit))]
+fn add_one(x: u64) -> u64 {
+    let y: u64;
+    // SAFETY: only adds between the given registers
+    unsafe {
+        std::arch::asm!("add {0}, {1}, #1", out(reg) y, in(reg) x);
+    }
+    y
+}
+
+static BUMPS: CallCounter = CallCounter::new();
+
+fn trace(ctx: &AxinCtx) {
+    record(format!("call #{} to {}", ctx.call_id, ctx.fn_name));
+}
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    /// The body keeps using `self` as written, since nothing captures it.
+    #[axin(raw_body, on_enter(trace(&__axin)), on_exit(exit), count_calls(BUMPS))]
+    fn bump(&mut self) -> u32 {
+        self.count += 1;
+        self.count
+    }
+}
+
+#[test]
+fn hooks_run_around_the_unchanged_body() {
+    assert_eq!(parse(" 42 "), Ok(42));
+    assert_eq!(parse("x"), Err("invalid".to_string()));
+    let mut expected = vec!["enter parse"];
+    if cfg!(target_pointer_width = "64") {
+        expected.push("64-bit");
+    }
+    expected.extend(["exit", "enter parse", "exit"]);
+    assert_eq!(take_events(), expected);
+}
+
+#[test]
+fn early_returns_run_the_exit_hook() {
+    assert_eq!(parse(""), Err("empty".to_string()));
+    let mut expected = vec!["enter parse"];
+    if cfg!(debug_assertions) {
+        expected.push("empty in debug");
+    }
+    expected.push("exit");
+    assert_eq!(take_events(), expected);
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn inline_assembly() {
+    assert_eq!(add_one(41), 42);
+    assert_eq!(take_events(), ["enter add_one", "exit"]);
+}
+
+#[test]
+fn methods_and_the_call_context() {
+    let mut counter = Counter { count: 0 };
+    assert_eq!(counter.bump(), 1);
+    assert_eq!(counter.bump(), 2);
+    assert_eq!(BUMPS.get(), 2);
+    assert_eq!(
+        take_events(),
+        ["call #1 to bump", "exit", "call #2 to bump", "exit"]
+    );
+}
+
+#[test]
+fn panics_skip_the_exit_hook() {
+    #[axin(raw_body, on_enter(enter("fail")), on_exit(exit))]
+    fn fail() {
+        panic!("failed");
+    }
+
+    assert!(std::panic::catch_unwind(fail).is_err());
+    assert_eq!(take_events(), ["enter fail"]);
+}
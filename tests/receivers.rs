@@ -0,0 +1,134 @@
+//! Exit hooks using the receiver of a method. Methods taking `&self` or `&mut self` lend it to the body, and get it back
+//! for the exit hook once the body has returned. A method taking `self` by value can only use it in its entry hook.
+//!
+//! With warnings denied, as in the other test crates, the expansion must not introduce unnecessary `mut`s either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn forward<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+fn log_state(stats: &Stats) {
+    record(format!("state {}", stats.calls));
+}
+
+fn log_item(item: &str) {
+    record(format!("item {}", item));
+}
+
+/// Records its drop, to tell whether a parameter is dropped before or after the exit hook.
+struct Tracked(&'static str);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        record(format!("drop {}", self.0));
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    calls: u32,
+}
+
+#[derive(Default)]
+struct Counter {
+    stats: Stats,
+    items: Vec<String>,
+}
+
+impl Counter {
+    fn record_completed(&self) {
+        record(format!("completed {}", self.items.len()));
+    }
+
+    fn fake_push(&mut self, item: String) -> usize {
+        self.items.push(format!("fake {}", item));
+        self.items.len()
+    }
+
+    #[axin(decorator(forward), adapt, on_exit(log_state(&self.stats)))]
+    fn peek(&self) -> usize {
+        self.items.len()
+    }
+
+    #[axin(decorator(forward), adapt, on_exit(Self::record_completed(self)))]
+    fn push(&mut self, mut item: String, _guard: Tracked) -> usize {
+        item.push('!');
+        self.stats.calls += 1;
+        self.items.push(item);
+        self.items.len()
+    }
+
+    // Without a decorator, with the predicate using `self` too, and the body returning early
+    #[axin(adapt, on_exit(log_state(&self.stats), when = self.stats.calls > 1))]
+    fn bump(&mut self, by: u32) -> u32 {
+        if by == 0 {
+            return self.stats.calls;
+        }
+        self.stats.calls += by;
+        self.stats.calls
+    }
+
+    // The mock is lent the receiver like the body
+    #[axin(
+        decorator(forward),
+        adapt,
+        on_exit(Self::record_completed(self)),
+        mock_in_tests(Self::fake_push)
+    )]
+    fn push_mocked(&mut self, item: String) -> usize {
+        self.items.push(item);
+        self.items.len()
+    }
+
+    #[axin(raw_body, on_exit(log_state(&self.stats)))]
+    fn calls(&self) -> u32 {
+        self.stats.calls
+    }
+
+    // A receiver taken by value is the body's, so only the entry hook can use it
+    #[axin(decorator(forward), adapt, on_enter(log_state(&self.stats)), on_exit(log_item("done")))]
+    fn into_items(self) -> Vec<String> {
+        self.items
+    }
+}
+
+#[test]
+fn shared_receivers() {
+    let counter = Counter::default();
+    assert_eq!(counter.peek(), 0);
+    assert_eq!(counter.calls(), 0);
+    assert_eq!(take_events(), ["decorator", "state 0", "state 0"]);
+}
+
+#[test]
+fn mutable_receivers() {
+    let mut counter = Counter::default();
+    assert_eq!(counter.push("a".to_string(), Tracked("guard")), 1);
+    // The parameters are still dropped with the body, before the exit hook
+    assert_eq!(take_events(), ["decorator", "drop guard", "completed 1"]);
+
+    assert_eq!(counter.bump(0), 1);
+    assert_eq!(counter.bump(2), 3);
+    assert_eq!(take_events(), ["state 3"]);
+
+    assert_eq!(counter.push_mocked("b".to_string()), 2);
+    assert_eq!(counter.items, ["a!", "fake b"]);
+    assert_eq!(take_events(), ["decorator", "completed 2"]);
+}
+
+#[test]
+fn receivers_taken_by_value() {
+    let mut counter = Counter::default();
+    counter.bump(1);
+    assert_eq!(counter.into_items(), Vec::<String>::new());
+    assert_eq!(take_events(), ["state 1", "decorator", "item done"]);
+}
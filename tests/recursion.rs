@@ -0,0 +1,189 @@
+//! Recursive functions, whose recursive calls refer to the instrumented function by its name and are thus instrumented
+//! themselves, unless the body recurses through an uninstrumented twin.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn count(events: &[String], event: &str) -> usize {
+    events.iter().filter(|recorded| *recorded == event).count()
+}
+
+fn traced<F: FnOnce(u64) -> R, R>(f: F, n: u64) -> R {
+    record("decorator");
+    f(n)
+}
+
+fn traced_slice<'a, T, F: FnOnce(&'a [T]) -> R, R>(f: F, items: &'a [T]) -> R {
+    record("decorator");
+    f(items)
+}
+
+#[axin(on_enter(record("enter")), on_exit(record("exit")))]
+fn fib(n: u64) -> u64 {
+    if n < 2 {
+        n
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[axin(on_enter(record(format!("enter {}", n))), decorator(traced))]
+fn factorial(n: u64) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    n * factorial(n - 1)
+}
+
+#[axin(raw_body, on_enter(record("enter")))]
+fn depth(n: u64) -> u64 {
+    if n == 0 {
+        0
+    } else {
+        1 + depth(n - 1)
+    }
+}
+
+// Only the outermost call is instrumented, since the body recurses through the twin, which recurses through itself
+#[axin(twin = "fib_fast", on_enter(record("enter")), decorator(traced))]
+fn fib_once(n: u64) -> u64 {
+    if n < 2 {
+        n
+    } else {
+        fib_fast(n - 1) + fib_fast(n - 2)
+    }
+}
+
+// With `nested_body`, the body runs in a nested function declaring the same generic parameters, under a name of its own
+#[axin(nested_body, on_enter(record("enter")), decorator(traced_slice))]
+fn sum_to<T: Copy + Into<u64>>(items: &[T]) -> u64 {
+    match items {
+        [] => 0,
+        [first, rest @ ..] => (*first).into() + sum_to(rest),
+    }
+}
+
+#[axin(nested_body, on_enter(record("enter")))]
+fn body_path(n: u64) -> &'static str {
+    fn marker() {}
+    fn path_of<T>(_: T) -> &'static str {
+        std::any::type_name::<T>()
+    }
+    if n == 0 {
+        path_of(marker)
+    } else {
+        body_path(n - 1)
+    }
+}
+
+struct Wrapper<T>(T);
+
+// Without `nested_body`, a method attributed on its own can use the generic parameters of its `impl` block, which a
+// nested function could not see
+impl<T: Clone> Wrapper<T> {
+    #[axin(on_enter(record("enter")))]
+    fn repeat(value: T, times: usize) -> Vec<T> {
+        if times == 0 {
+            Vec::new()
+        } else {
+            let mut rest = Wrapper::<T>::repeat(value.clone(), times - 1);
+            rest.push(value);
+            rest
+        }
+    }
+}
+
+fn new() {
+    record("free new");
+}
+
+fn make() {
+    record("free make");
+}
+
+struct Service;
+
+// Inside an `impl` block, a bare name refers to the free function, not to the associated function of the same name
+#[axin(on_enter(new), on_exit(self::make))]
+impl Service {
+    fn new() -> Self {
+        Service
+    }
+
+    fn make() -> Service {
+        Service
+    }
+}
+
+#[test]
+fn recursive_calls_are_instrumented() {
+    assert_eq!(fib(5), 5);
+    let events = take_events();
+    // fib(5) makes 15 calls in all, each entering and exiting once
+    assert_eq!((count(&events, "enter"), count(&events, "exit")), (15, 15));
+    assert_eq!(events[..2], ["enter", "enter"]);
+
+    assert_eq!(factorial(3), 6);
+    assert_eq!(
+        take_events(),
+        [
+            "enter 3",
+            "decorator",
+            "enter 2",
+            "decorator",
+            "enter 1",
+            "decorator",
+            "enter 0",
+            "decorator"
+        ]
+    );
+
+    assert_eq!(depth(4), 4);
+    assert_eq!(count(&take_events(), "enter"), 5);
+}
+
+#[test]
+fn nested_bodies_run_in_a_nested_function() {
+    assert_eq!(sum_to(&[1u8, 2, 3]), 6);
+    let events = take_events();
+    assert_eq!(
+        (count(&events, "enter"), count(&events, "decorator")),
+        (4, 4)
+    );
+
+    assert_eq!(
+        body_path(2),
+        "recursion::body_path::__axin_original::marker"
+    );
+    assert_eq!(count(&take_events(), "enter"), 3);
+}
+
+#[test]
+fn methods_of_generic_impl_blocks_run_their_body_in_a_closure() {
+    assert_eq!(Wrapper::repeat('x', 3), ['x', 'x', 'x']);
+    assert_eq!(count(&take_events(), "enter"), 4);
+    let _ = Wrapper(());
+}
+
+#[test]
+fn recursion_through_a_twin_is_not_instrumented() {
+    assert_eq!(fib_once(10), 55);
+    assert_eq!(take_events(), ["enter", "decorator"]);
+    assert_eq!(fib_fast(10), 55);
+    assert!(take_events().is_empty());
+}
+
+#[test]
+fn bare_names_in_impl_blocks_are_free_functions() {
+    let _service = Service::new();
+    let _service = Service::make();
+    assert_eq!(
+        take_events(),
+        ["free new", "free make", "free new", "free make"]
+    );
+}
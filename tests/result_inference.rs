@@ -0,0 +1,105 @@
+//! Decorators whose return type is only known from the function's return type, combined with exit hooks whose
+//! predicates use the result before it is returned.
+
+#![cfg(not(feature = "disable"))]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::fmt::{Debug, Display};
+
+fn exit(label: &str) {
+    record(format!("exit {}", label));
+}
+
+/// A decorator converting the function's output, so that its own output is only bound by the conversion.
+fn converted<F: FnOnce(T) -> U, T, U: Into<R>, R>(f: F, value: T) -> R {
+    f(value).into()
+}
+
+/// Like [`converted`], for functions adapted to take no arguments.
+fn converted_adapted<F: FnOnce() -> U, U: Into<R>, R>(f: F) -> R {
+    f().into()
+}
+
+/// A decorator returning the function's output as it is, whose type is all an `impl Trait` return type can be inferred
+/// from.
+fn traced<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("traced".to_string());
+    f(value)
+}
+
+/// A decorator producing its output itself, ignoring the function's.
+fn defaulted<F: FnOnce(T) -> R, T, R: Default>(_: F, _: T) -> R {
+    R::default()
+}
+
+#[axin(decorator(converted), on_exit(exit("unit"), when = result.eq(&())))]
+fn unit(value: u32) {
+    record(format!("unit {}", value));
+}
+
+#[axin(decorator(converted), on_exit(exit("widened"), when = result.is_power_of_two()))]
+fn widened(value: u32) -> u64 {
+    value as u64
+}
+
+#[axin(decorator(converted), on_exit(exit("parsed"), when = result.is_ok()))]
+fn parsed(input: &str) -> Result<i64, String> {
+    input.parse::<i64>().map_err(|error| error.to_string())
+}
+
+#[axin(decorator(defaulted), on_exit(exit("generic"), when = result.to_string().is_empty()))]
+fn generic<T: Default + Display>(value: T) -> T {
+    value
+}
+
+#[axin(decorator(converted_adapted), adapt, on_exit(exit("vec"), when = result.len() > 1))]
+fn generic_vec<T: Clone>(value: T, count: usize) -> Vec<T> {
+    vec![value; count]
+}
+
+#[axin(decorator(traced), on_exit(exit("impl"), when = format!("{:?}", result).len() > 2))]
+fn displayed(value: u32) -> impl Debug {
+    value * 100
+}
+
+#[axin(decorator(traced), on_exit(exit("pair"), when = result.1.is_empty()))]
+fn pair(value: u32) -> (impl Debug, String) {
+    (value, String::new())
+}
+
+#[test]
+fn unit_returns() {
+    unit(1);
+    assert_eq!(take_events(), ["unit 1", "exit unit"]);
+}
+
+#[test]
+fn concrete_returns() {
+    assert_eq!(widened(4), 4);
+    assert_eq!(widened(3), 3);
+    assert_eq!(parsed("12"), Ok(12));
+    assert!(parsed("x").is_err());
+    assert_eq!(take_events(), ["exit widened", "exit parsed"]);
+}
+
+#[test]
+fn generic_returns() {
+    assert_eq!(generic(7u8), 0);
+    assert_eq!(generic(String::from("ignored")), "");
+    assert_eq!(generic_vec('a', 2), ['a', 'a']);
+    assert_eq!(generic_vec('b', 1), ['b']);
+    assert_eq!(take_events(), ["exit generic", "exit vec"]);
+}
+
+#[test]
+fn impl_trait_returns() {
+    assert_eq!(format!("{:?}", displayed(3)), "300");
+    assert_eq!(format!("{:?}", pair(2).0), "2");
+    assert_eq!(
+        take_events(),
+        ["traced", "exit impl", "traced", "exit pair"]
+    );
+}
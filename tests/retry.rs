@@ -0,0 +1,74 @@
+//! Decorators calling the inner callable more than once, to retry a failed call. The decorator gets the arguments
+//! alongside the callable, so it reuses each one as its type allows: a `&mut` reference is reborrowed for every attempt,
+//! a shared reference copied, and only values passed by value are cloned.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+use std::cell::Cell;
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A name counting its clones.
+#[derive(Debug)]
+struct Name(String);
+
+impl Clone for Name {
+    fn clone(&self) -> Self {
+        CLONES.with(|clones| clones.set(clones.get() + 1));
+        Name(self.0.clone())
+    }
+}
+
+fn take_clones() -> usize {
+    CLONES.with(|clones| clones.replace(0))
+}
+
+/// Call `f` up to `attempts` times, until it succeeds. The buffer is reborrowed for every attempt, and the name cloned
+/// for all but the last.
+fn retry<F, T, E>(attempts: u32) -> impl FnOnce(F, &mut Vec<u8>, Name) -> Result<T, E>
+where
+    F: FnMut(&mut Vec<u8>, Name) -> Result<T, E>,
+{
+    move |mut f, buffer, name| {
+        for attempt in 1..attempts {
+            match f(&mut *buffer, name.clone()) {
+                Ok(value) => return Ok(value),
+                Err(_) => record(format!("attempt {} failed", attempt)),
+            }
+        }
+        f(buffer, name)
+    }
+}
+
+/// Append the name to the buffer, failing until it holds at least four bytes.
+#[axin(decorator(retry(2)), on_enter(record(format!("enter {}", name.0))))]
+fn append(buffer: &mut Vec<u8>, name: Name) -> Result<usize, usize> {
+    buffer.extend_from_slice(name.0.as_bytes());
+    if buffer.len() < 4 {
+        return Err(buffer.len());
+    }
+    Ok(buffer.len())
+}
+
+#[test]
+fn attempts_share_the_mutable_buffer() {
+    let mut buffer = Vec::new();
+    assert_eq!(append(&mut buffer, Name("ab".to_string())), Ok(4));
+    // Both attempts appended to the same buffer, and the name was cloned for the first one only
+    assert_eq!(buffer, b"abab");
+    assert_eq!(take_clones(), 1);
+    assert_eq!(take_events(), ["enter ab", "attempt 1 failed"]);
+
+    let mut buffer = Vec::new();
+    assert_eq!(append(&mut buffer, Name("a".to_string())), Err(2));
+    assert_eq!(buffer, b"aa");
+    assert_eq!(take_clones(), 1);
+    assert_eq!(take_events(), ["enter a", "attempt 1 failed"]);
+}
@@ -0,0 +1,185 @@
+//! Axin testing axin: test functions instrumented with a decorator that runs them one at a time and checks what they
+//! record, calling instrumented helpers, whose decorators are instrumented as well, which call instrumented leaves.
+//! Counters, placeholders, guards and async functions are used together on every level, as a canary for the
+//! interactions between features. The call ids the leaves record start over in every test, with the `test-support`
+//! feature.
+
+#![cfg(all(feature = "test-support", not(feature = "disable")))]
+#![deny(warnings)]
+
+mod common;
+
+#[cfg(test)]
+mod canary {
+    use crate::common::{block_on, record, take_events};
+    use axin::{axin, take_order_trace, test_support, AxinCtx, CallCounter};
+    use std::cell::Cell;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// Held by the test running, so that tests checking the counters run one at a time.
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    thread_local! {
+        /// Whether this thread holds [`TEST_LOCK`], so that checks nested in a test do not wait for it.
+        static HOLDS_LOCK: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// Lock [`TEST_LOCK`] unless this thread holds it already. A test failing while holding it does not fail the
+    /// others.
+    fn lock_tests() -> Option<MutexGuard<'static, ()>> {
+        if HOLDS_LOCK.with(Cell::get) {
+            return None;
+        }
+        let guard = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        HOLDS_LOCK.with(|holds| holds.set(true));
+        Some(guard)
+    }
+
+    /// Releases the lock taken by [`lock_tests`], if any, when dropped, even when the test panics.
+    struct Unlock(Option<MutexGuard<'static, ()>>);
+
+    impl Drop for Unlock {
+        fn drop(&mut self) {
+            if self.0.take().is_some() {
+                HOLDS_LOCK.with(|holds| holds.set(false));
+            }
+        }
+    }
+
+    /// The test decorator: runs the test alone, from fresh state, and checks the events it records and the phases it
+    /// traces. Checks may be nested, each taking what was traced while it ran, and keeping the events recorded before
+    /// it.
+    fn checked<F: FnOnce() -> R, R>(
+        events: &'static [&'static str],
+        phases: &'static [&'static str],
+    ) -> impl FnOnce(F) -> R {
+        move |f| {
+            let unlock = Unlock(lock_tests());
+            if unlock.0.is_some() {
+                test_support::reset_all();
+                LEAF_CALLS.reset();
+                FETCHES.reset();
+            }
+            let outer_events = take_events();
+            take_order_trace();
+            let result = f();
+            assert_eq!(take_events(), events);
+            assert_eq!(take_order_trace(), phases);
+            outer_events.into_iter().for_each(record);
+            result
+        }
+    }
+
+    fn entered(ctx: &AxinCtx) {
+        record(format!("{} #{}", ctx.fn_name, ctx.call_id));
+    }
+
+    static LEAF_CALLS: CallCounter = CallCounter::new();
+    static FETCHES: CallCounter = CallCounter::new();
+
+    // The leaves
+    #[axin(
+        count_calls(LEAF_CALLS),
+        on_enter(entered(&__axin)),
+        on_exit_always(record("leaf guard")),
+        assert_order("count,enter,body,exit_always")
+    )]
+    fn leaf(x: u32) -> u32 {
+        if x == 0 {
+            panic!("leaf of nothing");
+        }
+        x * 2
+    }
+
+    #[axin(
+        count_calls(FETCHES),
+        on_enter(entered(&__axin)),
+        on_exit(record(format!("fetched {}", __axin_result))),
+        on_exit_always(record("fetch guard")),
+        assert_order("count,enter,body,count,enter,body,exit_always,exit,exit_always")
+    )]
+    async fn fetch(x: u32) -> u32 {
+        leaf(x) + 1
+    }
+
+    // The decorator of the helpers, instrumented itself
+    #[axin(on_enter(record("guarded")), on_exit_always(record("guarded done")))]
+    fn guarded<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+        f(x)
+    }
+
+    // The helpers
+    #[axin(
+        decorator(guarded),
+        on_exit(record(format!("helper -> {}", __axin_result))),
+        on_panic(record("helper panicked")),
+        assert_order("decorator,body,count,enter,body,exit_always,count,enter,body,exit_always,exit")
+    )]
+    fn helper(x: u32) -> u32 {
+        leaf(x) + leaf(x + 1)
+    }
+
+    // A helper checked on its own, wherever it is called from
+    #[axin(
+        decorator(checked(&["fetch #1", "leaf #1", "leaf guard", "fetched 3", "fetch guard"], &[
+            "count", "enter", "body", "count", "enter", "body", "exit_always", "exit", "exit_always"
+        ])),
+        on_exit(record("checked helper")),
+        adapt
+    )]
+    fn checked_helper(x: u32) -> u32 {
+        block_on(fetch(x))
+    }
+
+    // Its trace is whole although the check takes the trace in the middle of it
+    #[axin(
+        on_exit(record("around")),
+        assert_order("body,count,enter,body,count,enter,body,exit_always,exit,exit_always,exit")
+    )]
+    fn traced_around(x: u32) -> u32 {
+        checked_helper(x)
+    }
+
+    // The tests
+    #[axin(decorator(checked(
+        &["guarded", "leaf #1", "leaf guard", "leaf #2", "leaf guard", "guarded done", "helper -> 6"],
+        &[
+            "decorator", "body", "count", "enter", "body", "exit_always", "count", "enter", "body", "exit_always", "exit"
+        ],
+    )))]
+    #[test]
+    fn three_levels() {
+        assert_eq!(helper(1), 6);
+        assert_eq!(LEAF_CALLS.get(), 2);
+    }
+
+    #[axin(decorator(checked(&["checked helper", "around"], &["exit"])))]
+    #[test]
+    fn nested_checks() {
+        assert_eq!(traced_around(1), 3);
+        assert_eq!((FETCHES.get(), LEAF_CALLS.get()), (1, 1));
+    }
+
+    #[axin(decorator(checked(&[], &[])))]
+    #[test]
+    #[should_panic(expected = "leaf of nothing")]
+    fn a_failing_test_leaves_the_others_alone() {
+        helper(0);
+    }
+
+    #[axin(decorator(checked(
+        &[
+            "guarded", "leaf #1", "leaf guard", "guarded done", "helper panicked", "guarded", "leaf #2", "leaf guard",
+            "leaf #3", "leaf guard", "guarded done", "helper -> 10"
+        ],
+        &[
+            "decorator", "body", "count", "enter", "body", "exit_always", "count", "enter", "body", "exit_always", "exit"
+        ],
+    )))]
+    #[test]
+    fn calls_after_a_panic_trace_their_own_phases() {
+        assert!(std::panic::catch_unwind(|| helper(0)).is_err());
+        take_order_trace();
+        assert_eq!(helper(2), 10);
+    }
+}
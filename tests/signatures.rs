@@ -0,0 +1,171 @@
+//! Every combination of `async`, `unsafe`, an `extern` ABI, generic parameters and a `&self` receiver, instrumented
+//! with hooks and a decorator. All of them keep their signature as written and run the instrumentation the same way.
+//!
+//! With warnings denied, as in the other test crates, the expansion must not introduce unnecessary `unsafe` blocks
+//! either.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::block_on;
+use std::cell::RefCell;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(event: &'static str) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+/// Check that the instrumentation ran in order for the call returning `value`, and pass it on.
+fn traced(value: u32) -> u32 {
+    EVENTS.with(|events| assert_eq!(events.take(), ["enter", "decorator", "exit"]));
+    value
+}
+
+fn enter() {
+    record("enter");
+}
+
+fn exit() {
+    record("exit");
+}
+
+// The decorators return what the inner callable returns, which async functions await
+fn forward<F: FnOnce(A) -> R, A, R>(f: F, arg: A) -> R {
+    record("decorator");
+    f(arg)
+}
+
+fn forward_adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+/// Define a function and a generic one reading a value through a raw pointer, and a method and a generic one adding
+/// their receiver's offset to it, all with the modifiers given. Pointers are read with `read_volatile`, which is
+/// `unsafe` whether or not the function is.
+macro_rules! signatures {
+    ($($(#[$attr:meta])* [$($modifiers:tt)*] $name:ident, $generic:ident, $method:ident, $generic_method:ident;)*) => {
+        $(
+            $(#[$attr])*
+            #[axin(on_enter(enter), on_exit(exit), decorator(forward))]
+            $($modifiers)* fn $name(value: *const u32) -> u32 {
+                #[allow(unused_unsafe)]
+                unsafe {
+                    value.read_volatile()
+                }
+            }
+
+            $(#[$attr])*
+            #[axin(on_enter(enter), on_exit(exit), decorator(forward))]
+            $($modifiers)* fn $generic<T: Copy + Into<u32>>(value: *const T) -> u32 {
+                #[allow(unused_unsafe)]
+                unsafe {
+                    value.read_volatile().into()
+                }
+            }
+        )*
+
+        impl Offset {
+            $(
+                $(#[$attr])*
+                #[axin(on_enter(enter), on_exit(exit), decorator(forward_adapted), adapt)]
+                $($modifiers)* fn $method(&self, value: *const u32) -> u32 {
+                    #[allow(unused_unsafe)]
+                    unsafe {
+                        self.0 + value.read_volatile()
+                    }
+                }
+
+                $(#[$attr])*
+                #[axin(on_enter(enter), on_exit(exit), decorator(forward_adapted), adapt)]
+                $($modifiers)* fn $generic_method<T: Copy + Into<u32>>(&self, value: *const T) -> u32 {
+                    #[allow(unused_unsafe)]
+                    unsafe {
+                        self.0 + value.read_volatile().into()
+                    }
+                }
+            )*
+        }
+    };
+}
+
+struct Offset(u32);
+
+signatures! {
+    [] plain, generic, method, generic_method;
+    [unsafe] unsafe_fn, unsafe_generic, unsafe_method, unsafe_generic_method;
+    [extern "C"] extern_fn, extern_generic, extern_method, extern_generic_method;
+    [unsafe extern "C"] unsafe_extern, unsafe_extern_generic, unsafe_extern_method, unsafe_extern_generic_method;
+    [async] async_fn, async_generic, async_method, async_generic_method;
+    [async unsafe] async_unsafe, async_unsafe_generic, async_unsafe_method, async_unsafe_generic_method;
+    // C cannot call async functions, whose futures have no C equivalent, but Rust can
+    #[allow(improper_ctypes_definitions)]
+    [async extern "C"] async_extern, async_extern_generic, async_extern_method, async_extern_generic_method;
+    #[allow(improper_ctypes_definitions)]
+    [async unsafe extern "C"]
+    async_unsafe_extern, async_unsafe_extern_generic, async_unsafe_extern_method, async_unsafe_extern_generic_method;
+}
+
+#[test]
+fn synchronous_signatures() {
+    let (value, small, offset) = (&1u32 as *const u32, &2u8 as *const u8, Offset(10));
+    assert_eq!(traced(plain(value)), 1);
+    assert_eq!(traced(generic(small)), 2);
+    assert_eq!(traced(offset.method(value)), 11);
+    assert_eq!(traced(offset.generic_method(small)), 12);
+    assert_eq!(traced(extern_fn(value)), 1);
+    assert_eq!(traced(extern_generic(small)), 2);
+    assert_eq!(traced(offset.extern_method(value)), 11);
+    assert_eq!(traced(offset.extern_generic_method(small)), 12);
+    unsafe {
+        assert_eq!(traced(unsafe_fn(value)), 1);
+        assert_eq!(traced(unsafe_generic(small)), 2);
+        assert_eq!(traced(offset.unsafe_method(value)), 11);
+        assert_eq!(traced(offset.unsafe_generic_method(small)), 12);
+        assert_eq!(traced(unsafe_extern(value)), 1);
+        assert_eq!(traced(unsafe_extern_generic(small)), 2);
+        assert_eq!(traced(offset.unsafe_extern_method(value)), 11);
+        assert_eq!(traced(offset.unsafe_extern_generic_method(small)), 12);
+    }
+}
+
+#[test]
+fn async_signatures() {
+    let (value, small, offset) = (&1u32 as *const u32, &2u8 as *const u8, Offset(10));
+    assert_eq!(traced(block_on(async_fn(value))), 1);
+    assert_eq!(traced(block_on(async_generic(small))), 2);
+    assert_eq!(traced(block_on(offset.async_method(value))), 11);
+    assert_eq!(traced(block_on(offset.async_generic_method(small))), 12);
+    assert_eq!(traced(block_on(async_extern(value))), 1);
+    assert_eq!(traced(block_on(async_extern_generic(small))), 2);
+    assert_eq!(traced(block_on(offset.async_extern_method(value))), 11);
+    assert_eq!(
+        traced(block_on(offset.async_extern_generic_method(small))),
+        12
+    );
+    unsafe {
+        assert_eq!(traced(block_on(async_unsafe(value))), 1);
+        assert_eq!(traced(block_on(async_unsafe_generic(small))), 2);
+        assert_eq!(traced(block_on(offset.async_unsafe_method(value))), 11);
+        assert_eq!(
+            traced(block_on(offset.async_unsafe_generic_method(small))),
+            12
+        );
+        assert_eq!(traced(block_on(async_unsafe_extern(value))), 1);
+        assert_eq!(traced(block_on(async_unsafe_extern_generic(small))), 2);
+        assert_eq!(
+            traced(block_on(offset.async_unsafe_extern_method(value))),
+            11
+        );
+        assert_eq!(
+            traced(block_on(offset.async_unsafe_extern_generic_method(small))),
+            12
+        );
+    }
+}
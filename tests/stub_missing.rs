@@ -0,0 +1,60 @@
+//! `stub_missing(hook, ...)`: hooks that do not exist yet, stubbed out so that the crate builds while they are being
+//! written. The stubs do nothing, but the arguments of the stubbed hooks are still evaluated and type checked, and the
+//! other hooks run as usual.
+//!
+//! Warnings are denied as in the other test crates, but the warning naming the stubbed hooks is allowed on each
+//! function, as it is meant to be seen until the hooks exist.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{record, take_events};
+
+fn log(message: &str) {
+    record(message);
+}
+
+fn forward<F: FnOnce(u32) -> R, R>(f: F, x: u32) -> R {
+    record("decorator");
+    f(x)
+}
+
+// Neither `audit` nor `report_failure` exists
+#[allow(deprecated)]
+#[axin(
+    stub_missing(audit, report_failure),
+    on_enter(audit("load", id, &__axin)),
+    decorator(forward),
+    on_error(report_failure),
+    on_exit(log("loaded"))
+)]
+fn load(id: u32) -> Result<u32, String> {
+    id.checked_sub(1).ok_or_else(|| "no id".to_string())
+}
+
+// The stubs are named apart from the hooks, so the body still sees its own `audit`
+#[allow(deprecated)]
+#[axin(stub_missing(audit), on_enter(audit, with_args), on_exit(audit("audited", &x)))]
+fn audited(x: u32) -> u32 {
+    fn audit(x: u32) -> u32 {
+        record(format!("body audit {}", x));
+        x + 1
+    }
+    audit(x)
+}
+
+#[test]
+fn stubbed_hooks_are_skipped() {
+    assert_eq!(load(7), Ok(6));
+    assert_eq!(load(0), Err("no id".to_string()));
+    assert_eq!(
+        take_events(),
+        ["decorator", "loaded", "decorator", "loaded"]
+    );
+
+    assert_eq!(audited(2), 3);
+    assert_eq!(take_events(), ["body audit 2"]);
+}
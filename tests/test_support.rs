@@ -0,0 +1,122 @@
+//! Resetting the state instrumented functions keep across calls, with the `test-support` feature: tests that would
+//! otherwise see each other's calls pass in any order once they reset it.
+
+#![cfg(all(feature = "test-support", not(feature = "disable")))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::{axin, test_support, AxinCtx};
+use cached::proc_macro::cached;
+use common::{record, take_events};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+fn call(ctx: &AxinCtx) {
+    record(format!("{} {}", ctx.fn_name, ctx.call_id));
+}
+
+/// The state is shared by the threads the tests run on, so the tests calling the functions run one at a time.
+fn serial() -> MutexGuard<'static, ()> {
+    static SERIAL: Mutex<()> = Mutex::new(());
+    SERIAL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[axin(init(record("init")), on_enter(call(&__axin)))]
+fn handler() {}
+
+#[axin(on_enter(call(&__axin)))]
+pub fn other() {}
+
+// The accessor comes from the attribute below `#[cached]`, and resets the state of both attributes
+#[axin(init(record("init square")))]
+#[cached]
+#[axin(on_enter(call(&__axin)))]
+fn square(x: u64) -> u64 {
+    x * x
+}
+
+struct Service;
+
+impl Service {
+    // An associated function without visibility referring to `Self` may be in a trait `impl` block, see below
+    #[axin(on_enter(call(&__axin)))]
+    pub(crate) fn create() -> Self {
+        Service
+    }
+
+    // Methods taking `self` get no accessor, `reset_all` resets them
+    #[axin(init(record("init run")), adapt)]
+    fn run(&self) {}
+}
+
+trait Job {
+    fn work(&self);
+}
+
+#[axin(on_enter(call(&__axin)), adapt)]
+impl Job for Service {
+    fn work(&self) {}
+}
+
+// The attribute of a method in a trait `impl` block cannot tell it is there, and emits no accessor for it
+impl From<u32> for Service {
+    #[axin(on_enter(call(&__axin)))]
+    fn from(_: u32) -> Self {
+        Service
+    }
+}
+
+#[test]
+fn first_calls_after_resetting_all() {
+    let _serial = serial();
+    test_support::reset_all();
+    handler();
+    other();
+    handler();
+    assert_eq!(take_events(), ["init", "handler 1", "other 1", "handler 2"]);
+}
+
+#[test]
+fn first_calls_after_resetting_one_function() {
+    let _serial = serial();
+    handler();
+    other();
+    take_events();
+
+    reset_handler_axin_state();
+    handler();
+    other();
+    assert_eq!(take_events()[..2], ["init", "handler 1"]);
+}
+
+#[test]
+fn methods_are_reset_as_well() {
+    let _serial = serial();
+    let service = Service::create();
+    service.run();
+    service.work();
+    let _ = Service::from(1);
+    take_events();
+
+    Service::reset_create_axin_state();
+    Service::create();
+    assert_eq!(take_events(), ["create 1"]);
+
+    test_support::reset_all();
+    service.run();
+    service.work();
+    let _ = Service::from(1);
+    assert_eq!(take_events(), ["init run", "work 1", "from 1"]);
+}
+
+#[test]
+fn functions_instrumented_around_other_macros_are_reset_once() {
+    let _serial = serial();
+    square(2);
+    square(3);
+    take_events();
+
+    reset_square_axin_state();
+    square(4);
+    assert_eq!(take_events(), ["square 1", "init square"]);
+}
@@ -0,0 +1,230 @@
+//! Functions returning boxed and otherwise owned trait objects, whose bodies rely on the return type to coerce concrete
+//! types to the trait object, instrumented with hooks and decorators.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use axin::axin;
+use common::{block_on, record, take_events};
+use std::rc::Rc;
+use std::sync::Arc;
+
+trait Handler {
+    fn handle(&self, input: &str) -> String;
+}
+
+struct Loud;
+
+impl Handler for Loud {
+    fn handle(&self, input: &str) -> String {
+        input.to_uppercase()
+    }
+}
+
+struct Quiet;
+
+impl Handler for Quiet {
+    fn handle(&self, input: &str) -> String {
+        input.to_lowercase()
+    }
+}
+
+/// A handler borrowing its prefix from the configuration.
+struct Prefixed<'a>(&'a str);
+
+impl Handler for Prefixed<'_> {
+    fn handle(&self, input: &str) -> String {
+        format!("{}{}", self.0, input)
+    }
+}
+
+struct Config {
+    loud: bool,
+    prefix: String,
+}
+
+fn enter() {
+    record("enter");
+}
+
+fn exit() {
+    record("exit");
+}
+
+fn failed(error: &String) {
+    record(format!("error: {}", error));
+}
+
+fn traced<F: FnOnce(T) -> R, T, R>(f: F, value: T) -> R {
+    record("decorator");
+    f(value)
+}
+
+/// A decorator bounding its output more than it needs to, which owned trait objects without borrows satisfy.
+fn stored<F: FnOnce(T) -> R, T, R: Sized + 'static>(f: F, value: T) -> R {
+    record("stored");
+    f(value)
+}
+
+fn adapted<F: FnOnce() -> R, R>(f: F) -> R {
+    record("adapted");
+    f()
+}
+
+// The branches have different types, which only the return type coerces to a common one
+#[axin(on_enter(enter), on_exit(exit))]
+fn make_handler(cfg: &Config) -> Box<dyn Handler> {
+    if cfg.loud {
+        Box::new(Loud)
+    } else {
+        Box::new(Quiet)
+    }
+}
+
+#[axin(decorator(traced))]
+fn make_decorated(cfg: &Config) -> Box<dyn Handler> {
+    if cfg.loud {
+        return Box::new(Loud);
+    }
+    Box::new(Quiet)
+}
+
+#[axin(decorator(stored), on_exit(exit))]
+fn make_stored(loud: bool) -> Box<dyn Handler> {
+    match loud {
+        true => Box::new(Loud),
+        false => Box::new(Quiet),
+    }
+}
+
+/// Adapting, so that the inner callable captures the parameter instead of taking it, and the borrow of its output is
+/// tied to the parameter's.
+#[axin(on_enter(enter), decorator(adapted), adapt)]
+fn make_borrowing(cfg: &Config) -> Box<dyn Handler + '_> {
+    Box::new(Prefixed(&cfg.prefix))
+}
+
+#[axin(decorator(adapted), adapt, on_exit(exit, when = result.is_ok()))]
+fn make_shared(name: &str) -> Result<Arc<dyn Handler + Send + Sync>, String> {
+    match name {
+        "loud" => Ok(Arc::new(Loud)),
+        "quiet" => Ok(Arc::new(Quiet)),
+        _ => Err(format!("no handler `{}`", name)),
+    }
+}
+
+#[axin(on_error(failed), decorator(traced))]
+fn make_all(names: Vec<&str>) -> Result<Vec<Rc<dyn Handler>>, String> {
+    names
+        .into_iter()
+        .map(|name| -> Result<Rc<dyn Handler>, String> {
+            match name {
+                "loud" => Ok(Rc::new(Loud)),
+                "quiet" => Ok(Rc::new(Quiet)),
+                _ => Err(format!("no handler `{}`", name)),
+            }
+        })
+        .collect()
+}
+
+/// An `impl Trait` type is inferred, while the trait object next to it still coerces.
+#[axin(on_enter(enter), decorator(traced))]
+fn make_pair(loud: bool) -> (impl Handler, Box<dyn Handler>) {
+    if loud {
+        (Loud, Box::new(Loud))
+    } else {
+        (Loud, Box::new(Quiet))
+    }
+}
+
+#[axin(on_enter(enter), on_exit(exit))]
+async fn make_async(loud: bool) -> Box<dyn Handler> {
+    if loud {
+        return Box::new(Loud);
+    }
+    Box::new(Quiet)
+}
+
+#[axin(decorator(traced))]
+async fn make_async_decorated(loud: bool) -> Box<dyn Handler> {
+    if loud {
+        Box::new(Loud)
+    } else {
+        Box::new(Quiet)
+    }
+}
+
+#[test]
+fn boxed_trait_objects_with_hooks() {
+    let loud = Config {
+        loud: true,
+        prefix: String::new(),
+    };
+    assert_eq!(make_handler(&loud).handle("Hi"), "HI");
+    assert_eq!(
+        make_handler(&Config {
+            loud: false,
+            ..loud
+        })
+        .handle("Hi"),
+        "hi"
+    );
+    assert_eq!(take_events(), ["enter", "exit", "enter", "exit"]);
+}
+
+#[test]
+fn boxed_trait_objects_through_decorators() {
+    let cfg = Config {
+        loud: true,
+        prefix: "> ".to_string(),
+    };
+    assert_eq!(make_decorated(&cfg).handle("Hi"), "HI");
+    assert_eq!(make_stored(false).handle("Hi"), "hi");
+    assert_eq!(make_borrowing(&cfg).handle("Hi"), "> Hi");
+    let (first, second) = make_pair(false);
+    assert_eq!(
+        (first.handle("Hi"), second.handle("Hi")),
+        ("HI".to_string(), "hi".to_string())
+    );
+    assert_eq!(
+        take_events(),
+        [
+            "decorator",
+            "stored",
+            "exit",
+            "enter",
+            "adapted",
+            "enter",
+            "decorator"
+        ]
+    );
+}
+
+#[test]
+fn trait_objects_in_results() {
+    assert_eq!(make_shared("loud").unwrap().handle("Hi"), "HI");
+    assert_eq!(make_shared("other").err().unwrap(), "no handler `other`");
+    let handlers = make_all(vec!["loud", "quiet"]).unwrap();
+    assert_eq!(handlers[1].handle("Hi"), "hi");
+    assert!(make_all(vec!["loud", "other"]).is_err());
+    assert_eq!(
+        take_events(),
+        [
+            "adapted",
+            "exit",
+            "adapted",
+            "decorator",
+            "decorator",
+            "error: no handler `other`"
+        ]
+    );
+}
+
+#[test]
+fn boxed_trait_objects_from_async_functions() {
+    assert_eq!(block_on(make_async(true)).handle("Hi"), "HI");
+    assert_eq!(block_on(make_async_decorated(false)).handle("Hi"), "hi");
+    assert_eq!(take_events(), ["enter", "exit", "decorator"]);
+}
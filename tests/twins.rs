@@ -0,0 +1,97 @@
+//! Twins, uninstrumented copies of instrumented functions emitted under names of their own with `twin = "name"`.
+
+#![cfg(not(feature = "disable"))]
+#![deny(warnings)]
+
+mod common;
+
+use common::{record, take_events};
+
+fn traced<F: FnOnce() -> R, R>(f: F) -> R {
+    record("decorator");
+    f()
+}
+
+mod users {
+    use super::{record, traced};
+    use axin::axin;
+    use std::fmt::Display;
+
+    /// Fetch the name of a user.
+    #[axin(
+        twin = "fetch_user_fast",
+        on_enter(record("enter")),
+        on_exit(record("exit"))
+    )]
+    #[must_use]
+    pub fn fetch_user(id: u32) -> String {
+        format!("user {}", id)
+    }
+
+    // Generics and their bounds are mirrored
+    #[axin(twin = "describe_fast", decorator(traced), adapt)]
+    pub fn describe<T, const N: usize>(items: [T; N]) -> String
+    where
+        T: Display + Clone,
+    {
+        items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    // No layer of stacked attributes instruments the twin
+    #[axin(on_enter(record("outer")))]
+    #[axin(twin = "stacked_fast", on_enter(record("inner")))]
+    pub fn stacked() -> u32 {
+        7
+    }
+
+    // A twin only is fine too
+    #[axin(twin = "plain_fast")]
+    pub fn plain() -> u32 {
+        1
+    }
+
+    pub struct Cache {
+        pub hits: u32,
+    }
+
+    impl Cache {
+        #[axin(twin = "hits_fast", on_enter(record("enter")), adapt)]
+        pub fn hits(&self) -> u32 {
+            self.hits
+        }
+    }
+}
+
+#[test]
+fn only_the_original_is_instrumented() {
+    assert_eq!(users::fetch_user(1), "user 1");
+    assert_eq!(take_events(), ["enter", "exit"]);
+    assert_eq!(users::fetch_user_fast(2), "user 2");
+    assert!(take_events().is_empty());
+}
+
+#[test]
+fn generics_are_mirrored() {
+    assert_eq!(users::describe([1, 2]), "1,2");
+    assert_eq!(take_events(), ["decorator"]);
+    assert_eq!(users::describe_fast(["a", "b", "c"]), "a,b,c");
+    assert!(take_events().is_empty());
+}
+
+#[test]
+fn stacked_attributes_and_methods() {
+    assert_eq!(users::stacked(), 7);
+    assert_eq!(take_events(), ["outer", "inner"]);
+    assert_eq!(users::stacked_fast(), 7);
+    assert_eq!(users::plain() + users::plain_fast(), 2);
+    assert!(take_events().is_empty());
+
+    let cache = users::Cache { hits: 3 };
+    assert_eq!(cache.hits(), 3);
+    assert_eq!(cache.hits_fast(), 3);
+    assert_eq!(take_events(), ["enter"]);
+}
@@ -0,0 +1,18 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn hook() {}
+
+fn decorator<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+#[axin(explain, on_enter(hook), decorator(decorator))]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn main() {
+    double(1);
+}
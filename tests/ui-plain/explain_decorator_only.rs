@@ -0,0 +1,16 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn twice<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x) * 2
+}
+
+#[axin(explain, decorator(twice))]
+fn square(x: i32) -> i32 {
+    x * x
+}
+
+fn main() {
+    square(3);
+}
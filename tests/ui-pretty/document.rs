@@ -0,0 +1,28 @@
+#![deny(deprecated)]
+
+use axin::{axin, CallCounter};
+
+static CALLS: CallCounter = CallCounter::new();
+
+fn setup() {}
+
+fn timing_decorator<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+/// Doubles its argument.
+#[axin(
+    explain,
+    document,
+    count_calls(CALLS),
+    on_enter(setup),
+    decorator(timing_decorator)
+)]
+#[inline]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn main() {
+    double(1);
+}
@@ -0,0 +1,56 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+#[allow(clippy::too_many_arguments)]
+fn forward<F>(
+    f: F,
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    g: u8,
+    h: u8,
+    i: u8,
+    j: u8,
+    k: u8,
+    l: u8,
+    m: u8,
+    n: u8,
+    o: u8,
+    p: u8,
+    q: u8,
+) -> u32
+where
+    F: FnOnce(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8) -> u32,
+{
+    f(a, b, c, d, e, g, h, i, j, k, l, m, n, o, p, q)
+}
+
+#[axin(explain, decorator(forward))]
+#[allow(clippy::too_many_arguments)]
+fn sum(
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    g: u8,
+    h: u8,
+    i: u8,
+    j: u8,
+    k: u8,
+    l: u8,
+    m: u8,
+    n: u8,
+    o: u8,
+    p: u8,
+    q: u8,
+) -> u32 {
+    [a, b, c, d, e, g, h, i, j, k, l, m, n, o, p, q].iter().map(|&x| x as u32).sum()
+}
+
+fn main() {
+    sum(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+}
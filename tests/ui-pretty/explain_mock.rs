@@ -0,0 +1,19 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn trace() {}
+
+// The mock misses the second parameter, which is only reported in test builds, where the explanation shows the call
+fn fake_fetch(id: u32) -> String {
+    format!("user {}", id)
+}
+
+#[axin(explain, on_enter(trace), mock_in_tests(fake_fetch))]
+fn fetch(id: u32, region: &str) -> String {
+    format!("{} in {}", id, region)
+}
+
+fn main() {
+    fetch(1, "eu");
+}
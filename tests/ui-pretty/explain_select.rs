@@ -0,0 +1,24 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn fast<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+fn checked<F: FnOnce(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x.max(0))
+}
+
+fn mode() -> usize {
+    0
+}
+
+#[axin(explain, decorator_select(mode(), [fast, checked]))]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn main() {
+    double(1);
+}
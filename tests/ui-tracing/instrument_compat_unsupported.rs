@@ -0,0 +1,24 @@
+use axin::axin;
+
+#[axin(instrument_compat(err))]
+fn with_err() -> Result<(), String> {
+    Ok(())
+}
+
+#[axin(instrument_compat(ret, level = "debug"))]
+fn with_ret() -> u32 {
+    1
+}
+
+#[axin(instrument_compat(skip(passwd)))]
+fn with_typo(password: &str) -> usize {
+    password.len()
+}
+
+#[axin(instrument_compat(level = "verbose"))]
+fn with_unknown_level() {}
+
+#[axin(instrument_compat(span_name = "x"))]
+fn with_unknown_option() {}
+
+fn main() {}
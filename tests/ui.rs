@@ -0,0 +1,33 @@
+//! Compile-fail tests for the diagnostics of the `axin` macro.
+
+#![cfg(not(feature = "disable"))]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+/// Diagnostics that render generated code, which is pretty-printed with `diagnostics-pretty` and differs without it.
+#[test]
+#[cfg(not(feature = "diagnostics-pretty"))]
+fn ui_plain() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui-plain/*.rs");
+}
+
+/// Counterpart of [`ui_plain`] with `diagnostics-pretty` enabled.
+#[test]
+#[cfg(feature = "diagnostics-pretty")]
+fn ui_pretty() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui-pretty/*.rs");
+}
+
+/// Diagnostics of `instrument_compat`, which is only available with the `tracing` feature.
+#[test]
+#[cfg(feature = "tracing")]
+fn ui_tracing() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui-tracing/*.rs");
+}
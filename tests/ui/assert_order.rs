@@ -0,0 +1,11 @@
+use axin::axin;
+
+fn hook() {}
+
+#[axin(on_enter(hook), assert_order("enter,bdy"))]
+fn misspelled() {}
+
+#[axin(raw_body, on_enter(hook), assert_order("enter,body"))]
+fn raw() {}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use axin::axin;
+
+fn log(_: u32) {}
+
+fn with_label<F: FnOnce(u32) -> u32>(_: &'static str) -> impl FnOnce(F, u32) -> u32 {
+    |f, x| f(x)
+}
+
+fn with_init<F: FnOnce(String) -> usize, I>(_: I) -> impl FnOnce(F, String) -> usize {
+    |f, name| f(name)
+}
+
+fn raw_value() -> *const u32 {
+    &7
+}
+
+// Unsafe operations need an `unsafe` block in the arguments, as anywhere else
+#[axin(on_enter(log(*raw_value())))]
+fn unguarded(x: u32) -> u32 {
+    x
+}
+
+// Errors inside a block point into it
+#[axin(decorator(with_label(unsafe { *raw_value() })), on_exit(log(1), when = unsafe { *raw_value() }))]
+fn mistyped(x: u32) -> u32 {
+    x
+}
+
+// An async block borrowing a parameter is held by the decorator it sets up, while the parameter is moved into the call
+#[axin(decorator(with_init(async { name.len() })))]
+fn borrowed(name: String) -> usize {
+    name.len()
+}
+
+fn main() {}
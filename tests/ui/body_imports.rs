@@ -0,0 +1,25 @@
+use axin::axin;
+
+mod telemetry {
+    pub fn enter() {}
+
+    pub mod hooks {
+        pub fn exit() {}
+    }
+}
+
+#[axin(on_enter(t_enter))]
+fn renamed() {
+    use crate::telemetry::enter as t_enter;
+    t_enter();
+}
+
+#[axin(on_exit(hooks::exit))]
+fn nested() {
+    {
+        use crate::telemetry::hooks;
+        hooks::exit();
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use axin::axin;
+
+// The decorator returned by the setup call holds on to the borrowed route, which is moved into the call afterwards
+fn label_by<'a, F, R>(route: &'a String) -> impl FnOnce(F, String) -> R + 'a
+where
+    F: FnOnce(String) -> R + 'a,
+{
+    move |f: F, owned: String| {
+        println!("{}", route);
+        f(owned)
+    }
+}
+
+#[axin(decorator(label_by, borrow_args))]
+fn handle(route: String) -> usize {
+    route.len()
+}
+
+fn main() {
+    handle("/".to_string());
+}
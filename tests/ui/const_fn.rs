@@ -0,0 +1,27 @@
+use axin::axin;
+
+fn hook() {}
+
+struct Config(u32);
+
+impl Config {
+    #[axin(on_enter(hook))]
+    const fn new() -> Config {
+        Config(1)
+    }
+}
+
+struct Port(u16);
+
+#[axin(on_enter(hook), skip(zero))]
+impl Port {
+    const fn zero() -> Port {
+        Port(0)
+    }
+
+    const fn default_port() -> Port {
+        Port(8080)
+    }
+}
+
+fn main() {}
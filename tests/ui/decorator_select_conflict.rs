@@ -0,0 +1,17 @@
+use axin::axin;
+
+fn fast<F: FnOnce() -> i32>(f: F) -> i32 {
+    f()
+}
+
+#[axin(decorator(fast), decorator_select(0, [fast]))]
+fn both() -> i32 {
+    1
+}
+
+#[axin(decorator_select(0, []))]
+fn empty() -> i32 {
+    1
+}
+
+fn main() {}
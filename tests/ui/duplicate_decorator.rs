@@ -0,0 +1,47 @@
+use axin::axin;
+
+fn timing<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+fn retry<F: FnOnce(u32) -> u32>(_times: u32) -> impl FnOnce(F, u32) -> u32 {
+    |f, x| f(x)
+}
+
+// The same decorator given twice would wrap the function twice
+#[axin(decorator(timing), on_enter(timing_start), decorator(timing))]
+fn fetch(x: u32) -> u32 {
+    x
+}
+
+// The same setup call as well, while decorators set up differently are nested
+#[axin(decorator(retry(3)), decorator(retry(5)), decorator(retry(3)))]
+fn load(x: u32) -> u32 {
+    x
+}
+
+// Across stacked attributes, which wrap one another
+#[axin(decorator(timing), on_enter(timing_start))]
+#[axin(decorator(timing))]
+fn store(x: u32) -> u32 {
+    x
+}
+
+struct Cache;
+
+// And across the attribute of an `impl` block and the one of a method
+#[axin(decorator(retry(3)))]
+impl Cache {
+    #[axin(decorator(retry(3)))]
+    fn get(&self, x: u32) -> u32 {
+        x
+    }
+}
+
+fn timing_start() {}
+
+fn main() {
+    fetch(1);
+    load(1);
+    store(1);
+}
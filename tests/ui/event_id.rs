@@ -0,0 +1,20 @@
+use axin::axin;
+
+fn trace_event(_: u16) {}
+
+#[axin(event_id = 0x10000, on_enter(trace_event(__axin_event_id)))]
+fn out_of_range() {}
+
+#[axin(event_id = 0x1042, on_enter(super::trace_event(__axin_event_id)))]
+mod handlers {
+    pub fn create() {}
+}
+
+struct Service;
+
+#[axin(event_id = 7, on_enter(trace_event(__axin_event_id)))]
+impl Service {
+    fn run(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use axin::axin;
+
+struct Connection;
+
+fn close(_connection: &Connection) {}
+
+fn log(_connection: &mut Connection) {}
+
+// The exit hook gets clones of the parameters it uses, which needs them to be `Clone`
+#[axin(on_exit(close(&connection)))]
+fn query(connection: Connection) -> usize {
+    drop(connection);
+    0
+}
+
+// A captured `&mut` reference is moved into the body with it
+#[axin(adapt, on_exit(log(connection)))]
+fn reset(connection: &mut Connection) {
+    let _ = connection;
+}
+
+fn main() {
+    query(Connection);
+    reset(&mut Connection);
+}
@@ -0,0 +1,28 @@
+use axin::axin;
+
+fn forward<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+fn log_len(_: usize) {}
+
+struct Queue(Vec<u32>);
+
+impl Queue {
+    #[axin(decorator(forward), adapt, on_exit(log_len(self.0.len())))]
+    fn into_inner(self) -> Vec<u32> {
+        self.0
+    }
+
+    #[axin(adapt, on_exit(log_len(0), when = self.0.is_empty()))]
+    async fn drain(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.0)
+    }
+
+    #[axin(raw_body, on_exit(log_len(self.0.len())))]
+    fn push(&mut self, value: u32) {
+        self.0.push(value);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use axin::axin;
+use std::fmt::Debug;
+
+fn audit<A: Debug>(_fn_name: &str, _args: &A) {}
+
+struct Connection;
+
+#[axin(on_enter(audit, with_args(skip(conection))))]
+fn query(connection: &Connection, table: &str) -> usize {
+    let _ = connection;
+    table.len()
+}
+
+#[axin(on_exit(audit, with_args))]
+fn close(connection: Connection) {
+    drop(connection);
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use axin::axin;
+
+fn hook() {}
+
+#[axin(minimal, on_exit(hook))]
+fn with_exit_hook() {}
+
+fn labeled_hook(_: &str) {}
+
+#[axin(minimal, on_enter(labeled_hook("label")))]
+fn with_hook_arguments() {}
+
+fn main() {}
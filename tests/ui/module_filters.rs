@@ -0,0 +1,27 @@
+use axin::axin;
+
+fn hook() {}
+
+#[axin(on_enter(hook), skip(missing))]
+mod handlers {
+    use super::*;
+
+    pub fn present() {}
+}
+
+#[axin(on_enter(hook), only(present))]
+fn not_a_module() {}
+
+#[axin(on_enter(hook))]
+struct NotAFunction;
+
+#[axin(on_enter(hook), only(missing))]
+impl NotAFunction {
+    fn present() {}
+}
+
+// The marker leaves a method of an instrumented `impl` block alone, and means nothing elsewhere
+#[axin(skip)]
+fn marked_alone() {}
+
+fn main() {}
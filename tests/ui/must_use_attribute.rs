@@ -0,0 +1,44 @@
+#![deny(unused_must_use)]
+
+use axin::axin;
+
+fn hook() {}
+
+fn decorator<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+#[axin(on_enter(hook), on_exit(hook))]
+#[must_use]
+fn with_hooks() -> u32 {
+    1
+}
+
+#[axin(decorator(decorator))]
+#[must_use = "the count is the point"]
+fn with_decorator() -> u32 {
+    1
+}
+
+#[axin(raw_body, on_enter(hook))]
+#[must_use]
+fn with_raw_body() -> u32 {
+    1
+}
+
+struct Counter;
+
+impl Counter {
+    #[axin(adapt, on_enter(hook))]
+    #[must_use]
+    fn count(&self) -> u32 {
+        1
+    }
+}
+
+fn main() {
+    with_hooks();
+    with_decorator();
+    with_raw_body();
+    Counter.count();
+}
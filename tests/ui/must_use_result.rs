@@ -0,0 +1,52 @@
+#![deny(unused_must_use)]
+
+use axin::{axin, CallCounter};
+
+fn hook() {}
+
+fn error_hook<E>(_: &E) {}
+
+fn decorator<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+static CALLS: CallCounter = CallCounter::new();
+
+#[axin(on_enter(hook), on_exit(hook))]
+fn with_hooks() -> Result<(), ()> {
+    Ok(())
+}
+
+#[axin(decorator(decorator))]
+fn with_decorator() -> Result<(), ()> {
+    Ok(())
+}
+
+#[axin(prologue(let _unused = 0;))]
+fn with_prologue() -> Result<(), ()> {
+    Ok(())
+}
+
+#[axin(on_error(error_hook))]
+fn with_error_hook() -> Result<(), ()> {
+    Ok(())
+}
+
+#[axin(assert_returns(Ok(())))]
+fn with_assertion() -> Result<(), ()> {
+    Ok(())
+}
+
+#[axin(minimal, count_calls(CALLS))]
+fn with_minimal() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {
+    with_hooks();
+    with_decorator();
+    with_prologue();
+    with_error_hook();
+    with_assertion();
+    with_minimal();
+}
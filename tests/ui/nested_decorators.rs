@@ -0,0 +1,19 @@
+use axin::axin;
+
+fn forward<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+fn label_by<F: FnOnce(u32) -> u32>(_x: &u32) -> impl FnOnce(F, u32) -> u32 {
+    forward
+}
+
+// A decorator after the first is set up inside the one around it, after the arguments are moved into its call
+#[axin(decorator(forward), decorator(label_by, borrow_args))]
+fn labeled(x: u32) -> u32 {
+    x
+}
+
+fn main() {
+    labeled(1);
+}
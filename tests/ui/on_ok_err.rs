@@ -0,0 +1,20 @@
+use axin::axin;
+
+fn log<T: std::fmt::Debug>(_value: T) {}
+
+// Success and failure hooks match the result as a `Result`
+#[axin(on_ok(log(__axin_ok)))]
+fn count() -> usize {
+    0
+}
+
+// The error is only bound for `on_err`
+#[axin(on_ok(log(__axin_err)), on_err(log(__axin_err)))]
+fn load() -> Result<u32, String> {
+    Ok(1)
+}
+
+fn main() {
+    count();
+    let _ = load();
+}
@@ -0,0 +1,38 @@
+use axin::axin;
+
+fn rollback<T>(_value: T) {}
+
+// An executor drops the future of an async function only once it caught its panic
+#[axin(on_panic(rollback(id)))]
+async fn fetch(id: u32) -> u32 {
+    id
+}
+
+// The guards would borrow what the body has mutably
+#[axin(on_exit_always(rollback(buffer.len())))]
+fn fill(buffer: &mut Vec<u8>) {
+    buffer.push(0);
+}
+
+struct Account(u32);
+
+impl Account {
+    #[axin(adapt, on_panic(rollback(self.0)))]
+    fn withdraw(&mut self, amount: u32) {
+        self.0 -= amount;
+    }
+}
+
+// The guards get clones of the parameters they use, which needs them to be `Clone`
+struct Connection;
+
+#[axin(on_exit_always(rollback(&connection)))]
+fn query(connection: Connection) {
+    drop(connection);
+}
+
+fn main() {
+    let _ = fetch(1);
+    fill(&mut Vec::new());
+    query(Connection);
+}
@@ -0,0 +1,9 @@
+use axin::axin;
+
+// Options only fill in the placeholders of the crate's defaults, and this crate has no `axin.toml`
+#[axin(opt(component = "billing"))]
+fn charge() {}
+
+fn main() {
+    charge();
+}
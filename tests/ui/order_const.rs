@@ -0,0 +1,27 @@
+use axin::axin;
+
+fn hook() {}
+
+struct Job;
+
+trait Run {
+    fn run(&self);
+}
+
+// A trait `impl` block cannot have the constant next to its methods
+#[axin(order_const, on_enter(hook), adapt)]
+impl Run for Job {
+    fn run(&self) {}
+}
+
+// Neither can an attribute of a method tell whether its `impl` block implements a trait
+impl Clone for Job {
+    #[axin(order_const, on_enter(hook))]
+    fn clone(&self) -> Self {
+        Job
+    }
+}
+
+fn main() {
+    Job.run();
+}
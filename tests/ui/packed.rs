@@ -0,0 +1,36 @@
+use axin::axin;
+
+fn pass<F: FnOnce(A) -> R, A, R>(f: F, args: A) -> R {
+    f(args)
+}
+
+#[axin(on_enter(pass), packed = struct)]
+fn undecorated(id: u32) -> u32 {
+    id
+}
+
+#[axin(decorator(pass), adapt, packed = struct)]
+fn adapted(id: u32) -> u32 {
+    id
+}
+
+#[axin(decorator(pass), packed = tuple)]
+fn tupled(id: u32) -> u32 {
+    id
+}
+
+#[axin(decorator(pass), packed = struct)]
+fn opaque(value: impl std::fmt::Debug) -> String {
+    format!("{:?}", value)
+}
+
+struct Cache;
+
+impl Cache {
+    #[axin(decorator(pass), packed = struct)]
+    fn merge(other: Self) -> Self {
+        other
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use axin::axin_pair;
+
+fn trace() {}
+
+axin_pair!({ on_enter(trace) } => {
+    fn single() {}
+});
+
+axin_pair!({ on_enter(trace), event_id = 7 } => {
+    fn fetch() {}
+    async fn fetch_async() {}
+});
+
+fn main() {}
@@ -0,0 +1,15 @@
+use axin::{axin, PhaseTimings};
+
+fn report(_: PhaseTimings) {}
+
+#[axin(phase_timing(report))]
+async fn fetch() -> u32 {
+    1
+}
+
+#[axin(minimal, phase_timing(report))]
+fn count() -> u32 {
+    1
+}
+
+fn main() {}
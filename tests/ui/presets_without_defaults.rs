@@ -0,0 +1,17 @@
+use axin::{axin, axin_use};
+
+// Presets are defined in the crate's `axin.toml`, which this crate does not have
+#[axin_use(service)]
+fn handle() {}
+
+#[axin(on_exit(flush), preset(metrics))]
+fn flush() {}
+
+#[axin_use()]
+fn nothing() {}
+
+fn main() {
+    handle();
+    flush();
+    nothing();
+}
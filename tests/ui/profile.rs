@@ -0,0 +1,25 @@
+use axin::{axin, PhaseTimings};
+
+fn report(_: PhaseTimings) {}
+
+#[axin(profile)]
+async fn fetch() -> u32 {
+    1
+}
+
+#[axin(profile, phase_timing(report))]
+fn timed() -> u32 {
+    1
+}
+
+#[axin(profile(slow = 250))]
+fn unitless() -> u32 {
+    1
+}
+
+#[axin(profile(fast = 1ms))]
+fn unknown() -> u32 {
+    1
+}
+
+fn main() {}
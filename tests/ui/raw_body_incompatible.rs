@@ -0,0 +1,21 @@
+use axin::axin;
+
+fn hook() {}
+
+fn timing<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+#[axin(raw_body, on_enter(hook), decorator(timing))]
+fn with_decorator() {}
+
+#[axin(raw_body, prologue(let x = 1;), on_exit(hook))]
+fn with_prologue() {}
+
+#[axin(raw_body, epilogue(hook();))]
+fn with_epilogue() {}
+
+#[axin(raw_body, on_exit(hook, after_drop))]
+fn with_after_drop(_guard: String) {}
+
+fn main() {}
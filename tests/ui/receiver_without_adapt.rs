@@ -0,0 +1,33 @@
+use axin::axin;
+
+fn hook() {}
+
+fn pass<F: FnOnce() -> R, R>(f: F) -> R {
+    f()
+}
+
+struct Counter(u32);
+
+impl Counter {
+    #[axin(on_enter(hook), decorator(pass))]
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    #[axin(on_enter(hook), decorator(pass), adapt)]
+    fn get_adapted(&self) -> u32 {
+        self.0
+    }
+
+    // Without a decorator, the receiver is captured anyway
+    #[axin(on_enter(hook))]
+    fn get_hooked(&self) -> u32 {
+        self.0
+    }
+}
+
+fn main() {
+    let counter = Counter(1);
+    counter.get_adapted();
+    counter.get_hooked();
+}
@@ -0,0 +1,53 @@
+use axin::axin;
+
+mod audit {
+    pub fn process_data() {}
+
+    pub fn flush<F: FnOnce() -> R, R>(f: F) -> R {
+        f()
+    }
+}
+
+fn flush() {}
+
+#[axin(on_enter(process_data))]
+fn process_data() {}
+
+#[axin(decorator(self::load))]
+fn load() -> u32 {
+    1
+}
+
+#[axin(on_exit(record("done")), on_error(store::<u32>))]
+fn store<T>() -> Result<(), String> {
+    Ok(())
+}
+
+fn record(_: &str) {}
+
+struct Cache;
+
+impl Cache {
+    #[axin(adapt, on_exit(Self::flush))]
+    fn flush(&self) {}
+
+    #[axin(on_enter(Self::clear))]
+    fn clear() {}
+
+    // A bare name and `self::` refer to the free function, not to the method
+    #[axin(adapt, on_enter(flush), on_exit(self::flush))]
+    fn sync(&self) {}
+}
+
+// Paths through other modules name other functions
+#[axin(on_enter(audit::process_data), decorator(crate::audit::flush))]
+fn audited() {}
+
+mod nested {
+    use axin::axin;
+
+    #[axin(on_enter(super::flush))]
+    pub fn flush() {}
+}
+
+fn main() {}
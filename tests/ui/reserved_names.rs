@@ -0,0 +1,22 @@
+use axin::{axin, AxinCtx};
+
+fn trace(_ctx: &AxinCtx) {}
+
+fn alert() {}
+
+#[axin(on_exit(alert, when = result.is_err()))]
+fn store(result: Result<u32, String>) -> Result<u32, String> {
+    result
+}
+
+#[axin(on_enter(trace(&__axin)))]
+fn handle((__axin, _): (u8, u8)) -> u8 {
+    __axin
+}
+
+#[axin(prologue(let elapsed = 0;), on_exit(alert, when = elapsed.as_secs() > 1))]
+fn wait() -> u32 {
+    elapsed
+}
+
+fn main() {}
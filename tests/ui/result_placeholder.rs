@@ -0,0 +1,22 @@
+use axin::axin;
+
+fn log<T: std::fmt::Debug>(value: &T) {
+    println!("{:?}", value);
+}
+
+#[axin(on_enter(log(__axin_result)))]
+fn early() -> u32 {
+    1
+}
+
+#[axin(on_exit(log(&1)), on_error(log(__axin_result)))]
+fn fallible() -> Result<u32, String> {
+    Ok(1)
+}
+
+#[axin(raw_body, on_exit(log(__axin_result)))]
+fn raw() -> u32 {
+    1
+}
+
+fn main() {}
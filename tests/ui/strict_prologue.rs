@@ -0,0 +1,36 @@
+use axin::axin;
+
+// Mutable statics can only be used in `unsafe` blocks
+#[axin(strict_prologue, prologue(unsafe { COUNTER += 1 };))]
+fn count() {}
+
+#[axin(strict_prologue, prologue(static mut SEEN: bool = false;))]
+fn declare() {}
+
+#[axin(
+    strict_prologue(deny = [std::process::exit]),
+    prologue(let code = 0; if code > 0 { ::std::process::exit(code) })
+)]
+fn quit() {}
+
+// The arguments of macros are checked too
+#[axin(strict_prologue, prologue(println!("{}", unsafe { COUNTER });))]
+fn print() {}
+
+// The strict mode of one attribute covers the prologues of all attributes of the function
+#[axin(strict_prologue(deny = [std::env::set_var]))]
+#[axin(prologue(std::env::set_var("MODE", "test");))]
+fn configure() {}
+
+// Epilogues are checked like prologues
+#[axin(strict_prologue(deny = [std::process::exit]), epilogue(std::process::exit(0);))]
+fn leave() {}
+
+#[axin(strict_prologue(deny = [std::process::exit]), prologue(let code = 1;))]
+fn allowed() -> i32 {
+    code
+}
+
+fn main() {
+    allowed();
+}
@@ -0,0 +1,39 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn log(_message: &str) {}
+
+fn forward<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+#[axin(stub_missing(audit, notify), on_enter(audit(x)), on_exit(notify))]
+fn stubbed(x: u32) -> u32 {
+    x
+}
+
+#[axin(stub_missing(cached), decorator(cached))]
+fn decorated(x: u32) -> u32 {
+    x
+}
+
+#[axin(stub_missing(audit, flush), on_enter(audit))]
+fn unused_stub(x: u32) -> u32 {
+    x
+}
+
+#[axin(stub_missing(audit), on_enter(audit(x)), on_exit(audit(x, "exit")))]
+fn mismatched(x: u32) -> u32 {
+    x
+}
+
+#[axin(stub_missing(log), on_enter(log), decorator(forward))]
+fn existing(x: u32) -> u32 {
+    x
+}
+
+fn main() {
+    stubbed(1);
+    existing(1);
+}
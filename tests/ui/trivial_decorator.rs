@@ -0,0 +1,38 @@
+#![deny(deprecated)]
+
+use axin::axin;
+
+fn decorator<F: FnOnce(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+fn inner(x: u32) -> u32 {
+    x + 1
+}
+
+#[axin(deny_trivial_decorator, decorator(decorator))]
+fn denied(x: u32) -> u32 {
+    inner(x)
+}
+
+#[axin(warn_if_trivial, decorator(decorator))]
+fn warned(x: u32) -> u32 {
+    inner(x)
+}
+
+#[axin(deny_trivial_decorator, allow_trivial, decorator(decorator))]
+fn allowed(x: u32) -> u32 {
+    inner(x)
+}
+
+#[axin(deny_trivial_decorator, decorator(decorator))]
+fn not_trivial(x: u32) -> u32 {
+    let y = inner(x);
+    y * 2
+}
+
+fn main() {
+    warned(1);
+    allowed(1);
+    not_trivial(1);
+}
@@ -0,0 +1,25 @@
+use axin::axin;
+
+fn trace() {}
+
+#[axin(twin = "fetch_user_fast", on_enter(trace))]
+pub fn fetch_user(id: u32) -> u32 {
+    id
+}
+
+pub fn fetch_user_fast(id: u32) -> u32 {
+    id
+}
+
+#[axin(twin = "load", on_enter(trace))]
+fn load() {}
+
+#[axin(twin = "not a name", on_enter(trace))]
+fn store() {}
+
+#[axin(twin = "handler_fast", on_enter(trace))]
+mod handlers {
+    pub fn create() {}
+}
+
+fn main() {}
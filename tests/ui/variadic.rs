@@ -0,0 +1,10 @@
+use axin::axin;
+
+fn enter() {}
+
+#[axin(on_enter(enter))]
+pub unsafe extern "C" fn sum(count: usize, mut args: ...) -> usize {
+    (0..count).map(|_| args.arg::<usize>()).sum()
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#![deny(deprecated)]
+
+mod legacy {
+    use axin::{axin, CallCounter};
+
+    pub static LEGACY_CALLS: CallCounter = CallCounter::new();
+
+    #[axin(warn_call_site("use new_api instead"), count_calls(LEGACY_CALLS))]
+    pub fn old_api() -> u32 {
+        42
+    }
+}
+
+fn main() {
+    legacy::old_api();
+}